@@ -0,0 +1,222 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use super::{LogAtlasApp, Session, Source, SourceId};
+use crate::case::{
+    AnalystState, CaseFile, CaseNote, CaseSource, CaseTriage, DetectionRef, EventRef, InputRef,
+    InputStatus, Note,
+};
+use crate::model::{EventId, EventStore};
+use crate::triage::{Subject, Triage};
+
+impl LogAtlasApp {
+    /// Save the session as a case file: every source's input reference (with the hash taken
+    /// in the background when it was read, if the file was unchanged; none while that is
+    /// still running), report, events and process tree, plus the selections, filters, notes
+    /// and triage.
+    pub fn save_case(&mut self, path: PathBuf) -> Result<()> {
+        if self.session.sources.is_empty() {
+            bail!("nothing to save: no input is open");
+        }
+        self.session.collect_hashes(false);
+        let mut sources = Vec::with_capacity(self.session.sources.len());
+        for source in &self.session.sources {
+            let input = source
+                .input
+                .clone()
+                .or_else(|| InputRef::stat(&source.path))
+                .unwrap_or_else(|| InputRef {
+                    path: source.path.clone(),
+                    size: 0,
+                    modified: None,
+                    sha256: None,
+                });
+            sources.push(CaseSource {
+                input,
+                visible: source.visible,
+                events: Cow::Borrowed(source.events.as_slice()),
+                dump: source.dump.as_ref().map(Cow::Borrowed),
+                process_tree: source.process_tree.as_ref().map(Cow::Borrowed),
+            });
+        }
+
+        let session = &self.session;
+        let index_of = |id: SourceId| session.sources.iter().position(|s| s.id == id);
+        let event_ref = |id: EventId| {
+            Some(EventRef {
+                source: index_of(session.source_of(id)?.id)?,
+                id: Session::local_id(id),
+            })
+        };
+        let ui = &mut self.ui;
+        let mut state = AnalystState {
+            focus: session.focus.and_then(index_of),
+            selected: self.selected.and_then(event_ref),
+            details_tab: ui.details_tab,
+            selected_module: ui.selected_module,
+            selected_thread: ui.selected_thread,
+            selected_stack_thread: ui.selected_stack_thread,
+            selected_exec_artifact: ui.selected_exec_artifact,
+            selected_injected_region: ui.selected_injected_region,
+            selected_ioc: ui.selected_ioc,
+            selected_tree_node: ui.selected_tree_node,
+            ..AnalystState::default()
+        };
+        for (scope, filter) in ui.filters_mut() {
+            if !filter.text.trim().is_empty() {
+                state.filters.insert(scope.to_string(), filter.text.clone());
+            }
+        }
+        // A subject whose source is gone is dropped.
+        let subject_ref = |subject: &Subject| match subject {
+            Subject::Event(id) => Some((Some(event_ref(*id)?), None)),
+            Subject::Detection(source, id) => Some((
+                None,
+                Some(DetectionRef {
+                    source: index_of(*source)?,
+                    id: id.clone(),
+                }),
+            )),
+        };
+        let notes = session
+            .notes()
+            .iter()
+            .filter_map(|n| {
+                let (event, detection) = match &n.about {
+                    Some(subject) => subject_ref(subject)?,
+                    None => (None, None),
+                };
+                Some(CaseNote {
+                    event,
+                    detection,
+                    author: n.author.clone(),
+                    created: n.created,
+                    text: n.text.clone(),
+                })
+            })
+            .collect();
+        let mut triage: Vec<CaseTriage> = session
+            .triage()
+            .iter()
+            .filter_map(|(subject, t)| {
+                let (event, detection) = subject_ref(subject)?;
+                Some(CaseTriage {
+                    event,
+                    detection,
+                    status: t.status,
+                    tags: t.tags.clone(),
+                })
+            })
+            .collect();
+        // Stable order so saving twice gives the same file.
+        triage.sort_by(|a, b| {
+            let key = |t: &CaseTriage| {
+                (
+                    t.event.map(|r| (r.source, r.id.0)),
+                    t.detection.as_ref().map(|d| (d.source, d.id.clone())),
+                )
+            };
+            key(a).cmp(&key(b))
+        });
+
+        CaseFile::new(sources, state, notes, triage).write(&path)?;
+        self.case_path = Some(path);
+        Ok(())
+    }
+
+    /// Replace the session with the case in `file` (`path` as opened, before decompression).
+    /// Inputs are looked up at their recorded path, then next to the case file, and marked
+    /// unchanged, changed or missing (checking while a changed size or modification time
+    /// sends them to be hashed in the background); the saved reports are used either way.
+    pub(super) fn open_case(&mut self, path: &Path, file: &Path) -> Result<SourceId> {
+        let case = CaseFile::read(file)?;
+        if case.sources.is_empty() {
+            bail!("{} has no sources", path.display());
+        }
+        self.clear_session();
+        let case_dir = path.parent().filter(|d| !d.as_os_str().is_empty());
+
+        let sources = case
+            .sources
+            .into_iter()
+            .map(|saved| {
+                let mut input = saved.input;
+                let status = input.check(case_dir);
+                let mut source = Source::new(
+                    input.path.clone(),
+                    EventStore::from_events(saved.events.into_owned()),
+                );
+                source.visible = saved.visible;
+                source.dump = saved.dump.map(Cow::into_owned);
+                source.process_tree = saved.process_tree.map(Cow::into_owned);
+                if status == InputStatus::Checking {
+                    source.hashing = Some(input.rehash_in_background());
+                }
+                source.input = Some(input);
+                source.status = status;
+                source
+            })
+            .collect();
+        let ids = self.session.add_all(sources);
+
+        let event_id = |r: EventRef| Some(Session::merged_id(*ids.get(r.source)?, r.id));
+        let state = case.state;
+        self.session.focus = state
+            .focus
+            .and_then(|i| ids.get(i).copied())
+            .or(self.session.focus);
+        self.selected = state.selected.and_then(event_id).or_else(|| {
+            self.session
+                .focus
+                .and_then(|id| self.session.first_event(id))
+        });
+        self.ui.details_tab = state.details_tab;
+        self.ui.selected_module = state.selected_module;
+        self.ui.selected_thread = state.selected_thread;
+        self.ui.selected_stack_thread = state.selected_stack_thread;
+        self.ui.selected_exec_artifact = state.selected_exec_artifact;
+        self.ui.selected_injected_region = state.selected_injected_region;
+        self.ui.selected_ioc = state.selected_ioc;
+        self.ui.selected_tree_node = state.selected_tree_node;
+        for (scope, filter) in self.ui.filters_mut() {
+            filter.text = state.filters.get(scope).cloned().unwrap_or_default();
+        }
+        let subject =
+            |event: Option<EventRef>, detection: Option<DetectionRef>| match (event, detection) {
+                (Some(r), _) => Some(Some(Subject::Event(event_id(r)?))),
+                (None, Some(d)) => Some(Some(Subject::Detection(*ids.get(d.source)?, d.id))),
+                (None, None) => Some(None),
+            };
+        let notes = case
+            .notes
+            .into_iter()
+            .filter_map(|n| {
+                Some(Note {
+                    about: subject(n.event, n.detection)?,
+                    author: n.author,
+                    created: n.created,
+                    text: n.text,
+                })
+            })
+            .collect();
+        let triage = case
+            .triage
+            .into_iter()
+            .filter_map(|t| {
+                let tags = t.tags;
+                Some((
+                    subject(t.event, t.detection)??,
+                    Triage {
+                        status: t.status,
+                        tags,
+                    },
+                ))
+            })
+            .collect();
+        self.session.restore_analysis(notes, triage);
+        self.case_path = Some(path.to_path_buf());
+        Ok(self.session.focus.unwrap_or(ids[0]))
+    }
+}
@@ -1,59 +1,456 @@
+mod case;
 mod run;
+mod session;
+mod timeline;
+mod triage;
 mod ui_state;
 
-use crate::model::{EventId, EventStore};
+use crate::model::{EntityRef, EventId, EventStore};
 use eframe::egui;
 use std::path::PathBuf;
 
 pub use run::run;
-pub use ui_state::DetailsTab;
+pub use session::{DumpData, Session, Source, SourceId};
+pub use timeline::{Timeline, TimelineEvent};
+pub use ui_state::{DetailsTab, FilteredRows, UiState};
 
 pub struct LogAtlasApp {
-    pub events: EventStore,
+    /// Opened inputs and their merged timeline.
+    pub session: Session,
     pub selected: Option<EventId>,
     pub ui: ui_state::UiState,
-    pub dump_path: Option<PathBuf>,
-    pub dump_summary: Option<crate::model::MinidumpSummary>,
-    pub dump_report: Option<crate::model::MinidumpReport>,
+    /// Case file the session was opened from or last saved to.
+    pub case_path: Option<PathBuf>,
+    /// Suppressions applied to every dump's detections, loaded at startup.
+    pub allowlist: crate::triage::Allowlist,
+    /// Known-good modules and command lines per product version, loaded at startup.
+    pub baselines: crate::baseline::Baselines,
+    pub(crate) detection_cache: std::cell::RefCell<triage::DetectionCache>,
 }
 
 impl Default for LogAtlasApp {
     fn default() -> Self {
-        let events = EventStore::demo();
-        let selected = events.first_id();
+        // Placeholder timeline until the first input is opened.
+        let session = Session::with_placeholder(EventStore::demo());
+        let selected = session.timeline().first_id();
         Self {
-            events,
+            session,
             selected,
             ui: ui_state::UiState::default(),
-            dump_path: None,
-            dump_summary: None,
-            dump_report: None,
+            case_path: None,
+            allowlist: crate::triage::Allowlist::default(),
+            baselines: crate::baseline::Baselines::default(),
+            detection_cache: Default::default(),
         }
     }
 }
 
 impl eframe::App for LogAtlasApp {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if self.session.collect_hashes(false) {
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        }
         crate::ui::render_app(ctx, frame, self);
     }
 }
 
 impl LogAtlasApp {
-    pub fn load_minidump(&mut self, path: PathBuf) -> anyhow::Result<()> {
-        self.ui.last_error = None;
+    pub fn load_minidump(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
         let ingested = crate::ingest::minidump::ingest(&path)?;
-        self.dump_path = Some(path);
-        self.dump_summary = Some(ingested.summary);
-        self.dump_report = Some(ingested.report);
-        self.events = ingested.events;
-        self.selected = self.events.first_id();
+        Ok(self.add_dump(path, ingested))
+    }
+
+    /// ELF cores go through the same report as minidumps, so every dump tab works for them.
+    pub fn load_elf_core(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        let ingested = crate::ingest::elf_core::ingest(&path)?;
+        Ok(self.add_dump(path, ingested))
+    }
+
+    /// A WER report folder or zip opens as one case: the dump with `Report.wer` attached.
+    pub fn load_wer_archive(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        let ingested = crate::ingest::wer::ingest(&path)?;
+        Ok(self.add_dump(path, ingested))
+    }
+
+    fn add_dump(
+        &mut self,
+        path: PathBuf,
+        ingested: crate::ingest::minidump::IngestedMinidump,
+    ) -> SourceId {
+        let mut source = Source::new(path, ingested.events);
+        source.dump = Some(DumpData {
+            summary: ingested.summary,
+            report: ingested.report,
+        });
+        let id = self.session.add(source);
         self.ui.details_tab = DetailsTab::Overview;
+        self.reset_report_selection();
+        self.select_first(id);
+        self.push_process_tree_match(id);
+        id
+    }
+
+    pub fn load_jsonl(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        let ingested = crate::ingest::jsonl::ingest(&path)?;
+        Ok(self.add_log(path, ingested.events))
+    }
+
+    pub fn load_evtx(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        let ingested = crate::ingest::evtx::ingest(&path)?;
+        Ok(self.add_log(path, ingested.events))
+    }
+
+    pub fn load_syslog(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        let ingested = crate::ingest::syslog::ingest(&path)?;
+        Ok(self.add_log(path, ingested.events))
+    }
+
+    pub fn load_journal(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        let ingested = crate::ingest::journal::ingest(&path)?;
+        Ok(self.add_log(path, ingested.events))
+    }
+
+    /// Add a log source. Its Sysmon process tree is rebuilt so a loaded dump's process can be
+    /// located in it.
+    fn add_log(&mut self, path: PathBuf, events: EventStore) -> SourceId {
+        let mut source = Source::new(path, events);
+        source.process_tree = crate::ingest::sysmon::build_tree(&source.events);
+        let id = self.session.add(source);
+        self.ui.details_tab = DetailsTab::Event;
+        self.ui.selected_tree_node = None;
+        self.select_first(id);
+        self.push_process_tree_match(id);
+        id
+    }
+
+    /// Close every source and start an empty session.
+    pub fn clear_session(&mut self) {
+        self.session.clear();
+        self.case_path = None;
+        self.selected = None;
+        self.ui.details_tab = DetailsTab::Event;
+        self.ui.selected_tree_node = None;
+        self.reset_report_selection();
+    }
+
+    /// Select a timeline event. The Inspector follows it to its source's report.
+    pub fn select(&mut self, id: EventId) {
+        self.selected = Some(id);
+        let before = self.session.dump_source().map(|s| s.id);
+        self.session.focus = self.session.source_of(id).map(|s| s.id);
+        if self.session.dump_source().map(|s| s.id) != before {
+            self.reset_report_selection();
+        }
+    }
+
+    /// Show source `id`'s report in the Inspector.
+    pub fn focus_source(&mut self, id: SourceId) {
+        if self.session.focus != Some(id) {
+            self.session.focus = Some(id);
+            self.reset_report_selection();
+        }
+    }
+
+    fn select_first(&mut self, id: SourceId) {
+        self.selected = self.session.first_event(id);
+    }
+
+    /// Row selections index into one report; drop them when another report is shown.
+    fn reset_report_selection(&mut self) {
         self.ui.selected_exec_artifact = None;
         self.ui.selected_injected_region = None;
         self.ui.selected_module = None;
         self.ui.selected_thread = None;
         self.ui.selected_stack_thread = None;
         self.ui.stack_filter.clear();
+        self.ui.selected_ioc = None;
+    }
+
+    /// Show a report entity: switch to its tab, select its row and scroll to it. Filters that
+    /// would hide the row are cleared.
+    pub fn jump_to(&mut self, target: EntityRef) {
+        let Some(report) = self.session.dump_report() else {
+            return;
+        };
+        match target {
+            EntityRef::Module(idx) => {
+                self.ui.details_tab = DetailsTab::Modules;
+                self.ui.module_filter.clear();
+                self.ui.selected_module = Some(idx);
+            }
+            EntityRef::Thread(tid) => {
+                self.ui.details_tab = DetailsTab::Threads;
+                self.ui.thread_filter.clear();
+                self.ui.selected_thread = Some(tid);
+            }
+            EntityRef::Region(base) => {
+                self.ui.details_tab = DetailsTab::Memory;
+                self.ui.region_filter.clear();
+                self.ui.selected_injected_region =
+                    report.injected_regions.iter().position(|r| r.base == base);
+            }
+            EntityRef::ExecArtifact(idx) => {
+                self.ui.details_tab = DetailsTab::Processes;
+                self.ui.process_filter.clear();
+                self.ui.selected_exec_artifact = Some(idx);
+            }
+            // The module or injected region holding the address, if any.
+            EntityRef::Address(addr) => {
+                if let Some(idx) = report
+                    .modules
+                    .iter()
+                    .position(|m| addr >= m.base && addr - m.base < m.size)
+                {
+                    return self.jump_to(EntityRef::Module(idx));
+                }
+                let Some(r) = report
+                    .injected_regions
+                    .iter()
+                    .find(|r| addr >= r.base && addr - r.base < r.size)
+                else {
+                    return;
+                };
+                return self.jump_to(EntityRef::Region(r.base));
+            }
+        }
+        self.ui.scroll_to_selected = true;
+    }
+
+    /// Tree node of the shown minidump's process (`ProcessInfo::pid`) in the shown process
+    /// tree, if both are loaded.
+    pub fn dump_process_node(&self) -> Option<usize> {
+        let tree = self.session.process_tree()?;
+        let process = self.session.dump_report()?.process.as_ref()?;
+        tree.find_pid(process.pid?, process.create_time_unix)
+    }
+
+    /// Note on the timeline, as an event of the just-added source `id`, who launched the
+    /// minidump's process and what it spawned.
+    fn push_process_tree_match(&mut self, id: SourceId) {
+        let (Some(tree), Some(pid)) = (
+            self.session.process_tree(),
+            self.session
+                .dump_report()
+                .and_then(|r| r.process.as_ref())
+                .and_then(|p| p.pid),
+        ) else {
+            return;
+        };
+        let (severity, title, details) = match self.dump_process_node() {
+            Some(idx) => {
+                let chain = tree
+                    .ancestry(idx)
+                    .into_iter()
+                    .map(|i| {
+                        let n = &tree.nodes[i];
+                        format!(
+                            "  {} (pid {})",
+                            if n.image.is_empty() { "?" } else { &n.image },
+                            n.pid.map_or("?".into(), |p| p.to_string())
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let spawned = tree.descendants(idx);
+                let node = &tree.nodes[idx];
+                let mut details = format!(
+                    "Command line: {}\nLaunch chain (crashing process first):\n{chain}\nSpawned processes: {}",
+                    if node.command_line.is_empty() {
+                        "-"
+                    } else {
+                        &node.command_line
+                    },
+                    spawned.len()
+                );
+                for &d in spawned.iter().take(20) {
+                    let n = &tree.nodes[d];
+                    details.push_str(&format!(
+                        "\n  {} (pid {}) {}",
+                        n.image,
+                        n.pid.map_or("?".into(), |p| p.to_string()),
+                        n.command_line
+                    ));
+                }
+                (
+                    crate::model::Severity::Info,
+                    format!("Minidump process {pid} found in Sysmon process tree"),
+                    details,
+                )
+            }
+            None => (
+                crate::model::Severity::Warning,
+                format!("Minidump process {pid} not in Sysmon process tree"),
+                format!(
+                    "{} processes were reconstructed from Sysmon events; none had pid {pid}.",
+                    tree.nodes.len()
+                ),
+            ),
+        };
+        let t_ms = self.session.source(id).map_or(0, |s| s.events.max_t_ms());
+        self.session.push_event(
+            id,
+            crate::model::Event {
+                id: EventId(0),
+                t_ms,
+                severity,
+                title,
+                details,
+                source: "correlate::process_tree".into(),
+                timestamp: None,
+                attributes: vec![("pid".into(), pid.into())],
+                links: Vec::new(),
+            },
+        );
+    }
+
+    /// Open any supported input as a new source of the session, picking the ingester from
+    /// the file contents (or extension). Compressed files and archives are unpacked first. A
+    /// case file replaces the session instead; the focused source's id is returned.
+    pub fn open_path(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        self.ui.last_error = None;
+        let input = crate::ingest::compressed::decompress(&path)?;
+        if crate::case::is_case_file(&input.path) {
+            return self.open_case(&path, &input.path);
+        }
+        let id = self.open_format(input.path.clone())?;
+        if let Some(source) = self.session.source_mut(id) {
+            source.input = crate::case::InputRef::stat(&path);
+            source.hashing = source
+                .input
+                .as_ref()
+                .map(crate::case::InputRef::hash_in_background);
+        }
+        if !input.is_decompressed() {
+            return Ok(id);
+        }
+        let mut details = format!("Source: {}\n{}", path.display(), input.steps.join("\n"));
+        if !input.skipped.is_empty() {
+            details.push_str(&format!(
+                "\n\nNot opened (open the archive members separately):\n{}",
+                input.skipped.join("\n")
+            ));
+        }
+        let extracted = input.path.display().to_string();
+        let Some(source) = self.session.source_mut(id) else {
+            return Ok(id);
+        };
+        source.path = path;
+        let t_ms = source.events.max_t_ms();
+        let title = format!("Decompressed {}", source.name());
+        // Logs are fully read at ingestion; only a dump still needs its file (export).
+        if source.dump.is_some() {
+            source.decompressed = Some(input);
+        }
+        self.session.push_event(
+            id,
+            crate::model::Event {
+                id: EventId(0),
+                t_ms,
+                severity: crate::model::Severity::Info,
+                title,
+                details,
+                source: "ingest::compressed".into(),
+                timestamp: None,
+                attributes: vec![("path".into(), extracted.into())],
+                links: Vec::new(),
+            },
+        );
+        Ok(id)
+    }
+
+    fn open_format(&mut self, path: PathBuf) -> anyhow::Result<SourceId> {
+        use crate::ingest::InputFormat;
+        match crate::ingest::detect_format(&path)? {
+            InputFormat::Minidump => self.load_minidump(path),
+            InputFormat::ElfCore => self.load_elf_core(path),
+            InputFormat::WerArchive => self.load_wer_archive(path),
+            InputFormat::Jsonl => self.load_jsonl(path),
+            InputFormat::Evtx => self.load_evtx(path),
+            InputFormat::Syslog => self.load_syslog(path),
+            InputFormat::Journal => self.load_journal(path),
+        }
+    }
+
+    /// Write a redacted copy of the dump shown in the Inspector and log what was removed on
+    /// its timeline.
+    pub fn export_redacted_minidump(
+        &mut self,
+        output: PathBuf,
+        replace_manifest: bool,
+    ) -> anyhow::Result<()> {
+        let Some((source_id, input)) = self.session.dump_source().map(|s| (s.id, s.file.clone()))
+        else {
+            anyhow::bail!("no minidump loaded");
+        };
+        // A dump restored from a case has no decompressed copy yet.
+        let unpacked = crate::ingest::compressed::decompress(&input)?;
+        let input = unpacked.path.clone();
+        match crate::ingest::detect_format(&input)? {
+            crate::ingest::InputFormat::ElfCore => anyhow::bail!(
+                "redacted export rewrites minidumps only; the loaded dump is an ELF core"
+            ),
+            crate::ingest::InputFormat::WerArchive => anyhow::bail!(
+                "redacted export rewrites minidumps only; open the dump inside the WER report directly"
+            ),
+            _ => {}
+        }
+        // The save dialog has already confirmed replacing an existing output; the manifest
+        // next to it is only replaced when the caller asked.
+        let options = crate::export::RedactionOptions {
+            overwrite: true,
+            overwrite_manifest: replace_manifest,
+            ..Default::default()
+        };
+        let manifest = crate::export::write_redacted_minidump(&input, &output, &options)?;
+
+        let mut details = format!(
+            "Output: {}\nManifest: {}\nZeroed {} bytes in {} ranges; kept {} thread stacks and {} modules.\n",
+            manifest.output,
+            crate::export::manifest_path(&output).display(),
+            manifest.bytes_zeroed,
+            manifest.ranges.len(),
+            manifest.stacks_kept,
+            manifest.modules_kept
+        );
+        for category in [
+            crate::export::RedactionCategory::Heap,
+            crate::export::RedactionCategory::Environment,
+            crate::export::RedactionCategory::Secret,
+            crate::export::RedactionCategory::Pii,
+        ] {
+            let of_kind: Vec<_> = manifest
+                .ranges
+                .iter()
+                .filter(|r| r.category == category)
+                .collect();
+            if !of_kind.is_empty() {
+                details.push_str(&format!(
+                    "\n{category:?}: {} ranges, {} bytes",
+                    of_kind.len(),
+                    of_kind.iter().map(|r| r.len).sum::<u64>()
+                ));
+            }
+        }
+        let t_ms = self
+            .session
+            .source(source_id)
+            .map_or(0, |s| s.events.max_t_ms())
+            + 10;
+        let id = self.session.push_event(
+            source_id,
+            crate::model::Event {
+                id: EventId(0),
+                t_ms,
+                severity: crate::model::Severity::Info,
+                title: "Redacted minidump exported".into(),
+                details,
+                source: "export::minidump_redact".into(),
+                timestamp: None,
+                attributes: Vec::new(),
+                links: Vec::new(),
+            },
+        );
+        self.selected = id;
         Ok(())
     }
 }
@@ -1,7 +1,9 @@
 use crate::app::LogAtlasApp;
 use eframe::egui;
 
-pub fn run() -> eframe::Result<()> {
+/// Start the UI, opening each of `inputs` (any format `LogAtlasApp::open_path` accepts) as a
+/// source of the session.
+pub fn run(inputs: Vec<std::path::PathBuf>) -> eframe::Result<()> {
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("Log Atlas")
@@ -12,6 +14,21 @@ pub fn run() -> eframe::Result<()> {
     eframe::run_native(
         "Log Atlas",
         native_options,
-        Box::new(|_cc| Box::<LogAtlasApp>::default()),
+        Box::new(|_cc| {
+            let mut app = LogAtlasApp::default();
+            app.ui.query_history = crate::query::QueryHistory::load();
+            app.allowlist = crate::triage::Allowlist::load();
+            match crate::baseline::Baselines::load() {
+                Ok(baselines) => app.baselines = baselines,
+                // Left empty and unsaved, so the broken file is not overwritten.
+                Err(e) => app.ui.last_error = Some(format!("{e:#}")),
+            }
+            for path in inputs {
+                if let Err(e) = app.open_path(path) {
+                    app.ui.last_error = Some(e.to_string());
+                }
+            }
+            Box::new(app)
+        }),
     )
 }
@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use eframe::egui::Color32;
+use time::OffsetDateTime;
+
+use serde::{Deserialize, Serialize};
+
+use super::timeline::{MergedIndex, Timeline};
+use crate::case::{InputRef, InputStatus, Note, PendingHash};
+use crate::correlate::{DumpAnchor, EventMatch, Incident};
+use crate::ingest::compressed::Decompressed;
+use crate::model::{
+    Event, EventId, EventStore, MinidumpReport, MinidumpSummary, ProcessTree, next_generation,
+};
+use crate::triage::{Subject, Triage};
+
+/// Colors given to sources in load order (then reused).
+const SOURCE_COLORS: [Color32; 8] = [
+    Color32::from_rgb(86, 156, 214),
+    Color32::from_rgb(206, 145, 120),
+    Color32::from_rgb(106, 190, 110),
+    Color32::from_rgb(197, 134, 192),
+    Color32::from_rgb(220, 200, 100),
+    Color32::from_rgb(78, 201, 176),
+    Color32::from_rgb(240, 120, 120),
+    Color32::from_rgb(160, 160, 230),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourceId(pub u32);
+
+/// Parsed dump of a source: minidump, ELF core or WER report.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DumpData {
+    pub summary: MinidumpSummary,
+    pub report: MinidumpReport,
+}
+
+/// One opened input.
+pub struct Source {
+    pub id: SourceId,
+    /// Path as opened (the archive or compressed file, if any).
+    pub path: PathBuf,
+    /// File that was parsed: `path`, or its decompressed copy.
+    pub file: PathBuf,
+    pub color: Color32,
+    pub visible: bool,
+    /// The source's own events; `t_ms` is relative to the source's start.
+    pub events: EventStore,
+    pub dump: Option<DumpData>,
+    /// Process tree rebuilt from the source's Sysmon events.
+    pub process_tree: Option<ProcessTree>,
+    /// Decompressed copy of a dump; the temporary file lives as long as the source.
+    pub decompressed: Option<Decompressed>,
+    /// `path` as it was when read (size, modification time; the hash once saved in a case).
+    pub input: Option<InputRef>,
+    /// Hash of `input` being computed in the background: since it was read, or, for a
+    /// `Checking` source restored from a case, of the file now on disk.
+    pub hashing: Option<PendingHash>,
+    /// Whether the input on disk still is the analysed one, for sources restored from a case.
+    pub status: InputStatus,
+}
+
+impl Source {
+    pub fn new(path: PathBuf, events: EventStore) -> Self {
+        Self {
+            id: SourceId(0),
+            file: path.clone(),
+            path,
+            color: SOURCE_COLORS[0],
+            visible: true,
+            events,
+            dump: None,
+            process_tree: None,
+            decompressed: None,
+            input: None,
+            hashing: None,
+            status: InputStatus::Loaded,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.path.file_name().map_or_else(
+            || self.path.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        )
+    }
+
+    pub fn dump_report(&self) -> Option<&MinidumpReport> {
+        self.dump.as_ref().map(|d| &d.report)
+    }
+
+    pub fn kind(&self) -> &'static str {
+        if self.dump.is_some() { "dump" } else { "log" }
+    }
+
+    /// Wall-clock time of `t_ms == 0`: the earliest event timestamp, else the dump's
+    /// `TimeDateStamp` (dump events are synthetic steps right after it).
+    fn origin(&self) -> Option<OffsetDateTime> {
+        self.events
+            .by_time()
+            .iter()
+            .find_map(|&pos| self.events.at(pos).timestamp)
+            .or_else(|| {
+                let stamp = self.dump.as_ref()?.summary.time_date_stamp?;
+                OffsetDateTime::from_unix_timestamp(stamp.into()).ok()
+            })
+    }
+}
+
+/// Every opened input and their merged timeline. Loading a file adds a source instead of
+/// replacing the previous one.
+#[derive(Default)]
+pub struct Session {
+    pub sources: Vec<Source>,
+    /// Events of the visible sources on one clock (`timeline`): `t_ms` is relative to the
+    /// earliest source origin and ids are `source << 32 | event id`, so they stay stable
+    /// across rebuilds.
+    merged: MergedIndex,
+    /// Events shown while no input is open.
+    placeholder: EventStore,
+    /// Source the Inspector shows; follows the selected event.
+    pub focus: Option<SourceId>,
+    /// Log events correlated with the dumps, recomputed when a source is added.
+    pub incidents: Vec<Incident>,
+    /// Source of each dump index in `Incident::dumps`.
+    incident_dumps: Vec<SourceId>,
+    /// Log events matched to each dump, extended as sources are added.
+    dump_matches: HashMap<SourceId, Vec<EventMatch>>,
+    /// Analyst notes on the session, its events and detections.
+    notes: Vec<Note>,
+    /// Status and tags the analyst set; subjects without any are left out.
+    triage: HashMap<Subject, Triage>,
+    /// Changes with the sources, notes and triage; caches derived from them compare it.
+    generation: u64,
+    next_id: u32,
+}
+
+impl Session {
+    /// Session without inputs whose timeline shows `events`.
+    pub fn with_placeholder(events: EventStore) -> Self {
+        let mut session = Self {
+            placeholder: events,
+            ..Self::default()
+        };
+        session.rebuild();
+        session
+    }
+
+    /// Add `source`, giving it an id and color, merge it into the timeline and correlate it
+    /// with the other sources.
+    pub fn add(&mut self, source: Source) -> SourceId {
+        self.add_all(vec![source])[0]
+    }
+
+    /// Add several sources at once (a case being opened): the timeline is merged and the
+    /// sources correlated once for all of them. The last one gets the focus.
+    pub fn add_all(&mut self, sources: Vec<Source>) -> Vec<SourceId> {
+        let ids: Vec<SourceId> = sources
+            .into_iter()
+            .map(|mut source| {
+                self.next_id += 1;
+                source.id = SourceId(self.next_id);
+                source.color = SOURCE_COLORS[self.sources.len() % SOURCE_COLORS.len()];
+                let id = source.id;
+                self.sources.push(source);
+                id
+            })
+            .collect();
+        if let Some(&last) = ids.last() {
+            self.focus = Some(last);
+        }
+        self.rebuild();
+        self.correlate(&ids);
+        self.generation = next_generation();
+        ids
+    }
+
+    /// Apply the background hashes that have finished (all of them, waiting, with `wait`): a
+    /// source read in this session gets its input's `sha256`, a `Checking` one becomes
+    /// unchanged or changed. Whether any is still running.
+    pub fn collect_hashes(&mut self, wait: bool) -> bool {
+        let mut running = false;
+        for source in &mut self.sources {
+            let Some(pending) = &source.hashing else {
+                continue;
+            };
+            let result = if wait {
+                pending.wait()
+            } else if let Some(result) = pending.try_wait() {
+                result
+            } else {
+                running = true;
+                continue;
+            };
+            source.hashing = None;
+            let hash = result.ok().flatten();
+            let Some(input) = &mut source.input else {
+                continue;
+            };
+            if source.status == InputStatus::Checking {
+                source.status = if hash.is_some() && hash == input.sha256 {
+                    InputStatus::Unchanged
+                } else {
+                    InputStatus::Changed
+                };
+            } else {
+                input.sha256 = hash;
+            }
+        }
+        running
+    }
+
+    pub fn clear(&mut self) {
+        self.sources.clear();
+        self.focus = None;
+        self.placeholder = EventStore::default();
+        self.rebuild();
+        self.incidents.clear();
+        self.incident_dumps.clear();
+        self.dump_matches.clear();
+        self.notes.clear();
+        self.triage.clear();
+        self.generation = next_generation();
+    }
+
+    pub fn source(&self, id: SourceId) -> Option<&Source> {
+        self.sources.iter().find(|s| s.id == id)
+    }
+
+    pub fn source_mut(&mut self, id: SourceId) -> Option<&mut Source> {
+        self.generation = next_generation();
+        self.sources.iter_mut().find(|s| s.id == id)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Source a merged-timeline event came from.
+    pub fn source_of(&self, event: EventId) -> Option<&Source> {
+        self.source(SourceId((event.0 >> 32) as u32))
+    }
+
+    /// Event by merged id, also when its source is hidden.
+    pub fn event(&self, id: EventId) -> Option<&Event> {
+        self.source_of(id)?.events.get(Self::local_id(id))
+    }
+
+    /// Dump sources of an incident.
+    pub fn incident_sources<'a>(
+        &'a self,
+        incident: &'a Incident,
+    ) -> impl Iterator<Item = &'a Source> + 'a {
+        incident
+            .dumps
+            .iter()
+            .filter_map(|&d| self.source(*self.incident_dumps.get(d)?))
+    }
+
+    /// Merged-timeline id of the source's event `event`.
+    pub fn merged_id(source: SourceId, event: EventId) -> EventId {
+        EventId((u64::from(source.0) << 32) | event.0)
+    }
+
+    /// Id of a merged-timeline event within its source.
+    pub fn local_id(event: EventId) -> EventId {
+        EventId(event.0 & u64::from(u32::MAX))
+    }
+
+    /// What triage and notes of event `id` are attached to: the detection, for an event with
+    /// a `detection_id` attribute, else the event itself.
+    pub fn subject_of(&self, id: EventId) -> Subject {
+        let detection = self.event(id).and_then(detection_id);
+        match (detection, self.source_of(id)) {
+            (Some(detection), Some(source)) => Subject::Detection(source.id, detection.to_string()),
+            _ => Subject::Event(id),
+        }
+    }
+
+    /// Merged id of the timeline event of detection `detection` of `source`.
+    pub fn detection_event(&self, source: SourceId, detection: &str) -> Option<EventId> {
+        let ev = self
+            .source(source)?
+            .events
+            .iter()
+            .find(|ev| detection_id(ev) == Some(detection))?;
+        Some(Self::merged_id(source, ev.id))
+    }
+
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    pub fn add_note(&mut self, note: Note) {
+        self.notes.push(note);
+        self.generation = next_generation();
+    }
+
+    pub fn remove_note(&mut self, index: usize) {
+        if index < self.notes.len() {
+            self.notes.remove(index);
+            self.generation = next_generation();
+        }
+    }
+
+    pub fn notes_on<'a>(&'a self, subject: &'a Subject) -> impl Iterator<Item = &'a Note> {
+        self.notes
+            .iter()
+            .filter(move |n| n.about.as_ref() == Some(subject))
+    }
+
+    pub fn triage_of(&self, subject: &Subject) -> Option<&Triage> {
+        self.triage.get(subject)
+    }
+
+    pub fn triage(&self) -> &HashMap<Subject, Triage> {
+        &self.triage
+    }
+
+    /// Replace the notes and triage, as saved in a case.
+    pub fn restore_analysis(&mut self, notes: Vec<Note>, triage: HashMap<Subject, Triage>) {
+        self.notes = notes;
+        self.triage = triage;
+        self.generation = next_generation();
+    }
+
+    /// Change the triage of `subject`; a state back to the default is dropped.
+    pub fn update_triage(&mut self, subject: Subject, update: impl FnOnce(&mut Triage)) {
+        let triage = self.triage.entry(subject.clone()).or_default();
+        update(triage);
+        if triage.is_empty() {
+            self.triage.remove(&subject);
+        }
+        self.generation = next_generation();
+    }
+
+    /// First event of `id` on the merged timeline.
+    pub fn first_event(&self, id: SourceId) -> Option<EventId> {
+        let source = self.source(id)?;
+        Some(Self::merged_id(id, source.events.first_id()?))
+    }
+
+    pub fn set_visible(&mut self, id: SourceId, visible: bool) {
+        if let Some(source) = self.source_mut(id)
+            && source.visible != visible
+        {
+            source.visible = visible;
+            self.rebuild();
+        }
+    }
+
+    /// Add an event to source `id` (and to the merged timeline when the source is visible).
+    /// Returns its merged id.
+    pub fn push_event(&mut self, id: SourceId, event: Event) -> Option<EventId> {
+        let index = self.sources.iter().position(|s| s.id == id)?;
+        let events = &mut self.sources[index].events;
+        let local = events.push(event);
+        let pos = events.position(local)?;
+        let mut merged = std::mem::take(&mut self.merged);
+        merged.push(&self.stores(), index, pos);
+        self.merged = merged;
+        Some(Self::merged_id(id, local))
+    }
+
+    pub fn timeline(&self) -> Timeline<'_> {
+        Timeline {
+            session: self,
+            index: &self.merged,
+        }
+    }
+
+    /// Store of source index `index`; the placeholder's while there are no sources.
+    pub(super) fn store(&self, index: usize) -> &EventStore {
+        self.sources
+            .get(index)
+            .map_or(&self.placeholder, |s| &s.events)
+    }
+
+    /// Id of source index `index` (`SourceId(0)` for the placeholder).
+    pub(super) fn source_id(&self, index: usize) -> SourceId {
+        self.sources.get(index).map_or(SourceId(0), |s| s.id)
+    }
+
+    /// Source index (into `sources`) of a merged-timeline event.
+    pub(super) fn source_index(&self, event: EventId) -> Option<usize> {
+        let id = SourceId((event.0 >> 32) as u32);
+        if self.sources.is_empty() {
+            return (id.0 == 0).then_some(0);
+        }
+        self.sources.iter().position(|s| s.id == id)
+    }
+
+    fn stores(&self) -> Vec<&EventStore> {
+        if self.sources.is_empty() {
+            return vec![&self.placeholder];
+        }
+        self.sources.iter().map(|s| &s.events).collect()
+    }
+
+    /// Dump the Inspector shows: the focused source's, else the last loaded one.
+    pub fn dump_source(&self) -> Option<&Source> {
+        self.focus
+            .and_then(|id| self.source(id))
+            .filter(|s| s.dump.is_some())
+            .or_else(|| self.sources.iter().rev().find(|s| s.dump.is_some()))
+    }
+
+    pub fn dump_report(&self) -> Option<&MinidumpReport> {
+        self.dump_source()?.dump_report()
+    }
+
+    pub fn dump_summary(&self) -> Option<&MinidumpSummary> {
+        self.dump_source()?.dump.as_ref().map(|d| &d.summary)
+    }
+
+    /// Report of the source an event came from, for resolving its links.
+    pub fn report_of(&self, event: EventId) -> Option<&MinidumpReport> {
+        self.source_of(event)?.dump_report()
+    }
+
+    /// Process tree the Inspector shows: the focused source's, else the last loaded one.
+    pub fn process_tree(&self) -> Option<&ProcessTree> {
+        self.focus
+            .and_then(|id| self.source(id))
+            .and_then(|s| s.process_tree.as_ref())
+            .or_else(|| {
+                self.sources
+                    .iter()
+                    .rev()
+                    .find_map(|s| s.process_tree.as_ref())
+            })
+    }
+
+    /// Match the new sources `added` against the others (a new dump against every log, a new
+    /// log against every earlier dump) and regroup the incidents (`crate::correlate`).
+    fn correlate(&mut self, added: &[SourceId]) {
+        let window = crate::correlate::window_secs();
+        let logs = |new_only: bool| {
+            self.sources
+                .iter()
+                .filter(move |s| s.dump.is_none() && (!new_only || added.contains(&s.id)))
+                .flat_map(log_events)
+        };
+        let mut matches: Vec<(SourceId, Vec<EventMatch>)> = Vec::new();
+        for s in &self.sources {
+            if let Some(dump) = anchor(s) {
+                let events = logs(!added.contains(&s.id));
+                matches.push((s.id, crate::correlate::match_events(&dump, events, window)));
+            }
+        }
+        for (dump, found) in matches {
+            self.dump_matches.entry(dump).or_default().extend(found);
+        }
+
+        let (ids, anchors): (Vec<SourceId>, Vec<DumpAnchor>) = self
+            .sources
+            .iter()
+            .filter_map(|s| Some((s.id, anchor(s)?)))
+            .unzip();
+        let found: Vec<&[EventMatch]> = ids
+            .iter()
+            .map(|id| self.dump_matches.get(id).map_or(&[][..], Vec::as_slice))
+            .collect();
+        self.incidents = crate::correlate::incidents(&anchors, &found, window);
+        self.incident_dumps = ids;
+    }
+
+    /// Merge the visible sources' events again, each shifted by how far its origin is after
+    /// the earliest one. Sources without wall-clock time start at 0.
+    fn rebuild(&mut self) {
+        let origins: Vec<Option<OffsetDateTime>> =
+            self.sources.iter().map(Source::origin).collect();
+        let start = origins.iter().flatten().min().copied();
+        let mut offsets: Vec<Option<u64>> = self
+            .sources
+            .iter()
+            .zip(&origins)
+            .map(|(s, origin)| {
+                let offset = match (start, origin) {
+                    (Some(start), Some(origin)) => {
+                        (*origin - start).whole_milliseconds().max(0) as u64
+                    }
+                    _ => 0,
+                };
+                s.visible.then_some(offset)
+            })
+            .collect();
+        if self.sources.is_empty() {
+            offsets.push(Some(0));
+        }
+        let mut merged = std::mem::take(&mut self.merged);
+        merged.rebuild(&self.stores(), offsets);
+        self.merged = merged;
+    }
+}
+
+fn anchor(source: &Source) -> Option<DumpAnchor<'_>> {
+    source.dump.as_ref().map(|dump| DumpAnchor {
+        summary: &dump.summary,
+        report: &dump.report,
+    })
+}
+
+/// Events of `source` with their merged ids.
+fn log_events(source: &Source) -> impl Iterator<Item = (EventId, &Event)> {
+    source
+        .events
+        .iter()
+        .map(|ev| (Session::merged_id(source.id, ev.id), ev))
+}
+
+/// `Detection::id` of a detection event.
+fn detection_id(ev: &Event) -> Option<&str> {
+    ev.attributes
+        .iter()
+        .find(|(k, _)| k == "detection_id")
+        .and_then(|(_, v)| v.as_str())
+}
@@ -0,0 +1,191 @@
+//! The merged timeline: the visible sources' events on one clock, as references into each
+//! source's own `EventStore`. Nothing is copied; the index is a k-way merge of the sources'
+//! time indexes, redone when a source is added or shown and hidden.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::session::Session;
+use crate::model::{Event, EventId, EventStore, MinidumpReport, Severity, next_generation};
+use crate::query::EventRecord;
+
+// A merged position packs the source index into the high half and the position in the
+// source's store into the low half.
+const _: () = assert!(usize::BITS >= 64);
+
+fn pack(source: usize, pos: usize) -> usize {
+    (source << 32) | pos
+}
+
+fn unpack(packed: usize) -> (usize, usize) {
+    (packed >> 32, packed & u32::MAX as usize)
+}
+
+/// Merged time indexes over `Session::sources`.
+#[derive(Default)]
+pub(super) struct MergedIndex {
+    /// Merged positions ordered by merged `t_ms`; ties by source, then as in the source.
+    by_time: Vec<usize>,
+    by_severity: [Vec<usize>; 3],
+    /// Per source index: how far its events start after the session (`None` when hidden).
+    offsets: Vec<Option<u64>>,
+    generation: u64,
+}
+
+impl MergedIndex {
+    /// Merge the time indexes of `stores` (one per source index, `None` for hidden sources),
+    /// shifted by `offsets`.
+    pub(super) fn rebuild(&mut self, stores: &[&EventStore], offsets: Vec<Option<u64>>) {
+        let shown: Vec<(usize, &EventStore, u64)> = stores
+            .iter()
+            .zip(&offsets)
+            .enumerate()
+            .filter_map(|(i, (store, offset))| Some((i, *store, (*offset)?)))
+            .collect();
+        self.by_time = merge(&shown, EventStore::by_time);
+        for severity in [Severity::Info, Severity::Warning, Severity::High] {
+            self.by_severity[severity as usize] = merge(&shown, |s| s.by_severity(severity));
+        }
+        self.offsets = offsets;
+        self.generation = next_generation();
+    }
+
+    /// Add event `pos` of source index `source`, already pushed to its store. Appended when it
+    /// is not older than the latest merged event, else the sources are merged again.
+    pub(super) fn push(&mut self, stores: &[&EventStore], source: usize, pos: usize) {
+        let Some(offset) = self.offsets.get(source).copied().flatten() else {
+            return;
+        };
+        let t_ms = |packed: usize| {
+            let (s, p) = unpack(packed);
+            stores[s].at(p).t_ms + self.offsets[s].unwrap_or(0)
+        };
+        let event = stores[source].at(pos);
+        let latest = self.by_time.last().map(|&p| t_ms(p));
+        if latest.is_none_or(|t| t <= event.t_ms + offset) {
+            self.by_time.push(pack(source, pos));
+            self.by_severity[event.severity as usize].push(pack(source, pos));
+            self.generation = next_generation();
+        } else {
+            let offsets = std::mem::take(&mut self.offsets);
+            self.rebuild(stores, offsets);
+        }
+    }
+}
+
+/// Merge the time-ordered position lists `positions(store)` of `shown` into merged positions.
+fn merge<'a>(
+    shown: &[(usize, &'a EventStore, u64)],
+    positions: impl Fn(&'a EventStore) -> &'a [usize],
+) -> Vec<usize> {
+    let lists: Vec<&[usize]> = shown.iter().map(|(_, store, _)| positions(store)).collect();
+    let t_ms = |k: usize, i: usize| shown[k].1.at(lists[k][i]).t_ms + shown[k].2;
+    let mut out = Vec::with_capacity(lists.iter().map(|l| l.len()).sum());
+    let mut heap: BinaryHeap<Reverse<(u64, usize, usize)>> = lists
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(k, _)| Reverse((t_ms(k, 0), k, 0)))
+        .collect();
+    while let Some(Reverse((_, k, i))) = heap.pop() {
+        out.push(pack(shown[k].0, lists[k][i]));
+        if i + 1 < lists[k].len() {
+            heap.push(Reverse((t_ms(k, i + 1), k, i + 1)));
+        }
+    }
+    out
+}
+
+/// An event on the merged timeline.
+#[derive(Clone, Copy)]
+pub struct TimelineEvent<'a> {
+    /// Merged id (`Session::merged_id`).
+    pub id: EventId,
+    /// Relative to the session's origin.
+    pub t_ms: u64,
+    /// The event as its source holds it (source-local id and `t_ms`).
+    pub event: &'a Event,
+}
+
+/// Read access to the merged timeline, from `Session::timeline`. Positions are opaque: take
+/// them from `by_time`, `by_severity` or `time_range`.
+#[derive(Clone, Copy)]
+pub struct Timeline<'a> {
+    pub(super) session: &'a Session,
+    pub(super) index: &'a MergedIndex,
+}
+
+impl<'a> Timeline<'a> {
+    pub fn len(&self) -> usize {
+        self.index.by_time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.by_time.is_empty()
+    }
+
+    /// Positions ordered by merged `t_ms`.
+    pub fn by_time(&self) -> &'a [usize] {
+        &self.index.by_time
+    }
+
+    /// Positions of `severity` events ordered by merged `t_ms`.
+    pub fn by_severity(&self, severity: Severity) -> &'a [usize] {
+        &self.index.by_severity[severity as usize]
+    }
+
+    pub fn at(&self, pos: usize) -> TimelineEvent<'a> {
+        let (source, local) = unpack(pos);
+        let event = self.session.store(source).at(local);
+        TimelineEvent {
+            id: Session::merged_id(self.session.source_id(source), event.id),
+            t_ms: event.t_ms + self.index.offsets[source].unwrap_or(0),
+            event,
+        }
+    }
+
+    /// Event by merged id, when its source is visible.
+    pub fn get(&self, id: EventId) -> Option<TimelineEvent<'a>> {
+        let source = self.session.source_index(id)?;
+        let offset = self.index.offsets.get(source).copied().flatten()?;
+        let event = self.session.store(source).get(Session::local_id(id))?;
+        Some(TimelineEvent {
+            id,
+            t_ms: event.t_ms + offset,
+            event,
+        })
+    }
+
+    /// The merged `t_ms` range `[from, to)` of `positions` (a time-ordered list).
+    pub fn time_range(&self, positions: &'a [usize], from: u64, to: u64) -> &'a [usize] {
+        let lo = positions.partition_point(|&p| self.at(p).t_ms < from);
+        let hi = positions.partition_point(|&p| self.at(p).t_ms < to);
+        &positions[lo..hi.max(lo)]
+    }
+
+    pub fn max_t_ms(&self) -> u64 {
+        self.index.by_time.last().map_or(0, |&p| self.at(p).t_ms)
+    }
+
+    pub fn first_id(&self) -> Option<EventId> {
+        self.index.by_time.first().map(|&p| self.at(p).id)
+    }
+
+    /// Changes whenever the merged events change; caches of filtered rows compare it.
+    pub fn generation(&self) -> u64 {
+        self.index.generation
+    }
+
+    /// Query record of the event at `pos`, using its source's search index.
+    pub fn record(&self, pos: usize, report: Option<&'a MinidumpReport>) -> EventRecord<'a> {
+        let (source, local) = unpack(pos);
+        let ev = self.at(pos);
+        EventRecord {
+            event: ev.event,
+            id: ev.id,
+            t_ms: ev.t_ms,
+            report,
+            search_text: Some(self.session.store(source).search_text(local)),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{LogAtlasApp, Source, SourceId};
+use crate::model::attack::AttackMapping;
+use crate::query::DetectionRecord;
+use crate::triage::{Subject, TriageStatus, TriagedDetection};
+
+/// Triaged detections per source, kept while the session, the allowlist and the baselines
+/// stay at the generations they were computed from.
+#[derive(Default)]
+pub struct DetectionCache {
+    generations: (u64, u64, u64),
+    sources: HashMap<SourceId, CachedDetections>,
+}
+
+struct CachedDetections {
+    list: Rc<[TriagedDetection]>,
+    by_id: HashMap<String, usize>,
+}
+
+impl LogAtlasApp {
+    /// Detections of `source`'s dump with their ids, notes and effective triage (see
+    /// `TriagedDetection`); detections the product baseline covers are downgraded or suppressed.
+    pub fn detections(&self, source: &Source) -> Rc<[TriagedDetection]> {
+        self.cached_detections(source, |cached| cached.list.clone())
+    }
+
+    /// Effective status of `subject` and, when an allowlist entry set it, the entry's reason.
+    pub fn effective_triage(&self, subject: &Subject) -> (TriageStatus, Option<String>) {
+        if let Subject::Detection(source, id) = subject
+            && let Some(source) = self.session.source(*source)
+            && let Some(found) = self.cached_detections(source, |cached| {
+                let d = &cached.list[*cached.by_id.get(id)?];
+                Some((d.status, d.allowlisted.clone()))
+            })
+        {
+            return found;
+        }
+        let status = self.session.triage_of(subject).map(|t| t.status);
+        (status.unwrap_or_default(), None)
+    }
+
+    fn cached_detections<R>(
+        &self,
+        source: &Source,
+        read: impl FnOnce(&CachedDetections) -> R,
+    ) -> R {
+        let generations = (
+            self.session.generation(),
+            self.allowlist.generation(),
+            self.baselines.generation(),
+        );
+        let mut cache = self.detection_cache.borrow_mut();
+        if cache.generations != generations {
+            cache.sources.clear();
+            cache.generations = generations;
+        }
+        let cached = cache.sources.entry(source.id).or_insert_with(|| {
+            let list: Rc<[TriagedDetection]> = self.triage_detections(source).into();
+            let by_id = list
+                .iter()
+                .enumerate()
+                .map(|(i, d)| (d.id.clone(), i))
+                .collect();
+            CachedDetections { list, by_id }
+        });
+        read(cached)
+    }
+
+    fn triage_detections(&self, source: &Source) -> Vec<TriagedDetection> {
+        let Some(report) = source.dump_report() else {
+            return Vec::new();
+        };
+        let baseline = self.baselines.for_report(report);
+        report
+            .detections()
+            .into_iter()
+            .map(|mut detection| {
+                let id = detection.id();
+                let subject = Subject::Detection(source.id, id.clone());
+                let triage = self
+                    .session
+                    .triage_of(&subject)
+                    .cloned()
+                    .unwrap_or_default();
+                let mut status = triage.status;
+                let mut allow_entry = None;
+                if status == TriageStatus::New {
+                    let record = DetectionRecord {
+                        detection: &detection,
+                        report,
+                        id: &id,
+                        status,
+                        tags: &triage.tags,
+                    };
+                    allow_entry = self.allowlist.matching(&record);
+                    if let Some(entry) = allow_entry.and_then(|i| self.allowlist.entries.get(i)) {
+                        status = entry.status;
+                    }
+                }
+                let baseline = baseline.as_ref().filter(|b| b.covers(&detection, report));
+                if let Some(baseline) = baseline {
+                    status = baseline.apply(&mut detection, status);
+                }
+                TriagedDetection {
+                    source: source.name(),
+                    notes: self
+                        .session
+                        .notes_on(&subject)
+                        .map(|n| n.text.clone())
+                        .collect(),
+                    allowlisted: allow_entry
+                        .and_then(|i| self.allowlist.entries.get(i))
+                        .map(|e| e.reason.clone()),
+                    id,
+                    evidence: detection.evidence(report),
+                    attack: AttackMapping::of(&detection.techniques),
+                    detection,
+                    status,
+                    tags: triage.tags,
+                    allow_entry,
+                    baseline: baseline.map(|b| b.label.clone()),
+                }
+            })
+            .collect()
+    }
+}
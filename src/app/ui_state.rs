@@ -1,5 +1,12 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+use crate::query::{QueryFilter, QueryHistory};
+use crate::triage::TriageStatus;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DetailsTab {
+    #[default]
     Event,
     Overview,
     Processes,
@@ -9,28 +16,101 @@ pub enum DetailsTab {
     Stacks,
     Exception,
     Detections,
+    Incidents,
+    Notes,
+    Iocs,
+    Sensitive,
+    ProcessTree,
+    Diff,
+}
+
+/// Timeline rows (store positions, time-ordered) that pass the filter. Rebuilt only when the
+/// event store's generation or the filter text changes, not every frame.
+#[derive(Default)]
+pub struct FilteredRows {
+    pub key: Option<(u64, String)>,
+    pub rows: Vec<usize>,
 }
 
-impl Default for DetailsTab {
+/// Allowlist entry being written in the Detections tab.
+pub struct AllowlistDraft {
+    pub status: TriageStatus,
+    pub query: String,
+    pub reason: String,
+}
+
+impl Default for AllowlistDraft {
     fn default() -> Self {
-        Self::Event
+        Self {
+            status: TriageStatus::FalsePositive,
+            query: String::new(),
+            reason: String::new(),
+        }
     }
 }
 
 #[derive(Default)]
 pub struct UiState {
-    pub filter: String,
+    pub filter: QueryFilter,
+    pub timeline_rows: FilteredRows,
+    /// Saved queries of every filter box, loaded at startup.
+    pub query_history: QueryHistory,
     pub show_about: bool,
     pub last_error: Option<String>,
 
     pub details_tab: DetailsTab,
-    pub process_filter: String,
+    pub process_filter: QueryFilter,
     pub selected_exec_artifact: Option<usize>,
     pub selected_injected_region: Option<usize>,
-    pub module_filter: String,
-    pub thread_filter: String,
+    pub region_filter: QueryFilter,
+    /// Scroll the selected row of the current tab into view on the next frame (set by
+    /// `LogAtlasApp::jump_to`).
+    pub scroll_to_selected: bool,
+    pub module_filter: QueryFilter,
+    /// Dump the Diff tab compares the shown dump with; the first other dump when unset.
+    pub diff_before: Option<crate::app::SourceId>,
+    /// Show only modules missing from the dump's baseline.
+    pub unknown_modules_only: bool,
+    pub thread_filter: QueryFilter,
     pub selected_module: Option<usize>,
     pub selected_thread: Option<u32>,
-    pub stack_filter: String,
+    pub stack_filter: QueryFilter,
     pub selected_stack_thread: Option<u32>,
+    pub detection_filter: QueryFilter,
+    /// Show only detections with this status; `None` shows all but dismissed ones, unless
+    /// `show_dismissed`.
+    pub detection_status_filter: Option<TriageStatus>,
+    pub show_dismissed: bool,
+    pub allowlist_draft: AllowlistDraft,
+    /// Open the allowlist section on the next frame (set when a draft is prefilled).
+    pub reveal_allowlist: bool,
+    pub incident_filter: QueryFilter,
+    pub ioc_filter: QueryFilter,
+    pub ioc_kind_filter: Option<crate::model::IocKind>,
+    pub selected_ioc: Option<usize>,
+    pub sensitive_kind_filter: Option<crate::model::SecretKind>,
+    pub annotation_filter: QueryFilter,
+    pub process_tree_filter: QueryFilter,
+    pub selected_tree_node: Option<usize>,
+    /// Text of the note being written in the Event or Notes tab.
+    pub note_draft: String,
+}
+
+impl UiState {
+    /// Every filter box with the scope its saved queries are stored under.
+    pub fn filters_mut(&mut self) -> [(&'static str, &mut QueryFilter); 11] {
+        [
+            ("timeline", &mut self.filter),
+            ("artifacts", &mut self.process_filter),
+            ("regions", &mut self.region_filter),
+            ("modules", &mut self.module_filter),
+            ("threads", &mut self.thread_filter),
+            ("stacks", &mut self.stack_filter),
+            ("detections", &mut self.detection_filter),
+            ("incidents", &mut self.incident_filter),
+            ("iocs", &mut self.ioc_filter),
+            ("annotations", &mut self.annotation_filter),
+            ("processes", &mut self.process_tree_filter),
+        ]
+    }
 }
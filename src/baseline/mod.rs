@@ -0,0 +1,613 @@
+//! Known-good baselines: per product version, the modules a healthy process loads and the
+//! command lines it is known to run. Detections about baseline modules and command lines are
+//! suppressed or downgraded, and modules missing from the baseline are reported.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Detection, EntityRef, MinidumpReport, ModuleInfo, Severity, next_generation};
+use crate::query::glob_match;
+use crate::triage::TriageStatus;
+
+const BASELINE_ENV_VAR: &str = "LOG_ATLAS_BASELINE";
+
+/// Every product baseline, stored as one JSON object in `LOG_ATLAS_BASELINE`, else
+/// `log-atlas/baseline.json` in the user's config directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baselines {
+    pub products: Vec<ProductBaseline>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Changes with the baselines, like `EventStore::generation`.
+    #[serde(skip)]
+    generation: u64,
+}
+
+/// What is known good for one product version.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProductBaseline {
+    /// File name of the main executable (the dump's first module); `*` / `?` wildcards,
+    /// case-insensitive.
+    pub product: String,
+    /// File version of the main executable, with wildcards; any version when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub action: BaselineAction,
+    #[serde(default)]
+    pub modules: Vec<ModuleEntry>,
+    /// Known-good command lines of execution artifacts; wildcards, case-insensitive.
+    #[serde(default)]
+    pub command_lines: Vec<String>,
+}
+
+impl ProductBaseline {
+    fn applies_to(&self, product: &str, version: Option<&str>) -> bool {
+        glob(&self.product, product)
+            && self
+                .version
+                .as_ref()
+                .is_none_or(|v| version.is_some_and(|dv| glob(v, dv)))
+    }
+}
+
+/// What happens to a detection whose modules and artifacts are all in the baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BaselineAction {
+    /// One severity step lower (high to warning, warning to info).
+    #[default]
+    Downgrade,
+    /// Status `Ignored`, unless the analyst or the allowlist set one.
+    Suppress,
+}
+
+impl BaselineAction {
+    pub const ALL: [BaselineAction; 2] = [BaselineAction::Downgrade, BaselineAction::Suppress];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BaselineAction::Downgrade => "Downgrade",
+            BaselineAction::Suppress => "Suppress",
+        }
+    }
+}
+
+/// A known-good module. Unset fields match anything.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleEntry {
+    /// File name, with wildcards.
+    pub name: String,
+    /// Full path, with wildcards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// File version, with wildcards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// PE `TimeDateStamp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u32>,
+    /// Image size in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// `ModuleInfo::debug_id`: identifies the exact build.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_id: Option<String>,
+}
+
+impl ModuleEntry {
+    /// Entry pinning `m` by name, version, timestamp, size and debug id; any path, so an
+    /// installer unpacked to a fresh temp folder still matches.
+    fn of(m: &ModuleInfo) -> Self {
+        Self {
+            name: file_name(&m.name).to_string(),
+            path: None,
+            version: m.file_version.clone(),
+            timestamp: Some(m.time_date_stamp).filter(|t| *t != 0),
+            size: Some(m.size),
+            debug_id: m.debug_id.clone(),
+        }
+    }
+
+    pub fn matches(&self, m: &ModuleInfo) -> bool {
+        glob(&self.name, file_name(&m.name))
+            && self.path.as_ref().is_none_or(|p| glob(p, &m.name))
+            && self
+                .version
+                .as_ref()
+                .is_none_or(|v| m.file_version.as_ref().is_some_and(|mv| glob(v, mv)))
+            && self.timestamp.is_none_or(|t| t == m.time_date_stamp)
+            && self.size.is_none_or(|s| s == m.size)
+            && self.debug_id.as_ref().is_none_or(|d| {
+                m.debug_id
+                    .as_ref()
+                    .is_some_and(|md| md.eq_ignore_ascii_case(d))
+            })
+    }
+}
+
+/// Whether a module is in the baseline of its dump.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModuleBaseline {
+    Known,
+    Unknown,
+}
+
+impl ModuleBaseline {
+    pub fn label(self) -> &'static str {
+        match self {
+            ModuleBaseline::Known => "known",
+            ModuleBaseline::Unknown => "unknown",
+        }
+    }
+}
+
+/// The product baselines that apply to one dump.
+pub struct Applied<'a> {
+    /// Product and version of the dump, e.g. `app.exe 1.2.3.4`.
+    pub label: String,
+    products: Vec<&'a ProductBaseline>,
+}
+
+impl Applied<'_> {
+    pub fn module(&self, m: &ModuleInfo) -> ModuleBaseline {
+        if self
+            .products
+            .iter()
+            .any(|p| p.modules.iter().any(|e| e.matches(m)))
+        {
+            ModuleBaseline::Known
+        } else {
+            ModuleBaseline::Unknown
+        }
+    }
+
+    pub fn command_line(&self, command_line: &str) -> bool {
+        self.command_lines()
+            .any(|pattern| glob(pattern, command_line))
+    }
+
+    /// Known-good command line patterns of every applying baseline.
+    pub fn command_lines(&self) -> impl Iterator<Item = &str> {
+        self.products
+            .iter()
+            .flat_map(|p| &p.command_lines)
+            .map(String::as_str)
+    }
+
+    /// Suppress when any applying baseline says so.
+    pub fn action(&self) -> BaselineAction {
+        if self
+            .products
+            .iter()
+            .any(|p| p.action == BaselineAction::Suppress)
+        {
+            BaselineAction::Suppress
+        } else {
+            BaselineAction::Downgrade
+        }
+    }
+
+    /// Whether `det` is about baseline entities only: it links at least one module or
+    /// execution artifact, and every one of them is known.
+    pub fn covers(&self, det: &Detection, report: &MinidumpReport) -> bool {
+        let mut any = false;
+        for link in &det.links {
+            let known = match *link {
+                EntityRef::Module(i) => report
+                    .modules
+                    .get(i)
+                    .is_some_and(|m| self.module(m) == ModuleBaseline::Known),
+                EntityRef::ExecArtifact(i) => report
+                    .exec_artifacts
+                    .get(i)
+                    .is_some_and(|a| self.command_line(&a.command_line)),
+                EntityRef::Thread(_) | EntityRef::Region(_) | EntityRef::Address(_) => continue,
+            };
+            if !known {
+                return false;
+            }
+            any = true;
+        }
+        any
+    }
+
+    /// Apply `action()` to a detection this baseline covers: lower its severity, or ignore it
+    /// when nobody triaged it yet. Returns the resulting status.
+    pub fn apply(&self, det: &mut Detection, status: TriageStatus) -> TriageStatus {
+        match self.action() {
+            BaselineAction::Downgrade => {
+                det.severity = downgrade(det.severity);
+                status
+            }
+            BaselineAction::Suppress if status == TriageStatus::New => TriageStatus::Ignored,
+            BaselineAction::Suppress => status,
+        }
+    }
+
+    /// Indexes of the dump's modules that are not in the baseline.
+    pub fn unknown_modules(&self, report: &MinidumpReport) -> Vec<usize> {
+        report
+            .modules
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| self.module(m) == ModuleBaseline::Unknown)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl Baselines {
+    /// Load the baseline file; a missing file gives no baselines, an unreadable one an error.
+    pub fn load() -> Result<Self> {
+        let path = crate::util::config_file(BASELINE_ENV_VAR, "baseline.json");
+        let mut baselines = match &path {
+            Some(p) if p.exists() => {
+                let text =
+                    std::fs::read_to_string(p).with_context(|| format!("read {}", p.display()))?;
+                serde_json::from_str(&text)
+                    .with_context(|| format!("read baseline {}", p.display()))?
+            }
+            _ => Self::default(),
+        };
+        baselines.path = path;
+        baselines.generation = next_generation();
+        Ok(baselines)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Baselines of the dump's product (main executable) and version, if any.
+    pub fn for_report(&self, report: &MinidumpReport) -> Option<Applied<'_>> {
+        let (product, version) = product_of(report)?;
+        let products: Vec<&ProductBaseline> = self
+            .products
+            .iter()
+            .filter(|p| p.applies_to(product, version))
+            .collect();
+        if products.is_empty() {
+            return None;
+        }
+        Some(Applied {
+            label: label(product, version),
+            products,
+        })
+    }
+
+    /// Add the dump's modules that are not known yet to the baseline of its exact product
+    /// version (created if needed). Returns how many were added.
+    pub fn record(&mut self, report: &MinidumpReport) -> Result<usize> {
+        let known: Vec<bool> = match self.for_report(report) {
+            Some(applied) => report
+                .modules
+                .iter()
+                .map(|m| applied.module(m) == ModuleBaseline::Known)
+                .collect(),
+            None => vec![false; report.modules.len()],
+        };
+        let product = self.exact_mut(report)?;
+        let mut added = 0;
+        for (m, known) in report.modules.iter().zip(known) {
+            if !known {
+                product.modules.push(ModuleEntry::of(m));
+                added += 1;
+            }
+        }
+        self.generation = next_generation();
+        self.write()?;
+        Ok(added)
+    }
+
+    /// Add a known-good command line pattern to the baseline of the dump's product version.
+    pub fn add_command_line(&mut self, report: &MinidumpReport, pattern: &str) -> Result<()> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            bail!("a command line pattern is empty");
+        }
+        let product = self.exact_mut(report)?;
+        if !product.command_lines.iter().any(|c| c == pattern) {
+            product.command_lines.push(pattern.to_string());
+        }
+        self.generation = next_generation();
+        self.write()
+    }
+
+    /// Remove a command line pattern from every baseline that applies to the dump.
+    pub fn remove_command_line(&mut self, report: &MinidumpReport, pattern: &str) -> Result<()> {
+        let Some((product, version)) = product_of(report) else {
+            return Ok(());
+        };
+        for p in &mut self.products {
+            if p.applies_to(product, version) {
+                p.command_lines.retain(|c| c != pattern);
+            }
+        }
+        self.generation = next_generation();
+        self.write()
+    }
+
+    pub fn set_action(&mut self, report: &MinidumpReport, action: BaselineAction) -> Result<()> {
+        self.exact_mut(report)?.action = action;
+        self.generation = next_generation();
+        self.write()
+    }
+
+    /// Index of the baseline recorded for exactly the dump's product and version.
+    fn exact_index(&self, report: &MinidumpReport) -> Option<usize> {
+        let (product, version) = product_of(report)?;
+        self.products.iter().position(|p| {
+            p.product.eq_ignore_ascii_case(product) && p.version.as_deref() == version
+        })
+    }
+
+    fn exact_mut(&mut self, report: &MinidumpReport) -> Result<&mut ProductBaseline> {
+        let Some((product, version)) = product_of(report) else {
+            bail!("the dump has no modules to name its product");
+        };
+        let index = match self.exact_index(report) {
+            Some(i) => i,
+            None => {
+                self.products.push(ProductBaseline {
+                    product: product.to_string(),
+                    version: version.map(str::to_string),
+                    action: BaselineAction::default(),
+                    modules: Vec::new(),
+                    command_lines: Vec::new(),
+                });
+                self.products.len() - 1
+            }
+        };
+        Ok(&mut self.products[index])
+    }
+
+    fn write(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let text = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, text + "\n").with_context(|| format!("write {}", path.display()))
+    }
+}
+
+/// Main executable file name and version of a dump.
+pub fn product_of(report: &MinidumpReport) -> Option<(&str, Option<&str>)> {
+    let main = report.modules.first()?;
+    Some((file_name(&main.name), main.file_version.as_deref()))
+}
+
+/// `app.exe 1.2.3.4`, or just the name without a version.
+pub fn label(product: &str, version: Option<&str>) -> String {
+    match version {
+        Some(v) => format!("{product} {v}"),
+        None => product.to_string(),
+    }
+}
+
+/// One step lower, for `BaselineAction::Downgrade`.
+pub fn downgrade(severity: Severity) -> Severity {
+    match severity {
+        Severity::High => Severity::Warning,
+        Severity::Warning | Severity::Info => Severity::Info,
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
+
+fn glob(pattern: &str, text: &str) -> bool {
+    glob_match(&pattern.to_lowercase(), &text.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ExecArtifactEncoding, ProcessExecArtifact};
+
+    use super::*;
+
+    fn module(name: &str, version: &str, size: u64) -> ModuleInfo {
+        ModuleInfo {
+            name: name.to_string(),
+            base: 0,
+            size,
+            checksum: 0,
+            time_date_stamp: 0x5f00_0000,
+            file_version: Some(version.to_string()),
+            debug_id: Some("ABCD1".to_string()),
+        }
+    }
+
+    fn report() -> MinidumpReport {
+        MinidumpReport {
+            modules: vec![
+                module("C:\\Program Files\\App\\App.exe", "1.2.3.4", 0x1000),
+                module("C:\\Program Files\\App\\plugin.dll", "1.2.3.4", 0x2000),
+                module(
+                    "C:\\Users\\x\\AppData\\Local\\Temp\\evil.dll",
+                    "0.1",
+                    0x3000,
+                ),
+            ],
+            exec_artifacts: vec![ProcessExecArtifact {
+                image: "cmd.exe".into(),
+                command_line: "cmd.exe /c app-updater.exe --check".into(),
+                encoding: ExecArtifactEncoding::Ascii,
+                address: None,
+                decoded_via: None,
+                children: Vec::new(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn product(product: &str, version: Option<&str>, modules: Vec<ModuleEntry>) -> ProductBaseline {
+        ProductBaseline {
+            product: product.to_string(),
+            version: version.map(str::to_string),
+            action: BaselineAction::Downgrade,
+            modules,
+            command_lines: vec!["cmd.exe /c app-updater.exe *".into()],
+        }
+    }
+
+    fn entry(name: &str) -> ModuleEntry {
+        ModuleEntry {
+            name: name.to_string(),
+            path: None,
+            version: None,
+            timestamp: None,
+            size: None,
+            debug_id: None,
+        }
+    }
+
+    fn detection(severity: Severity, links: Vec<EntityRef>) -> Detection {
+        Detection {
+            rule: "test".into(),
+            key: String::new(),
+            severity,
+            title: String::new(),
+            details: String::new(),
+            techniques: Vec::new(),
+            confidence: 50,
+            links,
+        }
+    }
+
+    #[test]
+    fn baselines_apply_by_product_and_version_pattern() {
+        let report = report();
+        let baselines = |products| Baselines {
+            products,
+            ..Default::default()
+        };
+        for (pattern, version, applies) in [
+            ("app.EXE", None, true),
+            ("app*", Some("1.2.*"), true),
+            ("app.exe", Some("1.2.3.5"), false),
+            ("other.exe", None, false),
+        ] {
+            let b = baselines(vec![product(pattern, version, Vec::new())]);
+            assert_eq!(
+                b.for_report(&report).is_some(),
+                applies,
+                "{pattern} {version:?}"
+            );
+        }
+        let b = baselines(vec![product("app.exe", Some("1.2.3.4"), Vec::new())]);
+        assert_eq!(b.for_report(&report).unwrap().label, "App.exe 1.2.3.4");
+    }
+
+    #[test]
+    fn module_entries_match_every_pinned_field() {
+        let m = &report().modules[1];
+        assert!(entry("PLUGIN.dll").matches(m));
+        assert!(entry("plug*").matches(m));
+        assert!(!entry("other.dll").matches(m));
+        let pinned = ModuleEntry::of(m);
+        assert!(pinned.matches(m));
+        // A rebuilt module with the same name and version is not the same build.
+        let mut rebuilt = m.clone();
+        rebuilt.debug_id = Some("ABCD2".into());
+        assert!(!pinned.matches(&rebuilt));
+        let mut moved = m.clone();
+        moved.name = "D:\\elsewhere\\plugin.dll".into();
+        assert!(pinned.matches(&moved));
+        let at_path = ModuleEntry {
+            path: Some("c:\\program files\\*".into()),
+            ..entry("plugin.dll")
+        };
+        assert!(at_path.matches(m));
+        assert!(!at_path.matches(&moved));
+    }
+
+    #[test]
+    fn detections_are_covered_only_when_every_entity_is_known() {
+        let report = report();
+        let baselines = Baselines {
+            products: vec![product(
+                "app.exe",
+                None,
+                vec![entry("app.exe"), entry("plugin.dll")],
+            )],
+            ..Default::default()
+        };
+        let applied = baselines.for_report(&report).unwrap();
+        assert_eq!(applied.unknown_modules(&report), vec![2]);
+
+        let covers = |links| applied.covers(&detection(Severity::High, links), &report);
+        assert!(covers(vec![EntityRef::Module(1), EntityRef::Address(0x10)]));
+        assert!(covers(vec![EntityRef::ExecArtifact(0)]));
+        assert!(!covers(vec![EntityRef::Module(1), EntityRef::Module(2)]));
+        assert!(!covers(vec![EntityRef::Module(9)]));
+        // Nothing to vouch for: thread and address evidence alone is not covered.
+        assert!(!covers(vec![
+            EntityRef::Thread(1),
+            EntityRef::Address(0x10)
+        ]));
+        assert!(!covers(Vec::new()));
+    }
+
+    #[test]
+    fn covered_detections_are_downgraded_or_suppressed() {
+        let report = report();
+        let mut baselines = Baselines {
+            products: vec![product("app.exe", None, vec![entry("plugin.dll")])],
+            ..Default::default()
+        };
+        let apply = |baselines: &Baselines, severity, status| {
+            let mut det = detection(severity, vec![EntityRef::Module(1)]);
+            let status = baselines
+                .for_report(&report)
+                .unwrap()
+                .apply(&mut det, status);
+            (det.severity, status)
+        };
+        assert_eq!(
+            apply(&baselines, Severity::High, TriageStatus::New),
+            (Severity::Warning, TriageStatus::New)
+        );
+        assert_eq!(
+            apply(&baselines, Severity::Info, TriageStatus::Confirmed),
+            (Severity::Info, TriageStatus::Confirmed)
+        );
+
+        // Any applying baseline that suppresses wins over one that downgrades.
+        let mut suppress = product("app*", None, Vec::new());
+        suppress.action = BaselineAction::Suppress;
+        baselines.products.push(suppress);
+        assert_eq!(
+            apply(&baselines, Severity::High, TriageStatus::New),
+            (Severity::High, TriageStatus::Ignored)
+        );
+        // The analyst's own status is kept.
+        assert_eq!(
+            apply(&baselines, Severity::High, TriageStatus::Confirmed),
+            (Severity::High, TriageStatus::Confirmed)
+        );
+    }
+
+    #[test]
+    fn recording_adds_only_unknown_modules_to_the_exact_version() {
+        let report = report();
+        let mut baselines = Baselines {
+            products: vec![product("app*", None, vec![entry("plugin.dll")])],
+            ..Default::default()
+        };
+        assert_eq!(baselines.record(&report).unwrap(), 2);
+        assert_eq!(baselines.products.len(), 2);
+        let exact = &baselines.products[1];
+        assert_eq!(exact.product, "App.exe");
+        assert_eq!(exact.version.as_deref(), Some("1.2.3.4"));
+        let names: Vec<&str> = exact.modules.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["App.exe", "evil.dll"]);
+        assert_eq!(baselines.record(&report).unwrap(), 0);
+    }
+}
@@ -0,0 +1,640 @@
+//! Case files: a saved session (input references with content hashes, the computed reports,
+//! events and process trees, and the analyst's selections, filters, notes and triage) that
+//! reopens without re-running ingestion.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+
+use crate::app::{DetailsTab, DumpData};
+use crate::model::{Event, EventId, ProcessTree};
+use crate::triage::{Subject, TriageStatus};
+
+/// Value of `CaseFile::format`; also how an input is recognised as a case.
+pub const FORMAT: &str = "log-atlas-case";
+pub const VERSION: u32 = 1;
+pub const EXTENSION: &str = "atlascase";
+const ANALYST_ENV_VAR: &str = "LOG_ATLAS_ANALYST";
+
+/// A saved session, written as JSON (gzip-compressed when the file name ends in `.gz`).
+#[derive(Serialize, Deserialize)]
+pub struct CaseFile<'a> {
+    /// Always `FORMAT`; written first so the file can be recognised from its first bytes.
+    pub format: String,
+    pub version: u32,
+    /// `log-atlas <version>` that wrote the case.
+    pub generator: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub saved: OffsetDateTime,
+    pub sources: Vec<CaseSource<'a>>,
+    pub state: AnalystState,
+    pub notes: Vec<CaseNote>,
+    /// Status and tags of events and detections.
+    #[serde(default)]
+    pub triage: Vec<CaseTriage>,
+}
+
+/// One source of the session, with everything ingestion computed for it. Saving borrows
+/// from the session; reading owns.
+#[derive(Serialize, Deserialize)]
+pub struct CaseSource<'a> {
+    pub input: InputRef,
+    pub visible: bool,
+    /// The source's own events (ids and `t_ms` as in the session).
+    pub events: Cow<'a, [Event]>,
+    pub dump: Option<Cow<'a, DumpData>>,
+    pub process_tree: Option<Cow<'a, ProcessTree>>,
+}
+
+/// The input file a source was read from.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InputRef {
+    /// Path as opened (the archive or compressed file, if any), absolute when it resolves.
+    pub path: PathBuf,
+    pub size: u64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub modified: Option<OffsetDateTime>,
+    /// Hex SHA-256 of the file (of every file, by name order, for a WER report folder).
+    /// `None` when the input changed on disk after it was analysed, or was still being hashed
+    /// when the case was saved (size and modification time then decide on reopening).
+    pub sha256: Option<String>,
+}
+
+impl InputRef {
+    /// Size and modification time of `path`, as read now; the hash is computed on save.
+    pub fn stat(path: &Path) -> Option<Self> {
+        let (size, modified) = stat(path)?;
+        Some(Self {
+            path: std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+            size,
+            modified,
+            sha256: None,
+        })
+    }
+
+    fn same_stat(&self, path: &Path) -> bool {
+        stat(path).is_some_and(|(size, modified)| size == self.size && modified == self.modified)
+    }
+
+    /// Hash the input if it still is what was analysed; else leave `sha256` empty.
+    pub fn hash(&mut self) -> Result<()> {
+        if self.sha256.is_none() && self.same_stat(&self.path) {
+            self.sha256 = Some(sha256(&self.path)?);
+        }
+        Ok(())
+    }
+
+    /// Run [`InputRef::hash`] on a background thread, so saving a case does not read whole
+    /// inputs on the UI thread.
+    pub fn hash_in_background(&self) -> PendingHash {
+        let mut input = self.clone();
+        PendingHash::spawn(move || input.hash().map(|()| input.sha256))
+    }
+
+    /// Hash the file now at `path` on a background thread, for an input [`InputRef::check`]
+    /// left `Checking`; equal to `sha256` means unchanged.
+    pub fn rehash_in_background(&self) -> PendingHash {
+        let path = self.path.clone();
+        PendingHash::spawn(move || sha256(&path).map(Some))
+    }
+
+    /// Locate the input again: at its recorded path, else next to the case file (a case handed
+    /// over with its inputs in one folder). Size and modification time decide when they
+    /// match; otherwise the content hash does, and the input is `Checking` until
+    /// [`InputRef::rehash_in_background`] has it. `path` is updated to where it was found.
+    pub fn check(&mut self, case_dir: Option<&Path>) -> InputStatus {
+        let beside = case_dir
+            .zip(self.path.file_name())
+            .map(|(dir, name)| dir.join(name));
+        let Some(found) = std::iter::once(self.path.clone())
+            .chain(beside)
+            .find(|p| p.exists())
+        else {
+            return InputStatus::Missing;
+        };
+        self.path = found;
+        if self.same_stat(&self.path) {
+            InputStatus::Unchanged
+        } else if self.sha256.is_some() {
+            InputStatus::Checking
+        } else {
+            InputStatus::Changed
+        }
+    }
+}
+
+/// A hash started by [`InputRef::hash_in_background`].
+pub struct PendingHash(mpsc::Receiver<Result<Option<String>>>);
+
+impl PendingHash {
+    fn spawn(hash: impl FnOnce() -> Result<Option<String>> + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(hash());
+        });
+        Self(rx)
+    }
+
+    /// The hash (`None` if the input changed), waiting for it if it is still being computed.
+    pub fn wait(&self) -> Result<Option<String>> {
+        self.0.recv().context("input hashing stopped")?
+    }
+
+    /// The hash if it has been computed, without waiting.
+    pub fn try_wait(&self) -> Option<Result<Option<String>>> {
+        match self.0.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Some(Err(anyhow::anyhow!("input hashing stopped")))
+            }
+        }
+    }
+}
+
+/// How a source's input compares with the file on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum InputStatus {
+    /// Read in this session.
+    #[default]
+    Loaded,
+    /// Restored from a case; the file on disk is the one that was analysed.
+    Unchanged,
+    /// Restored from a case; the file differs from the one that was analysed.
+    Changed,
+    /// Restored from a case; the file was not found.
+    Missing,
+    /// Restored from a case; the file's size or modification time differ and its content is
+    /// being hashed in the background.
+    Checking,
+}
+
+impl InputStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            InputStatus::Loaded => "loaded",
+            InputStatus::Unchanged => "unchanged",
+            InputStatus::Changed => "changed on disk",
+            InputStatus::Missing => "missing",
+            InputStatus::Checking => "checking",
+        }
+    }
+}
+
+/// Selections, tab and filters of the UI.
+#[derive(Default, Serialize, Deserialize)]
+pub struct AnalystState {
+    /// Index into `CaseFile::sources` of the source the Inspector showed.
+    pub focus: Option<usize>,
+    pub selected: Option<EventRef>,
+    pub details_tab: DetailsTab,
+    /// Filter text by filter box (`timeline`, `modules`, ...); empty filters are left out.
+    pub filters: BTreeMap<String, String>,
+    pub selected_module: Option<usize>,
+    pub selected_thread: Option<u32>,
+    pub selected_stack_thread: Option<u32>,
+    pub selected_exec_artifact: Option<usize>,
+    pub selected_injected_region: Option<usize>,
+    pub selected_ioc: Option<usize>,
+    pub selected_tree_node: Option<usize>,
+}
+
+/// An event of a saved source.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct EventRef {
+    /// Index into `CaseFile::sources`.
+    pub source: usize,
+    /// Id within the source's events.
+    pub id: EventId,
+}
+
+/// An analyst note on the case, one event or one detection.
+#[derive(Clone, Debug)]
+pub struct Note {
+    /// What the note is about; `None` for the whole case.
+    pub about: Option<Subject>,
+    pub author: String,
+    pub created: OffsetDateTime,
+    pub text: String,
+}
+
+impl Note {
+    pub fn new(about: Option<Subject>, text: String) -> Self {
+        Self {
+            about,
+            author: analyst(),
+            created: OffsetDateTime::now_utc(),
+            text,
+        }
+    }
+}
+
+/// A detection of a saved source.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DetectionRef {
+    /// Index into `CaseFile::sources`.
+    pub source: usize,
+    /// `Detection::id`.
+    pub id: String,
+}
+
+/// A `Note` as saved: its event or detection is addressed by source index. Neither is set
+/// for a note on the case.
+#[derive(Serialize, Deserialize)]
+pub struct CaseNote {
+    pub event: Option<EventRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection: Option<DetectionRef>,
+    pub author: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    pub text: String,
+}
+
+/// The triage of one event or detection, addressed like `CaseNote`.
+#[derive(Serialize, Deserialize)]
+pub struct CaseTriage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<EventRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection: Option<DetectionRef>,
+    pub status: TriageStatus,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Name notes are signed with: `LOG_ATLAS_ANALYST`, else the login name.
+pub fn analyst() -> String {
+    [ANALYST_ENV_VAR, "USER", "USERNAME"]
+        .iter()
+        .find_map(|v| std::env::var(v).ok().filter(|s| !s.trim().is_empty()))
+        .unwrap_or_else(|| "analyst".into())
+}
+
+impl<'a> CaseFile<'a> {
+    pub fn new(
+        sources: Vec<CaseSource<'a>>,
+        state: AnalystState,
+        notes: Vec<CaseNote>,
+        triage: Vec<CaseTriage>,
+    ) -> Self {
+        Self {
+            format: FORMAT.into(),
+            version: VERSION,
+            generator: format!("log-atlas {}", env!("CARGO_PKG_VERSION")),
+            saved: OffsetDateTime::now_utc(),
+            sources,
+            state,
+            notes,
+            triage,
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+        let case: CaseFile = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("read case file {}", path.display()))?;
+        if case.format != FORMAT {
+            bail!("{} is not a Log Atlas case file", path.display());
+        }
+        if case.version > VERSION {
+            bail!(
+                "{} is a version {} case file; this build reads up to version {VERSION}",
+                path.display(),
+                case.version
+            );
+        }
+        Ok(case)
+    }
+
+    /// Write the case, gzip-compressed when `path` ends in `.gz`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let file = File::create(path).with_context(|| format!("create {}", path.display()))?;
+        let mut out: Box<dyn Write> = if path.extension().is_some_and(|e| e == "gz") {
+            Box::new(flate2::write::GzEncoder::new(
+                BufWriter::new(file),
+                flate2::Compression::default(),
+            ))
+        } else {
+            Box::new(BufWriter::new(file))
+        };
+        serde_json::to_writer(&mut out, self)
+            .with_context(|| format!("write {}", path.display()))?;
+        out.flush()
+            .with_context(|| format!("write {}", path.display()))
+    }
+}
+
+/// Whether `path` (already decompressed) is a case file: its extension, or JSON starting with
+/// the `format` field.
+pub fn is_case_file(path: &Path) -> bool {
+    if path.extension().is_some_and(|e| e == EXTENSION) {
+        return true;
+    }
+    let mut head = [0u8; 64];
+    let Ok(n) = File::open(path).and_then(|mut f| f.read(&mut head)) else {
+        return false;
+    };
+    let head: String = String::from_utf8_lossy(&head[..n])
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    head.starts_with(&format!("{{\"format\":\"{FORMAT}\""))
+}
+
+fn stat(path: &Path) -> Option<(u64, Option<OffsetDateTime>)> {
+    let meta = std::fs::metadata(path).ok()?;
+    if !meta.is_dir() {
+        return Some((meta.len(), meta.modified().ok().map(OffsetDateTime::from)));
+    }
+    // WER report folder: total size and latest change of its files.
+    let mut size = 0;
+    let mut modified = None;
+    for entry in std::fs::read_dir(path).ok()?.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_file() {
+            size += meta.len();
+            modified = modified.max(meta.modified().ok().map(OffsetDateTime::from));
+        }
+    }
+    Some((size, modified))
+}
+
+fn sha256(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+            .with_context(|| format!("read {}", path.display()))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .collect();
+        files.sort();
+        for file in files {
+            if let Some(name) = file.file_name() {
+                hasher.update(name.as_encoded_bytes());
+            }
+            hash_file(&file, &mut hasher)?;
+        }
+    } else {
+        hash_file(path, &mut hasher)?;
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+fn hash_file(path: &Path, hasher: &mut Sha256) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .with_context(|| format!("read {}", path.display()))?;
+        if n == 0 {
+            return Ok(());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::model::{
+        AttrValue, MinidumpReport, MinidumpSummary, ModuleInfo, ProcessNode, Severity,
+    };
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("log-atlas-case-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn hashed_input(path: &Path) -> InputRef {
+        let mut input = InputRef::stat(path).unwrap();
+        input.sha256 = input.hash_in_background().wait().unwrap();
+        input
+    }
+
+    #[test]
+    fn case_files_round_trip() {
+        let dir = temp_dir("round-trip");
+        let input_path = dir.join("app.log");
+        std::fs::write(&input_path, "line\n").unwrap();
+        let input = hashed_input(&input_path);
+        assert_eq!(
+            input.sha256.as_deref(),
+            Some("c73b73af8851e9e91bc6b4dc12e7dace0a2bfb931c1d0b8b36ef367319f58cd1")
+        );
+
+        let events = vec![Event {
+            id: EventId(7),
+            t_ms: 1500,
+            timestamp: Some(OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap()),
+            severity: Severity::High,
+            title: "crash".into(),
+            details: "details".into(),
+            source: "app".into(),
+            attributes: vec![
+                ("pid".into(), AttrValue::UInt(42)),
+                ("image".into(), "app.exe".into()),
+            ],
+            links: Vec::new(),
+        }];
+        let dump = DumpData {
+            summary: MinidumpSummary {
+                time_date_stamp: Some(1_700_000_000),
+                ..Default::default()
+            },
+            report: MinidumpReport {
+                modules: vec![ModuleInfo {
+                    name: "C:\\App\\app.exe".into(),
+                    base: 0x1000,
+                    size: 0x2000,
+                    checksum: 3,
+                    time_date_stamp: 4,
+                    file_version: Some("1.0".into()),
+                    debug_id: None,
+                }],
+                ..Default::default()
+            },
+        };
+        let tree = ProcessTree {
+            nodes: vec![ProcessNode {
+                guid: "{A}".into(),
+                pid: Some(42),
+                image: "app.exe".into(),
+                command_line: "app.exe --run".into(),
+                user: String::new(),
+                hashes: Vec::new(),
+                start_unix: Some(1_699_999_000),
+                observed: true,
+                parent: None,
+                children: Vec::new(),
+                activity: Vec::new(),
+            }],
+            roots: vec![0],
+            records: Vec::new(),
+        };
+        let state = AnalystState {
+            focus: Some(0),
+            selected: Some(EventRef {
+                source: 0,
+                id: EventId(7),
+            }),
+            details_tab: DetailsTab::Detections,
+            filters: [("timeline".to_string(), "severity:high".to_string())].into(),
+            selected_module: Some(0),
+            ..Default::default()
+        };
+        let notes = vec![CaseNote {
+            event: None,
+            detection: Some(DetectionRef {
+                source: 0,
+                id: "0123456789abcdef".into(),
+            }),
+            author: "jane".into(),
+            created: OffsetDateTime::from_unix_timestamp(1_700_000_100).unwrap(),
+            text: "benign".into(),
+        }];
+        let triage = vec![CaseTriage {
+            event: Some(EventRef {
+                source: 0,
+                id: EventId(7),
+            }),
+            detection: None,
+            status: TriageStatus::Confirmed,
+            tags: vec!["lateral".into()],
+        }];
+        let case = CaseFile::new(
+            vec![CaseSource {
+                input: input.clone(),
+                visible: false,
+                events: Cow::Borrowed(&events),
+                dump: Some(Cow::Borrowed(&dump)),
+                process_tree: Some(Cow::Borrowed(&tree)),
+            }],
+            state,
+            notes,
+            triage,
+        );
+
+        let path = dir.join(format!("case.{EXTENSION}"));
+        case.write(&path).unwrap();
+        assert!(is_case_file(&path));
+        let renamed = dir.join("case.json");
+        std::fs::rename(&path, &renamed).unwrap();
+        assert!(is_case_file(&renamed));
+        assert!(!is_case_file(&input_path));
+
+        let read = CaseFile::read(&renamed).unwrap();
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&case).unwrap()
+        );
+        let source = &read.sources[0];
+        assert!(!source.visible);
+        assert_eq!(source.input.sha256, input.sha256);
+        assert_eq!(source.events[0].id, EventId(7));
+        assert_eq!(source.events[0].attr("pid"), Some(&AttrValue::UInt(42)));
+        assert_eq!(
+            source.dump.as_ref().unwrap().report.modules[0].name,
+            "C:\\App\\app.exe"
+        );
+        assert_eq!(source.process_tree.as_ref().unwrap().nodes[0].pid, Some(42));
+        assert_eq!(read.state.details_tab, DetailsTab::Detections);
+        assert_eq!(read.state.filters["timeline"], "severity:high");
+        assert_eq!(
+            read.notes[0].detection.as_ref().unwrap().id,
+            "0123456789abcdef"
+        );
+        assert_eq!(read.triage[0].status, TriageStatus::Confirmed);
+        assert_eq!(read.triage[0].tags, vec!["lateral"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newer_or_foreign_case_files_are_rejected() {
+        let dir = temp_dir("versions");
+        let path = dir.join("case.json");
+        let case = CaseFile::new(Vec::new(), AnalystState::default(), Vec::new(), Vec::new());
+        let mut value = serde_json::to_value(&case).unwrap();
+
+        value["version"] = (VERSION + 1).into();
+        std::fs::write(&path, value.to_string()).unwrap();
+        let err = CaseFile::read(&path).err().unwrap();
+        assert!(err.to_string().contains("this build reads up to version"));
+
+        value["version"] = VERSION.into();
+        value["format"] = "something-else".into();
+        std::fs::write(&path, value.to_string()).unwrap();
+        assert!(CaseFile::read(&path).is_err());
+        assert!(!is_case_file(&path));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reopened_inputs_are_checked_by_stat_then_hash() {
+        let dir = temp_dir("check");
+        let path = dir.join("input.log");
+        std::fs::write(&path, "original\n").unwrap();
+        let saved = hashed_input(&path);
+        assert!(saved.sha256.is_some());
+
+        let mut input = saved.clone();
+        assert_eq!(input.check(None), InputStatus::Unchanged);
+
+        // Touched but identical: the hash decides.
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        drop(file);
+        let mut input = saved.clone();
+        assert_eq!(input.check(None), InputStatus::Checking);
+        assert_eq!(input.rehash_in_background().wait().unwrap(), saved.sha256);
+
+        std::fs::write(&path, "modified\n").unwrap();
+        let mut input = saved.clone();
+        assert_eq!(input.check(None), InputStatus::Checking);
+        assert_ne!(input.rehash_in_background().wait().unwrap(), saved.sha256);
+        // Without a saved hash, a stat mismatch is a change.
+        let mut unhashed = InputRef {
+            sha256: None,
+            ..saved.clone()
+        };
+        assert_eq!(unhashed.check(None), InputStatus::Changed);
+        // An input that changed since it was analysed is not hashed on save.
+        assert_eq!(unhashed.hash_in_background().wait().unwrap(), None);
+
+        // Moved next to the case file.
+        let case_dir = dir.join("handed-over");
+        std::fs::create_dir_all(&case_dir).unwrap();
+        std::fs::rename(&path, case_dir.join("input.log")).unwrap();
+        let mut input = saved.clone();
+        assert_eq!(input.check(None), InputStatus::Missing);
+        assert_eq!(input.check(Some(&case_dir)), InputStatus::Checking);
+        assert_eq!(input.path, case_dir.join("input.log"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
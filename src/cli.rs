@@ -0,0 +1,505 @@
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::app::{LogAtlasApp, Source, TimelineEvent};
+use crate::baseline::{Applied, Baselines};
+use crate::case::InputStatus;
+use crate::model::MinidumpReport;
+use crate::query::{
+    AnnotationRecord, ArtifactRecord, DetectionRecord, EventRecord, IndicatorRecord, LinkRecord,
+    ModuleRecord, ProcessRecord, Query, Record, RegionRecord, StackRecord, ThreadRecord,
+};
+
+pub const USAGE: &str = "\
+Usage: log-atlas [INPUT...]
+       log-atlas INPUT... --query QUERY [--table TABLE]
+       log-atlas INPUT... --save-case CASE
+       log-atlas INPUT... --record-baseline
+       log-atlas BEFORE AFTER --diff
+
+Each INPUT is opened at startup as a source of one session: a minidump
+(.dmp/.mdmp/.hdmp), ELF core, WER report folder or zip, JSON Lines, .evtx,
+syslog or journal export. Inputs may be compressed (.gz, .zst, .xz) or inside
+a .zip. Their events are merged into one timeline. An INPUT may also be a case
+file (.atlascase) saved earlier, which replaces the session with the saved one.
+
+With --query, the INPUTs are loaded without the UI and the rows of TABLE that
+match QUERY are printed tab-separated, one per line. An empty QUERY prints
+every row. With several INPUTs each row starts with its input's file name.
+
+With --save-case, the INPUTs are loaded without the UI and saved as a case
+file that reopens without re-running the analysis.
+
+With --record-baseline, the modules of every dump INPUT are added to the
+known-good baseline of its product version (LOG_ATLAS_BASELINE).
+
+With --diff, the two dumps of the INPUTs are compared and the changes from the first to
+the second (modules, threads, injected regions, execution artifacts, IOCs and
+the crash signature) are printed as JSON.
+
+Options:
+  -q, --query QUERY  Filter, e.g. 'severity>=warn AND source:detector::*'
+  -t, --table TABLE  timeline (default), modules, threads, stacks, regions,
+                     artifacts, iocs, annotations, detections, processes,
+                     incidents (log events correlated with the dumps)
+  -s, --save-case CASE
+                     Write a case file (gzip-compressed if CASE ends in .gz)
+  -b, --record-baseline
+                     Record the dumps' modules as known good
+  -d, --diff         Print the diff of two dumps as JSON
+  -h, --help         Print this help";
+
+/// Tables `--table` accepts.
+pub const TABLES: &[&str] = &[
+    "timeline",
+    "modules",
+    "threads",
+    "stacks",
+    "regions",
+    "artifacts",
+    "iocs",
+    "annotations",
+    "detections",
+    "processes",
+    "incidents",
+];
+
+/// Command-line arguments.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub inputs: Vec<PathBuf>,
+    pub query: Option<String>,
+    pub table: Option<String>,
+    pub save_case: Option<PathBuf>,
+    pub record_baseline: bool,
+    pub diff: bool,
+    pub help: bool,
+}
+
+impl Args {
+    /// Whether to run without the UI.
+    pub fn headless(&self) -> bool {
+        self.query.is_some() || self.save_case.is_some() || self.record_baseline || self.diff
+    }
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self> {
+        let mut out = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .and_then(|v| v.into_string().ok())
+                    .with_context(|| format!("{name} needs a value"))
+            };
+            match arg.to_str() {
+                Some("-h" | "--help") => out.help = true,
+                Some("-b" | "--record-baseline") => out.record_baseline = true,
+                Some("-d" | "--diff") => out.diff = true,
+                Some("-q" | "--query") => out.query = Some(value("--query")?),
+                Some("-s" | "--save-case") => {
+                    out.save_case = Some(PathBuf::from(value("--save-case")?));
+                }
+                Some("-t" | "--table") => {
+                    let table = value("--table")?;
+                    if !TABLES.contains(&table.as_str()) {
+                        bail!(
+                            "unknown table {table} (expected one of {})",
+                            TABLES.join(", ")
+                        );
+                    }
+                    out.table = Some(table);
+                }
+                Some(flag) if flag.starts_with('-') && flag.len() > 1 => {
+                    bail!("unknown option {flag}")
+                }
+                _ => out.inputs.push(PathBuf::from(arg)),
+            }
+        }
+        if out.table.is_some() && out.query.is_none() {
+            bail!("--table needs --query");
+        }
+        if out.query.is_some() && out.inputs.is_empty() && !out.help {
+            bail!("--query needs an INPUT");
+        }
+        if out.save_case.is_some() && out.inputs.is_empty() && !out.help {
+            bail!("--save-case needs an INPUT");
+        }
+        if out.record_baseline && out.inputs.is_empty() && !out.help {
+            bail!("--record-baseline needs an INPUT");
+        }
+        if out.diff && out.query.is_some() {
+            bail!("--diff and --query both write to standard output");
+        }
+        if out.diff && out.inputs.is_empty() && !out.help {
+            bail!("--diff needs an INPUT");
+        }
+        Ok(out)
+    }
+}
+
+/// Load the inputs into one session without the UI, then record their modules in the
+/// baseline, save the session as a case file and / or write the rows of the `--table` that
+/// match `--query` or the diff of the two dumps.
+pub fn run_headless(args: &Args, out: &mut impl Write) -> Result<()> {
+    let query = args
+        .query
+        .as_deref()
+        .map(|text| {
+            Query::parse(text).map_err(|e| {
+                anyhow!(
+                    "invalid query at column {}:\n{}",
+                    e.column(text),
+                    e.annotate(text)
+                )
+            })
+        })
+        .transpose()?;
+    let mut app = LogAtlasApp {
+        allowlist: crate::triage::Allowlist::load(),
+        baselines: Baselines::load()?,
+        ..LogAtlasApp::default()
+    };
+    for input in &args.inputs {
+        app.open_path(input.clone())
+            .with_context(|| format!("open {}", input.display()))?;
+    }
+    app.session.collect_hashes(true);
+    for source in &app.session.sources {
+        if matches!(source.status, InputStatus::Changed | InputStatus::Missing) {
+            eprintln!(
+                "log-atlas: warning: {}: input {} since the case was saved; using the saved analysis",
+                source.path.display(),
+                source.status.label()
+            );
+        }
+    }
+    if args.record_baseline {
+        let mut dumps = app
+            .session
+            .sources
+            .iter()
+            .filter_map(|s| Some((s, s.dump_report()?)))
+            .peekable();
+        if dumps.peek().is_none() {
+            bail!("no INPUT is a minidump, ELF core or WER report");
+        }
+        for (source, report) in dumps {
+            let added = app.baselines.record(report)?;
+            let (product, version) =
+                crate::baseline::product_of(report).context("dump has no modules")?;
+            eprintln!(
+                "log-atlas: {}: {added} modules added to the baseline of {}",
+                source.name(),
+                crate::baseline::label(product, version)
+            );
+        }
+    }
+    if let Some(path) = &args.save_case {
+        app.save_case(path.clone())
+            .with_context(|| format!("save case {}", path.display()))?;
+    }
+    if args.diff {
+        let dumps: Vec<_> = app
+            .session
+            .sources
+            .iter()
+            .filter_map(|s| Some((s.name(), s.dump_report()?)))
+            .collect();
+        let [before, after] = dumps.as_slice() else {
+            bail!("--diff needs two dumps, found {}", dumps.len());
+        };
+        let diff = crate::diff::diff((before.0.as_str(), before.1), (after.0.as_str(), after.1));
+        out.write_all(crate::export::diff_to_json(&diff)?.as_bytes())?;
+    }
+    if let Some(query) = query {
+        let table = args.table.as_deref().unwrap_or("timeline");
+        run_query(&app, &query, table, out)?;
+    }
+    Ok(())
+}
+
+/// Write the rows of `table` that match `query`. Returns the number of rows written.
+pub fn run_query(
+    app: &LogAtlasApp,
+    query: &Query,
+    table: &str,
+    out: &mut impl Write,
+) -> Result<usize> {
+    let session = &app.session;
+
+    // With several inputs, rows are prefixed by the file name of the input they came from.
+    let multi = session.sources.len() > 1;
+    let mut rows = 0usize;
+    let mut emit = |source: &Source, record: &dyn Record, line: String| -> Result<()> {
+        if query.matches(record) {
+            if multi {
+                write!(out, "{}\t", source.name())?;
+            }
+            writeln!(out, "{line}")?;
+            rows += 1;
+        }
+        Ok(())
+    };
+
+    if table == "timeline" {
+        let timeline = session.timeline();
+        for &pos in timeline.by_time() {
+            let TimelineEvent {
+                id,
+                t_ms,
+                event: ev,
+            } = timeline.at(pos);
+            let Some(source) = session.source_of(id) else {
+                continue;
+            };
+            let when = ev
+                .timestamp
+                .map_or_else(|| format!("+{t_ms}ms"), crate::util::time::format_timestamp);
+            let record = timeline.record(pos, source.dump_report());
+            emit(
+                source,
+                &record,
+                format!(
+                    "{when}\t{}\t{}\t{}",
+                    ev.severity.label(),
+                    ev.source,
+                    ev.title
+                ),
+            )?;
+        }
+        return Ok(rows);
+    }
+    if table == "incidents" {
+        for (index, incident) in session.incidents.iter().enumerate() {
+            for link in &incident.links {
+                let (Some(source), Some(ev)) =
+                    (session.source_of(link.event), session.event(link.event))
+                else {
+                    continue;
+                };
+                let record = LinkRecord {
+                    incident: index,
+                    link,
+                    event: EventRecord {
+                        id: link.event,
+                        ..EventRecord::new(ev, None)
+                    },
+                };
+                let reasons: Vec<String> = link.reasons.iter().map(ToString::to_string).collect();
+                emit(
+                    source,
+                    &record,
+                    format!(
+                        "{index}\t{}\t{}\t{}\t{}",
+                        link.delta_ms
+                            .map_or_else(|| "-".into(), crate::correlate::format_delta),
+                        ev.severity.label(),
+                        ev.title,
+                        reasons.join("; ")
+                    ),
+                )?;
+            }
+        }
+        return Ok(rows);
+    }
+    if table == "processes" {
+        let mut trees = session
+            .sources
+            .iter()
+            .filter_map(|s| Some((s, s.process_tree.as_ref().filter(|t| !t.is_empty())?)))
+            .peekable();
+        if trees.peek().is_none() {
+            bail!("no INPUT has Sysmon process events");
+        }
+        for (source, tree) in trees {
+            for node in &tree.nodes {
+                emit(
+                    source,
+                    &ProcessRecord(node),
+                    format!(
+                        "{}\t{}\t{}",
+                        node.pid.map_or_else(|| "-".into(), |p| p.to_string()),
+                        node.image,
+                        node.command_line
+                    ),
+                )?;
+            }
+        }
+        return Ok(rows);
+    }
+
+    let mut dumps = session
+        .sources
+        .iter()
+        .filter_map(|s| Some((s, s.dump_report()?)))
+        .peekable();
+    if dumps.peek().is_none() {
+        bail!("no INPUT is a minidump, ELF core or WER report");
+    }
+    for (source, report) in dumps {
+        if table == "detections" {
+            // Needs the session's triage and the allowlist, not just the report.
+            for d in app.detections(source).iter() {
+                let record = DetectionRecord {
+                    detection: &d.detection,
+                    report,
+                    id: &d.id,
+                    status: d.status,
+                    tags: &d.tags,
+                };
+                emit(
+                    source,
+                    &record,
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                        d.detection.severity.label(),
+                        d.detection.title,
+                        d.status.name(),
+                        d.id,
+                        d.detection.rule,
+                        d.detection.techniques.join(","),
+                        d.detection.confidence,
+                        d.tags.join(",")
+                    ),
+                )?;
+            }
+            continue;
+        }
+        let baseline = app.baselines.for_report(report);
+        report_rows(source, report, baseline.as_ref(), table, &mut emit)?;
+    }
+    Ok(rows)
+}
+
+/// Rows of a report table of one dump source.
+fn report_rows(
+    source: &Source,
+    report: &MinidumpReport,
+    baseline: Option<&Applied>,
+    table: &str,
+    emit: &mut impl FnMut(&Source, &dyn Record, String) -> Result<()>,
+) -> Result<()> {
+    match table {
+        "modules" => {
+            for (index, m) in report.modules.iter().enumerate() {
+                emit(
+                    source,
+                    &ModuleRecord {
+                        index,
+                        module: m,
+                        baseline: baseline.map(|b| b.module(m)),
+                    },
+                    format!(
+                        "{index}\t0x{:016X}\t0x{:X}\t{}\t{}",
+                        m.base,
+                        m.size,
+                        m.file_version.as_deref().unwrap_or("-"),
+                        m.name
+                    ),
+                )?;
+            }
+        }
+        "threads" => {
+            for t in &report.threads {
+                emit(
+                    source,
+                    &ThreadRecord(t),
+                    format!(
+                        "0x{:X}\t{}\t{}",
+                        t.thread_id,
+                        t.name.as_deref().unwrap_or("-"),
+                        t.start_address
+                            .map_or_else(|| "-".into(), |a| format!("0x{a:016X}"))
+                    ),
+                )?;
+            }
+        }
+        "stacks" => {
+            for t in report.stackwalk.iter().flat_map(|sw| &sw.threads) {
+                let top = t.frames.first().map_or_else(
+                    || "-".into(),
+                    |f| {
+                        format!(
+                            "{}!{}",
+                            f.module.as_deref().unwrap_or("<unknown>"),
+                            f.function
+                                .clone()
+                                .unwrap_or_else(|| format!("0x{:X}", f.instruction))
+                        )
+                    },
+                );
+                emit(
+                    source,
+                    &StackRecord(t),
+                    format!(
+                        "0x{:X}\t{}\t{}\t{top}",
+                        t.thread_id,
+                        t.status,
+                        t.frames.len()
+                    ),
+                )?;
+            }
+        }
+        "regions" => {
+            for r in &report.injected_regions {
+                emit(
+                    source,
+                    &RegionRecord(r),
+                    format!(
+                        "0x{:016X}\t0x{:X}\t{}\t{}\t{}",
+                        r.base,
+                        r.size,
+                        r.protection,
+                        r.risk.label(),
+                        r.reasons.join("; ")
+                    ),
+                )?;
+            }
+        }
+        "artifacts" => {
+            for (index, a) in report.exec_artifacts.iter().enumerate() {
+                emit(
+                    source,
+                    &ArtifactRecord { index, artifact: a },
+                    format!(
+                        "{index}\t{}\t{}\t{}",
+                        a.encoding.label(),
+                        a.image,
+                        a.command_line
+                    ),
+                )?;
+            }
+        }
+        "iocs" => {
+            for i in &report.iocs {
+                emit(
+                    source,
+                    &IndicatorRecord(i),
+                    format!(
+                        "{}\t{}\t{}\t{}",
+                        i.kind.label(),
+                        i.value,
+                        i.count,
+                        i.container.as_deref().unwrap_or("-")
+                    ),
+                )?;
+            }
+        }
+        "annotations" => {
+            for a in report.crashpad.iter().flat_map(|c| &c.annotations) {
+                emit(
+                    source,
+                    &AnnotationRecord(a),
+                    format!("{}\t{}\t{}", a.scope(), a.key, a.value),
+                )?;
+            }
+        }
+        other => bail!("unknown table {other}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,608 @@
+//! Cross-source correlation: link log events to loaded dumps by stable identifiers (PID, thread
+//! id, image and module names) and by time around the dump capture, and group dumps of the same
+//! crash into incidents.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use time::OffsetDateTime;
+
+use crate::model::{AttrValue, Event, EventId, MinidumpReport, MinidumpSummary, Severity};
+
+const WINDOW_ENV_VAR: &str = "LOG_ATLAS_CORRELATION_WINDOW_SECS";
+const DEFAULT_WINDOW_SECS: i64 = 120;
+
+/// Attribute keys (last dotted segment, lowercase) holding the PID an event is about: generic
+/// `pid`, journal `_PID`, EVTX / Sysmon `ProcessId` variants and Linux crash lines.
+const PID_KEYS: &[&str] = &[
+    "pid",
+    "_pid",
+    "process_id",
+    "processid",
+    "newprocessid",
+    "parentprocessid",
+    "sourceprocessid",
+    "targetprocessid",
+];
+
+const TID_KEYS: &[&str] = &["tid", "_tid", "thread_id", "threadid", "sourcethreadid"];
+
+/// How far around the dump capture time events are considered, from
+/// `LOG_ATLAS_CORRELATION_WINDOW_SECS` (default 120).
+pub fn window_secs() -> i64 {
+    std::env::var(WINDOW_ENV_VAR)
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|&s| s >= 0)
+        .unwrap_or(DEFAULT_WINDOW_SECS)
+}
+
+/// What correlation needs from a loaded dump.
+pub struct DumpAnchor<'a> {
+    pub summary: &'a MinidumpSummary,
+    pub report: &'a MinidumpReport,
+}
+
+impl DumpAnchor<'_> {
+    fn pid(&self) -> Option<u32> {
+        self.report.process.as_ref()?.pid
+    }
+
+    /// Dump capture time (`TimeDateStamp`).
+    fn capture(&self) -> Option<OffsetDateTime> {
+        OffsetDateTime::from_unix_timestamp(self.summary.time_date_stamp?.into()).ok()
+    }
+
+    fn process_start(&self) -> Option<OffsetDateTime> {
+        let secs = self.report.process.as_ref()?.create_time_unix?;
+        OffsetDateTime::from_unix_timestamp(i64::try_from(secs).ok()?).ok()
+    }
+
+    /// File name of the main executable: `ProcessInfo::main_image`, else the first module,
+    /// else the first word of the command line.
+    fn image(&self) -> Option<String> {
+        let process = self.report.process.as_ref();
+        process
+            .and_then(|p| p.main_image.clone())
+            .or_else(|| self.report.modules.first().map(|m| m.name.clone()))
+            .or_else(|| {
+                process
+                    .and_then(|p| p.command_line.as_deref())
+                    .and_then(|c| c.split_whitespace().next())
+                    .map(str::to_string)
+            })
+            .map(|p| base_name(&p).to_string())
+            .filter(|n| !n.is_empty())
+    }
+}
+
+/// Why an event was linked to an incident.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkReason {
+    /// The event names the dump's process id, within the process lifetime.
+    Pid { key: String, pid: u32 },
+    /// The event names one of the dump's threads (only alongside a PID match).
+    Thread { key: String, tid: u32 },
+    /// An attribute is the dump's executable, within the process lifetime.
+    Image { key: String, name: String },
+    /// An attribute is one of the dump's modules, near the capture time.
+    Module { key: String, name: String },
+    /// A warning or error logged near the capture time.
+    NearCapture { delta_ms: i64 },
+}
+
+impl LinkReason {
+    /// Short kind, for the `reason` query field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LinkReason::Pid { .. } => "pid",
+            LinkReason::Thread { .. } => "thread",
+            LinkReason::Image { .. } => "image",
+            LinkReason::Module { .. } => "module",
+            LinkReason::NearCapture { .. } => "time",
+        }
+    }
+}
+
+impl fmt::Display for LinkReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkReason::Pid { key, pid } => write!(f, "same pid {pid} ({key})"),
+            LinkReason::Thread { key, tid } => write!(f, "same thread 0x{tid:X} ({key})"),
+            LinkReason::Image { key, name } => write!(f, "same image {name} ({key})"),
+            LinkReason::Module { key, name } => write!(f, "loaded module {name} ({key})"),
+            LinkReason::NearCapture { delta_ms } => {
+                write!(f, "{} within the capture window", format_delta(*delta_ms))
+            }
+        }
+    }
+}
+
+/// A log event linked to an incident.
+#[derive(Clone, Debug)]
+pub struct Link {
+    pub event: EventId,
+    /// Event time minus the incident's capture time, when both are known.
+    pub delta_ms: Option<i64>,
+    pub reasons: Vec<LinkReason>,
+}
+
+/// Dumps of one crash (same process, captured within the window) and the log events linked to
+/// them.
+#[derive(Clone, Debug, Default)]
+pub struct Incident {
+    /// Indices into the `dumps` given to `correlate`.
+    pub dumps: Vec<usize>,
+    pub pid: Option<u32>,
+    pub image: Option<String>,
+    /// Earliest capture time of the incident's dumps.
+    pub capture: Option<OffsetDateTime>,
+    /// Ordered by time (events without a timestamp last).
+    pub links: Vec<Link>,
+}
+
+impl Incident {
+    pub fn title(&self) -> String {
+        let image = self.image.as_deref().unwrap_or("unknown process");
+        match self.pid {
+            Some(pid) => format!("{image} (pid {pid})"),
+            None => image.to_string(),
+        }
+    }
+}
+
+/// Per-dump facts, lowercased for matching.
+struct Anchor {
+    pid: Option<u32>,
+    image: Option<String>,
+    modules: HashSet<String>,
+    threads: HashSet<u32>,
+    capture: Option<OffsetDateTime>,
+    /// Events outside `[start, end]` cannot be about this process instance.
+    start: Option<OffsetDateTime>,
+    end: Option<OffsetDateTime>,
+}
+
+/// A log event that matched one dump, before it is put in an incident.
+#[derive(Clone, Debug)]
+pub struct EventMatch {
+    pub event: EventId,
+    pub timestamp: Option<OffsetDateTime>,
+    pub reasons: Vec<LinkReason>,
+}
+
+/// Match `events` (from log sources; the dumps' own events are left out by the caller) against
+/// one dump. Sessions keep the result per dump, so adding a source only matches what is new.
+pub fn match_events<'e>(
+    dump: &DumpAnchor<'_>,
+    events: impl IntoIterator<Item = (EventId, &'e Event)>,
+    window_secs: i64,
+) -> Vec<EventMatch> {
+    let window = time::Duration::seconds(window_secs);
+    let image = dump.image().map(|i| i.to_lowercase());
+    let capture = dump.capture();
+    let anchor = Anchor {
+        pid: dump.pid(),
+        modules: dump
+            .report
+            .modules
+            .iter()
+            .map(|m| base_name(&m.name).to_lowercase())
+            .filter(|m| Some(m) != image.as_ref())
+            .collect(),
+        image,
+        threads: dump.report.threads.iter().map(|t| t.thread_id).collect(),
+        capture,
+        start: dump.process_start().or(capture.map(|c| c - window)),
+        end: capture.map(|c| c + window),
+    };
+    events
+        .into_iter()
+        .filter_map(|(id, ev)| {
+            let reasons = match_event(&anchor, ev, window);
+            (!reasons.is_empty()).then_some(EventMatch {
+                event: id,
+                timestamp: ev.timestamp,
+                reasons,
+            })
+        })
+        .collect()
+}
+
+/// Group `dumps` into incidents and link each incident to the events its dumps matched
+/// (`matches[d]` from `match_events` on `dumps[d]`). Only incidents with at least one link, or
+/// with several dumps, are returned.
+pub fn incidents(
+    dumps: &[DumpAnchor<'_>],
+    matches: &[&[EventMatch]],
+    window_secs: i64,
+) -> Vec<Incident> {
+    let window = time::Duration::seconds(window_secs);
+    let mut incidents = cluster(dumps, window);
+    let mut pending: Vec<Vec<(EventId, usize, &EventMatch)>> = vec![Vec::new(); incidents.len()];
+    for (d, matches) in matches.iter().enumerate() {
+        let incident = incidents
+            .iter()
+            .position(|i| i.dumps.contains(&d))
+            .expect("every dump is clustered");
+        pending[incident].extend(matches.iter().map(|m| (m.event, d, m)));
+    }
+
+    for (incident, mut pending) in incidents.iter_mut().zip(pending) {
+        // Event order (merged ids follow load order), so an event matched by several of the
+        // incident's dumps becomes one link with the reasons of each, in dump order.
+        pending.sort_by_key(|&(event, d, _)| (event.0, d));
+        for (event, _, m) in pending {
+            match incident.links.last_mut().filter(|l| l.event == event) {
+                Some(link) => {
+                    for r in &m.reasons {
+                        if !link.reasons.contains(r) {
+                            link.reasons.push(r.clone());
+                        }
+                    }
+                }
+                None => incident.links.push(Link {
+                    event,
+                    delta_ms: m
+                        .timestamp
+                        .zip(incident.capture)
+                        .map(|(t, c)| (t - c).whole_milliseconds() as i64),
+                    reasons: m.reasons.clone(),
+                }),
+            }
+        }
+        incident
+            .links
+            .sort_by_key(|l| (l.delta_ms.is_none(), l.delta_ms));
+    }
+    incidents.retain(|i| !i.links.is_empty() || i.dumps.len() > 1);
+    incidents
+}
+
+/// Put dumps of the same PID and image captured within `window` of each other in one
+/// incident.
+fn cluster(dumps: &[DumpAnchor<'_>], window: time::Duration) -> Vec<Incident> {
+    let mut incidents: Vec<Incident> = Vec::new();
+    for (d, dump) in dumps.iter().enumerate() {
+        let (pid, image, capture) = (dump.pid(), dump.image(), dump.capture());
+        let same = incidents.iter_mut().find(|i| {
+            pid.is_some()
+                && i.pid == pid
+                && i.image.as_deref().map(str::to_lowercase)
+                    == image.as_deref().map(str::to_lowercase)
+                && i.capture
+                    .zip(capture)
+                    .is_none_or(|(a, b)| (a - b).abs() <= window)
+        });
+        match same {
+            Some(incident) => {
+                incident.dumps.push(d);
+                incident.capture = match (incident.capture, capture) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            None => incidents.push(Incident {
+                dumps: vec![d],
+                pid,
+                image,
+                capture,
+                links: Vec::new(),
+            }),
+        }
+    }
+    incidents
+}
+
+fn match_event(anchor: &Anchor, ev: &Event, window: time::Duration) -> Vec<LinkReason> {
+    let mut reasons = Vec::new();
+    let in_lifetime = match ev.timestamp {
+        Some(t) => anchor.start.is_none_or(|s| t >= s) && anchor.end.is_none_or(|e| t <= e),
+        None => true,
+    };
+    let delta = ev.timestamp.zip(anchor.capture).map(|(t, c)| t - c);
+    let near_capture = delta.is_some_and(|d| d.abs() <= window);
+
+    let mut pid_matched = false;
+    for (key, value) in &ev.attributes {
+        let field = key.rsplit('.').next().unwrap_or(key).to_ascii_lowercase();
+        if PID_KEYS.contains(&field.as_str())
+            && in_lifetime
+            && let Some(pid) = anchor.pid
+            && value.as_u64() == Some(pid.into())
+        {
+            pid_matched = true;
+            reasons.push(LinkReason::Pid {
+                key: key.clone(),
+                pid,
+            });
+            continue;
+        }
+        if TID_KEYS.contains(&field.as_str())
+            && let Some(tid) = value.as_u64().and_then(|t| u32::try_from(t).ok())
+            && anchor.threads.contains(&tid)
+        {
+            reasons.push(LinkReason::Thread {
+                key: key.clone(),
+                tid,
+            });
+            continue;
+        }
+        let AttrValue::Str(text) = value else {
+            continue;
+        };
+        let name = base_name(text).to_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        if in_lifetime && ev.timestamp.is_some() && anchor.image.as_ref() == Some(&name) {
+            reasons.push(LinkReason::Image {
+                key: key.clone(),
+                name: base_name(text).to_string(),
+            });
+        } else if near_capture && anchor.modules.contains(&name) {
+            reasons.push(LinkReason::Module {
+                key: key.clone(),
+                name: base_name(text).to_string(),
+            });
+        }
+    }
+    // Thread ids are only unique within a process.
+    if !pid_matched {
+        reasons.retain(|r| !matches!(r, LinkReason::Thread { .. }));
+    }
+    if let Some(delta) = delta
+        && near_capture
+        && (ev.severity >= Severity::Warning || !reasons.is_empty())
+    {
+        reasons.push(LinkReason::NearCapture {
+            delta_ms: delta.whole_milliseconds() as i64,
+        });
+    }
+    reasons
+}
+
+/// `+1.5s`, `-2m03s`.
+pub fn format_delta(delta_ms: i64) -> String {
+    let sign = if delta_ms < 0 { '-' } else { '+' };
+    let ms = delta_ms.unsigned_abs();
+    if ms < 60_000 {
+        format!("{sign}{}.{}s", ms / 1000, ms % 1000 / 100)
+    } else {
+        format!("{sign}{}m{:02}s", ms / 60_000, ms % 60_000 / 1000)
+    }
+}
+
+fn base_name(path: &str) -> &str {
+    path.trim().rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ModuleInfo, ProcessInfo, ThreadInfo};
+
+    use super::*;
+
+    const CAPTURE: i64 = 1_700_000_000;
+
+    fn dump(pid: u32, capture: i64, start: Option<i64>) -> (MinidumpSummary, MinidumpReport) {
+        let summary = MinidumpSummary {
+            time_date_stamp: Some(capture as u32),
+            ..Default::default()
+        };
+        let module = |name: &str| ModuleInfo {
+            name: name.to_string(),
+            base: 0,
+            size: 0,
+            checksum: 0,
+            time_date_stamp: 0,
+            file_version: None,
+            debug_id: None,
+        };
+        let report = MinidumpReport {
+            process: Some(ProcessInfo {
+                pid: Some(pid),
+                ppid: None,
+                uid: None,
+                command_line: None,
+                environment: Vec::new(),
+                create_time_unix: start.map(|s| s as u64),
+                integrity_level: None,
+                execute_flags: None,
+                protected_process: None,
+                main_image: Some("C:\\App\\App.exe".into()),
+                main_image_version: None,
+            }),
+            modules: vec![module("C:\\App\\app.exe"), module("C:\\Windows\\ntdll.dll")],
+            threads: vec![ThreadInfo {
+                thread_id: 0x10,
+                name: None,
+                create_time_filetime: None,
+                create_time_unix: None,
+                start_address: None,
+                suspend_count: 0,
+                priority_class: 0,
+                priority: 0,
+                teb: 0,
+                stack_start: 0,
+                stack_size: 0,
+            }],
+            ..Default::default()
+        };
+        (summary, report)
+    }
+
+    fn event(at: Option<i64>, severity: Severity, attributes: &[(&str, AttrValue)]) -> Event {
+        Event {
+            id: EventId(0),
+            t_ms: 0,
+            timestamp: at.map(|s| OffsetDateTime::from_unix_timestamp(s).unwrap()),
+            severity,
+            title: String::new(),
+            details: String::new(),
+            source: "test".into(),
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            links: Vec::new(),
+        }
+    }
+
+    fn kinds(
+        dump: &(MinidumpSummary, MinidumpReport),
+        ev: &Event,
+        window: i64,
+    ) -> Vec<&'static str> {
+        let anchor = DumpAnchor {
+            summary: &dump.0,
+            report: &dump.1,
+        };
+        match_events(&anchor, [(EventId(1), ev)], window)
+            .into_iter()
+            .flat_map(|m| m.reasons)
+            .map(|r| r.kind())
+            .collect()
+    }
+
+    #[test]
+    fn pid_matches_only_within_the_process_lifetime() {
+        let d = dump(42, CAPTURE, Some(CAPTURE - 3600));
+        let pid = |at: i64| event(Some(at), Severity::Info, &[("_PID", 42u64.into())]);
+        assert_eq!(kinds(&d, &pid(CAPTURE - 1800), 120), vec!["pid"]);
+        assert_eq!(kinds(&d, &pid(CAPTURE - 60), 120), vec!["pid", "time"]);
+        assert!(kinds(&d, &pid(CAPTURE - 3601), 120).is_empty());
+        assert!(kinds(&d, &pid(CAPTURE + 121), 120).is_empty());
+        assert_eq!(kinds(&d, &pid(CAPTURE + 121), 300), vec!["pid", "time"]);
+        assert!(
+            kinds(
+                &d,
+                &event(Some(CAPTURE), Severity::Info, &[("pid", 43u64.into())]),
+                120
+            )
+            .is_empty()
+        );
+        // Untimed events cannot be placed outside the lifetime.
+        assert_eq!(
+            kinds(
+                &d,
+                &event(None, Severity::Info, &[("pid", 42u64.into())]),
+                120
+            ),
+            vec!["pid"]
+        );
+    }
+
+    #[test]
+    fn without_a_start_time_the_window_bounds_the_lifetime() {
+        let d = dump(42, CAPTURE, None);
+        let pid = |at: i64| event(Some(at), Severity::Info, &[("ProcessId", 42u64.into())]);
+        assert_eq!(kinds(&d, &pid(CAPTURE - 120), 120), vec!["pid", "time"]);
+        assert!(kinds(&d, &pid(CAPTURE - 121), 120).is_empty());
+        assert!(kinds(&d, &pid(CAPTURE - 1), 0).is_empty());
+    }
+
+    #[test]
+    fn modules_and_warnings_match_only_near_capture() {
+        let d = dump(42, CAPTURE, Some(CAPTURE - 3600));
+        let module = |at: i64| event(Some(at), Severity::Info, &[("path", "/x/NTDLL.dll".into())]);
+        assert_eq!(
+            kinds(&d, &module(CAPTURE + 30), 120),
+            vec!["module", "time"]
+        );
+        assert!(kinds(&d, &module(CAPTURE + 300), 120).is_empty());
+        // The executable itself matches as the image over the whole lifetime.
+        let image = event(
+            Some(CAPTURE - 1800),
+            Severity::Info,
+            &[("image", "app.EXE".into())],
+        );
+        assert_eq!(kinds(&d, &image, 120), vec!["image"]);
+
+        assert_eq!(
+            kinds(&d, &event(Some(CAPTURE + 5), Severity::Warning, &[]), 120),
+            vec!["time"]
+        );
+        assert!(kinds(&d, &event(Some(CAPTURE + 5), Severity::Info, &[]), 120).is_empty());
+        assert!(kinds(&d, &event(Some(CAPTURE + 200), Severity::High, &[]), 120).is_empty());
+    }
+
+    #[test]
+    fn threads_match_only_alongside_the_pid() {
+        let d = dump(42, CAPTURE, None);
+        let tid = event(None, Severity::Info, &[("tid", 0x10u64.into())]);
+        assert!(kinds(&d, &tid, 120).is_empty());
+        let both = event(
+            None,
+            Severity::Info,
+            &[("pid", 42u64.into()), ("tid", 0x10u64.into())],
+        );
+        assert_eq!(kinds(&d, &both, 120), vec!["pid", "thread"]);
+    }
+
+    #[test]
+    fn dumps_within_the_window_share_an_incident() {
+        let dumps = [
+            dump(42, CAPTURE, None),
+            dump(42, CAPTURE + 60, None),
+            dump(42, CAPTURE + 600, None),
+            dump(7, CAPTURE, None),
+        ];
+        let anchors: Vec<DumpAnchor> = dumps
+            .iter()
+            .map(|(summary, report)| DumpAnchor { summary, report })
+            .collect();
+        let before = event(
+            Some(CAPTURE - 10),
+            Severity::Warning,
+            &[("pid", 42u64.into())],
+        );
+        let after = event(Some(CAPTURE + 30), Severity::Info, &[("pid", 42u64.into())]);
+        let events = [(EventId(1), &after), (EventId(2), &before)];
+        let matches: Vec<Vec<EventMatch>> = anchors
+            .iter()
+            .map(|a| match_events(a, events, 120))
+            .collect();
+        let matches: Vec<&[EventMatch]> = matches.iter().map(Vec::as_slice).collect();
+
+        let incidents = incidents(&anchors, &matches, 120);
+        // The third dump is a later crash of a reused PID with no links. The fourth, another
+        // process, only shares the warning logged near its capture.
+        assert_eq!(incidents.len(), 2);
+        assert_eq!(incidents[1].dumps, vec![3]);
+        assert_eq!(incidents[1].links.len(), 1);
+        assert_eq!(incidents[1].links[0].event, EventId(2));
+        assert_eq!(incidents[1].links[0].reasons[0].kind(), "time");
+        let incident = &incidents[0];
+        assert_eq!(incident.dumps, vec![0, 1]);
+        assert_eq!(incident.title(), "App.exe (pid 42)");
+        assert_eq!(incident.capture.map(|c| c.unix_timestamp()), Some(CAPTURE));
+        let links: Vec<(u64, Option<i64>)> = incident
+            .links
+            .iter()
+            .map(|l| (l.event.0, l.delta_ms))
+            .collect();
+        assert_eq!(links, vec![(2, Some(-10_000)), (1, Some(30_000))]);
+        // Both dumps matched the later event; their reasons are merged without repeats.
+        let reasons: Vec<String> = incident.links[1]
+            .reasons
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "same pid 42 (pid)",
+                "+30.0s within the capture window",
+                "-30.0s within the capture window"
+            ]
+        );
+    }
+
+    #[test]
+    fn deltas_are_formatted_in_seconds_or_minutes() {
+        assert_eq!(format_delta(1_500), "+1.5s");
+        assert_eq!(format_delta(-123_000), "-2m03s");
+        assert_eq!(format_delta(0), "+0.0s");
+    }
+}
@@ -0,0 +1,514 @@
+//! Dump-to-dump diff: what changed between two `MinidumpReport`s, e.g. a crash that reproduced
+//! after a fix, or a healthy snapshot of a process next to a compromised one.
+//!
+//! Addresses move between runs (ASLR), so entities are matched on address-free keys: modules by
+//! file name, threads by start location (`module+offset`), injected regions by size,
+//! protection and reasons. Keys may repeat; each item of one side pairs with at most one of the
+//! other.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use serde::Serialize;
+
+use crate::model::{
+    DecodeStep, Indicator, InjectedRegion, MinidumpReport, ModuleInfo, ProcessExecArtifact,
+};
+
+/// Differences from the `before` dump to the `after` dump.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReportDiff {
+    /// Name of the source each side was loaded from.
+    pub before: String,
+    pub after: String,
+    pub modules: ModuleDiff,
+    pub threads: Changes<ThreadStart>,
+    pub injected_regions: Changes<InjectedRegion>,
+    pub exec_artifacts: Changes<ArtifactLine>,
+    pub iocs: Changes<Indicator>,
+    /// Crash signature parameters (`CrashSignature::parameters`) that differ; a parameter
+    /// missing on one side (e.g. no exception) has `None` there.
+    pub signature: Vec<SignatureChange>,
+}
+
+impl ReportDiff {
+    pub fn is_empty(&self) -> bool {
+        self.modules.added.is_empty()
+            && self.modules.removed.is_empty()
+            && self.modules.changed.is_empty()
+            && self.threads.is_empty()
+            && self.injected_regions.is_empty()
+            && self.exec_artifacts.is_empty()
+            && self.iocs.is_empty()
+            && self.signature.is_empty()
+    }
+}
+
+/// Items only in the `after` dump, and items only in the `before` dump.
+#[derive(Clone, Debug, Serialize)]
+pub struct Changes<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+}
+
+impl<T> Changes<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ModuleDiff {
+    pub added: Vec<ModuleInfo>,
+    pub removed: Vec<ModuleInfo>,
+    pub changed: Vec<ModuleChange>,
+}
+
+/// A module loaded in both dumps (same file name) with a different build or location.
+#[derive(Clone, Debug, Serialize)]
+pub struct ModuleChange {
+    pub before: ModuleInfo,
+    pub after: ModuleInfo,
+    /// What differs: `path`, `version`, `timestamp`, `size`, `checksum`, `debug_id`.
+    pub fields: Vec<&'static str>,
+}
+
+/// A thread, by where it started.
+#[derive(Clone, Debug, Serialize)]
+pub struct ThreadStart {
+    pub thread_id: u32,
+    pub start_address: Option<u64>,
+    /// `module+0xOFFSET` when the start address lies in a module, else the absolute address;
+    /// `unknown` without one.
+    pub start: String,
+}
+
+/// An execution artifact or one of its decoded payloads.
+#[derive(Clone, Debug, Serialize)]
+pub struct ArtifactLine {
+    pub image: String,
+    pub command_line: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded_via: Option<DecodeStep>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SignatureChange {
+    pub parameter: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Compare two dumps; each side is the source name and its report.
+pub fn diff(before: (&str, &MinidumpReport), after: (&str, &MinidumpReport)) -> ReportDiff {
+    let (before_name, b) = before;
+    let (after_name, a) = after;
+
+    let b_threads = thread_starts(b);
+    let a_threads = thread_starts(a);
+    let b_artifacts = artifact_lines(b);
+    let a_artifacts = artifact_lines(a);
+    ReportDiff {
+        before: before_name.to_string(),
+        after: after_name.to_string(),
+        modules: modules(&b.modules, &a.modules),
+        threads: changes(&b_threads, &a_threads, |t| t.start.to_lowercase()),
+        injected_regions: changes(&b.injected_regions, &a.injected_regions, |r| {
+            (
+                r.size,
+                r.protection.clone(),
+                r.ty.clone(),
+                r.reasons.clone(),
+            )
+        }),
+        exec_artifacts: changes(&b_artifacts, &a_artifacts, |l| {
+            (l.image.to_lowercase(), l.command_line.clone())
+        }),
+        iocs: changes(&b.iocs, &a.iocs, |i| (i.kind, i.value.clone())),
+        signature: signature(b, a),
+    }
+}
+
+/// Multiset difference of `before` and `after` by `key`, in each side's order.
+fn changes<T, K>(before: &[T], after: &[T], key: impl Fn(&T) -> K) -> Changes<T>
+where
+    T: Clone,
+    K: Eq + Hash,
+{
+    let only_in = |side: &[T], other: &[T]| {
+        let mut counts: HashMap<K, usize> = HashMap::new();
+        for item in other {
+            *counts.entry(key(item)).or_default() += 1;
+        }
+        side.iter()
+            .filter(|item| match counts.get_mut(&key(item)) {
+                Some(n) if *n > 0 => {
+                    *n -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect()
+    };
+    Changes {
+        added: only_in(after, before),
+        removed: only_in(before, after),
+    }
+}
+
+/// Modules paired by file name (case-insensitive, in load order when a name repeats).
+fn modules(before: &[ModuleInfo], after: &[ModuleInfo]) -> ModuleDiff {
+    let mut unpaired: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, m) in before.iter().enumerate().rev() {
+        unpaired.entry(module_key(m)).or_default().push(i);
+    }
+    let mut paired = vec![false; before.len()];
+    let mut out = ModuleDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for m in after {
+        let Some(i) = unpaired.get_mut(&module_key(m)).and_then(Vec::pop) else {
+            out.added.push(m.clone());
+            continue;
+        };
+        paired[i] = true;
+        let fields = module_fields(&before[i], m);
+        if !fields.is_empty() {
+            out.changed.push(ModuleChange {
+                before: before[i].clone(),
+                after: m.clone(),
+                fields,
+            });
+        }
+    }
+    out.removed = before
+        .iter()
+        .zip(paired)
+        .filter(|(_, p)| !p)
+        .map(|(m, _)| m.clone())
+        .collect();
+    out
+}
+
+fn module_key(m: &ModuleInfo) -> String {
+    file_name(&m.name).to_lowercase()
+}
+
+fn module_fields(b: &ModuleInfo, a: &ModuleInfo) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if !b.name.eq_ignore_ascii_case(&a.name) {
+        fields.push("path");
+    }
+    if b.file_version != a.file_version {
+        fields.push("version");
+    }
+    if b.time_date_stamp != a.time_date_stamp {
+        fields.push("timestamp");
+    }
+    if b.size != a.size {
+        fields.push("size");
+    }
+    if b.checksum != a.checksum {
+        fields.push("checksum");
+    }
+    if b.debug_id != a.debug_id {
+        fields.push("debug_id");
+    }
+    fields
+}
+
+fn thread_starts(report: &MinidumpReport) -> Vec<ThreadStart> {
+    report
+        .threads
+        .iter()
+        .map(|t| ThreadStart {
+            thread_id: t.thread_id,
+            start_address: t.start_address,
+            start: t
+                .start_address
+                .map_or_else(|| "unknown".to_string(), |addr| report.location(addr)),
+        })
+        .collect()
+}
+
+fn artifact_lines(report: &MinidumpReport) -> Vec<ArtifactLine> {
+    let line = |a: &ProcessExecArtifact| ArtifactLine {
+        image: a.image.clone(),
+        command_line: a.command_line.clone(),
+        decoded_via: a.decoded_via,
+    };
+    report
+        .exec_artifacts
+        .iter()
+        .flat_map(|a| std::iter::once(a).chain(a.descendants()))
+        .map(line)
+        .collect()
+}
+
+fn signature(before: &MinidumpReport, after: &MinidumpReport) -> Vec<SignatureChange> {
+    let params = |r: &MinidumpReport| {
+        r.crash_signature()
+            .map(|s| s.parameters())
+            .unwrap_or_default()
+    };
+    let b = params(before);
+    let a = params(after);
+    let mut names: Vec<&str> = b.iter().map(|(n, _)| *n).collect();
+    for (n, _) in &a {
+        if !names.contains(n) {
+            names.push(n);
+        }
+    }
+    let value = |side: &[(&str, String)], name: &str| {
+        side.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.clone())
+    };
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let before = value(&b, name);
+            let after = value(&a, name);
+            (before != after).then(|| SignatureChange {
+                parameter: name.to_string(),
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ExceptionInfo, ExecArtifactEncoding, Severity, ThreadInfo};
+
+    use super::*;
+
+    fn module(name: &str, base: u64, version: &str) -> ModuleInfo {
+        ModuleInfo {
+            name: name.to_string(),
+            base,
+            size: 0x1_0000,
+            checksum: 1,
+            time_date_stamp: 2,
+            file_version: Some(version.to_string()),
+            debug_id: Some("ID".into()),
+        }
+    }
+
+    fn thread(thread_id: u32, start_address: Option<u64>) -> ThreadInfo {
+        ThreadInfo {
+            thread_id,
+            name: None,
+            create_time_filetime: None,
+            create_time_unix: None,
+            start_address,
+            suspend_count: 0,
+            priority_class: 0,
+            priority: 0,
+            teb: 0,
+            stack_start: 0,
+            stack_size: 0,
+        }
+    }
+
+    fn artifact(image: &str, command_line: &str) -> ProcessExecArtifact {
+        ProcessExecArtifact {
+            image: image.to_string(),
+            command_line: command_line.to_string(),
+            encoding: ExecArtifactEncoding::Ascii,
+            address: None,
+            decoded_via: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn region(base: u64, size: u64) -> InjectedRegion {
+        InjectedRegion {
+            base,
+            size,
+            protection: "PAGE_EXECUTE_READWRITE".into(),
+            ty: "MEM_PRIVATE".into(),
+            state: "MEM_COMMIT".into(),
+            reasons: vec!["rwx private".into()],
+            risk: Severity::High,
+        }
+    }
+
+    /// The same process at `base`, as another run would load it under ASLR.
+    fn report(base: u64) -> MinidumpReport {
+        MinidumpReport {
+            modules: vec![
+                module("C:\\App\\app.exe", base, "1.0"),
+                module("C:\\App\\dep.dll", base + 0x10_0000, "1.0"),
+            ],
+            threads: vec![
+                thread(1, Some(base + 0x100)),
+                thread(2, Some(base + 0x10_0200)),
+                thread(3, Some(base + 0x10_0200)),
+            ],
+            injected_regions: vec![region(base + 0x100_0000, 0x1000)],
+            exec_artifacts: vec![artifact("cmd.exe", "cmd.exe /c echo")],
+            exception: Some(ExceptionInfo {
+                thread_id: 1,
+                code: 0xC000_0005,
+                flags: 0,
+                address: base + 0x10_0010,
+                number_parameters: 0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn relocated_dumps_of_the_same_run_do_not_differ() {
+        let d = diff(
+            ("a", &report(0x1_4000_0000)),
+            ("b", &report(0x7ff6_0000_0000)),
+        );
+        assert!(d.is_empty(), "{d:#?}");
+        assert_eq!((d.before.as_str(), d.after.as_str()), ("a", "b"));
+    }
+
+    #[test]
+    fn modules_pair_by_file_name() {
+        let before = report(0x1000_0000);
+        let mut after = report(0x2000_0000);
+        after.modules[1].name = "D:\\Other\\DEP.dll".into();
+        after.modules[1].file_version = Some("1.1".into());
+        after.modules[1].debug_id = Some("ID2".into());
+        after
+            .modules
+            .push(module("C:\\App\\new.dll", 0x3000_0000, "1.0"));
+        let mut before = before;
+        before
+            .modules
+            .push(module("C:\\App\\old.dll", 0x4000_0000, "1.0"));
+
+        let d = diff(("a", &before), ("b", &after));
+        let names = |ms: &[ModuleInfo]| {
+            ms.iter()
+                .map(|m| file_name(&m.name).to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&d.modules.added), vec!["new.dll"]);
+        assert_eq!(names(&d.modules.removed), vec!["old.dll"]);
+        assert_eq!(d.modules.changed.len(), 1);
+        assert_eq!(
+            d.modules.changed[0].fields,
+            vec!["path", "version", "debug_id"]
+        );
+    }
+
+    #[test]
+    fn repeated_keys_pair_one_to_one() {
+        let before = report(0x1000_0000);
+        let mut after = report(0x1000_0000);
+        // One fewer thread at dep.dll+0x200, one more at app.exe+0x100.
+        after.threads.pop();
+        after.threads.push(thread(4, Some(0x1000_0100)));
+        after.threads.push(thread(5, None));
+        after
+            .exec_artifacts
+            .push(artifact("CMD.EXE", "cmd.exe /c echo"));
+
+        let d = diff(("a", &before), ("b", &after));
+        let starts = |ts: &[ThreadStart]| {
+            ts.iter()
+                .map(|t| (t.thread_id, t.start.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            starts(&d.threads.added),
+            vec![(4, "app.exe+0x100".to_string()), (5, "unknown".to_string())]
+        );
+        assert_eq!(
+            starts(&d.threads.removed),
+            vec![(3, "dep.dll+0x200".to_string())]
+        );
+        assert_eq!(d.exec_artifacts.added.len(), 1);
+        assert!(d.exec_artifacts.removed.is_empty());
+    }
+
+    #[test]
+    fn regions_artifacts_and_decoded_payloads_are_compared() {
+        let before = report(0x1000_0000);
+        let mut after = report(0x1000_0000);
+        after.injected_regions[0].size = 0x2000;
+        let mut encoded = artifact("powershell.exe", "powershell -enc ...");
+        encoded.children.push(ProcessExecArtifact {
+            decoded_via: Some(DecodeStep::EncodedCommand),
+            ..artifact("", "iex (new-object net.webclient)")
+        });
+        after.exec_artifacts = vec![encoded];
+
+        let d = diff(("a", &before), ("b", &after));
+        assert_eq!(d.injected_regions.added[0].size, 0x2000);
+        assert_eq!(d.injected_regions.removed[0].size, 0x1000);
+        let lines: Vec<(&str, Option<DecodeStep>)> = d
+            .exec_artifacts
+            .added
+            .iter()
+            .map(|l| (l.command_line.as_str(), l.decoded_via))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("powershell -enc ...", None),
+                (
+                    "iex (new-object net.webclient)",
+                    Some(DecodeStep::EncodedCommand)
+                ),
+            ]
+        );
+        assert_eq!(d.exec_artifacts.removed[0].command_line, "cmd.exe /c echo");
+    }
+
+    #[test]
+    fn signature_changes_list_each_differing_parameter() {
+        let before = report(0x1000_0000);
+        let mut after = report(0x2000_0000);
+        after.exception.as_mut().unwrap().address = 0x2000_0020;
+
+        let d = diff(("a", &before), ("b", &after));
+        let changes: Vec<(&str, Option<&str>, Option<&str>)> = d
+            .signature
+            .iter()
+            .map(|c| {
+                (
+                    c.parameter.as_str(),
+                    c.before.as_deref(),
+                    c.after.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("Fault Module Name", Some("dep.dll"), Some("app.exe")),
+                (
+                    "Exception Offset",
+                    Some("0000000000000010"),
+                    Some("0000000000000020")
+                ),
+            ]
+        );
+
+        // No exception on one side: its parameters are missing there.
+        after.exception = None;
+        let d = diff(("a", &before), ("b", &after));
+        assert!(!d.signature.is_empty());
+        assert!(
+            d.signature
+                .iter()
+                .all(|c| c.before.is_some() && c.after.is_none())
+        );
+    }
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use crate::ingest::minidump_secrets::redact_secrets;
+use crate::triage::TriagedDetection;
+
+/// Render detections as JSON Lines with their id, triage status, tags, notes and the
+/// allowlist reason that suppressed them, if any. Details and notes are redacted the same way
+/// as IOC exports.
+pub fn detections_to_json(detections: &[TriagedDetection]) -> Result<String> {
+    let mut out = String::new();
+    for d in detections {
+        let mut d = d.clone();
+        d.detection.details = redact_secrets(&d.detection.details);
+        for note in &mut d.notes {
+            *note = redact_secrets(note);
+        }
+        out.push_str(&serde_json::to_string(&d)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
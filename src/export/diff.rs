@@ -0,0 +1,22 @@
+use anyhow::Result;
+
+use crate::diff::ReportDiff;
+use crate::ingest::minidump_secrets::redact_secrets;
+
+/// Render a dump diff as one pretty-printed JSON object. IOC values and command lines are
+/// redacted the same way as IOC exports.
+pub fn diff_to_json(diff: &ReportDiff) -> Result<String> {
+    let mut diff = diff.clone();
+    for line in diff
+        .exec_artifacts
+        .added
+        .iter_mut()
+        .chain(&mut diff.exec_artifacts.removed)
+    {
+        line.command_line = redact_secrets(&line.command_line);
+    }
+    for ioc in diff.iocs.added.iter_mut().chain(&mut diff.iocs.removed) {
+        ioc.value = redact_secrets(&ioc.value);
+    }
+    Ok(serde_json::to_string_pretty(&diff)? + "\n")
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+
+use crate::ingest::minidump_secrets::redact_secrets;
+use crate::model::Indicator;
+
+const CSV_HEADER: &str = "kind,value,count,address,encoding,container";
+
+/// Render indicators as CSV (RFC 4180 quoting), one row per deduplicated indicator.
+///
+/// Credentials embedded in values (URL userinfo, SAS signatures, tokens) are redacted.
+pub fn iocs_to_csv(iocs: &[Indicator]) -> String {
+    let mut out = String::from(CSV_HEADER);
+    out.push('\n');
+    for ioc in iocs {
+        let row = [
+            ioc.kind.label().to_string(),
+            redact_secrets(&ioc.value),
+            ioc.count.to_string(),
+            format!("0x{:016X}", ioc.address),
+            ioc.encoding.label().to_string(),
+            ioc.container.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Render indicators as JSON Lines, which most SIEM importers accept directly.
+/// Values are redacted the same way as [`iocs_to_csv`].
+pub fn iocs_to_json(iocs: &[Indicator]) -> Result<String> {
+    let mut out = String::new();
+    for ioc in iocs {
+        let ioc = Indicator {
+            value: redact_secrets(&ioc.value),
+            ..ioc.clone()
+        };
+        out.push_str(&serde_json::to_string(&ioc)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -0,0 +1,669 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::ingest::minidump_ioc::extract_from_text;
+use crate::ingest::minidump_scan::{ScanBudget, StringLimits, for_each_string, scan_strings};
+use crate::ingest::minidump_secrets::{extract_secrets, find_secrets};
+use crate::model::{ExecArtifactEncoding, IocKind};
+
+/// Bytes copied from the source dump to the redacted one per write.
+const COPY_CHUNK: usize = 1024 * 1024;
+
+/// Strings the environment, email and non-memory passes look at.
+const STRING_LIMITS: StringLimits = StringLimits {
+    min_len: 6,
+    max_len: 8192,
+};
+
+/// Largest environment block we trust from `RTL_USER_PROCESS_PARAMETERS.EnvironmentSize`.
+const MAX_ENVIRONMENT_BYTES: u64 = 1024 * 1024;
+
+/// Variables that only ever appear as `NAME=value` inside an environment block (or a copy of
+/// one); used to catch environment strings the PEB walk cannot reach.
+static ENV_VAR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^(?:ALLUSERSPROFILE|APPDATA|COMPUTERNAME|ComSpec|HOMEDRIVE|HOMEPATH|LOCALAPPDATA|LOGONSERVER|Path|PATHEXT|ProgramData|ProgramFiles|PUBLIC|SystemDrive|SystemRoot|TEMP|TMP|USERDOMAIN|USERDOMAIN_ROAMINGPROFILE|USERNAME|USERPROFILE|windir|HOME|USER|LOGNAME|SHELL|PWD|MAIL|SSH_AUTH_SOCK|SSH_CONNECTION)=",
+    )
+    .expect("env var regex")
+});
+
+/// What to strip from a dump. Thread stacks and module images are always kept so the
+/// redacted dump still unwinds.
+#[derive(Clone, Debug, Serialize)]
+pub struct RedactionOptions {
+    /// Captured memory outside thread stacks and module images.
+    pub heap: bool,
+    /// The process environment block (and stray `NAME=value` environment strings).
+    pub environment: bool,
+    /// Strings matching secret or PII patterns, wherever they are (including stacks, and
+    /// streams and names outside captured memory).
+    pub sensitive_strings: bool,
+    /// Replace an existing output instead of failing.
+    #[serde(skip)]
+    pub overwrite: bool,
+    /// Replace an existing manifest instead of failing.
+    #[serde(skip)]
+    pub overwrite_manifest: bool,
+}
+
+impl Default for RedactionOptions {
+    fn default() -> Self {
+        Self {
+            heap: true,
+            environment: true,
+            sensitive_strings: true,
+            overwrite: false,
+            overwrite_manifest: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionCategory {
+    Heap,
+    Environment,
+    Secret,
+    Pii,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RedactedRange {
+    pub category: RedactionCategory,
+    /// Virtual address of the zeroed memory; `None` for data outside captured memory (whole
+    /// streams, or strings in streams and names).
+    pub address: Option<u64>,
+    pub len: u64,
+    pub file_offset: u64,
+    pub detail: String,
+}
+
+/// Everything removed from the dump, written next to it as `<output>.redaction.json`.
+#[derive(Clone, Debug, Serialize)]
+pub struct RedactionManifest {
+    pub source: String,
+    pub output: String,
+    pub options: RedactionOptions,
+    /// Distinct bytes zeroed (ranges below may overlap, e.g. a secret inside heap memory).
+    pub bytes_zeroed: u64,
+    pub stacks_kept: usize,
+    pub modules_kept: usize,
+    pub ranges: Vec<RedactedRange>,
+}
+
+/// A captured memory range and where its bytes live in the dump file.
+struct Segment {
+    base: u64,
+    len: u64,
+    file_offset: u64,
+}
+
+impl Segment {
+    fn end(&self) -> u64 {
+        self.base.saturating_add(self.len)
+    }
+}
+
+/// Write a copy of `input` to `output` with the selected memory zeroed, plus a manifest at
+/// [`manifest_path`]. Zeroing (rather than dropping ranges) keeps every stream offset valid,
+/// so the result parses and stackwalks like the original. The source is memory-mapped and
+/// streamed to the output; an existing output is only replaced with `options.overwrite`, an
+/// existing manifest only with `options.overwrite_manifest`.
+pub fn write_redacted_minidump(
+    input: &Path,
+    output: &Path,
+    options: &RedactionOptions,
+) -> Result<RedactionManifest> {
+    let manifest_file = manifest_path(output);
+    for target in [output, manifest_file.as_path()] {
+        if same_file(input, target) {
+            bail!("refusing to overwrite the source dump {}", input.display());
+        }
+    }
+    let file = std::fs::File::open(input).with_context(|| format!("open {}", input.display()))?;
+    // SAFETY: see `ingest::minidump::ingest`; the source is only read, and the output is
+    // checked above not to be the same file.
+    let map =
+        unsafe { memmap2::Mmap::map(&file) }.with_context(|| format!("map {}", input.display()))?;
+    let dump = minidump::Minidump::read(&map[..]).context("parse minidump")?;
+
+    let (ranges, stacks_kept, modules_kept) = plan_redactions(&dump, &map, options);
+    let spans = merged_spans(&ranges);
+
+    let out = create_output(output, options.overwrite)?;
+    let manifest_out = match create_output(&manifest_file, options.overwrite_manifest) {
+        Ok(f) => f,
+        Err(e) => {
+            drop(out);
+            let _ = std::fs::remove_file(output);
+            return Err(e);
+        }
+    };
+    let manifest = RedactionManifest {
+        source: input.display().to_string(),
+        output: output.display().to_string(),
+        options: options.clone(),
+        bytes_zeroed: spans.iter().map(|(s, e)| (e - s) as u64).sum(),
+        stacks_kept,
+        modules_kept,
+        ranges,
+    };
+    let written = copy_zeroed(&map, &spans, &mut std::io::BufWriter::new(&out))
+        .with_context(|| format!("write {}", output.display()))
+        .and_then(|()| {
+            // SAFETY: the output was created above and nothing else writes to it.
+            let check = unsafe { memmap2::Mmap::map(&out) }
+                .with_context(|| format!("map {}", output.display()))?;
+            minidump::Minidump::read(&check[..]).context("redacted dump no longer parses")?;
+            Ok(())
+        })
+        .and_then(|()| {
+            (&manifest_out)
+                .write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())
+                .with_context(|| format!("write {}", manifest_file.display()))
+        });
+    if let Err(e) = written {
+        drop((out, manifest_out));
+        let _ = std::fs::remove_file(output);
+        let _ = std::fs::remove_file(&manifest_file);
+        return Err(e);
+    }
+    Ok(manifest)
+}
+
+/// Whether `a` and `b` name the same existing file, through symlinks, `..` or hard links.
+fn same_file(a: &Path, b: &Path) -> bool {
+    let (Ok(ca), Ok(cb)) = (std::fs::canonicalize(a), std::fs::canonicalize(b)) else {
+        return false;
+    };
+    if ca == cb {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let (Ok(ma), Ok(mb)) = (std::fs::metadata(&ca), std::fs::metadata(&cb)) {
+            return ma.dev() == mb.dev() && ma.ino() == mb.ino();
+        }
+    }
+    false
+}
+
+fn create_output(path: &Path, overwrite: bool) -> Result<std::fs::File> {
+    let mut open = std::fs::File::options();
+    open.read(true).write(true);
+    if overwrite {
+        open.create(true).truncate(true);
+    } else {
+        open.create_new(true);
+    }
+    open.open(path).with_context(|| {
+        if path.exists() && !overwrite {
+            format!("{} already exists", path.display())
+        } else {
+            format!("create {}", path.display())
+        }
+    })
+}
+
+/// Copy `file` to `out` through one buffer, zeroing `spans` (sorted, disjoint file ranges).
+fn copy_zeroed(file: &[u8], spans: &[(usize, usize)], out: &mut impl Write) -> std::io::Result<()> {
+    let mut buf = vec![0u8; COPY_CHUNK.min(file.len())];
+    let mut next = 0;
+    for chunk_start in (0..file.len()).step_by(COPY_CHUNK) {
+        let chunk_end = (chunk_start + COPY_CHUNK).min(file.len());
+        let chunk = &mut buf[..chunk_end - chunk_start];
+        chunk.copy_from_slice(&file[chunk_start..chunk_end]);
+        while let Some(&(start, end)) = spans.get(next)
+            && start < chunk_end
+        {
+            chunk[start.max(chunk_start) - chunk_start..end.min(chunk_end) - chunk_start].fill(0);
+            if end > chunk_end {
+                break;
+            }
+            next += 1;
+        }
+        out.write_all(chunk)?;
+    }
+    out.flush()
+}
+
+pub fn manifest_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(".redaction.json");
+    PathBuf::from(name)
+}
+
+fn plan_redactions(
+    dump: &minidump::Minidump<'_, &[u8]>,
+    file: &[u8],
+    options: &RedactionOptions,
+) -> (Vec<RedactedRange>, usize, usize) {
+    let segments = collect_segments(dump, file);
+
+    let stacks: Vec<(u64, u64)> = dump
+        .get_stream::<minidump::MinidumpThreadList>()
+        .map(|tl| {
+            tl.threads
+                .iter()
+                .map(|t| {
+                    let start = t.raw.stack.start_of_memory_range;
+                    (
+                        start,
+                        start.saturating_add(t.raw.stack.memory.data_size as u64),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut images: Vec<(u64, u64)> = dump
+        .get_stream::<minidump::MinidumpModuleList>()
+        .map(|ml| {
+            ml.iter()
+                .map(|m| {
+                    (
+                        m.raw.base_of_image,
+                        m.raw
+                            .base_of_image
+                            .saturating_add(m.raw.size_of_image as u64),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let modules_kept = images.len();
+
+    let memory_info: Vec<(u64, u64, minidump::format::MemoryType)> = dump
+        .get_stream::<minidump::MinidumpMemoryInfoList>()
+        .map(|mi| {
+            mi.iter()
+                .map(|r| {
+                    (
+                        r.raw.base_address,
+                        r.raw.base_address.saturating_add(r.raw.region_size),
+                        r.ty,
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    images.extend(
+        memory_info
+            .iter()
+            .filter(|(_, _, ty)| ty.contains(minidump::format::MemoryType::MEM_IMAGE))
+            .map(|(s, e, _)| (*s, *e)),
+    );
+
+    let mut ranges: Vec<RedactedRange> = Vec::new();
+
+    if options.heap {
+        let mut keep: Vec<(u64, u64)> = stacks.iter().chain(images.iter()).copied().collect();
+        keep.sort_unstable();
+        for seg in &segments {
+            for (start, end) in subtract(seg.base, seg.end(), &keep) {
+                let ty = memory_info
+                    .iter()
+                    .find(|(s, e, _)| start >= *s && start < *e)
+                    .map(|(_, _, ty)| memory_type_label(*ty))
+                    .unwrap_or("captured");
+                ranges.push(RedactedRange {
+                    category: RedactionCategory::Heap,
+                    address: Some(start),
+                    len: end - start,
+                    file_offset: seg.file_offset + (start - seg.base),
+                    detail: format!("{ty} memory"),
+                });
+            }
+        }
+    }
+
+    if options.environment {
+        if let Some((addr, len)) = locate_environment_block(dump, &segments, file) {
+            push_memory_range(
+                &mut ranges,
+                &segments,
+                RedactionCategory::Environment,
+                addr,
+                len,
+                "PEB environment block",
+            );
+        }
+        if let Ok(environ) =
+            dump.get_raw_stream(minidump::format::MINIDUMP_STREAM_TYPE::LinuxEnviron as u32)
+            && !environ.is_empty()
+        {
+            ranges.push(RedactedRange {
+                category: RedactionCategory::Environment,
+                address: None,
+                len: environ.len() as u64,
+                file_offset: file_offset_of(file, environ),
+                detail: "LinuxEnviron stream".into(),
+            });
+        }
+    }
+
+    // Both string passes ignore the scan budget: a partial scan would silently leak.
+    let budget = ScanBudget::default();
+    if options.sensitive_strings {
+        for finding in extract_secrets(dump, &budget, &[], &[]).hits {
+            for span in &finding.spans {
+                push_memory_range(
+                    &mut ranges,
+                    &segments,
+                    RedactionCategory::Secret,
+                    span.address,
+                    span.len,
+                    &format!("{}: {}", finding.kind.label(), finding.preview),
+                );
+            }
+        }
+    }
+    if options.sensitive_strings || options.environment {
+        let strings = scan_strings(dump, &budget, STRING_LIMITS, |s| {
+            let unit = match s.encoding {
+                ExecArtifactEncoding::Ascii => 1,
+                ExecArtifactEncoding::Utf16Le => 2,
+            };
+            let mut hits: Vec<(RedactionCategory, u64, u64, &'static str)> = Vec::new();
+            if options.environment && ENV_VAR_RE.is_match(s.text) {
+                hits.push((
+                    RedactionCategory::Environment,
+                    s.address,
+                    s.text.len() as u64 * unit,
+                    "environment string",
+                ));
+            }
+            if options.sensitive_strings && s.text.contains('@') {
+                for (kind, offset, value) in extract_from_text(s.text) {
+                    if kind == IocKind::Email {
+                        hits.push((
+                            RedactionCategory::Pii,
+                            s.address + offset as u64 * unit,
+                            value.len() as u64 * unit,
+                            "email address",
+                        ));
+                    }
+                }
+            }
+            (!hits.is_empty()).then_some(hits)
+        });
+        for (category, addr, len, detail) in strings.hits.into_iter().flatten() {
+            push_memory_range(&mut ranges, &segments, category, addr, len, detail);
+        }
+    }
+
+    if options.sensitive_strings {
+        push_unmapped_strings(&mut ranges, dump, &segments, file);
+    }
+
+    (ranges, stacks.len(), modules_kept)
+}
+
+/// Secrets and email addresses in the dump file outside captured memory: the Linux command
+/// line and environment streams, Crashpad annotations, thread and module names.
+fn push_unmapped_strings(
+    ranges: &mut Vec<RedactedRange>,
+    dump: &minidump::Minidump<'_, &[u8]>,
+    segments: &[Segment],
+    file: &[u8],
+) {
+    let mut memory: Vec<(u64, u64)> = segments
+        .iter()
+        .map(|s| (s.file_offset, s.file_offset.saturating_add(s.len)))
+        .collect();
+    memory.sort_unstable();
+    let streams: Vec<(u64, u64, u32)> = dump
+        .all_streams()
+        .map(|d| {
+            let rva = u64::from(d.location.rva);
+            (rva, rva + u64::from(d.location.data_size), d.stream_type)
+        })
+        .collect();
+
+    for (start, end) in subtract(0, file.len() as u64, &memory) {
+        for_each_string(
+            &file[start as usize..end as usize],
+            start,
+            STRING_LIMITS,
+            |s| {
+                let unit = match s.encoding {
+                    ExecArtifactEncoding::Ascii => 1,
+                    ExecArtifactEncoding::Utf16Le => 2,
+                };
+                let secrets = find_secrets(s.text).into_iter().map(|m| {
+                    (
+                        RedactionCategory::Secret,
+                        m.value.start,
+                        m.value.len(),
+                        format!("{}: {}", m.kind.label(), m.preview),
+                    )
+                });
+                let emails = extract_from_text(s.text)
+                    .into_iter()
+                    .filter(|(kind, _, _)| *kind == IocKind::Email)
+                    .map(|(_, offset, value)| {
+                        (
+                            RedactionCategory::Pii,
+                            offset,
+                            value.len(),
+                            "email address".to_string(),
+                        )
+                    });
+                for (category, offset, len, what) in secrets.chain(emails) {
+                    let file_offset = s.address + offset as u64 * unit;
+                    let place = streams
+                        .iter()
+                        .find(|(lo, hi, _)| (*lo..*hi).contains(&file_offset))
+                        .map_or_else(
+                            || "names and annotations outside the streams".to_string(),
+                            |(_, _, ty)| format!("{} stream", stream_label(*ty)),
+                        );
+                    ranges.push(RedactedRange {
+                        category,
+                        address: None,
+                        len: len as u64 * unit,
+                        file_offset,
+                        detail: format!("{what} ({place})"),
+                    });
+                }
+            },
+        );
+    }
+}
+
+/// Name of a stream type that can carry text; the number for the others.
+fn stream_label(ty: u32) -> String {
+    use minidump::format::MINIDUMP_STREAM_TYPE as S;
+    [
+        S::LinuxCmdLine,
+        S::LinuxEnviron,
+        S::LinuxProcStatus,
+        S::LinuxMaps,
+        S::CrashpadInfoStream,
+        S::ThreadNamesStream,
+        S::ModuleListStream,
+        S::MiscInfoStream,
+    ]
+    .into_iter()
+    .find(|s| *s as u32 == ty)
+    .map_or_else(|| format!("0x{ty:X}"), |s| format!("{s:?}"))
+}
+
+/// Memory from both memory lists; a dump normally carries only one of them.
+fn collect_segments(dump: &minidump::Minidump<'_, &[u8]>, file: &[u8]) -> Vec<Segment> {
+    let mut out: Vec<Segment> = Vec::new();
+    if let Ok(mem) = dump.get_stream::<minidump::MinidumpMemoryList>() {
+        out.extend(mem.iter().map(|r| Segment {
+            base: r.base_address,
+            len: r.bytes.len() as u64,
+            file_offset: file_offset_of(file, r.bytes),
+        }));
+    }
+    if let Ok(mem64) = dump.get_stream::<minidump::MinidumpMemory64List>() {
+        out.extend(mem64.iter().map(|r| Segment {
+            base: r.base_address,
+            len: r.bytes.len() as u64,
+            file_offset: file_offset_of(file, r.bytes),
+        }));
+    }
+    out.sort_by_key(|s| s.base);
+    out
+}
+
+/// Stream and memory slices borrow from the file buffer, so their position is their offset.
+fn file_offset_of(file: &[u8], slice: &[u8]) -> u64 {
+    (slice.as_ptr() as usize - file.as_ptr() as usize) as u64
+}
+
+/// Zero `[addr, addr + len)` wherever it is captured, split at segment boundaries.
+fn push_memory_range(
+    ranges: &mut Vec<RedactedRange>,
+    segments: &[Segment],
+    category: RedactionCategory,
+    addr: u64,
+    len: u64,
+    detail: &str,
+) {
+    let end = addr.saturating_add(len);
+    for seg in segments {
+        let lo = addr.max(seg.base);
+        let hi = end.min(seg.end());
+        if lo < hi {
+            ranges.push(RedactedRange {
+                category,
+                address: Some(lo),
+                len: hi - lo,
+                file_offset: seg.file_offset + (lo - seg.base),
+                detail: detail.to_string(),
+            });
+        }
+    }
+}
+
+/// Parts of `[start, end)` not covered by `keep` (sorted by start).
+fn subtract(start: u64, end: u64, keep: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut out = Vec::new();
+    let mut cursor = start;
+    for &(ks, ke) in keep {
+        if ke <= cursor || ks >= end {
+            continue;
+        }
+        if ks > cursor {
+            out.push((cursor, ks));
+        }
+        cursor = cursor.max(ke);
+        if cursor >= end {
+            break;
+        }
+    }
+    if cursor < end {
+        out.push((cursor, end));
+    }
+    out
+}
+
+/// Follow TEB -> PEB -> ProcessParameters -> Environment for the first thread.
+fn locate_environment_block(
+    dump: &minidump::Minidump<'_, &[u8]>,
+    segments: &[Segment],
+    file: &[u8],
+) -> Option<(u64, u64)> {
+    let sys = dump.get_stream::<minidump::MinidumpSystemInfo>().ok()?;
+    if sys.os != minidump::system_info::Os::Windows {
+        return None;
+    }
+    // (pointer size, TEB.ProcessEnvironmentBlock, PEB.ProcessParameters,
+    //  RTL_USER_PROCESS_PARAMETERS.Environment, .EnvironmentSize)
+    let (ptr, teb_peb, peb_params, env, env_size) = match sys.cpu {
+        minidump::system_info::Cpu::X86 => (4, 0x30, 0x10, 0x48, 0x290),
+        minidump::system_info::Cpu::X86_64 | minidump::system_info::Cpu::Arm64 => {
+            (8, 0x60, 0x20, 0x80, 0x3F0)
+        }
+        _ => return None,
+    };
+    let teb = dump
+        .get_stream::<minidump::MinidumpThreadList>()
+        .ok()?
+        .threads
+        .first()?
+        .raw
+        .teb;
+    let read = |addr: u64| read_pointer(segments, file, addr, ptr);
+
+    let peb = read(teb.checked_add(teb_peb)?)?;
+    let params = read(peb.checked_add(peb_params)?)?;
+    let block = read(params.checked_add(env)?)?;
+    if block == 0 {
+        return None;
+    }
+    let size = read(params.checked_add(env_size)?)
+        .filter(|s| *s > 0 && *s <= MAX_ENVIRONMENT_BYTES)
+        .or_else(|| environment_len_by_terminator(segments, file, block))?;
+    Some((block, size))
+}
+
+fn read_pointer(segments: &[Segment], file: &[u8], addr: u64, size: usize) -> Option<u64> {
+    let seg = segments
+        .iter()
+        .find(|s| addr >= s.base && addr.saturating_add(size as u64) <= s.end())?;
+    let off = (seg.file_offset + (addr - seg.base)) as usize;
+    let bytes = file.get(off..off + size)?;
+    Some(match size {
+        4 => u32::from_le_bytes(bytes.try_into().ok()?) as u64,
+        _ => u64::from_le_bytes(bytes.try_into().ok()?),
+    })
+}
+
+/// Older dumps may not have a usable `EnvironmentSize`; the block ends at a UTF-16 `\0\0`.
+fn environment_len_by_terminator(segments: &[Segment], file: &[u8], block: u64) -> Option<u64> {
+    let seg = segments
+        .iter()
+        .find(|s| block >= s.base && block < s.end())?;
+    let start = (seg.file_offset + (block - seg.base)) as usize;
+    let end = (seg.file_offset + seg.len) as usize;
+    let bytes = &file[start..end.min(start + MAX_ENVIRONMENT_BYTES as usize)];
+    bytes
+        .chunks_exact(2)
+        .collect::<Vec<_>>()
+        .windows(2)
+        .position(|w| w[0] == [0, 0] && w[1] == [0, 0])
+        .map(|units| (units as u64 + 2) * 2)
+}
+
+fn memory_type_label(ty: minidump::format::MemoryType) -> &'static str {
+    use minidump::format::MemoryType as MT;
+    if ty.contains(MT::MEM_MAPPED) {
+        "mapped"
+    } else if ty.contains(MT::MEM_PRIVATE) {
+        "private"
+    } else {
+        "unknown"
+    }
+}
+
+/// File ranges of `ranges`, sorted with overlaps merged (ranges may overlap, e.g. a secret
+/// inside heap memory).
+fn merged_spans(ranges: &[RedactedRange]) -> Vec<(usize, usize)> {
+    let mut spans: Vec<(usize, usize)> = ranges
+        .iter()
+        .map(|r| {
+            let start = r.file_offset as usize;
+            (start, start + r.len as usize)
+        })
+        .collect();
+    spans.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+    for (s, e) in spans {
+        match merged.last_mut() {
+            Some(last) if s <= last.1 => last.1 = last.1.max(e),
+            _ if e > s => merged.push((s, e)),
+            _ => {}
+        }
+    }
+    merged
+}
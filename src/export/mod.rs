@@ -0,0 +1,16 @@
+mod detections;
+mod diff;
+mod ioc;
+mod minidump_redact;
+mod process_tree;
+mod sensitive;
+
+pub use detections::detections_to_json;
+pub use diff::diff_to_json;
+pub use ioc::{iocs_to_csv, iocs_to_json};
+pub use minidump_redact::{
+    RedactedRange, RedactionCategory, RedactionManifest, RedactionOptions, manifest_path,
+    write_redacted_minidump,
+};
+pub use process_tree::process_tree_to_json;
+pub use sensitive::sensitive_to_json;
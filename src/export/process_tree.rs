@@ -0,0 +1,93 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::model::{ProcessTree, SysmonRecord};
+
+#[derive(Serialize)]
+struct TreeExport<'a> {
+    processes: usize,
+    sysmon_records: usize,
+    /// Path from the minidump's process up to its root, when it was located in the tree.
+    minidump_process: Option<Vec<NodeRef<'a>>>,
+    roots: Vec<NodeExport<'a>>,
+}
+
+#[derive(Serialize)]
+struct NodeRef<'a> {
+    guid: &'a str,
+    pid: Option<u32>,
+    image: &'a str,
+}
+
+#[derive(Serialize)]
+struct NodeExport<'a> {
+    guid: &'a str,
+    pid: Option<u32>,
+    image: &'a str,
+    command_line: &'a str,
+    user: &'a str,
+    hashes: &'a [(String, String)],
+    start_unix: Option<i64>,
+    observed: bool,
+    minidump_process: bool,
+    activity: Vec<&'a SysmonRecord>,
+    children: Vec<NodeExport<'a>>,
+}
+
+/// Render the process tree as nested JSON. `dump_node` marks the minidump's process.
+pub fn process_tree_to_json(tree: &ProcessTree, dump_node: Option<usize>) -> Result<String> {
+    let mut visited = vec![false; tree.nodes.len()];
+    let export = TreeExport {
+        processes: tree.nodes.len(),
+        sysmon_records: tree.records.len(),
+        minidump_process: dump_node.map(|idx| {
+            tree.ancestry(idx)
+                .into_iter()
+                .map(|i| {
+                    let n = &tree.nodes[i];
+                    NodeRef {
+                        guid: &n.guid,
+                        pid: n.pid,
+                        image: &n.image,
+                    }
+                })
+                .collect()
+        }),
+        roots: tree
+            .roots
+            .iter()
+            .filter_map(|&r| node_export(tree, r, dump_node, &mut visited))
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+fn node_export<'a>(
+    tree: &'a ProcessTree,
+    idx: usize,
+    dump_node: Option<usize>,
+    visited: &mut [bool],
+) -> Option<NodeExport<'a>> {
+    // Guards against parent cycles from malformed logs.
+    if std::mem::replace(&mut visited[idx], true) {
+        return None;
+    }
+    let n = &tree.nodes[idx];
+    Some(NodeExport {
+        guid: &n.guid,
+        pid: n.pid,
+        image: &n.image,
+        command_line: &n.command_line,
+        user: &n.user,
+        hashes: &n.hashes,
+        start_unix: n.start_unix,
+        observed: n.observed,
+        minidump_process: dump_node == Some(idx),
+        activity: n.activity.iter().map(|&a| &tree.records[a]).collect(),
+        children: n
+            .children
+            .iter()
+            .filter_map(|&c| node_export(tree, c, dump_node, visited))
+            .collect(),
+    })
+}
@@ -0,0 +1,14 @@
+use anyhow::Result;
+
+use crate::model::SensitiveFinding;
+
+/// Render findings as JSON Lines. Only masked previews and memory spans are written; the
+/// secrets themselves are never kept after the scan.
+pub fn sensitive_to_json(findings: &[SensitiveFinding]) -> Result<String> {
+    let mut out = String::new();
+    for f in findings {
+        out.push_str(&serde_json::to_string(f)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
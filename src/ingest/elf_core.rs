@@ -0,0 +1,495 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::ingest::minidump::IngestedMinidump;
+use crate::model::{Event, EventId, EventStore, Severity};
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PN_XNUM: u16 = 0xffff;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_FILE: u32 = 0x4649_4c45;
+const NT_SIGINFO: u32 = 0x5349_4749;
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// Offset of `pr_reg` in `struct elf_prstatus` (64-bit: siginfo, cursig, sigpend, sighold,
+/// pid/ppid/pgrp/sid and four timevals precede it).
+const PRSTATUS_REGS_OFFSET: usize = 112;
+
+/// `NT_FILE` entries beyond this are ignored (a corrupt count should not allocate gigabytes).
+const MAX_FILE_ENTRIES: usize = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CoreMachine {
+    X86_64,
+    Aarch64,
+}
+
+impl CoreMachine {
+    fn register_count(self) -> usize {
+        match self {
+            // `struct user_regs_struct`: r15 .. gs.
+            CoreMachine::X86_64 => 27,
+            // x0..x30, sp, pc, pstate.
+            CoreMachine::Aarch64 => 34,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            CoreMachine::X86_64 => "x86_64",
+            CoreMachine::Aarch64 => "aarch64",
+        }
+    }
+}
+
+/// The parts of an ELF core file the minidump pipeline needs.
+pub(crate) struct ElfCore {
+    pub machine: CoreMachine,
+    /// One entry per `NT_PRSTATUS` note; the kernel writes the faulting thread first.
+    pub threads: Vec<CoreThread>,
+    pub process: Option<CoreProcess>,
+    pub signal: Option<CoreSignal>,
+    /// `NT_FILE`: file-backed mappings.
+    pub files: Vec<CoreFileMapping>,
+    /// Raw `NT_AUXV` words.
+    pub auxv: Vec<u8>,
+    pub segments: Vec<CoreSegment>,
+    /// `PT_LOAD` segments cut short because the file ends early.
+    pub truncated_segments: usize,
+}
+
+pub(crate) struct CoreThread {
+    pub tid: u32,
+    pub cursig: u16,
+    /// `pr_reg`, in the kernel's `user_regs_struct` order for the machine.
+    pub regs: Vec<u64>,
+}
+
+impl CoreThread {
+    pub(crate) fn pc(&self, machine: CoreMachine) -> u64 {
+        match machine {
+            CoreMachine::X86_64 => self.regs[16],
+            CoreMachine::Aarch64 => self.regs[32],
+        }
+    }
+
+    pub(crate) fn sp(&self, machine: CoreMachine) -> u64 {
+        match machine {
+            CoreMachine::X86_64 => self.regs[19],
+            CoreMachine::Aarch64 => self.regs[31],
+        }
+    }
+}
+
+/// `NT_PRPSINFO`.
+pub(crate) struct CoreProcess {
+    pub state: char,
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    /// First 80 bytes of the command line, arguments separated by spaces.
+    pub args: String,
+}
+
+/// `NT_SIGINFO`.
+pub(crate) struct CoreSignal {
+    pub signo: u32,
+    pub code: i32,
+    /// `si_addr`: the faulting address for SIGSEGV / SIGBUS / SIGILL / SIGFPE.
+    pub addr: u64,
+}
+
+pub(crate) struct CoreFileMapping {
+    pub start: u64,
+    pub end: u64,
+    /// Offset into the file, in bytes.
+    pub offset: u64,
+    pub path: String,
+}
+
+/// A `PT_LOAD` segment: one VMA of the process.
+pub(crate) struct CoreSegment {
+    pub vaddr: u64,
+    pub memsz: u64,
+    /// Where the captured bytes start in the core file.
+    pub offset: u64,
+    /// Captured bytes; 0 for mappings the kernel did not dump (e.g. read-only file pages).
+    pub filesz: u64,
+    /// `PF_X` = 1, `PF_W` = 2, `PF_R` = 4.
+    pub flags: u32,
+}
+
+impl CoreSegment {
+    pub(crate) fn perms(&self) -> String {
+        format!(
+            "{}{}{}p",
+            if self.flags & 4 != 0 { 'r' } else { '-' },
+            if self.flags & 2 != 0 { 'w' } else { '-' },
+            if self.flags & 1 != 0 { 'x' } else { '-' },
+        )
+    }
+}
+
+impl ElfCore {
+    /// Captured bytes at `[addr, addr + len)`, if one segment holds all of them.
+    pub(crate) fn memory<'f>(&self, file: &'f [u8], addr: u64, len: usize) -> Option<&'f [u8]> {
+        let seg = self
+            .segments
+            .iter()
+            .find(|s| addr >= s.vaddr && addr < s.vaddr.saturating_add(s.filesz))?;
+        let skip = addr - seg.vaddr;
+        if skip.checked_add(len as u64)? > seg.filesz {
+            return None;
+        }
+        slice(file, seg.offset.checked_add(skip)?, len as u64)
+    }
+
+    pub(crate) fn auxv_value(&self, key: u64) -> Option<u64> {
+        self.auxv
+            .chunks_exact(16)
+            .map(|pair| (le_u64(pair, 0), le_u64(pair, 8)))
+            .take_while(|&(k, _)| k != 0)
+            .find(|&(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// GNU build id of the ELF image mapped at `base`, read from its in-memory program headers.
+    /// Cores keep the first page of every ELF mapping by default (`coredump_filter` bit 4),
+    /// which normally covers the headers and the build-id note.
+    pub(crate) fn build_id(&self, file: &[u8], base: u64) -> Option<Vec<u8>> {
+        let ehdr = self.memory(file, base, 64)?;
+        if !ehdr.starts_with(b"\x7fELF") || ehdr[4] != 2 {
+            return None;
+        }
+        let phoff = le_u64(ehdr, 32);
+        let phentsize = le_u16(ehdr, 54) as u64;
+        let phnum = le_u16(ehdr, 56) as u64;
+        let table = phentsize.checked_mul(phnum)?;
+        let phdrs = self.memory(file, base.checked_add(phoff)?, table as usize)?;
+        let phdr = |i: u64| &phdrs[(i * phentsize) as usize..];
+        let first_load = (0..phnum)
+            .filter(|&i| le_u32(phdr(i), 0) == PT_LOAD)
+            .map(|i| le_u64(phdr(i), 16))
+            .min()?;
+        let bias = base.wrapping_sub(first_load & !0xfff);
+        (0..phnum)
+            .filter(|&i| le_u32(phdr(i), 0) == PT_NOTE)
+            .find_map(|i| {
+                let notes = self.memory(
+                    file,
+                    bias.wrapping_add(le_u64(phdr(i), 16)),
+                    le_u64(phdr(i), 32) as usize,
+                )?;
+                parse_notes(notes)
+                    .find(|n| n.ty == NT_GNU_BUILD_ID && n.name == b"GNU")
+                    .map(|n| n.desc.to_vec())
+            })
+    }
+}
+
+/// Parse an ELF core, convert it to a minidump and run the minidump pipeline on it, so
+/// stackwalking, scans and detections work the same for both crash formats. The converted dump
+/// is a temporary file about the size of the captured memory, mapped while it is analysed.
+pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
+    let file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mtime = file
+        .metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs() as u32);
+    // SAFETY: see `ingest::minidump::ingest`; cores are read-only inputs as well.
+    let map =
+        unsafe { memmap2::Mmap::map(&file) }.with_context(|| format!("map {}", path.display()))?;
+
+    let core = parse(&map).context("parse ELF core")?;
+    let converted = super::elf_core_minidump::to_minidump(&core, &map, mtime);
+    let spilled = converted.spill(&map)?;
+    let dump = minidump::Minidump::read(spilled.bytes()).context("read converted core")?;
+    let mut ingested = super::minidump::ingest_dump(&dump, spilled.bytes(), path)?;
+    ingested.summary.file_size = Some(map.len() as u64);
+
+    // Swap the generic "Minidump loaded" event for one that describes the core.
+    let mut events: Vec<Event> = ingested.events.iter().cloned().collect();
+    if let Some(first) = events.first_mut() {
+        *first = loaded_event(&core, path, map.len(), converted.size());
+    }
+    if let Some(summary) = events.iter_mut().find(|e| e.title == "Minidump summary") {
+        summary.details = ingested.summary.pretty();
+    }
+    ingested.events = EventStore::from_events(events);
+    Ok(ingested)
+}
+
+fn loaded_event(core: &ElfCore, path: &Path, size: usize, converted: u64) -> Event {
+    let captured: u64 = core.segments.iter().map(|s| s.filesz).sum();
+    let mut details = format!(
+        "Path: {}\nSize: {} bytes\nFormat: ELF core ({})\nThreads: {}\nLoad segments: {} ({} bytes captured)\nMapped files: {}",
+        path.display(),
+        size,
+        core.machine.label(),
+        core.threads.len(),
+        core.segments.len(),
+        captured,
+        core.files.len(),
+    );
+    if let Some(p) = &core.process {
+        details.push_str(&format!(
+            "\nProcess: {} (pid {}, ppid {}, uid {}, state {})\nArguments: {}",
+            p.name, p.pid, p.ppid, p.uid, p.state, p.args
+        ));
+    }
+    if let Some(sig) = &core.signal {
+        details.push_str(&format!(
+            "\nSignal: {} (code {}, address 0x{:016X})",
+            sig.signo, sig.code, sig.addr
+        ));
+    }
+    if core.truncated_segments > 0 {
+        details.push_str(&format!(
+            "\nTruncated: {} load segments extend past the end of the file",
+            core.truncated_segments
+        ));
+    }
+    details.push_str(&format!(
+        "\nConverted to a minidump ({converted} bytes, in a temporary file) for analysis."
+    ));
+    Event {
+        id: EventId(0),
+        t_ms: 0,
+        severity: if core.truncated_segments > 0 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        },
+        title: "ELF core loaded".into(),
+        details,
+        source: "ingest::elf_core".into(),
+        timestamp: None,
+        attributes: Vec::new(),
+        links: Vec::new(),
+    }
+}
+
+/// Whether `head` starts an ELF core file (`ET_CORE`).
+pub fn is_elf_core(head: &[u8]) -> bool {
+    head.len() >= 18 && head.starts_with(b"\x7fELF") && le_u16(head, 16) == 4
+}
+
+pub(crate) fn parse(file: &[u8]) -> Result<ElfCore> {
+    if file.len() < 64 || !file.starts_with(b"\x7fELF") {
+        bail!("not an ELF file");
+    }
+    if file[4] != 2 {
+        bail!("only 64-bit ELF cores are supported");
+    }
+    if file[5] != 1 {
+        bail!("only little-endian ELF cores are supported");
+    }
+    let e_type = le_u16(file, 16);
+    if e_type != 4 {
+        bail!("not an ELF core (e_type {e_type})");
+    }
+    let machine = match le_u16(file, 18) {
+        62 => CoreMachine::X86_64,
+        183 => CoreMachine::Aarch64,
+        other => bail!("unsupported ELF machine {other}"),
+    };
+
+    let phoff = le_u64(file, 32);
+    let phentsize = le_u16(file, 54) as u64;
+    let mut phnum = le_u16(file, 56) as u64;
+    if phnum == PN_XNUM as u64 {
+        // The real count lives in `sh_info` of section header 0.
+        let shoff = le_u64(file, 40);
+        let Some(sh_info) = shoff.checked_add(44) else {
+            bail!("section header offset 0x{shoff:X} overflows");
+        };
+        phnum = slice(file, sh_info, 4)
+            .map(|b| le_u32(b, 0) as u64)
+            .context("PN_XNUM without a section header")?;
+    }
+    if phentsize < 56 {
+        bail!("bad program header size {phentsize}");
+    }
+
+    let mut core = ElfCore {
+        machine,
+        threads: Vec::new(),
+        process: None,
+        signal: None,
+        files: Vec::new(),
+        auxv: Vec::new(),
+        segments: Vec::new(),
+        truncated_segments: 0,
+    };
+    for i in 0..phnum {
+        let Some(at) = i.checked_mul(phentsize).and_then(|o| phoff.checked_add(o)) else {
+            bail!("program header {i} offset overflows");
+        };
+        let ph =
+            slice(file, at, 56).with_context(|| format!("program header {i} past end of file"))?;
+        let (p_type, flags, offset) = (le_u32(ph, 0), le_u32(ph, 4), le_u64(ph, 8));
+        let (vaddr, filesz, memsz) = (le_u64(ph, 16), le_u64(ph, 32), le_u64(ph, 40));
+        match p_type {
+            PT_LOAD => {
+                let available = (file.len() as u64).saturating_sub(offset);
+                if filesz > available {
+                    core.truncated_segments += 1;
+                }
+                core.segments.push(CoreSegment {
+                    vaddr,
+                    memsz,
+                    offset,
+                    filesz: filesz.min(available),
+                    flags,
+                });
+            }
+            PT_NOTE => {
+                let notes = slice(file, offset, filesz).context("PT_NOTE past end of file")?;
+                for note in parse_notes(notes) {
+                    read_note(&mut core, &note);
+                }
+            }
+            _ => {}
+        }
+    }
+    if core.threads.is_empty() {
+        bail!("core has no NT_PRSTATUS notes");
+    }
+    Ok(core)
+}
+
+struct Note<'a> {
+    name: &'a [u8],
+    ty: u32,
+    desc: &'a [u8],
+}
+
+fn parse_notes(mut data: &[u8]) -> impl Iterator<Item = Note<'_>> {
+    std::iter::from_fn(move || {
+        if data.len() < 12 {
+            return None;
+        }
+        let namesz = le_u32(data, 0) as usize;
+        let descsz = le_u32(data, 4) as usize;
+        let ty = le_u32(data, 8);
+        let name_end = 12usize.checked_add(namesz)?;
+        let desc_start = name_end.checked_add(3)? & !3;
+        let desc_end = desc_start.checked_add(descsz)?;
+        let name = data.get(12..name_end)?;
+        let desc = data.get(desc_start..desc_end)?;
+        let next = desc_end.checked_add(3)? & !3;
+        data = data.get(next..).unwrap_or_default();
+        Some(Note {
+            name: name.strip_suffix(b"\0").unwrap_or(name),
+            ty,
+            desc,
+        })
+    })
+}
+
+fn read_note(core: &mut ElfCore, note: &Note) {
+    if note.name != b"CORE" && note.name != b"LINUX" {
+        return;
+    }
+    let desc = note.desc;
+    match note.ty {
+        NT_PRSTATUS => {
+            let count = core.machine.register_count();
+            if desc.len() < PRSTATUS_REGS_OFFSET + count * 8 {
+                return;
+            }
+            core.threads.push(CoreThread {
+                tid: le_u32(desc, 32),
+                cursig: le_u16(desc, 12),
+                regs: (0..count)
+                    .map(|i| le_u64(desc, PRSTATUS_REGS_OFFSET + i * 8))
+                    .collect(),
+            });
+        }
+        NT_PRPSINFO if desc.len() >= 136 => {
+            core.process = Some(CoreProcess {
+                state: desc[1] as char,
+                uid: le_u32(desc, 16),
+                gid: le_u32(desc, 20),
+                pid: le_u32(desc, 24),
+                ppid: le_u32(desc, 28),
+                name: c_string(&desc[40..56]),
+                args: c_string(&desc[56..136]).trim_end().to_string(),
+            });
+        }
+        NT_SIGINFO if desc.len() >= 24 => {
+            core.signal = Some(CoreSignal {
+                signo: le_u32(desc, 0),
+                code: le_u32(desc, 8) as i32,
+                addr: le_u64(desc, 16),
+            });
+        }
+        NT_AUXV => core.auxv = desc.to_vec(),
+        NT_FILE => core.files = parse_nt_file(desc),
+        _ => {}
+    }
+}
+
+/// `count, page_size, count * (start, end, page_offset)`, then `count` NUL-terminated paths.
+fn parse_nt_file(desc: &[u8]) -> Vec<CoreFileMapping> {
+    if desc.len() < 16 {
+        return Vec::new();
+    }
+    let count = (le_u64(desc, 0) as usize).min(MAX_FILE_ENTRIES);
+    let page_size = le_u64(desc, 8);
+    let names_at = 16usize.saturating_add(count.saturating_mul(24));
+    let mut names = desc
+        .get(names_at..)
+        .unwrap_or_default()
+        .split(|&b| b == 0)
+        .map(|n| String::from_utf8_lossy(n).into_owned());
+    (0..count)
+        .map_while(|i| {
+            let entry = desc.get(16 + i * 24..16 + (i + 1) * 24)?;
+            Some(CoreFileMapping {
+                start: le_u64(entry, 0),
+                end: le_u64(entry, 8),
+                offset: le_u64(entry, 16).saturating_mul(page_size),
+                path: names.next().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// `file[at..at + len]`; `None` when the range overflows or runs past the end.
+fn slice(file: &[u8], at: u64, len: u64) -> Option<&[u8]> {
+    let start = usize::try_from(at).ok()?;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+    file.get(start..end)
+}
+
+fn le_u16(buf: &[u8], at: usize) -> u16 {
+    buf.get(at..at + 2)
+        .map_or(0, |b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le_u32(buf: &[u8], at: usize) -> u32 {
+    buf.get(at..at + 4)
+        .and_then(|b| b.try_into().ok())
+        .map_or(0, u32::from_le_bytes)
+}
+
+fn le_u64(buf: &[u8], at: usize) -> u64 {
+    buf.get(at..at + 8)
+        .and_then(|b| b.try_into().ok())
+        .map_or(0, u64::from_le_bytes)
+}
@@ -0,0 +1,529 @@
+use std::io::Write;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+use minidump::format::MINIDUMP_STREAM_TYPE;
+
+use crate::ingest::elf_core::{CoreMachine, ElfCore};
+
+const MINIDUMP_SIGNATURE: u32 = 0x504d_444d;
+const MINIDUMP_VERSION: u32 = 0xa793;
+const PLATFORM_LINUX: u32 = 0x8201;
+const CV_SIGNATURE_ELF: u32 = 0x4270_454c;
+
+const CONTEXT_AMD64_SIZE: usize = 1232;
+/// `CONTEXT_AMD64 | CONTROL | INTEGER | SEGMENTS`.
+const CONTEXT_AMD64_FLAGS: u32 = 0x0010_0007;
+const CONTEXT_ARM64_SIZE: usize = 912;
+/// `CONTEXT_ARM64 | CONTROL | INTEGER`.
+const CONTEXT_ARM64_FLAGS: u32 = 0x0040_0003;
+
+/// Bytes above the stack pointer kept as a thread's stack (the stack segment is often 8 MiB).
+const MAX_STACK_BYTES: u64 = 1024 * 1024;
+/// The x86_64 ABI lets leaf functions use 128 bytes below the stack pointer.
+const RED_ZONE: u64 = 128;
+
+const AT_ENTRY: u64 = 9;
+const AT_SYSINFO_EHDR: u64 = 33;
+
+static NEXT_FILE: AtomicU64 = AtomicU64::new(0);
+
+/// A minidump synthesized from a core. Only the header, directory and streams are held in
+/// memory; the `Memory64List` data after them is the core's captured segments, copied from the
+/// core file when the dump is written out.
+pub(crate) struct ConvertedCore {
+    head: Vec<u8>,
+    /// Ranges of the core file that follow `head`, in `Memory64List` order.
+    segments: Vec<Range<usize>>,
+}
+
+impl ConvertedCore {
+    pub(crate) fn size(&self) -> u64 {
+        let data: u64 = self.segments.iter().map(|r| r.len() as u64).sum();
+        self.head.len() as u64 + data
+    }
+
+    fn write_to(&self, file: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+        out.write_all(&self.head)?;
+        for range in &self.segments {
+            out.write_all(&file[range.clone()])?;
+        }
+        out.flush()
+    }
+
+    /// Write the dump to a temporary file and map it, so the captured memory stays out of the
+    /// heap: the `minidump` crate reads from one contiguous buffer, and a core's segments are
+    /// not laid out the way a `Memory64List` needs them.
+    pub(crate) fn spill(&self, file: &[u8]) -> Result<SpilledDump> {
+        let path = std::env::temp_dir().join(format!(
+            "log-atlas-core-{}-{}.dmp",
+            std::process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let out = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .with_context(|| format!("create {}", path.display()))?;
+        let mut spilled = SpilledDump { path, map: None };
+        self.write_to(file, &mut std::io::BufWriter::new(&out))
+            .with_context(|| format!("write {}", spilled.path.display()))?;
+        // SAFETY: the file was created above with `create_new`, is private to this process and
+        // removed when the mapping is dropped.
+        let map = unsafe { memmap2::Mmap::map(&out) }
+            .with_context(|| format!("map {}", spilled.path.display()))?;
+        spilled.map = Some(map);
+        Ok(spilled)
+    }
+}
+
+/// A converted core written to a temporary file and mapped; the file is removed on drop.
+pub(crate) struct SpilledDump {
+    path: PathBuf,
+    map: Option<memmap2::Mmap>,
+}
+
+impl SpilledDump {
+    pub(crate) fn bytes(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or_default()
+    }
+}
+
+impl Drop for SpilledDump {
+    fn drop(&mut self) {
+        // Unmap first: Windows does not delete mapped files.
+        self.map = None;
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Build a minidump equivalent to `core`: system info, threads with register contexts, mapped
+/// ELF images as modules, the signal as the exception, `/proc`-style Linux streams and every
+/// captured segment in a `Memory64List`.
+pub(crate) fn to_minidump(core: &ElfCore, file: &[u8], time_date_stamp: u32) -> ConvertedCore {
+    let mut streams: Vec<(u32, Vec<u8>)> = Vec::new();
+    let crashing = &core.threads[0];
+    let sp = crashing.sp(core.machine);
+
+    let mut status = String::new();
+    if let Some(p) = &core.process {
+        status = format!(
+            "Name:\t{}\nState:\t{}\nPid:\t{}\nPPid:\t{}\nUid:\t{uid}\t{uid}\t{uid}\t{uid}\nGid:\t{gid}\t{gid}\t{gid}\t{gid}\n",
+            p.name,
+            p.state,
+            p.pid,
+            p.ppid,
+            uid = p.uid,
+            gid = p.gid
+        );
+        if !p.args.is_empty() {
+            streams.push((
+                MINIDUMP_STREAM_TYPE::LinuxCmdLine as u32,
+                p.args.as_bytes().to_vec(),
+            ));
+        }
+    }
+    status.push_str(&format!("Threads:\t{}\n", core.threads.len()));
+    streams.push((
+        MINIDUMP_STREAM_TYPE::LinuxProcStatus as u32,
+        status.into_bytes(),
+    ));
+    if !core.auxv.is_empty() {
+        streams.push((MINIDUMP_STREAM_TYPE::LinuxAuxv as u32, core.auxv.clone()));
+    }
+    streams.push((
+        MINIDUMP_STREAM_TYPE::LinuxMaps as u32,
+        maps_text(core, sp).into_bytes(),
+    ));
+
+    let mut w = Writer::default();
+    // Header and directory are patched once the streams are laid out.
+    let stream_count = streams.len() + 5;
+    w.zeros(32 + stream_count * 12);
+    let mut directory: Vec<(u32, u32, u32)> = Vec::new();
+
+    // SystemInfo.
+    let csd_version = w.string("");
+    let at = w.pos();
+    w.u16(match core.machine {
+        CoreMachine::X86_64 => 9,
+        CoreMachine::Aarch64 => 12,
+    });
+    w.u16(0); // processor_level
+    w.u16(0); // processor_revision
+    w.u8(1); // number_of_processors
+    w.u8(0); // product_type
+    w.u32(0);
+    w.u32(0);
+    w.u32(0);
+    w.u32(PLATFORM_LINUX);
+    w.u32(csd_version);
+    w.u16(0);
+    w.u16(0);
+    w.zeros(24);
+    directory.push((MINIDUMP_STREAM_TYPE::SystemInfoStream as u32, 56, at as u32));
+
+    // Thread contexts first so the thread list and exception stream can point at them.
+    let contexts: Vec<(u32, u32)> = core
+        .threads
+        .iter()
+        .map(|t| {
+            let ctx = context_bytes(core.machine, &t.regs);
+            let rva = w.pos() as u32;
+            w.bytes(&ctx);
+            (ctx.len() as u32, rva)
+        })
+        .collect();
+
+    // ThreadList; stack RVAs are patched after the memory data is placed.
+    let at = w.pos();
+    w.u32(core.threads.len() as u32);
+    let mut stack_slots = Vec::new();
+    for (t, &(ctx_size, ctx_rva)) in core.threads.iter().zip(&contexts) {
+        w.u32(t.tid);
+        w.u32(0); // suspend_count
+        w.u32(0); // priority_class
+        w.u32(0); // priority
+        w.u64(0); // teb
+        let (start, len) = stack_range(core, t.sp(core.machine));
+        w.u64(start);
+        w.u32(len as u32);
+        stack_slots.push((w.pos(), start));
+        w.u32(0);
+        w.u32(ctx_size);
+        w.u32(ctx_rva);
+    }
+    directory.push((
+        MINIDUMP_STREAM_TYPE::ThreadListStream as u32,
+        (w.pos() - at) as u32,
+        at as u32,
+    ));
+
+    // ModuleList.
+    let modules = modules(core, file);
+    let names: Vec<u32> = modules.iter().map(|m| w.string(&m.path)).collect();
+    let cv_records: Vec<(u32, u32)> = modules
+        .iter()
+        .map(|m| match &m.build_id {
+            Some(id) => {
+                let rva = w.pos() as u32;
+                w.u32(CV_SIGNATURE_ELF);
+                w.bytes(id);
+                ((4 + id.len()) as u32, rva)
+            }
+            None => (0, 0),
+        })
+        .collect();
+    w.align(4);
+    let at = w.pos();
+    w.u32(modules.len() as u32);
+    for ((m, name), (cv_size, cv_rva)) in modules.iter().zip(&names).zip(&cv_records) {
+        w.u64(m.base);
+        w.u32(m.size.min(u32::MAX as u64) as u32);
+        w.u32(0); // checksum
+        w.u32(0); // time_date_stamp
+        w.u32(*name);
+        w.zeros(52); // VS_FIXEDFILEINFO
+        w.u32(*cv_size);
+        w.u32(*cv_rva);
+        w.zeros(8); // misc record
+        w.zeros(16); // reserved
+    }
+    directory.push((
+        MINIDUMP_STREAM_TYPE::ModuleListStream as u32,
+        (w.pos() - at) as u32,
+        at as u32,
+    ));
+
+    // Exception: Breakpad's convention is the signal number as the code, `si_code` as the
+    // flags and the fault address.
+    let at = w.pos();
+    let (signo, code, address) = match &core.signal {
+        Some(sig) => (sig.signo, sig.code as u32, sig.addr),
+        None => (crashing.cursig as u32, 0, crashing.pc(core.machine)),
+    };
+    w.u32(crashing.tid);
+    w.u32(0);
+    w.u32(signo);
+    w.u32(code);
+    w.u64(0);
+    w.u64(address);
+    w.u32(0);
+    w.u32(0);
+    w.zeros(15 * 8);
+    w.u32(contexts[0].0);
+    w.u32(contexts[0].1);
+    directory.push((
+        MINIDUMP_STREAM_TYPE::ExceptionStream as u32,
+        (w.pos() - at) as u32,
+        at as u32,
+    ));
+
+    for (ty, data) in &streams {
+        let at = w.pos();
+        w.bytes(data);
+        w.align(4);
+        directory.push((*ty, data.len() as u32, at as u32));
+    }
+
+    // Memory64List with the bytes appended after it.
+    w.align(8);
+    let at = w.pos();
+    let captured: Vec<_> = core.segments.iter().filter(|s| s.filesz > 0).collect();
+    let data_rva = (at + 16 + captured.len() * 16) as u64;
+    w.u64(captured.len() as u64);
+    w.u64(data_rva);
+    let mut segment_rvas = Vec::with_capacity(captured.len());
+    let mut rva = data_rva;
+    for seg in &captured {
+        w.u64(seg.vaddr);
+        w.u64(seg.filesz);
+        segment_rvas.push((seg.vaddr, seg.filesz, rva));
+        rva += seg.filesz;
+    }
+    directory.push((
+        MINIDUMP_STREAM_TYPE::Memory64ListStream as u32,
+        (w.pos() - at) as u32,
+        at as u32,
+    ));
+    let segments = captured
+        .iter()
+        .map(|seg| seg.offset as usize..(seg.offset + seg.filesz) as usize)
+        .collect();
+
+    for (slot, start) in stack_slots {
+        // RVAs are 32-bit; stacks past 4 GiB are found by address through the memory list.
+        let rva = segment_rvas
+            .iter()
+            .find(|&&(vaddr, size, _)| start >= vaddr && start < vaddr.saturating_add(size))
+            .map(|&(vaddr, _, rva)| rva + (start - vaddr))
+            .filter(|&rva| rva <= u32::MAX as u64)
+            .unwrap_or(0);
+        w.patch_u32(slot, rva as u32);
+    }
+
+    let count = directory.len();
+    for (i, (ty, size, rva)) in directory.into_iter().enumerate() {
+        let at = 32 + i * 12;
+        w.patch_u32(at, ty);
+        w.patch_u32(at + 4, size);
+        w.patch_u32(at + 8, rva);
+    }
+    w.patch_u32(0, MINIDUMP_SIGNATURE);
+    w.patch_u32(4, MINIDUMP_VERSION);
+    w.patch_u32(8, count as u32);
+    w.patch_u32(12, 32);
+    w.patch_u32(20, time_date_stamp);
+    ConvertedCore {
+        head: w.buf,
+        segments,
+    }
+}
+
+/// `[start, start + len)` of the stack around `sp`, clamped to the captured segment.
+fn stack_range(core: &ElfCore, sp: u64) -> (u64, u64) {
+    let Some(seg) = core
+        .segments
+        .iter()
+        .find(|s| sp >= s.vaddr && sp < s.vaddr.saturating_add(s.filesz))
+    else {
+        return (sp, 0);
+    };
+    let start = sp.saturating_sub(RED_ZONE).max(seg.vaddr);
+    let end = seg
+        .vaddr
+        .saturating_add(seg.filesz)
+        .min(start.saturating_add(MAX_STACK_BYTES));
+    (start, end - start)
+}
+
+struct CoreModule {
+    base: u64,
+    size: u64,
+    path: String,
+    build_id: Option<Vec<u8>>,
+}
+
+/// File mappings that hold ELF images, merged per path; the executable comes first because
+/// the report takes the first module as the main image.
+fn modules(core: &ElfCore, file: &[u8]) -> Vec<CoreModule> {
+    let mut out: Vec<CoreModule> = Vec::new();
+    for f in &core.files {
+        if let Some(m) = out.iter_mut().find(|m| m.path == f.path) {
+            let end = (m.base + m.size).max(f.end);
+            m.base = m.base.min(f.start);
+            m.size = end - m.base;
+            continue;
+        }
+        out.push(CoreModule {
+            base: f.start,
+            size: f.end.saturating_sub(f.start),
+            path: f.path.clone(),
+            build_id: None,
+        });
+    }
+    let entry = core.auxv_value(AT_ENTRY);
+    out.retain(|m| {
+        let is_elf = core
+            .memory(file, m.base, 4)
+            .is_some_and(|magic| magic == b"\x7fELF");
+        is_elf
+            || m.path.contains(".so")
+            || entry.is_some_and(|e| e >= m.base && e < m.base + m.size)
+    });
+    for m in &mut out {
+        m.build_id = core.build_id(file, m.base);
+    }
+    out.sort_by_key(|m| {
+        let is_main = entry.is_some_and(|e| e >= m.base && e < m.base + m.size);
+        (!is_main, m.base)
+    });
+    out
+}
+
+/// `/proc/self/maps` lines for every load segment. Paths come from `NT_FILE`; the vDSO,
+/// vsyscall page and the faulting thread's stack get their kernel pseudo-paths.
+fn maps_text(core: &ElfCore, crashing_sp: u64) -> String {
+    let vdso = core.auxv_value(AT_SYSINFO_EHDR);
+    let mut out = String::new();
+    for seg in &core.segments {
+        let end = seg.vaddr.saturating_add(seg.memsz);
+        let file = core
+            .files
+            .iter()
+            .find(|f| seg.vaddr >= f.start && seg.vaddr < f.end);
+        let (offset, path) = match file {
+            Some(f) => (
+                f.offset.saturating_add(seg.vaddr - f.start),
+                f.path.as_str(),
+            ),
+            None if vdso == Some(seg.vaddr) => (0, "[vdso]"),
+            None if seg.vaddr >= 0xffff_ffff_ff60_0000 => (0, "[vsyscall]"),
+            None if crashing_sp >= seg.vaddr && crashing_sp < end => (0, "[stack]"),
+            None => (0, ""),
+        };
+        out.push_str(&format!(
+            "{:x}-{:x} {} {:08x} 00:00 0 {}\n",
+            seg.vaddr,
+            end,
+            seg.perms(),
+            offset,
+            path
+        ));
+    }
+    out
+}
+
+fn context_bytes(machine: CoreMachine, regs: &[u64]) -> Vec<u8> {
+    match machine {
+        CoreMachine::X86_64 => {
+            let mut ctx = vec![0u8; CONTEXT_AMD64_SIZE];
+            put_u32(&mut ctx, 48, CONTEXT_AMD64_FLAGS);
+            // `user_regs_struct` index -> CONTEXT_AMD64 offset.
+            for (reg, at) in [(17, 56), (23, 58), (24, 60), (25, 62), (26, 64), (20, 66)] {
+                ctx[at..at + 2].copy_from_slice(&(regs[reg] as u16).to_le_bytes());
+            }
+            put_u32(&mut ctx, 68, regs[18] as u32);
+            for (reg, at) in [
+                (10, 120), // rax
+                (11, 128), // rcx
+                (12, 136), // rdx
+                (5, 144),  // rbx
+                (19, 152), // rsp
+                (4, 160),  // rbp
+                (13, 168), // rsi
+                (14, 176), // rdi
+                (9, 184),  // r8
+                (8, 192),  // r9
+                (7, 200),  // r10
+                (6, 208),  // r11
+                (3, 216),  // r12
+                (2, 224),  // r13
+                (1, 232),  // r14
+                (0, 240),  // r15
+                (16, 248), // rip
+            ] {
+                put_u64(&mut ctx, at, regs[reg]);
+            }
+            ctx
+        }
+        CoreMachine::Aarch64 => {
+            let mut ctx = vec![0u8; CONTEXT_ARM64_SIZE];
+            put_u32(&mut ctx, 0, CONTEXT_ARM64_FLAGS);
+            put_u32(&mut ctx, 4, regs[33] as u32);
+            for (i, &reg) in regs[..31].iter().enumerate() {
+                put_u64(&mut ctx, 8 + i * 8, reg);
+            }
+            put_u64(&mut ctx, 256, regs[31]);
+            put_u64(&mut ctx, 264, regs[32]);
+            ctx
+        }
+    }
+}
+
+fn put_u32(buf: &mut [u8], at: usize, v: u32) {
+    buf[at..at + 4].copy_from_slice(&v.to_le_bytes());
+}
+
+fn put_u64(buf: &mut [u8], at: usize, v: u64) {
+    buf[at..at + 8].copy_from_slice(&v.to_le_bytes());
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn pos(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn bytes(&mut self, b: &[u8]) {
+        self.buf.extend_from_slice(b);
+    }
+
+    fn zeros(&mut self, n: usize) {
+        self.buf.resize(self.buf.len() + n, 0);
+    }
+
+    fn align(&mut self, to: usize) {
+        self.zeros(self.buf.len().next_multiple_of(to) - self.buf.len());
+    }
+
+    /// `MINIDUMP_STRING` (byte length, UTF-16LE, NUL); returns its RVA.
+    fn string(&mut self, s: &str) -> u32 {
+        self.align(4);
+        let at = self.pos() as u32;
+        let units: Vec<u16> = s.encode_utf16().collect();
+        self.u32((units.len() * 2) as u32);
+        for u in units {
+            self.u16(u);
+        }
+        self.u16(0);
+        at
+    }
+
+    fn patch_u32(&mut self, at: usize, v: u32) {
+        put_u32(&mut self.buf, at, v);
+    }
+}
@@ -0,0 +1,564 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::model::{AttrValue, Event, EventId, EventStore, Severity};
+
+const SOURCE_NAME: &str = "ingest::evtx";
+
+/// Audit Failure bit in `System/Keywords`.
+const KEYWORD_AUDIT_FAILURE: u64 = 0x0010_0000_0000_0000;
+
+/// Positional `<Data>` names for Application Error (1000), which older providers log unnamed.
+const APPLICATION_ERROR_FIELDS: &[&str] = &[
+    "AppName",
+    "AppVersion",
+    "AppTimeStamp",
+    "ModuleName",
+    "ModuleVersion",
+    "ModuleTimeStamp",
+    "ExceptionCode",
+    "FaultingOffset",
+    "ProcessId",
+    "ProcessCreationTime",
+    "AppPath",
+    "ModulePath",
+    "IntegratorReportId",
+    "PackageFullName",
+    "PackageRelativeAppId",
+];
+
+/// Positional `<Data>` names for Windows Error Reporting (1001).
+const WER_FIELDS: &[&str] = &[
+    "Bucket",
+    "BucketType",
+    "EventName",
+    "Response",
+    "CabId",
+    "P1",
+    "P2",
+    "P3",
+    "P4",
+    "P5",
+    "P6",
+    "P7",
+    "P8",
+    "P9",
+    "P10",
+    "AttachedFiles",
+    "StorePath",
+    "AnalysisSymbol",
+    "Rechecking",
+    "ReportId",
+    "ReportStatus",
+    "HashedBucket",
+];
+
+pub struct IngestedEvtx {
+    pub events: EventStore,
+    pub records: u64,
+    pub failed: u64,
+}
+
+/// Parse a `.evtx` file with a pure-Rust BinXML decoder (no Windows APIs involved).
+pub fn ingest(path: &Path) -> Result<IngestedEvtx> {
+    let file_size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let mut parser = evtx::EvtxParser::from_path(path)
+        .with_context(|| format!("open EVTX {}", path.display()))?;
+
+    let mut records: Vec<Event> = Vec::new();
+    let mut failed = 0u64;
+    let mut first_error: Option<String> = None;
+    for record in parser.records_json_value() {
+        match record {
+            Ok(r) => {
+                let ts =
+                    OffsetDateTime::from_unix_timestamp_nanos(r.timestamp.as_nanosecond()).ok();
+                records.push(record_to_event(r.event_record_id, ts, &r.data));
+            }
+            Err(e) => {
+                failed += 1;
+                first_error.get_or_insert_with(|| e.to_string());
+            }
+        }
+    }
+
+    let start = crate::model::derive_t_ms(&mut records);
+    let mut details = format!(
+        "Path: {}\nSize: {} bytes\nRecords: {}\nUnreadable records: {}",
+        path.display(),
+        file_size,
+        records.len(),
+        failed
+    );
+    if let Some(err) = &first_error {
+        details.push_str(&format!("\nFirst error: {err}"));
+    }
+
+    let mut events = EventStore::default();
+    events.push(Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp: start,
+        severity: if failed > 0 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        },
+        title: "Event log loaded".into(),
+        details,
+        source: SOURCE_NAME.into(),
+        attributes: Vec::new(),
+        links: Vec::new(),
+    });
+    let count = records.len() as u64;
+    for ev in records {
+        events.push(ev);
+    }
+
+    Ok(IngestedEvtx {
+        events,
+        records: count,
+        failed,
+    })
+}
+
+/// Normalized fields of one record's `System` element.
+struct SystemFields {
+    provider: String,
+    event_id: u32,
+    level: u8,
+    keywords: u64,
+    channel: String,
+    computer: String,
+    pid: Option<u64>,
+    tid: Option<u64>,
+}
+
+/// Map one record (as rendered by `evtx`'s JSON output) onto an `Event`.
+pub fn record_to_event(record_id: u64, timestamp: Option<OffsetDateTime>, data: &Value) -> Event {
+    let event = data.get("Event").unwrap_or(data);
+    let sys = system_fields(event.get("System").unwrap_or(&Value::Null));
+    let fields = event_data_fields(event, &sys);
+
+    let mut severity = match sys.level {
+        1 | 2 => Severity::High,
+        3 => Severity::Warning,
+        _ => Severity::Info,
+    };
+    if sys.keywords & KEYWORD_AUDIT_FAILURE != 0 {
+        severity = severity.max(Severity::Warning);
+    }
+
+    let (title, summary, dedicated_severity) = summarize(&sys, &fields);
+    if let Some(s) = dedicated_severity {
+        severity = severity.max(s);
+    }
+
+    let mut details = String::new();
+    if let Some(summary) = summary {
+        details.push_str(&summary);
+        details.push_str("\n\n");
+    }
+    details.push_str(&format!(
+        "Provider: {}\nEvent ID: {}\nChannel: {}\nComputer: {}\nRecord: {}",
+        sys.provider, sys.event_id, sys.channel, sys.computer, record_id
+    ));
+    if !fields.is_empty() {
+        details.push_str("\n\nEventData:");
+        for (k, v) in &fields {
+            details.push_str(&format!("\n  {k}: {v}"));
+        }
+    }
+
+    let mut attributes: Vec<(String, AttrValue)> = vec![
+        ("provider".to_string(), sys.provider.clone().into()),
+        ("event_id".to_string(), sys.event_id.into()),
+        ("channel".to_string(), sys.channel.clone().into()),
+        ("computer".to_string(), sys.computer.clone().into()),
+        ("record_id".to_string(), record_id.into()),
+        ("level".to_string(), u64::from(sys.level).into()),
+    ];
+    if let Some(pid) = sys.pid {
+        attributes.push(("pid".into(), pid.into()));
+    }
+    if let Some(tid) = sys.tid {
+        attributes.push(("tid".into(), tid.into()));
+    }
+    attributes.extend(fields.into_iter().map(|(k, v)| (k, v.into())));
+
+    Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp,
+        severity,
+        title,
+        details,
+        source: sys.provider,
+        attributes,
+        links: Vec::new(),
+    }
+}
+
+fn system_fields(system: &Value) -> SystemFields {
+    let attr = |node: &str, name: &str| {
+        system
+            .get(node)
+            .and_then(|n| n.get("#attributes")?.get(name))
+            .map(text)
+    };
+    SystemFields {
+        provider: attr("Provider", "Name").unwrap_or_default(),
+        event_id: system
+            .get("EventID")
+            .map(text)
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0),
+        level: system
+            .get("Level")
+            .map(text)
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0),
+        keywords: system
+            .get("Keywords")
+            .map(text)
+            .and_then(|s| u64::from_str_radix(s.trim().trim_start_matches("0x"), 16).ok())
+            .unwrap_or(0),
+        channel: system.get("Channel").map(text).unwrap_or_default(),
+        computer: system.get("Computer").map(text).unwrap_or_default(),
+        pid: attr("Execution", "ProcessID").and_then(|s| s.parse().ok()),
+        tid: attr("Execution", "ThreadID").and_then(|s| s.parse().ok()),
+    }
+}
+
+/// Flatten `EventData` / `UserData` into `(name, value)` pairs. Unnamed `<Data>` elements get
+/// their well-known names for the events we summarize, `Data[i]` otherwise.
+fn event_data_fields(event: &Value, sys: &SystemFields) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    if let Some(Value::Object(data)) = event.get("EventData") {
+        for (k, v) in data {
+            if k == "#attributes" {
+                continue;
+            }
+            if k == "Data" {
+                let names = positional_names(sys);
+                let values: Vec<String> = match v.get("#text").unwrap_or(v) {
+                    Value::Array(items) => items.iter().map(text).collect(),
+                    other => vec![text(other)],
+                };
+                for (i, value) in values.into_iter().enumerate() {
+                    let name = names
+                        .get(i)
+                        .map(|n| n.to_string())
+                        .unwrap_or_else(|| format!("Data[{i}]"));
+                    out.push((name, value));
+                }
+            } else {
+                out.push((k.clone(), text(v)));
+            }
+        }
+    }
+    if let Some(user) = event.get("UserData") {
+        flatten("", user, &mut out);
+    }
+    out
+}
+
+fn positional_names(sys: &SystemFields) -> &'static [&'static str] {
+    match (sys.event_id, sys.provider.as_str()) {
+        (1000, "Application Error") => APPLICATION_ERROR_FIELDS,
+        (1001, "Windows Error Reporting") => WER_FIELDS,
+        _ => &[],
+    }
+}
+
+fn flatten(prefix: &str, v: &Value, out: &mut Vec<(String, String)>) {
+    match v {
+        Value::Object(map) => {
+            for (k, child) in map {
+                if k == "#attributes" {
+                    continue;
+                }
+                let key = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{prefix}.{k}")
+                };
+                flatten(&key, child, out);
+            }
+        }
+        other => out.push((prefix.to_string(), text(other))),
+    }
+}
+
+/// Scalar text of a rendered node (`"x"`, `4688`, `{"#text": 6009, "#attributes": ...}`).
+fn text(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(text).collect::<Vec<_>>().join(", "),
+        Value::Object(map) => map.get("#text").map(text).unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+/// Dedicated title / summary for events that matter in crash and intrusion triage.
+fn summarize(
+    sys: &SystemFields,
+    fields: &[(String, String)],
+) -> (String, Option<String>, Option<Severity>) {
+    let get = |name: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+            .filter(|v| !v.is_empty() && *v != "-")
+    };
+    let or_dash = |name: &str| get(name).unwrap_or("-");
+
+    if sys.provider == super::sysmon::PROVIDER
+        && let Some(record) = super::sysmon::parse_fields(sys.event_id, fields)
+    {
+        return (
+            record.title(),
+            None,
+            super::sysmon::record_severity(&record),
+        );
+    }
+
+    match (sys.event_id, sys.provider.as_str()) {
+        (1000, "Application Error") => (
+            format!(
+                "Application crash: {} in {}",
+                or_dash("AppName"),
+                or_dash("ModuleName")
+            ),
+            Some(format!(
+                "Faulting application: {} {}\nFaulting module: {} {}\nException code: {}\nFault offset: {}\nProcess id: {}\nApplication path: {}\nModule path: {}\nReport id: {}",
+                or_dash("AppName"),
+                or_dash("AppVersion"),
+                or_dash("ModuleName"),
+                or_dash("ModuleVersion"),
+                or_dash("ExceptionCode"),
+                or_dash("FaultingOffset"),
+                or_dash("ProcessId"),
+                or_dash("AppPath"),
+                or_dash("ModulePath"),
+                or_dash("IntegratorReportId"),
+            )),
+            Some(Severity::High),
+        ),
+        (1001, "Windows Error Reporting") => (
+            format!(
+                "WER report: {} ({})",
+                or_dash("EventName"),
+                get("P1").unwrap_or("unknown")
+            ),
+            Some(format!(
+                "Event name: {}\nBucket: {} (type {})\nP1-P5: {} | {} | {} | {} | {}\nAttached files: {}\nReport path: {}\nReport id: {}",
+                or_dash("EventName"),
+                or_dash("Bucket"),
+                or_dash("BucketType"),
+                or_dash("P1"),
+                or_dash("P2"),
+                or_dash("P3"),
+                or_dash("P4"),
+                or_dash("P5"),
+                or_dash("AttachedFiles"),
+                or_dash("StorePath"),
+                or_dash("ReportId"),
+            )),
+            Some(Severity::Warning),
+        ),
+        (7045, _) => (
+            format!("Service installed: {}", or_dash("ServiceName")),
+            Some(format!(
+                "Service: {}\nImage path: {}\nType: {}\nStart type: {}\nAccount: {}",
+                or_dash("ServiceName"),
+                or_dash("ImagePath"),
+                or_dash("ServiceType"),
+                or_dash("StartType"),
+                or_dash("AccountName"),
+            )),
+            Some(Severity::Warning),
+        ),
+        (4688, _) => (
+            format!("Process created: {}", or_dash("NewProcessName")),
+            Some(format!(
+                "Image: {}\nCommand line: {}\nParent: {}\nUser: {}\\{}\nNew process id: {}\nCreator process id: {}\nElevation: {}",
+                or_dash("NewProcessName"),
+                or_dash("CommandLine"),
+                or_dash("ParentProcessName"),
+                or_dash("SubjectDomainName"),
+                or_dash("SubjectUserName"),
+                or_dash("NewProcessId"),
+                or_dash("ProcessId"),
+                or_dash("TokenElevationType"),
+            )),
+            None,
+        ),
+        _ => (
+            format!(
+                "{} {}",
+                if sys.provider.is_empty() {
+                    "EventLog"
+                } else {
+                    sys.provider.as_str()
+                },
+                sys.event_id
+            ),
+            None,
+            None,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn record(system: Value, body: Value) -> Value {
+        let mut event = json!({ "System": system });
+        if let (Value::Object(event), Value::Object(body)) = (&mut event, body) {
+            event.extend(body);
+        }
+        json!({ "Event": event })
+    }
+
+    fn system(provider: &str, event_id: Value, level: u8) -> Value {
+        json!({
+            "Provider": { "#attributes": { "Name": provider } },
+            "EventID": event_id,
+            "Level": level,
+            "Keywords": "0x80000000000000",
+            "Channel": "Application",
+            "Computer": "HOST01",
+            "Execution": { "#attributes": { "ProcessID": 1234, "ThreadID": 5678 } },
+        })
+    }
+
+    fn attr<'a>(ev: &'a Event, key: &str) -> Option<&'a AttrValue> {
+        ev.attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn application_error_names_positional_data() {
+        let data = record(
+            system(
+                "Application Error",
+                json!({ "#attributes": { "Qualifiers": 0 }, "#text": 1000 }),
+                2,
+            ),
+            json!({ "EventData": { "Data": { "#text": [
+                "app.exe", "1.2.3.4", "5f000000", "ntdll.dll", "10.0.1", "6a000000",
+                "c0000005", "0x1234", "0x4d2"
+            ] } } }),
+        );
+        let ev = record_to_event(42, None, &data);
+        assert_eq!(ev.title, "Application crash: app.exe in ntdll.dll");
+        assert_eq!(ev.severity, Severity::High);
+        assert_eq!(ev.source, "Application Error");
+        assert!(
+            ev.details
+                .starts_with("Faulting application: app.exe 1.2.3.4\n")
+        );
+        assert!(ev.details.contains("Exception code: c0000005"));
+        assert!(ev.details.contains("Record: 42"));
+        assert_eq!(attr(&ev, "event_id"), Some(&AttrValue::from(1000u32)));
+        assert_eq!(attr(&ev, "pid"), Some(&AttrValue::from(1234u64)));
+        assert_eq!(attr(&ev, "tid"), Some(&AttrValue::from(5678u64)));
+        assert_eq!(attr(&ev, "ModuleName"), Some(&AttrValue::from("ntdll.dll")));
+        assert_eq!(attr(&ev, "ProcessId"), Some(&AttrValue::from("0x4d2")));
+    }
+
+    #[test]
+    fn unnamed_data_of_other_events_is_positional() {
+        let data = record(
+            system("SomeProvider", json!(7), 4),
+            json!({ "EventData": { "Data": ["first", "second"] } }),
+        );
+        let ev = record_to_event(1, None, &data);
+        assert_eq!(ev.title, "SomeProvider 7");
+        assert_eq!(ev.severity, Severity::Info);
+        assert_eq!(attr(&ev, "Data[0]"), Some(&AttrValue::from("first")));
+        assert_eq!(attr(&ev, "Data[1]"), Some(&AttrValue::from("second")));
+    }
+
+    #[test]
+    fn wer_and_service_installs_are_at_least_warnings() {
+        let wer = record(
+            system("Windows Error Reporting", json!(1001), 4),
+            json!({ "EventData": { "Data": [
+                "12345", "5", "APPCRASH", "Not available", "0", "app.exe"
+            ] } }),
+        );
+        let ev = record_to_event(2, None, &wer);
+        assert_eq!(ev.title, "WER report: APPCRASH (app.exe)");
+        assert_eq!(ev.severity, Severity::Warning);
+        assert!(ev.details.contains("Bucket: 12345 (type 5)"));
+
+        let service = record(
+            system("Service Control Manager", json!(7045), 4),
+            json!({ "EventData": {
+                "ServiceName": "updater",
+                "ImagePath": "C:\\Users\\Public\\u.exe",
+                "ServiceType": "user mode service",
+                "StartType": "auto start",
+                "AccountName": "LocalSystem",
+            } }),
+        );
+        let ev = record_to_event(3, None, &service);
+        assert_eq!(ev.title, "Service installed: updater");
+        assert_eq!(ev.severity, Severity::Warning);
+        assert!(ev.details.contains("Image path: C:\\Users\\Public\\u.exe"));
+    }
+
+    #[test]
+    fn audit_failures_raise_severity_and_user_data_is_flattened() {
+        let mut sys = system("Microsoft-Windows-Security-Auditing", json!(4625), 0);
+        sys["Keywords"] = json!("0x8010000000000000");
+        let data = record(
+            sys,
+            json!({ "UserData": { "LogonFailure": {
+                "#attributes": { "xmlns": "x" },
+                "TargetUserName": "admin",
+                "Source": { "Ip": "10.0.0.5" },
+            } } }),
+        );
+        let ev = record_to_event(4, None, &data);
+        assert_eq!(ev.title, "Microsoft-Windows-Security-Auditing 4625");
+        assert_eq!(ev.severity, Severity::Warning);
+        assert_eq!(
+            attr(&ev, "LogonFailure.TargetUserName"),
+            Some(&AttrValue::from("admin"))
+        );
+        assert_eq!(
+            attr(&ev, "LogonFailure.Source.Ip"),
+            Some(&AttrValue::from("10.0.0.5"))
+        );
+    }
+
+    #[test]
+    fn process_creation_is_summarized() {
+        let data = record(
+            system("Microsoft-Windows-Security-Auditing", json!(4688), 0),
+            json!({ "EventData": {
+                "NewProcessName": "C:\\Windows\\System32\\cmd.exe",
+                "CommandLine": "cmd.exe /c whoami",
+                "ParentProcessName": "C:\\app\\app.exe",
+                "SubjectDomainName": "CORP",
+                "SubjectUserName": "jane",
+            } }),
+        );
+        let ev = record_to_event(5, None, &data);
+        assert_eq!(ev.title, "Process created: C:\\Windows\\System32\\cmd.exe");
+        assert_eq!(ev.severity, Severity::Info);
+        assert!(ev.details.contains("Command line: cmd.exe /c whoami"));
+        assert!(ev.details.contains("User: CORP\\jane"));
+        assert!(ev.details.contains("Creator process id: -"));
+    }
+}
@@ -0,0 +1,369 @@
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value};
+use time::OffsetDateTime;
+
+use crate::model::{AttrValue, Event, EventId, EventStore, Severity};
+
+const SOURCE_NAME: &str = "ingest::journal";
+
+/// Fields that are mapped onto `Event` (or only identify the entry) and are not repeated as
+/// attributes.
+const CONSUMED_FIELDS: &[&str] = &[
+    "MESSAGE",
+    "PRIORITY",
+    "__CURSOR",
+    "__REALTIME_TIMESTAMP",
+    "__MONOTONIC_TIMESTAMP",
+    "_SOURCE_REALTIME_TIMESTAMP",
+    "_HOSTNAME",
+    "_SYSTEMD_UNIT",
+    "_PID",
+    "SYSLOG_IDENTIFIER",
+];
+
+/// Binary field values larger than this are rejected as corrupt input.
+const MAX_BINARY_FIELD: u64 = 64 * 1024 * 1024;
+
+pub struct IngestedJournal {
+    pub events: EventStore,
+    pub entries: u64,
+    pub malformed: u64,
+}
+
+/// Parse `journalctl -o export` or `journalctl -o json` output (picked from the first byte).
+pub fn ingest(path: &Path) -> Result<IngestedJournal> {
+    let file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let file_size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    ingest_reader(BufReader::new(file), path, file_size)
+}
+
+pub fn ingest_reader(
+    mut reader: impl BufRead,
+    path: &Path,
+    file_size: u64,
+) -> Result<IngestedJournal> {
+    let is_json = reader
+        .fill_buf()
+        .with_context(|| format!("read {}", path.display()))?
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        == Some(&b'{');
+
+    let mut records = Vec::new();
+    let mut malformed = 0u64;
+    let mut first_error: Option<String> = None;
+    let mut on_entry = |entry: Result<Vec<(String, String)>>| match entry {
+        Ok(fields) => records.push(entry_to_event(fields)),
+        Err(e) => {
+            malformed += 1;
+            first_error.get_or_insert_with(|| format!("{e:#}"));
+        }
+    };
+    if is_json {
+        read_json(&mut reader, &mut on_entry)?;
+    } else {
+        read_export(&mut reader, &mut on_entry)
+            .with_context(|| format!("read {}", path.display()))?;
+    }
+
+    super::linux_crash::extract_crashes(&mut records);
+    let start = crate::model::derive_t_ms(&mut records);
+    let crashes = records
+        .iter()
+        .filter(|e| e.attributes.iter().any(|(k, _)| k == "crash.kind"))
+        .count();
+
+    let mut details = format!(
+        "Path: {}\nSize: {} bytes\nFormat: {}\nEntries: {}\nMalformed entries: {}\nCrash events: {}",
+        path.display(),
+        file_size,
+        if is_json { "json" } else { "export" },
+        records.len(),
+        malformed,
+        crashes
+    );
+    if let Some(err) = &first_error {
+        details.push_str(&format!("\nFirst error: {err}"));
+    }
+
+    let entries = records.len() as u64;
+    let mut events = EventStore::default();
+    events.push(Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp: start,
+        severity: if malformed > 0 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        },
+        title: "Journal loaded".into(),
+        details,
+        source: SOURCE_NAME.into(),
+        attributes: Vec::new(),
+        links: Vec::new(),
+    });
+    for ev in records {
+        events.push(ev);
+    }
+
+    Ok(IngestedJournal {
+        events,
+        entries,
+        malformed,
+    })
+}
+
+/// One JSON object per line. Values are strings, numbers, byte arrays (non-UTF-8 data) or
+/// arrays of those (repeated fields).
+fn read_json(
+    reader: &mut impl BufRead,
+    on_entry: &mut impl FnMut(Result<Vec<(String, String)>>),
+) -> Result<()> {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&line);
+        if text.trim().is_empty() {
+            continue;
+        }
+        on_entry(
+            serde_json::from_str::<Map<String, Value>>(text.trim())
+                .map(|obj| {
+                    obj.into_iter()
+                        .filter_map(|(k, v)| json_field(&v).map(|v| (k, v)))
+                        .collect()
+                })
+                .map_err(Into::into),
+        );
+    }
+}
+
+fn json_field(v: &Value) -> Option<String> {
+    match v {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) if items.iter().all(|i| i.is_u64()) => {
+            let bytes: Vec<u8> = items
+                .iter()
+                .filter_map(|i| i.as_u64())
+                .map(|b| b as u8)
+                .collect();
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(json_field)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        other => Some(other.to_string()),
+    }
+}
+
+/// The journal export format: `KEY=value` lines, or `KEY\n<u64 LE length><data>\n` for binary
+/// values; entries are separated by an empty line.
+fn read_export(
+    reader: &mut impl BufRead,
+    on_entry: &mut impl FnMut(Result<Vec<(String, String)>>),
+) -> Result<()> {
+    let mut fields = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            if !fields.is_empty() {
+                on_entry(Ok(std::mem::take(&mut fields)));
+            }
+            return Ok(());
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        if line.is_empty() {
+            if !fields.is_empty() {
+                on_entry(Ok(std::mem::take(&mut fields)));
+            }
+            continue;
+        }
+        if let Some(eq) = line.iter().position(|&b| b == b'=') {
+            fields.push((
+                String::from_utf8_lossy(&line[..eq]).into_owned(),
+                String::from_utf8_lossy(&line[eq + 1..]).into_owned(),
+            ));
+            continue;
+        }
+        let key = String::from_utf8_lossy(&line).into_owned();
+        let mut len = [0u8; 8];
+        reader.read_exact(&mut len)?;
+        let len = u64::from_le_bytes(len);
+        if len > MAX_BINARY_FIELD {
+            bail!("binary field {key} claims {len} bytes");
+        }
+        let mut data = vec![0u8; len as usize];
+        reader.read_exact(&mut data)?;
+        let mut newline = [0u8; 1];
+        reader.read_exact(&mut newline)?;
+        fields.push((key, String::from_utf8_lossy(&data).into_owned()));
+    }
+}
+
+fn entry_to_event(fields: Vec<(String, String)>) -> Event {
+    let get = |name: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+            .filter(|v| !v.is_empty())
+    };
+    let timestamp = get("__REALTIME_TIMESTAMP")
+        .or_else(|| get("_SOURCE_REALTIME_TIMESTAMP"))
+        .and_then(|us| us.parse::<i128>().ok())
+        .and_then(|us| OffsetDateTime::from_unix_timestamp_nanos(us * 1_000).ok());
+    let severity = get("PRIORITY")
+        .and_then(|p| p.parse::<u8>().ok())
+        .map(super::syslog::priority_severity)
+        .unwrap_or(Severity::Info);
+    let message = get("MESSAGE").unwrap_or_default().to_string();
+    let source = get("SYSLOG_IDENTIFIER")
+        .or_else(|| get("_COMM"))
+        .or_else(|| get("_SYSTEMD_UNIT"))
+        .unwrap_or(SOURCE_NAME)
+        .to_string();
+
+    let mut attributes = Vec::new();
+    for (name, key) in [
+        ("host", "_HOSTNAME"),
+        ("unit", "_SYSTEMD_UNIT"),
+        ("pid", "_PID"),
+        ("priority", "PRIORITY"),
+    ] {
+        if let Some(v) = get(key) {
+            let value = match v.parse::<u64>() {
+                Ok(n) if name == "pid" || name == "priority" => AttrValue::UInt(n),
+                _ => v.into(),
+            };
+            attributes.push((name.to_string(), value));
+        }
+    }
+    attributes.extend(
+        fields
+            .iter()
+            .filter(|(k, _)| !CONSUMED_FIELDS.contains(&k.as_str()))
+            .map(|(k, v)| (k.clone(), v.as_str().into())),
+    );
+
+    Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp,
+        severity,
+        title: if message.is_empty() {
+            "(no message)".into()
+        } else {
+            super::message_title(&message)
+        },
+        details: message,
+        source,
+        attributes,
+        links: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn attr<'a>(ev: &'a Event, key: &str) -> Option<&'a AttrValue> {
+        ev.attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn ingest_bytes(input: &[u8]) -> IngestedJournal {
+        ingest_reader(Cursor::new(input), Path::new("journal"), input.len() as u64).unwrap()
+    }
+
+    fn binary_field(key: &str, data: &[u8]) -> Vec<u8> {
+        let mut out = format!("{key}\n").into_bytes();
+        out.extend((data.len() as u64).to_le_bytes());
+        out.extend(data);
+        out.push(b'\n');
+        out
+    }
+
+    #[test]
+    fn export_format_reads_text_and_binary_fields() {
+        let mut input = b"__CURSOR=s=1\n__REALTIME_TIMESTAMP=1700000000123456\nPRIORITY=3\n_HOSTNAME=web01\n_PID=77\nSYSLOG_IDENTIFIER=sshd\n".to_vec();
+        input.extend(binary_field("MESSAGE", b"line one\nline two=x"));
+        input.extend(b"CUSTOM=a=b\n\n");
+        // The last entry may end without a separating empty line.
+        input.extend(b"MESSAGE=second\n_COMM=cron");
+
+        let journal = ingest_bytes(&input);
+        assert_eq!(journal.entries, 2);
+        assert_eq!(journal.malformed, 0);
+        let events: Vec<&Event> = journal.events.iter().collect();
+        assert!(events[0].details.contains("Format: export"));
+
+        let first = events.iter().find(|e| e.source == "sshd").unwrap();
+        assert_eq!(first.details, "line one\nline two=x");
+        assert_eq!(first.severity, Severity::High);
+        assert_eq!(
+            first.timestamp.map(|t| t.unix_timestamp_nanos()),
+            Some(1_700_000_000_123_456_000)
+        );
+        assert_eq!(attr(first, "host"), Some(&AttrValue::from("web01")));
+        assert_eq!(attr(first, "pid"), Some(&AttrValue::UInt(77)));
+        assert_eq!(attr(first, "priority"), Some(&AttrValue::UInt(3)));
+        assert_eq!(attr(first, "CUSTOM"), Some(&AttrValue::from("a=b")));
+        assert!(attr(first, "__CURSOR").is_none());
+
+        let second = events.iter().find(|e| e.source == "cron").unwrap();
+        assert_eq!(second.details, "second");
+        assert_eq!(second.timestamp, None);
+    }
+
+    #[test]
+    fn oversized_binary_field_fails_the_read() {
+        let mut input = b"MESSAGE\n".to_vec();
+        input.extend((MAX_BINARY_FIELD + 1).to_le_bytes());
+        let err = ingest_reader(Cursor::new(input), Path::new("journal"), 0)
+            .err()
+            .unwrap();
+        assert!(format!("{err:#}").contains("claims"));
+    }
+
+    #[test]
+    fn json_format_decodes_byte_arrays_and_repeated_fields() {
+        let input = concat!(
+            r#"{"MESSAGE":[104,105],"PRIORITY":"4","TAG":["a","b"],"_PID":"12","N":null}"#,
+            "\n",
+            "{broken\n",
+            r#"{"MESSAGE":"","SYSLOG_IDENTIFIER":"app"}"#,
+            "\n",
+        );
+        let journal = ingest_bytes(input.as_bytes());
+        assert_eq!(journal.entries, 2);
+        assert_eq!(journal.malformed, 1);
+        let events: Vec<&Event> = journal.events.iter().collect();
+        assert_eq!(events[0].severity, Severity::Warning);
+        assert!(events[0].details.contains("Format: json"));
+        assert!(events[0].details.contains("First error: "));
+
+        let first = events.iter().find(|e| e.details == "hi").unwrap();
+        assert_eq!(first.severity, Severity::Warning);
+        assert_eq!(first.source, SOURCE_NAME);
+        assert_eq!(attr(first, "TAG"), Some(&AttrValue::from("a\nb")));
+        assert!(attr(first, "N").is_none());
+        let second = events.iter().find(|e| e.source == "app").unwrap();
+        assert_eq!(second.title, "(no message)");
+    }
+}
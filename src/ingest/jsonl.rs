@@ -0,0 +1,464 @@
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::model::{AttrValue, Event, EventId, EventStore, Severity};
+
+const FIELD_MAP_ENV_VAR: &str = "LOG_ATLAS_JSONL_FIELDS";
+const SOURCE_NAME: &str = "ingest::jsonl";
+
+/// Which keys hold the well-known event fields. Each entry is a list of candidates tried in
+/// order; a candidate may be a dotted path (`log.level`) into nested objects.
+#[derive(Clone, Debug)]
+pub struct JsonlFieldMap {
+    pub timestamp: Vec<String>,
+    pub severity: Vec<String>,
+    pub message: Vec<String>,
+    pub source: Vec<String>,
+}
+
+impl Default for JsonlFieldMap {
+    fn default() -> Self {
+        let keys = |k: &[&str]| k.iter().map(|s| s.to_string()).collect();
+        Self {
+            timestamp: keys(&["timestamp", "@timestamp", "time", "ts", "datetime", "date"]),
+            severity: keys(&["level", "severity", "lvl", "loglevel", "log.level"]),
+            message: keys(&["message", "msg", "@message", "text", "event"]),
+            source: keys(&[
+                "source",
+                "logger",
+                "component",
+                "service",
+                "module",
+                "target",
+            ]),
+        }
+    }
+}
+
+impl JsonlFieldMap {
+    /// Defaults, overridden by `LOG_ATLAS_JSONL_FIELDS` (see [`JsonlFieldMap::parse`]).
+    pub fn from_env() -> Self {
+        std::env::var(FIELD_MAP_ENV_VAR).map_or_else(|_| Self::default(), |spec| Self::parse(&spec))
+    }
+
+    /// Defaults, overridden per field by `spec`, e.g.
+    /// `timestamp=ts,severity=lvl,message=msg|text,source=svc` (`|` separates candidates).
+    /// Unknown fields and entries without `=` are ignored.
+    pub fn parse(spec: &str) -> Self {
+        let mut map = Self::default();
+        for entry in spec.split(',') {
+            let Some((field, keys)) = entry.split_once('=') else {
+                continue;
+            };
+            let keys: Vec<String> = keys
+                .split('|')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect();
+            match field.trim() {
+                "timestamp" => map.timestamp = keys,
+                "severity" => map.severity = keys,
+                "message" => map.message = keys,
+                "source" => map.source = keys,
+                _ => {}
+            }
+        }
+        map
+    }
+}
+
+/// The map in `LOG_ATLAS_JSONL_FIELDS` syntax.
+impl std::fmt::Display for JsonlFieldMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields = [
+            ("timestamp", &self.timestamp),
+            ("severity", &self.severity),
+            ("message", &self.message),
+            ("source", &self.source),
+        ];
+        for (i, (field, keys)) in fields.into_iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{sep}{field}={}", keys.join("|"))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct IngestedJsonl {
+    pub events: EventStore,
+    pub lines: u64,
+    pub malformed: u64,
+}
+
+pub fn ingest(path: &Path) -> Result<IngestedJsonl> {
+    let file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let file_size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    ingest_reader(
+        BufReader::new(file),
+        path,
+        file_size,
+        &JsonlFieldMap::from_env(),
+    )
+}
+
+/// Stream records from `reader` one line at a time; only the resulting events are kept.
+pub fn ingest_reader(
+    mut reader: impl BufRead,
+    path: &Path,
+    file_size: u64,
+    fields: &JsonlFieldMap,
+) -> Result<IngestedJsonl> {
+    let mut records: Vec<Event> = Vec::new();
+    let mut lines = 0u64;
+    let mut malformed = 0u64;
+    let mut unparsed_timestamps = 0u64;
+    let mut first_malformed: Option<(u64, String)> = None;
+    let mut line: Vec<u8> = Vec::new();
+
+    loop {
+        line.clear();
+        let n = reader
+            .read_until(b'\n', &mut line)
+            .with_context(|| format!("read {} line {}", path.display(), lines + 1))?;
+        if n == 0 {
+            break;
+        }
+        lines += 1;
+        // A stray invalid byte should cost one record, not the whole file.
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Map<String, Value>>(text) {
+            Ok(obj) => {
+                let (event, timestamp_parsed) = record_to_event(obj, fields);
+                unparsed_timestamps += u64::from(!timestamp_parsed);
+                records.push(event);
+            }
+            Err(e) => {
+                malformed += 1;
+                first_malformed.get_or_insert((lines, e.to_string()));
+            }
+        }
+    }
+
+    let start = crate::model::derive_t_ms(&mut records);
+
+    let mut details = format!(
+        "Path: {}\nSize: {} bytes\nLines: {}\nEvents: {}\nMalformed lines: {}\nUnparsed timestamps: {}\nFields: {}",
+        path.display(),
+        file_size,
+        lines,
+        records.len(),
+        malformed,
+        unparsed_timestamps,
+        fields
+    );
+    if let Some((line_no, err)) = &first_malformed {
+        details.push_str(&format!("\nFirst malformed line {line_no}: {err}"));
+    }
+
+    let mut events = EventStore::default();
+    events.push(Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp: start,
+        severity: if malformed > 0 || unparsed_timestamps > 0 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        },
+        title: "JSON Lines loaded".into(),
+        details,
+        source: SOURCE_NAME.into(),
+        attributes: Vec::new(),
+        links: Vec::new(),
+    });
+    for ev in records {
+        events.push(ev);
+    }
+
+    Ok(IngestedJsonl {
+        events,
+        lines,
+        malformed,
+    })
+}
+
+/// The record as an event, and whether its timestamp field (if any) parsed. A timestamp
+/// that does not parse, or is out of range, is kept as the `timestamp` attribute.
+fn record_to_event(mut obj: Map<String, Value>, fields: &JsonlFieldMap) -> (Event, bool) {
+    let raw_timestamp = take_field(&mut obj, &fields.timestamp);
+    let timestamp = raw_timestamp.as_ref().and_then(parse_timestamp);
+    let unparsed = raw_timestamp.filter(|_| timestamp.is_none());
+    let severity = take_field(&mut obj, &fields.severity)
+        .map(|v| parse_severity(&v))
+        .unwrap_or(Severity::Info);
+    let message = take_field(&mut obj, &fields.message).map(|v| value_to_string(&v));
+    let source = take_field(&mut obj, &fields.source)
+        .map(|v| value_to_string(&v))
+        .unwrap_or_else(|| SOURCE_NAME.into());
+
+    let mut attributes = Vec::new();
+    let timestamp_parsed = unparsed.is_none();
+    if let Some(v) = unparsed {
+        attributes.push(("timestamp".to_string(), json_attr(&v)));
+    }
+    flatten_attributes("", Value::Object(obj), &mut attributes);
+
+    let details = message.clone().unwrap_or_else(|| {
+        attributes
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    let title = message
+        .as_deref()
+        .map(super::message_title)
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "(no message)".into());
+
+    let event = Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp,
+        severity,
+        title,
+        details,
+        source,
+        attributes,
+        links: Vec::new(),
+    };
+    (event, timestamp_parsed)
+}
+
+/// Remove and return the first candidate key present, following dotted paths into objects.
+fn take_field(obj: &mut Map<String, Value>, candidates: &[String]) -> Option<Value> {
+    for key in candidates {
+        if let Some(v) = obj.shift_remove(key) {
+            return Some(v);
+        }
+        if let Some((head, rest)) = key.split_once('.')
+            && let Some(Value::Object(inner)) = obj.get_mut(head)
+            && let Some(v) = take_field(inner, &[rest.to_string()])
+        {
+            return Some(v);
+        }
+    }
+    None
+}
+
+fn flatten_attributes(prefix: &str, value: Value, out: &mut Vec<(String, AttrValue)>) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() {
+                    k
+                } else {
+                    format!("{prefix}.{k}")
+                };
+                flatten_attributes(&key, v, out);
+            }
+        }
+        other => out.push((prefix.to_string(), json_attr(&other))),
+    }
+}
+
+/// Numbers stay numbers and arrays become lists; everything else is kept as text.
+fn json_attr(v: &Value) -> AttrValue {
+    match v {
+        Value::Number(n) => n
+            .as_u64()
+            .map(AttrValue::UInt)
+            .or_else(|| n.as_i64().map(AttrValue::Int))
+            .unwrap_or_else(|| n.to_string().into()),
+        Value::Array(items) => AttrValue::List(items.iter().map(json_attr).collect()),
+        other => value_to_string(other).into(),
+    }
+}
+
+fn value_to_string(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// RFC 3339 (also with a space instead of `T`), or unix epoch seconds / milliseconds /
+/// microseconds / nanoseconds, picked by magnitude. `None` for anything else, including
+/// epochs outside the representable range.
+pub fn parse_timestamp(v: &Value) -> Option<OffsetDateTime> {
+    match v {
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                epoch_to_datetime(i as i128)
+            } else {
+                float_epoch_to_datetime(n.as_f64()?)
+            }
+        }
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(i) = s.parse::<i128>() {
+                return epoch_to_datetime(i);
+            }
+            if let Ok(f) = s.parse::<f64>() {
+                return float_epoch_to_datetime(f);
+            }
+            OffsetDateTime::parse(s, &Rfc3339).ok().or_else(|| {
+                let fixed = s.replacen(' ', "T", 1);
+                OffsetDateTime::parse(&fixed, &Rfc3339).ok()
+            })
+        }
+        _ => None,
+    }
+}
+
+fn epoch_to_datetime(v: i128) -> Option<OffsetDateTime> {
+    let nanos_per_unit = match v.unsigned_abs() {
+        0..100_000_000_000 => 1_000_000_000,
+        100_000_000_000..100_000_000_000_000 => 1_000_000,
+        100_000_000_000_000..100_000_000_000_000_000 => 1_000,
+        _ => 1,
+    };
+    OffsetDateTime::from_unix_timestamp_nanos(v.checked_mul(nanos_per_unit)?).ok()
+}
+
+/// Fractional epoch seconds; NaN and infinities are not timestamps.
+fn float_epoch_to_datetime(secs: f64) -> Option<OffsetDateTime> {
+    let nanos = secs * 1e9;
+    if !nanos.is_finite() {
+        return None;
+    }
+    OffsetDateTime::from_unix_timestamp_nanos(nanos as i128).ok()
+}
+
+/// Map level names and the common numeric scales (syslog 0-7, bunyan/pino 10-60) onto
+/// `Severity`. Unknown levels are `Info`.
+pub fn parse_severity(v: &Value) -> Severity {
+    match v {
+        Value::Number(n) => match n.as_u64() {
+            Some(0..=3) => Severity::High,
+            Some(4) => Severity::Warning,
+            Some(5..=9) => Severity::Info,
+            Some(50..) => Severity::High,
+            Some(40..=49) => Severity::Warning,
+            _ => Severity::Info,
+        },
+        Value::String(s) => {
+            let lc = s.trim().to_ascii_lowercase();
+            if let Ok(n) = lc.parse::<u64>() {
+                return parse_severity(&Value::from(n));
+            }
+            match lc.as_str() {
+                "warn" | "warning" | "wrn" => Severity::Warning,
+                "error" | "err" | "eror" | "fatal" | "critical" | "crit" | "alert" | "emerg"
+                | "emergency" | "panic" | "severe" => Severity::High,
+                _ => Severity::Info,
+            }
+        }
+        _ => Severity::Info,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ingest_lines(text: &str, fields: &JsonlFieldMap) -> IngestedJsonl {
+        ingest_reader(text.as_bytes(), Path::new("test.jsonl"), 0, fields).expect("ingest")
+    }
+
+    fn unix(v: Value) -> Option<i128> {
+        parse_timestamp(&v).map(|t| t.unix_timestamp_nanos())
+    }
+
+    #[test]
+    fn epoch_units_are_picked_by_magnitude() {
+        let nanos = 1_700_000_000_123_000_000i128;
+        assert_eq!(
+            unix(Value::from(1_700_000_000)),
+            Some(1_700_000_000_000_000_000)
+        );
+        assert_eq!(unix(Value::from(1_700_000_000_123i64)), Some(nanos));
+        assert_eq!(unix(Value::from(1_700_000_000_123_000i64)), Some(nanos));
+        assert_eq!(unix(Value::from(nanos as i64)), Some(nanos));
+        assert_eq!(unix(Value::from("1700000000123")), Some(nanos));
+        assert_eq!(
+            unix(Value::from(1_700_000_000.5)),
+            Some(1_700_000_000_500_000_000)
+        );
+        assert_eq!(unix(Value::from(-86_400)), Some(-86_400_000_000_000));
+        assert_eq!(
+            unix(Value::from("2023-11-14 22:13:20Z")),
+            Some(1_700_000_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn out_of_range_epochs_are_not_timestamps() {
+        for v in [
+            i128::MIN.to_string(),
+            i128::MAX.to_string(),
+            "99999999999999999999999999".to_string(),
+            "NaN".to_string(),
+            "inf".to_string(),
+            "1e300".to_string(),
+        ] {
+            assert_eq!(unix(Value::from(v.as_str())), None, "{v}");
+        }
+    }
+
+    #[test]
+    fn unparsed_timestamps_are_counted_and_kept() {
+        let out = ingest_lines(
+            "{\"ts\":\"-170141183460469231731687303715884105728\",\"msg\":\"a\"}\n{\"ts\":1700000000,\"msg\":\"b\"}\n",
+            &JsonlFieldMap::parse("timestamp=ts"),
+        );
+        let events: Vec<&Event> = out.events.iter().collect();
+        assert_eq!(events[0].severity, Severity::Warning);
+        assert!(events[0].details.contains("Unparsed timestamps: 1"));
+        let bad = events.iter().find(|e| e.title == "a").expect("event a");
+        assert!(bad.timestamp.is_none());
+        assert!(bad.attributes.iter().any(|(k, _)| k == "timestamp"));
+        let good = events.iter().find(|e| e.title == "b").expect("event b");
+        assert!(good.timestamp.is_some());
+        assert!(good.attributes.is_empty());
+    }
+
+    #[test]
+    fn field_map_overrides_only_the_named_fields() {
+        let map = JsonlFieldMap::parse(" severity = lvl , message=text|body.msg,bogus=x,source");
+        let defaults = JsonlFieldMap::default();
+        assert_eq!(map.severity, ["lvl"]);
+        assert_eq!(map.message, ["text", "body.msg"]);
+        assert_eq!(map.timestamp, defaults.timestamp);
+        assert_eq!(map.source, defaults.source);
+        // The displayed map parses back to itself.
+        let shown = map.to_string();
+        assert!(shown.starts_with("timestamp=timestamp|@timestamp|"));
+        assert_eq!(JsonlFieldMap::parse(&shown).to_string(), shown);
+    }
+
+    #[test]
+    fn mapped_fields_leave_the_rest_as_attributes() {
+        let fields = JsonlFieldMap::parse("severity=lvl,message=text|body.msg,source=svc");
+        let out = ingest_lines(
+            "{\"time\":\"2024-01-02T03:04:05Z\",\"lvl\":\"error\",\"body\":{\"msg\":\"disk full\",\"dev\":\"sda\"},\"svc\":\"db\",\"level\":\"info\"}\n",
+            &fields,
+        );
+        let ev = out.events.iter().nth(1).expect("record event");
+        assert_eq!(ev.title, "disk full");
+        assert_eq!(ev.severity, Severity::High);
+        assert_eq!(ev.source, "db");
+        assert!(ev.timestamp.is_some());
+        let keys: Vec<&str> = ev.attributes.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["body.dev", "level"]);
+    }
+}
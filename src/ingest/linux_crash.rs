@@ -0,0 +1,268 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::model::{AttrValue, Event, Severity};
+
+/// Continuation lines (`CPU: ... Comm:`, `RIP:`) are looked for this many events after an oops.
+const OOPS_CONTEXT_EVENTS: usize = 60;
+
+/// `[ 1234.567890] ` prefix of dmesg-style lines.
+static DMESG_PREFIX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[\s*\d+\.\d+\]\s*").expect("dmesg prefix regex"));
+
+/// `app[1234]: segfault at 10 ip 00005581e7d2a1b2 sp 00007ffd4c3f7e90 error 4 in app[5581e7d29000+2000]`
+static SEGFAULT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?P<comm>\S+?)\[(?P<pid>\d+)\]: segfault at (?P<addr>[0-9a-fA-F]+) ip (?P<ip>[0-9a-fA-F]+) sp (?P<sp>[0-9a-fA-F]+) error (?P<err>\d+)(?: in (?P<module>[^\[\s]+)\[(?P<base>[0-9a-fA-F]+)\+[0-9a-fA-F]+\])?",
+    )
+    .expect("segfault regex")
+});
+
+/// `traps: app[1234] general protection fault ip:7f.. sp:7ff.. error:0 in libc.so.6[7f..+1000]`
+static TRAP_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"traps: (?P<comm>\S+?)\[(?P<pid>\d+)\] (?P<what>[a-z][a-z ]+?) ip:(?P<ip>[0-9a-fA-F]+) sp:(?P<sp>[0-9a-fA-F]+) error:(?P<err>[0-9a-fA-F]+)(?: in (?P<module>[^\[\s]+)\[(?P<base>[0-9a-fA-F]+)\+[0-9a-fA-F]+\])?",
+    )
+    .expect("trap regex")
+});
+
+/// `Out of memory: Killed process 1234 (java) total-vm:123kB, anon-rss:45kB, ...` and the older
+/// `Out of memory: Kill process 1234 (java) score 900 or sacrifice child`.
+static OOM_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:Out of memory|Memory cgroup out of memory): Kill(?:ed)? process (?P<pid>\d+) \((?P<comm>[^)]*)\)(?P<rest>.*)")
+        .expect("oom regex")
+});
+
+static OOM_RSS_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"anon-rss:(\d+)kB").expect("oom rss regex"));
+
+/// First line of a kernel oops / panic.
+static OOPS_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:BUG: unable to handle (?:kernel )?(?P<what>[a-zA-Z ]+?)(?: at | for address: )(?P<addr>[0-9a-fA-Fx]+)|BUG: (?P<bug>.+)|Oops: (?P<oops>[0-9a-fA-F]+ \[#\d+\].*)|general protection fault(?P<gpf>.*)|Kernel panic - not syncing: (?P<panic>.*))",
+    )
+    .expect("oops regex")
+});
+
+static OOPS_TASK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^CPU: \d+ (?:UID: \d+ )?PID: (?P<pid>\d+) Comm: (?P<comm>\S+)")
+        .expect("oops task regex")
+});
+
+static OOPS_RIP_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:RIP|pc|PC is at|EIP): ?(?:[0-9a-fA-F]{4}:)?(?P<rip>.+)")
+        .expect("oops rip regex")
+});
+
+/// Rewrite kernel segfault / trap / OOM-killer / oops lines in place as crash events: `High`
+/// severity, a one-line title and `crash.*` attributes with the faulting process and address.
+///
+/// Events are matched on `details`, which log ingesters set to the raw message. Oops reports
+/// span many lines; the task and faulting instruction that follow the first line are folded
+/// into the crash event.
+pub(crate) fn extract_crashes(events: &mut [Event]) {
+    let mut open_oops: Option<usize> = None;
+    for i in 0..events.len() {
+        let message = DMESG_PREFIX_RE
+            .replace(events[i].details.trim_end(), "")
+            .into_owned();
+        let message = message.as_str();
+
+        if let Some(start) = open_oops
+            && i - start <= OOPS_CONTEXT_EVENTS
+            && events[i].source == events[start].source
+        {
+            if let Some(c) = OOPS_TASK_RE.captures(message) {
+                let (pid, comm) = (c["pid"].to_string(), c["comm"].to_string());
+                let crash = &mut events[start];
+                if push_unique(crash, "crash.process", comm.as_str().into()) {
+                    push_unique(crash, "crash.pid", number(&pid));
+                    crash.title.push_str(&format!(" in {comm} (pid {pid})"));
+                    crash
+                        .details
+                        .push_str(&format!("\nTask: {comm} (pid {pid})"));
+                }
+                continue;
+            }
+            if let Some(c) = OOPS_RIP_RE.captures(message) {
+                let rip = c["rip"].trim().to_string();
+                let crash = &mut events[start];
+                if push_unique(crash, "crash.ip", rip.as_str().into()) {
+                    crash
+                        .details
+                        .push_str(&format!("\nFaulting instruction: {rip}"));
+                }
+                continue;
+            }
+        }
+
+        let Some(crash) = parse_crash(message) else {
+            continue;
+        };
+        if crash.kind == "oops" {
+            // One report often produces several first-line matches (`BUG:` then `Oops:`).
+            if let Some(start) = open_oops
+                && i - start <= 2
+            {
+                events[start].details.push_str(&format!("\n{message}"));
+                continue;
+            }
+            open_oops = Some(i);
+        }
+        let ev = &mut events[i];
+        let original = std::mem::take(&mut ev.details);
+        ev.severity = Severity::High;
+        ev.title = crash.title;
+        ev.details = format!("{}\n\n{original}", crash.summary);
+        ev.attributes.push(("crash.kind".into(), crash.kind.into()));
+        ev.attributes.extend(crash.fields);
+    }
+}
+
+struct LinuxCrash {
+    kind: &'static str,
+    title: String,
+    summary: String,
+    fields: Vec<(String, AttrValue)>,
+}
+
+fn parse_crash(message: &str) -> Option<LinuxCrash> {
+    if let Some(c) = SEGFAULT_RE.captures(message) {
+        let mut fields = vec![
+            ("crash.process".to_string(), c["comm"].into()),
+            ("crash.pid".to_string(), number(&c["pid"])),
+            ("crash.address".to_string(), address(&c["addr"])),
+            ("crash.ip".to_string(), address(&c["ip"])),
+            ("crash.sp".to_string(), address(&c["sp"])),
+            ("crash.error".to_string(), c["err"].into()),
+        ];
+        module_fields(&c, &mut fields);
+        return Some(LinuxCrash {
+            kind: "segfault",
+            title: format!(
+                "Segfault: {} (pid {}) at {}",
+                &c["comm"],
+                &c["pid"],
+                hex(&c["addr"])
+            ),
+            summary: summary("Segmentation fault", &fields),
+            fields,
+        });
+    }
+    if let Some(c) = TRAP_RE.captures(message) {
+        let mut fields = vec![
+            ("crash.process".to_string(), c["comm"].into()),
+            ("crash.pid".to_string(), number(&c["pid"])),
+            ("crash.trap".to_string(), c["what"].into()),
+            ("crash.ip".to_string(), address(&c["ip"])),
+            ("crash.sp".to_string(), address(&c["sp"])),
+            ("crash.error".to_string(), c["err"].into()),
+        ];
+        module_fields(&c, &mut fields);
+        return Some(LinuxCrash {
+            kind: "trap",
+            title: format!(
+                "Trap: {} in {} (pid {}) at {}",
+                &c["what"],
+                &c["comm"],
+                &c["pid"],
+                hex(&c["ip"])
+            ),
+            summary: summary("Trap", &fields),
+            fields,
+        });
+    }
+    if let Some(c) = OOM_RE.captures(message) {
+        let mut fields = vec![
+            ("crash.process".to_string(), c["comm"].into()),
+            ("crash.pid".to_string(), number(&c["pid"])),
+        ];
+        if let Some(rss) = OOM_RSS_RE.captures(&c["rest"]) {
+            fields.push(("crash.anon_rss_kb".to_string(), number(&rss[1])));
+        }
+        return Some(LinuxCrash {
+            kind: "oom_kill",
+            title: format!("OOM killer: killed {} (pid {})", &c["comm"], &c["pid"]),
+            summary: summary("Out-of-memory kill", &fields),
+            fields,
+        });
+    }
+    if let Some(c) = OOPS_RE.captures(message) {
+        let mut fields = Vec::new();
+        let title = if let Some(what) = c.name("what") {
+            let raw = c.name("addr").map_or("", |m| m.as_str());
+            let addr = hex(raw);
+            fields.push(("crash.address".to_string(), address(raw)));
+            format!("Kernel oops: {} at {addr}", what.as_str())
+        } else if let Some(panic) = c.name("panic") {
+            fields.push(("crash.reason".to_string(), panic.as_str().into()));
+            format!("Kernel panic: {}", panic.as_str())
+        } else if let Some(bug) = c.name("bug") {
+            fields.push(("crash.reason".to_string(), bug.as_str().into()));
+            format!("Kernel BUG: {}", bug.as_str())
+        } else if c.name("gpf").is_some() {
+            "Kernel oops: general protection fault".to_string()
+        } else {
+            format!("Kernel oops: {}", c.name("oops").map_or("", |m| m.as_str()))
+        };
+        return Some(LinuxCrash {
+            kind: "oops",
+            summary: summary("Kernel oops", &fields),
+            title,
+            fields,
+        });
+    }
+    None
+}
+
+fn module_fields(c: &regex::Captures, fields: &mut Vec<(String, AttrValue)>) {
+    if let (Some(module), Some(base)) = (c.name("module"), c.name("base")) {
+        fields.push(("crash.module".to_string(), module.as_str().into()));
+        fields.push(("crash.module_base".to_string(), address(base.as_str())));
+        // The instruction pointer relative to the module is what symbolization needs.
+        if let (Some(ip), Ok(base)) = (
+            fields
+                .iter()
+                .find(|(k, _)| k == "crash.ip")
+                .and_then(|(_, v)| v.as_u64()),
+            u64::from_str_radix(base.as_str(), 16),
+        ) && ip >= base
+        {
+            fields.push((
+                "crash.module_offset".to_string(),
+                AttrValue::Addr(ip - base),
+            ));
+        }
+    }
+}
+
+fn summary(what: &str, fields: &[(String, AttrValue)]) -> String {
+    let mut out = what.to_string();
+    for (k, v) in fields {
+        out.push_str(&format!("\n{}: {v}", k.trim_start_matches("crash.")));
+    }
+    out
+}
+
+fn push_unique(ev: &mut Event, key: &str, value: AttrValue) -> bool {
+    if ev.attributes.iter().any(|(k, _)| k == key) {
+        return false;
+    }
+    ev.attributes.push((key.to_string(), value));
+    true
+}
+
+/// Hex digits (with or without `0x`) as an address; unparsable text is kept as is.
+fn address(s: &str) -> AttrValue {
+    u64::from_str_radix(s.trim_start_matches("0x"), 16).map_or_else(|_| s.into(), AttrValue::Addr)
+}
+
+fn number(s: &str) -> AttrValue {
+    s.parse::<u64>().map_or_else(|_| s.into(), AttrValue::UInt)
+}
+
+fn hex(s: &str) -> String {
+    let digits = s.trim_start_matches("0x").trim_start_matches('0');
+    format!("0x{}", if digits.is_empty() { "0" } else { digits })
+}
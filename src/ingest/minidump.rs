@@ -1,10 +1,13 @@
+use std::ops::Deref;
 use std::path::Path;
 
 use anyhow::{Context, Result};
 
+use crate::ingest::minidump_scan::{ScanBudget, ScanOutput, scan_strings};
 use crate::model::{
-    Event, EventId, EventStore, ExceptionInfo, MinidumpReport, MinidumpSummary, ModuleInfo,
-    ProcessInfo, Severity, StackwalkReport, ThreadInfo, ThreadStackTrace,
+    AttrValue, EntityRef, Event, EventId, EventStore, ExceptionInfo, Indicator, MinidumpReport,
+    MinidumpSummary, ModuleInfo, ProcessExecArtifact, ProcessInfo, SensitiveFinding, Severity,
+    StackwalkReport, ThreadInfo, ThreadStackTrace, render_attributes,
 };
 
 pub struct IngestedMinidump {
@@ -14,13 +17,33 @@ pub struct IngestedMinidump {
 }
 
 pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
-    let data = std::fs::read(path).with_context(|| format!("read {}", path.display()))?;
-    let file_size = data.len() as u64;
-    let dump = minidump::Minidump::read(data).context("parse minidump")?;
+    let file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    // SAFETY: the mapping is read-only and only lives for the duration of ingestion. Dumps are
+    // treated as immutable inputs; truncating the file underneath us is not a supported workflow.
+    let map =
+        unsafe { memmap2::Mmap::map(&file) }.with_context(|| format!("map {}", path.display()))?;
+    let dump = minidump::Minidump::read(&map[..]).context("parse minidump")?;
+
+    ingest_dump(&dump, &map, path)
+}
 
-    let mut summary = MinidumpSummary::default();
-    summary.file_size = Some(file_size);
-    summary.time_date_stamp = Some(dump.header.time_date_stamp);
+/// Ingest an already-parsed minidump, independent of how its bytes are backed
+/// (memory-mapped file, owned buffer, ...). `file` is the raw dump, needed to follow RVAs
+/// inside streams the parser does not model.
+pub fn ingest_dump<'a, T>(
+    dump: &minidump::Minidump<'a, T>,
+    file: &[u8],
+    path: &Path,
+) -> Result<IngestedMinidump>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let file_size = file.len() as u64;
+    let mut summary = MinidumpSummary {
+        file_size: Some(file_size),
+        time_date_stamp: Some(dump.header.time_date_stamp),
+        ..Default::default()
+    };
 
     let mut report = MinidumpReport::default();
 
@@ -35,7 +58,7 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
 
     if let Ok(threads) = dump.get_stream::<minidump::MinidumpThreadList>() {
         summary.thread_count = Some(threads.threads.len());
-        report.threads = extract_threads(&dump, &threads);
+        report.threads = extract_threads(dump, &threads);
     }
 
     if let Ok(modules) = dump.get_stream::<minidump::MinidumpModuleList>() {
@@ -43,8 +66,12 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
         report.modules = extract_modules(&modules);
     }
 
+    report.linux = crate::ingest::minidump_linux::extract_linux_streams(dump, file);
+    report.crashpad = crate::ingest::minidump_crashpad::extract_crashpad(dump, &report.modules);
+    report.annotation_rules = crate::ingest::minidump_crashpad::annotation_rules_from_env();
+
     // Process info (best-effort, optional stream).
-    report.process = extract_process_info(&dump, &report.modules);
+    report.process = extract_process_info(dump, &report.modules, report.linux.as_ref());
 
     if let Ok(mem) = dump.get_stream::<minidump::MinidumpMemoryList>() {
         report.memory_region_count = Some(mem.iter().count());
@@ -58,14 +85,23 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
         report.memory_info_region_count = Some(mi.iter().count());
     }
 
-    // Execution artifacts: best-effort string scan over dump memory.
-    report.exec_artifacts = crate::ingest::minidump_exec::extract_exec_artifacts(&dump);
+    // Execution artifacts, IOCs and secrets: best-effort string scan over all dump memory,
+    // within budget.
+    let budget = crate::ingest::minidump_scan::ScanBudget::from_env();
+    let (exec, iocs, secrets) = scan_memory(dump, &budget, &report.modules, &report.threads);
+    report.exec_artifacts = exec.hits;
+    report.string_scan = Some(exec.coverage);
+    report.iocs = iocs.hits;
+    report.ioc_scan = Some(iocs.coverage);
+    report.sensitive = secrets.hits;
+    report.sensitive_scan = Some(secrets.coverage);
 
     report.injected_regions = crate::ingest::minidump_injection::detect_injected_regions(
-        &dump,
+        dump,
         &report.modules,
         &report.threads,
         &report.exec_artifacts,
+        report.linux.as_ref().map_or(&[][..], |l| &l.maps[..]),
     );
 
     if let Ok(exc) = dump.get_stream::<minidump::MinidumpException>() {
@@ -84,7 +120,7 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
         ));
     }
 
-    match crate::ingest::minidump_stackwalk::extract_stackwalk(&dump) {
+    match crate::ingest::minidump_stackwalk::extract_stackwalk(dump) {
         Ok(stackwalk) => {
             report.stackwalk = Some(stackwalk);
         }
@@ -96,13 +132,20 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
     let mut t_ms = 0u64;
     let mut events: Vec<Event> = Vec::new();
 
+    let attributes = vec![
+        ("path".to_string(), path.display().to_string().into()),
+        ("size".to_string(), file_size.into()),
+    ];
     events.push(Event {
         id: EventId(0),
         t_ms,
         severity: Severity::Info,
         title: "Minidump loaded".into(),
-        details: format!("Path: {}\nSize: {} bytes", path.display(), file_size),
+        details: render_attributes(&attributes),
         source: "ingest::minidump".into(),
+        timestamp: None,
+        attributes,
+        links: Vec::new(),
     });
 
     t_ms += 10;
@@ -113,17 +156,49 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
         title: "Minidump summary".into(),
         details: summary.pretty(),
         source: "ingest::minidump".into(),
+        timestamp: None,
+        attributes: summary.attributes(),
+        links: Vec::new(),
     });
 
     if let Some(proc) = &report.process {
         t_ms += 10;
+        let attributes = process_attributes(proc);
         events.push(Event {
             id: EventId(0),
             t_ms,
             severity: Severity::Info,
             title: "Process info".into(),
-            details: format_process_info(proc),
+            details: render_attributes(&attributes),
             source: "ingest::minidump".into(),
+            timestamp: None,
+            attributes,
+            links: if report.modules.is_empty() {
+                Vec::new()
+            } else {
+                vec![EntityRef::Module(0)]
+            },
+        });
+    }
+
+    if let Some(crashpad) = &report.crashpad
+        && !crashpad.annotations.is_empty()
+    {
+        t_ms += 10;
+        events.push(Event {
+            id: EventId(0),
+            t_ms,
+            severity: Severity::Info,
+            title: format!("Crashpad annotations ({})", crashpad.annotations.len()),
+            details: format_crashpad(crashpad),
+            source: "ingest::minidump".into(),
+            timestamp: None,
+            attributes: crashpad
+                .annotations
+                .iter()
+                .map(|a| (a.key.clone(), a.value.as_str().into()))
+                .collect(),
+            links: Vec::new(),
         });
     }
 
@@ -134,8 +209,72 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
             t_ms,
             severity: Severity::Warning,
             title: "Execution artifacts recovered".into(),
-            details: format_exec_artifacts(&report.exec_artifacts, 12),
+            details: format_exec_artifacts(&report.exec_artifacts, report.string_scan.as_ref(), 12),
+            source: "ingest::minidump".into(),
+            timestamp: None,
+            attributes: vec![("artifacts".to_string(), report.exec_artifacts.len().into())],
+            links: (0..report.exec_artifacts.len())
+                .map(EntityRef::ExecArtifact)
+                .collect(),
+        });
+    }
+
+    if !report.iocs.is_empty() {
+        t_ms += 10;
+        events.push(Event {
+            id: EventId(0),
+            t_ms,
+            severity: Severity::Info,
+            title: "Indicators extracted".into(),
+            details: format_ioc_summary(&report.iocs, 5),
             source: "ingest::minidump".into(),
+            timestamp: None,
+            attributes: Vec::new(),
+            links: Vec::new(),
+        });
+    }
+
+    if !report.sensitive.is_empty() {
+        t_ms += 10;
+        events.push(Event {
+            id: EventId(0),
+            t_ms,
+            severity: Severity::Warning,
+            title: "Sensitive data in dump memory".into(),
+            details: format_sensitive_summary(&report.sensitive, 5),
+            source: "ingest::minidump".into(),
+            timestamp: None,
+            attributes: Vec::new(),
+            links: Vec::new(),
+        });
+    }
+
+    let incomplete: Vec<String> = [
+        ("Execution artifacts", &report.string_scan),
+        ("IOCs", &report.ioc_scan),
+        ("Secrets", &report.sensitive_scan),
+    ]
+    .into_iter()
+    .filter_map(|(scan, cov)| {
+        let cov = cov.as_ref().filter(|c| !c.is_complete())?;
+        Some(format!("{scan}: {}", cov.pretty()))
+    })
+    .collect();
+    if !incomplete.is_empty() {
+        t_ms += 10;
+        events.push(Event {
+            id: EventId(0),
+            t_ms,
+            severity: Severity::Warning,
+            title: "Memory scan incomplete".into(),
+            details: format!(
+                "{}\n\nRaise or unset LOG_ATLAS_SCAN_BUDGET_MB / LOG_ATLAS_SCAN_MAX_HITS for full coverage.",
+                incomplete.join("\n")
+            ),
+            source: "ingest::minidump".into(),
+            timestamp: None,
+            attributes: Vec::new(),
+            links: Vec::new(),
         });
     }
 
@@ -144,28 +283,50 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
 
     if let Some(exc) = &report.exception {
         t_ms += 10;
+        let fault = report.fault_module_index();
+        let mut attributes = vec![
+            ("thread_id".to_string(), exc.thread_id.into()),
+            ("code".to_string(), AttrValue::Addr(exc.code.into())),
+            ("address".to_string(), AttrValue::Addr(exc.address)),
+        ];
+        if let Some(idx) = fault {
+            attributes.push((
+                "fault_module".to_string(),
+                report.modules[idx].name.as_str().into(),
+            ));
+        }
+        let mut links = vec![EntityRef::Thread(exc.thread_id)];
+        links.extend(fault.map(EntityRef::Module));
         events.push(Event {
             id: EventId(0),
             t_ms,
             severity: Severity::High,
             title: "Exception stream present".into(),
-            details: format!(
-                "thread_id={}\ncode=0x{:08X}\naddress=0x{:016X}",
-                exc.thread_id, exc.code, exc.address
-            ),
+            details: render_attributes(&attributes),
             source: "ingest::minidump".into(),
+            timestamp: None,
+            attributes,
+            links,
         });
     }
 
     if let Some(sw) = &report.stackwalk {
         t_ms += 10;
+        let attributes = stackwalk_attributes(sw);
         events.push(Event {
             id: EventId(0),
             t_ms,
             severity: Severity::Info,
             title: "Stackwalk completed".into(),
-            details: format_stackwalk_summary(sw),
+            details: render_attributes(&attributes),
             source: "ingest::stackwalk".into(),
+            timestamp: None,
+            attributes,
+            links: sw
+                .requesting_thread_id
+                .map(EntityRef::Thread)
+                .into_iter()
+                .collect(),
         });
 
         if let Some(stack) = report.exception_stack()
@@ -179,6 +340,12 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
                 title: "Exception thread call stack".into(),
                 details: format_exception_stack_preview(stack, 10),
                 source: "ingest::stackwalk".into(),
+                timestamp: None,
+                attributes: vec![
+                    ("thread_id".to_string(), stack.thread_id.into()),
+                    ("frames".to_string(), stack.frames.len().into()),
+                ],
+                links: vec![EntityRef::Thread(stack.thread_id)],
             });
         }
     } else if let Some(err) = &report.stackwalk_error {
@@ -190,12 +357,22 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
             title: "Stackwalk failed".into(),
             details: err.clone(),
             source: "ingest::stackwalk".into(),
+            timestamp: None,
+            attributes: Vec::new(),
+            links: Vec::new(),
         });
     }
 
     // Detections derived from extracted info.
     for det in report.detections() {
         t_ms += 5;
+        let mut attributes = vec![
+            ("detection_id".into(), det.id().into()),
+            ("rule".into(), det.rule.into()),
+        ];
+        if !det.techniques.is_empty() {
+            attributes.push(("technique".into(), det.techniques.into()));
+        }
         events.push(Event {
             id: EventId(0),
             t_ms,
@@ -203,6 +380,9 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
             title: format!("Detection: {}", det.title),
             details: det.details,
             source: "detector::basic".into(),
+            timestamp: None,
+            attributes,
+            links: det.links,
         });
     }
 
@@ -213,7 +393,67 @@ pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
     })
 }
 
+/// Execution artifacts, IOCs and secrets from one pass over dump memory: each scan's visitor
+/// sees the strings within its own limits, and each gets the pass's coverage.
+fn scan_memory<'a, T>(
+    dump: &minidump::Minidump<'a, T>,
+    budget: &ScanBudget,
+    modules: &[ModuleInfo],
+    threads: &[ThreadInfo],
+) -> (
+    ScanOutput<ProcessExecArtifact>,
+    ScanOutput<Indicator>,
+    ScanOutput<SensitiveFinding>,
+)
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    use crate::ingest::{minidump_exec as exec, minidump_ioc as ioc, minidump_secrets as secrets};
+
+    let limits = exec::LIMITS.union(ioc::LIMITS).union(secrets::LIMITS);
+    let raw = scan_strings(dump, budget, limits, |s| {
+        let len = s.text.len();
+        let hit = (
+            exec::LIMITS.contains(len).then(|| exec::visit(s)).flatten(),
+            ioc::LIMITS.contains(len).then(|| ioc::visit(s)).flatten(),
+            secrets::LIMITS
+                .contains(len)
+                .then(|| secrets::visit(s))
+                .flatten(),
+        );
+        (hit.0.is_some() || hit.1.is_some() || hit.2.is_some()).then_some(hit)
+    });
+
+    let mut exec_hits = Vec::new();
+    let mut ioc_hits = Vec::new();
+    let mut secret_hits = Vec::new();
+    for (e, i, s) in raw.hits {
+        exec_hits.extend(e);
+        ioc_hits.extend(i);
+        secret_hits.extend(s);
+    }
+    let locator = ioc::Locator::new(dump, modules, threads);
+    let exec = ScanOutput {
+        hits: exec_hits,
+        coverage: raw.coverage.clone(),
+    };
+    let iocs = ScanOutput {
+        hits: ioc_hits,
+        coverage: raw.coverage.clone(),
+    };
+    let secrets = ScanOutput {
+        hits: secret_hits,
+        coverage: raw.coverage,
+    };
+    (
+        exec::finish(exec, budget),
+        ioc::finish(iocs, budget, &locator),
+        secrets::finish(secrets, budget, &locator),
+    )
+}
+
 fn extract_modules(list: &minidump::MinidumpModuleList) -> Vec<ModuleInfo> {
+    use minidump::Module;
     list.iter()
         .map(|m| ModuleInfo {
             name: m.name.clone(),
@@ -222,10 +462,25 @@ fn extract_modules(list: &minidump::MinidumpModuleList) -> Vec<ModuleInfo> {
             checksum: m.raw.checksum,
             time_date_stamp: m.raw.time_date_stamp,
             file_version: format_file_version(&m.raw.version_info),
+            debug_id: m.debug_identifier().map(|id| id.breakpad().to_string()),
         })
         .collect()
 }
 
+fn format_crashpad(info: &crate::model::CrashpadInfo) -> String {
+    let mut lines = Vec::new();
+    if !info.report_id.is_empty() {
+        lines.push(format!("Report ID: {}", info.report_id));
+    }
+    if !info.client_id.is_empty() {
+        lines.push(format!("Client ID: {}", info.client_id));
+    }
+    for a in &info.annotations {
+        lines.push(format!("[{}] {}={}", a.scope(), a.key, a.value));
+    }
+    lines.join("\n")
+}
+
 fn format_file_version(v: &minidump::format::VS_FIXEDFILEINFO) -> Option<String> {
     let ms = v.file_version_hi;
     let ls = v.file_version_lo;
@@ -239,10 +494,13 @@ fn format_file_version(v: &minidump::format::VS_FIXEDFILEINFO) -> Option<String>
     Some(format!("{major}.{minor}.{build}.{rev}"))
 }
 
-fn extract_threads(
-    dump: &minidump::Minidump<Vec<u8>>,
+fn extract_threads<'a, T>(
+    dump: &minidump::Minidump<'a, T>,
     threads: &minidump::MinidumpThreadList<'_>,
-) -> Vec<ThreadInfo> {
+) -> Vec<ThreadInfo>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
     let names = dump.get_stream::<minidump::MinidumpThreadNames>().ok();
     let infos = dump.get_stream::<minidump::MinidumpThreadInfoList>().ok();
 
@@ -281,12 +539,20 @@ fn extract_threads(
         .collect()
 }
 
-fn extract_process_info(
-    dump: &minidump::Minidump<Vec<u8>>,
+fn extract_process_info<'a, T>(
+    dump: &minidump::Minidump<'a, T>,
     modules: &[ModuleInfo],
-) -> Option<ProcessInfo> {
+    linux: Option<&crate::model::LinuxProcessStreams>,
+) -> Option<ProcessInfo>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
     let mut info = ProcessInfo {
         pid: None,
+        ppid: None,
+        uid: None,
+        command_line: crate::ingest::minidump_linux::command_line(dump),
+        environment: crate::ingest::minidump_linux::environment(dump),
         create_time_unix: None,
         integrity_level: None,
         execute_flags: None,
@@ -306,8 +572,24 @@ fn extract_process_info(
         info.protected_process = misc.raw.protected_process().copied();
     }
 
+    if let Some(linux) = linux {
+        let number = |name: &str| {
+            linux
+                .status_field(name)
+                .and_then(|v| v.split_whitespace().next())
+                .and_then(|v| v.parse().ok())
+        };
+        info.pid = info.pid.or_else(|| number("Pid"));
+        info.ppid = number("PPid");
+        info.uid = number("Uid");
+        if info.main_image.is_none() {
+            info.main_image = linux.status_field("Name").map(str::to_string);
+        }
+    }
+
     if info.pid.is_none()
         && info.main_image.is_none()
+        && info.command_line.is_none()
         && info.integrity_level.is_none()
         && info.execute_flags.is_none()
         && info.protected_process.is_none()
@@ -318,42 +600,62 @@ fn extract_process_info(
     Some(info)
 }
 
-fn format_process_info(p: &ProcessInfo) -> String {
-    let mut lines: Vec<String> = Vec::new();
+fn process_attributes(p: &ProcessInfo) -> Vec<(String, AttrValue)> {
+    let mut attrs: Vec<(String, AttrValue)> = Vec::new();
+    let mut push = |key: &str, value: AttrValue| attrs.push((key.to_string(), value));
     if let Some(img) = &p.main_image {
-        lines.push(format!("Main image: {img}"));
+        push("main_image", img.as_str().into());
     }
     if let Some(v) = &p.main_image_version {
-        lines.push(format!("Main version: {v}"));
+        push("main_version", v.as_str().into());
     }
     if let Some(pid) = p.pid {
-        lines.push(format!("PID: {pid}"));
+        push("pid", pid.into());
     }
-    if let Some(t) = p.create_time_unix {
-        lines.push(format!("Create time (unix): {t}"));
-        if let Some(utc) = crate::util::time::unix_seconds_to_utc_string(t) {
-            lines.push(format!("Create time (utc): {utc}"));
-        }
+    if let Some(ppid) = p.ppid {
+        push("ppid", ppid.into());
+    }
+    if let Some(uid) = p.uid {
+        push("uid", uid.into());
+    }
+    if let Some(cl) = &p.command_line {
+        push(
+            "command_line",
+            crate::ingest::minidump_secrets::redact_secrets(cl).into(),
+        );
+    }
+    if !p.environment.is_empty() {
+        push("environment_variables", p.environment.len().into());
+    }
+    if let Some(ts) = p
+        .create_time_unix
+        .and_then(|t| time::OffsetDateTime::from_unix_timestamp(t as i64).ok())
+    {
+        push("create_time", ts.into());
     }
     if let Some(il) = p.integrity_level {
-        lines.push(format!("Integrity level: {il}"));
+        push("integrity_level", il.into());
     }
     if let Some(f) = p.execute_flags {
-        lines.push(format!("Execute flags: 0x{f:08X}"));
+        push("execute_flags", AttrValue::Addr(f.into()));
     }
     if let Some(pp) = p.protected_process {
-        lines.push(format!("Protected process: {pp}"));
-    }
-    if lines.is_empty() {
-        "<no process info>".into()
-    } else {
-        lines.join("\n")
+        push("protected_process", pp.into());
     }
+    attrs
 }
 
-fn format_exec_artifacts(arts: &[crate::model::ProcessExecArtifact], limit: usize) -> String {
+fn format_exec_artifacts(
+    arts: &[crate::model::ProcessExecArtifact],
+    coverage: Option<&crate::model::ScanCoverage>,
+    limit: usize,
+) -> String {
     let mut out = String::new();
-    out.push_str(&format!("Recovered: {}\n\n", arts.len()));
+    out.push_str(&format!("Recovered: {}\n", arts.len()));
+    if let Some(cov) = coverage {
+        out.push_str(&format!("Coverage: {}\n", cov.pretty()));
+    }
+    out.push('\n');
     for (i, a) in arts.iter().take(limit).enumerate() {
         out.push_str(&format!(
             "{:>3}. [{}] {}\n     {}\n",
@@ -365,6 +667,13 @@ fn format_exec_artifacts(arts: &[crate::model::ProcessExecArtifact], limit: usiz
             a.image,
             a.command_line
         ));
+        for child in a.descendants() {
+            out.push_str(&format!(
+                "     decoded ({}): {}\n",
+                child.decoded_via.map_or("-", |s| s.label()),
+                preview(&child.command_line, 160)
+            ));
+        }
     }
     if arts.len() > limit {
         out.push_str(&format!("... ({} more)\n", arts.len() - limit));
@@ -372,26 +681,86 @@ fn format_exec_artifacts(arts: &[crate::model::ProcessExecArtifact], limit: usiz
     out
 }
 
-fn format_stackwalk_summary(sw: &StackwalkReport) -> String {
-    let mut lines = Vec::new();
-    lines.push(format!("threads_walked={}", sw.threads.len()));
-    lines.push(format!("total_frames={}", sw.total_frames()));
-    lines.push(format!("symbolicated_frames={}", sw.symbolicated_frames));
-    lines.push(format!("modules_with_symbols={}", sw.modules_with_symbols));
+fn preview(s: &str, max_chars: usize) -> String {
+    let one_line = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    if one_line.chars().count() <= max_chars {
+        return one_line;
+    }
+    let mut out: String = one_line.chars().take(max_chars).collect();
+    out.push_str("...");
+    out
+}
+
+fn format_ioc_summary(iocs: &[crate::model::Indicator], per_kind: usize) -> String {
+    let mut out = format!("Distinct indicators: {}\n", iocs.len());
+    for kind in crate::model::IocKind::ALL {
+        let of_kind: Vec<_> = iocs.iter().filter(|i| i.kind == kind).collect();
+        if of_kind.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{} ({}):\n", kind.label(), of_kind.len()));
+        for ioc in of_kind.iter().take(per_kind) {
+            out.push_str(&format!("  {} (x{})\n", ioc.value, ioc.count));
+        }
+        if of_kind.len() > per_kind {
+            out.push_str(&format!("  ... ({} more)\n", of_kind.len() - per_kind));
+        }
+    }
+    out
+}
+
+fn format_sensitive_summary(
+    findings: &[crate::model::SensitiveFinding],
+    per_kind: usize,
+) -> String {
+    let mut out = format!(
+        "Likely secrets: {} (values masked; review before sharing this dump)\n",
+        findings.len()
+    );
+    for kind in crate::model::SecretKind::ALL {
+        let of_kind: Vec<_> = findings.iter().filter(|f| f.kind == kind).collect();
+        if of_kind.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{} ({}):\n", kind.label(), of_kind.len()));
+        for f in of_kind.iter().take(per_kind) {
+            out.push_str(&format!(
+                "  {} @ 0x{:016X} (x{})\n",
+                f.preview,
+                f.address(),
+                f.count()
+            ));
+        }
+        if of_kind.len() > per_kind {
+            out.push_str(&format!("  ... ({} more)\n", of_kind.len() - per_kind));
+        }
+    }
+    out
+}
+
+fn stackwalk_attributes(sw: &StackwalkReport) -> Vec<(String, AttrValue)> {
+    let mut attrs: Vec<(String, AttrValue)> = vec![
+        ("threads_walked".to_string(), sw.threads.len().into()),
+        ("total_frames".to_string(), sw.total_frames().into()),
+        (
+            "symbolicated_frames".to_string(),
+            sw.symbolicated_frames.into(),
+        ),
+        (
+            "modules_with_symbols".to_string(),
+            sw.modules_with_symbols.into(),
+        ),
+    ];
     if let Some(tid) = sw.requesting_thread_id {
-        lines.push(format!("requesting_thread=0x{tid:X}"));
+        attrs.push(("requesting_thread".to_string(), tid.into()));
     }
     if !sw.symbol_paths.is_empty() {
-        lines.push(format!("symbol_paths={}", sw.symbol_paths.join("; ")));
+        attrs.push(("symbol_paths".to_string(), sw.symbol_paths.clone().into()));
     }
     if !sw.notes.is_empty() {
-        lines.push(String::new());
-        lines.push("Notes:".to_string());
-        for n in &sw.notes {
-            lines.push(format!("- {n}"));
-        }
+        attrs.push(("notes".to_string(), sw.notes.clone().into()));
     }
-    lines.join("\n")
+    attrs
 }
 
 fn format_exception_stack_preview(stack: &ThreadStackTrace, limit: usize) -> String {
@@ -0,0 +1,86 @@
+use std::ops::Deref;
+
+use crate::model::{Annotation, AnnotationKind, AnnotationRule, CrashpadInfo, ModuleInfo};
+
+const ANNOTATION_RULES_ENV_VAR: &str = "LOG_ATLAS_ANNOTATION_RULES";
+
+/// Process-level simple annotations, then per-module simple, list and object annotations.
+pub(crate) fn extract_crashpad<'a, T>(
+    dump: &minidump::Minidump<'a, T>,
+    modules: &[ModuleInfo],
+) -> Option<CrashpadInfo>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let info = dump.get_stream::<minidump::MinidumpCrashpadInfo>().ok()?;
+    let id = |guid: &minidump::format::GUID| {
+        let s = guid.to_string();
+        if s.chars().all(|c| c == '0' || c == '-') {
+            String::new()
+        } else {
+            s
+        }
+    };
+
+    let mut annotations: Vec<Annotation> = info
+        .simple_annotations
+        .iter()
+        .map(|(k, v)| Annotation {
+            key: k.clone(),
+            value: v.clone(),
+            module: None,
+            kind: AnnotationKind::Simple,
+        })
+        .collect();
+    for m in &info.module_list {
+        let module = modules.get(m.module_index).map(|m| m.name.clone());
+        let annotation = |key: String, value: String, kind| Annotation {
+            key,
+            value,
+            module: module.clone(),
+            kind,
+        };
+        annotations.extend(
+            m.simple_annotations
+                .iter()
+                .map(|(k, v)| annotation(k.clone(), v.clone(), AnnotationKind::Simple)),
+        );
+        annotations.extend(
+            m.list_annotations
+                .iter()
+                .enumerate()
+                .map(|(i, v)| annotation(i.to_string(), v.clone(), AnnotationKind::List)),
+        );
+        annotations.extend(m.annotation_objects.iter().map(|(k, v)| {
+            let value = match v {
+                minidump::MinidumpAnnotation::String(s) => s.clone(),
+                minidump::MinidumpAnnotation::UserDefined(raw) => {
+                    format!("(user-defined type 0x{:04X})", raw.ty)
+                }
+                minidump::MinidumpAnnotation::Unsupported(raw) => {
+                    format!("(unsupported type 0x{:04X})", raw.ty)
+                }
+                _ => "(invalid)".into(),
+            };
+            annotation(k.clone(), value, AnnotationKind::Object)
+        }));
+    }
+
+    Some(CrashpadInfo {
+        report_id: id(&info.raw.report_id),
+        client_id: id(&info.raw.client_id),
+        annotations,
+    })
+}
+
+/// `LOG_ATLAS_ANNOTATION_RULES`: rules separated by `;` or newlines, e.g.
+/// `channel=beta; high:gpu-driver~nvidia`.
+pub(crate) fn annotation_rules_from_env() -> Vec<AnnotationRule> {
+    std::env::var(ANNOTATION_RULES_ENV_VAR)
+        .map(|v| {
+            v.split([';', '\n'])
+                .filter_map(AnnotationRule::parse)
+                .collect()
+        })
+        .unwrap_or_default()
+}
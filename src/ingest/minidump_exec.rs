@@ -1,135 +1,46 @@
-use crate::model::{ExecArtifactEncoding, ProcessExecArtifact};
-
-const MAX_SCAN_BYTES: usize = 32 * 1024 * 1024; // hard cap to keep UI responsive on full dumps
-const MAX_ARTIFACTS: usize = 200;
-
-pub fn extract_exec_artifacts(dump: &minidump::Minidump<Vec<u8>>) -> Vec<ProcessExecArtifact> {
-    // A minidump is a snapshot of a single process. It doesn't reliably contain
-    // "child process list" data. We therefore do a best-effort scan for strings
-    // that look like command-lines or LOLBin invocations.
-
-    let mut out: Vec<ProcessExecArtifact> = Vec::new();
-    let mut scanned = 0usize;
-
-    // Prefer Memory64 when present (full dumps), otherwise MemoryList.
-    if let Ok(mem64) = dump.get_stream::<minidump::MinidumpMemory64List>() {
-        for region in mem64.iter() {
-            if scanned >= MAX_SCAN_BYTES || out.len() >= MAX_ARTIFACTS {
-                break;
-            }
-            let take = (MAX_SCAN_BYTES - scanned).min(region.bytes.len());
-            scan_region(&region.bytes[..take], region.base_address, &mut out);
-            scanned += take;
-        }
-    } else if let Ok(mem) = dump.get_stream::<minidump::MinidumpMemoryList>() {
-        for region in mem.iter() {
-            if scanned >= MAX_SCAN_BYTES || out.len() >= MAX_ARTIFACTS {
-                break;
-            }
-            let take = (MAX_SCAN_BYTES - scanned).min(region.bytes.len());
-            scan_region(&region.bytes[..take], region.base_address, &mut out);
-            scanned += take;
-        }
-    }
-
-    dedup_artifacts(out)
-}
-
-fn scan_region(bytes: &[u8], base: u64, out: &mut Vec<ProcessExecArtifact>) {
-    if out.len() >= MAX_ARTIFACTS || bytes.is_empty() {
-        return;
-    }
-
-    scan_ascii(bytes, base, out);
-    if out.len() >= MAX_ARTIFACTS {
-        return;
-    }
-    scan_utf16le(bytes, base, out);
-}
-
-fn scan_ascii(bytes: &[u8], base: u64, out: &mut Vec<ProcessExecArtifact>) {
-    let mut i = 0usize;
-    while i < bytes.len() && out.len() < MAX_ARTIFACTS {
-        if !is_ascii_printable(bytes[i]) {
-            i += 1;
-            continue;
-        }
-        let start = i;
-        while i < bytes.len() && is_ascii_printable(bytes[i]) {
-            i += 1;
-        }
-        let s = &bytes[start..i];
-        if s.len() >= 12 && s.len() <= 800 {
-            if let Ok(text) = std::str::from_utf8(s) {
-                let text = text.trim();
-                if is_likely_command_line(text) {
-                    out.push(ProcessExecArtifact {
-                        image: parse_image(text),
-                        command_line: text.to_string(),
-                        encoding: ExecArtifactEncoding::Ascii,
-                        address: Some(base + start as u64),
-                    });
-                }
-            }
-        }
-        // Skip separator bytes (often NUL)
-        while i < bytes.len() && !is_ascii_printable(bytes[i]) {
-            i += 1;
-        }
-    }
+use crate::model::ProcessExecArtifact;
+
+use crate::ingest::minidump_scan::{ScanBudget, ScanOutput, ScannedString, StringLimits};
+
+/// Strings the exec artifact scan looks at; the shortest plausible command line up to
+/// typical `-EncodedCommand` payloads (Windows allows up to 32767 chars).
+pub(crate) const LIMITS: StringLimits = StringLimits {
+    min_len: 12,
+    max_len: 8192,
+};
+
+/// The artifact a scanned string is, if it looks like a command line.
+///
+/// A minidump is a snapshot of a single process. It doesn't reliably contain "child process
+/// list" data. We therefore do a best-effort scan for strings that look like command-lines
+/// or LOLBin invocations.
+pub(crate) fn visit(s: &ScannedString<'_>) -> Option<ProcessExecArtifact> {
+    let text = s.text.trim();
+    is_likely_command_line(text).then(|| ProcessExecArtifact {
+        image: parse_image(text),
+        command_line: text.to_string(),
+        encoding: s.encoding,
+        address: Some(s.address),
+        decoded_via: None,
+        children: Vec::new(),
+    })
 }
 
-fn scan_utf16le(bytes: &[u8], base: u64, out: &mut Vec<ProcessExecArtifact>) {
-    // Look for (printable,0) (printable,0) ... sequences.
-    let mut i = 0usize;
-    while i + 1 < bytes.len() && out.len() < MAX_ARTIFACTS {
-        let b0 = bytes[i];
-        let b1 = bytes[i + 1];
-        if !(b1 == 0 && is_ascii_printable(b0)) {
-            i += 1;
-            continue;
-        }
-
-        let start = i;
-        let mut j = i;
-        let mut chars: Vec<u8> = Vec::new();
-        while j + 1 < bytes.len() {
-            let lo = bytes[j];
-            let hi = bytes[j + 1];
-            if hi != 0 || !is_ascii_printable(lo) {
-                break;
-            }
-            chars.push(lo);
-            if chars.len() > 800 {
-                break;
-            }
-            j += 2;
-        }
-
-        if chars.len() >= 12 && chars.len() <= 800 {
-            if let Ok(text) = std::str::from_utf8(&chars) {
-                let text = text.trim();
-                if is_likely_command_line(text) {
-                    out.push(ProcessExecArtifact {
-                        image: parse_image(text),
-                        command_line: text.to_string(),
-                        encoding: ExecArtifactEncoding::Utf16Le,
-                        address: Some(base + start as u64),
-                    });
-                }
-            }
-        }
-
-        i = j + 2;
+/// Deduplicate the scan's artifacts, apply the hit budget and decode their payloads.
+pub(crate) fn finish(
+    mut out: ScanOutput<ProcessExecArtifact>,
+    budget: &ScanBudget,
+) -> ScanOutput<ProcessExecArtifact> {
+    out.hits = dedup_artifacts(std::mem::take(&mut out.hits));
+    out.apply_hit_budget(budget);
+    for a in &mut out.hits {
+        a.children = crate::ingest::decode::decode_children(a);
     }
-}
-
-fn is_ascii_printable(b: u8) -> bool {
-    matches!(b, b'\t' | b' '..=b'~')
+    out
 }
 
 fn is_likely_command_line(s: &str) -> bool {
-    if s.len() < 12 || s.len() > 800 {
+    if !LIMITS.contains(s.len()) {
         return false;
     }
     let lc = s.to_ascii_lowercase();
@@ -190,14 +101,14 @@ fn parse_image(command_line: &str) -> String {
     }
 
     let mut img = String::new();
-    if let Some(rest) = s.strip_prefix('"') {
-        if let Some(end) = rest.find('"') {
-            img.push_str(&rest[..end]);
-            return normalize_image(img);
-        }
+    if let Some(rest) = s.strip_prefix('"')
+        && let Some(end) = rest.find('"')
+    {
+        img.push_str(&rest[..end]);
+        return normalize_image(img);
     }
 
-    let end = s.find(char::is_whitespace).unwrap_or_else(|| s.len());
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
     img.push_str(&s[..end]);
     normalize_image(img)
 }
@@ -223,7 +134,6 @@ fn dedup_artifacts(mut artifacts: Vec<ProcessExecArtifact>) -> Vec<ProcessExecAr
         seen.insert(key);
         true
     });
-    artifacts.truncate(MAX_ARTIFACTS);
     artifacts
 }
 
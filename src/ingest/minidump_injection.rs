@@ -1,19 +1,31 @@
 use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
 
-use crate::model::{InjectedRegion, ModuleInfo, ProcessExecArtifact, Severity, ThreadInfo};
+use crate::model::{
+    InjectedRegion, LinuxMapping, ModuleInfo, ProcessExecArtifact, Severity, ThreadInfo,
+};
 
-pub fn detect_injected_regions(
-    dump: &minidump::Minidump<Vec<u8>>,
+/// `linux_maps` (`/proc/self/maps` from a Linux dump) is used when the dump has no
+/// `MemoryInfoListStream`.
+pub fn detect_injected_regions<'a, T>(
+    dump: &minidump::Minidump<'a, T>,
     modules: &[ModuleInfo],
     threads: &[ThreadInfo],
     exec_artifacts: &[ProcessExecArtifact],
-) -> Vec<InjectedRegion> {
+    linux_maps: &[LinuxMapping],
+) -> Vec<InjectedRegion>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
     let module_ranges: Vec<(u64, u64)> = modules
         .iter()
         .map(|m| (m.base, m.base.saturating_add(m.size)))
         .collect();
 
     let Ok(meminfo) = dump.get_stream::<minidump::MinidumpMemoryInfoList>() else {
+        if !linux_maps.is_empty() {
+            return detect_in_linux_maps(linux_maps, &module_ranges, threads, exec_artifacts);
+        }
         // Fallback: we can still flag thread entrypoints that start outside any loaded module.
         let mut by_start: HashMap<u64, Vec<String>> = HashMap::new();
         for t in threads {
@@ -210,6 +222,90 @@ pub fn detect_injected_regions(
     out
 }
 
+/// Linux counterpart of the `MemoryInfoList` analysis: executable mappings that are anonymous,
+/// on the heap / stack, or backed by a memfd / deleted file, and not part of a loaded module.
+/// JIT runtimes legitimately create anonymous `r-x` code, so those stay `Warning` unless a
+/// thread starts there or the mapping is writable too.
+fn detect_in_linux_maps(
+    maps: &[LinuxMapping],
+    module_ranges: &[(u64, u64)],
+    threads: &[ThreadInfo],
+    exec_artifacts: &[ProcessExecArtifact],
+) -> Vec<InjectedRegion> {
+    let mut out: Vec<InjectedRegion> = Vec::new();
+    for m in maps {
+        if !m.is_executable() || m.is_kernel_provided() {
+            continue;
+        }
+        let (kind, reason, mut risk) = if m.is_fileless() {
+            (
+                "fileless",
+                format!("executable mapping of a memfd or deleted file: {}", m.path),
+                Severity::High,
+            )
+        } else if m.is_anonymous() {
+            (
+                "anonymous",
+                format!("anonymous {} mapping not backed by a file", m.perms),
+                Severity::Warning,
+            )
+        } else if m.path == "[heap]" || m.path.starts_with("[stack") {
+            (
+                "heap/stack",
+                format!("executable {}", m.path),
+                Severity::High,
+            )
+        } else {
+            continue;
+        };
+        if overlaps_any(module_ranges, m.start, m.end.saturating_sub(m.start)) {
+            continue;
+        }
+        let mut reasons = vec![reason];
+        if m.is_writable() {
+            risk = Severity::High;
+            reasons.push("RWX protection".into());
+        }
+        for t in threads {
+            if let Some(start) = t.start_address
+                && start >= m.start
+                && start < m.end
+            {
+                risk = Severity::High;
+                reasons.push(format!(
+                    "thread start in this mapping: tid={} start=0x{:016X}",
+                    t.thread_id, start
+                ));
+            }
+        }
+        if exec_artifacts
+            .iter()
+            .filter_map(|a| a.address)
+            .any(|addr| addr >= m.start && addr < m.end)
+        {
+            push_reason_once(
+                &mut reasons,
+                "recovered execution artifact string points into this mapping".into(),
+            );
+        }
+        out.push(InjectedRegion {
+            base: m.start,
+            size: m.end.saturating_sub(m.start),
+            protection: m.perms.clone(),
+            ty: kind.into(),
+            state: if m.path.is_empty() {
+                "mapped".into()
+            } else {
+                format!("mapped ({})", m.path)
+            },
+            reasons,
+            risk,
+        });
+    }
+    out.sort_by_key(|r| (risk_rank(r.risk), std::cmp::Reverse(r.size), r.base));
+    out
+}
+
 fn risk_rank(s: Severity) -> u8 {
     match s {
         Severity::High => 0,
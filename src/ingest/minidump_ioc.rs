@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+use std::net::Ipv6Addr;
+use std::ops::Deref;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::ingest::minidump_scan::{ScanBudget, ScanOutput, ScannedString, StringLimits};
+use crate::model::{ExecArtifactEncoding, Indicator, IocKind, ModuleInfo, ThreadInfo};
+
+/// Strings the IOC scan looks at.
+pub(crate) const LIMITS: StringLimits = StringLimits {
+    min_len: 6,
+    max_len: 4096,
+};
+
+/// Top-level domains we accept for bare domain names. Without a URL scheme around them,
+/// `name.ext` strings are overwhelmingly file names (`kernel32.dll`, `app.config`), so names
+/// under any other TLD are dropped.
+const DOMAIN_TLDS: &[&str] = &[
+    "com", "net", "org", "edu", "gov", "mil", "int", "io", "co", "us", "uk", "de", "ru", "cn",
+    "jp", "fr", "nl", "br", "in", "it", "es", "pl", "ir", "kr", "ua", "tk", "top", "xyz", "info",
+    "biz", "onion", "online", "site", "club", "live", "cloud", "app", "dev", "tech", "store",
+    "link", "shop", "me", "tv", "cc", "su", "ws", "pw", "to", "ly", "gg",
+];
+
+/// TLDs above that are also common file extensions (`command.com`, `build.pl`, `main.cc`,
+/// `Makefile.in`, `Safari.app`): a bare `name.tld` under them is a file name, so they count
+/// only after a URL scheme or with a subdomain (`www.example.com`).
+const EXTENSION_TLDS: &[&str] = &["com", "pl", "cc", "in", "app", "info", "ws"];
+
+static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(?:https?|ftp|wss?)://[^\s"'<>`{}|\\^]{3,}"#).expect("url regex")
+});
+static EMAIL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z0-9._%+-]{1,64}@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,24}\b")
+        .expect("email regex")
+});
+static DOMAIN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+([a-z]{2,10})\b")
+        .expect("domain regex")
+});
+static IPV4_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)(?:\.(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)){3}\b",
+    )
+    .expect("ipv4 regex")
+});
+static IPV6_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)[0-9a-f:]*:[0-9a-f:]*:[0-9a-f:]*").expect("ipv6 regex"));
+static FILE_PATH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)(?:\b[a-z]:\\|\\\\[a-z0-9_][a-z0-9_.$-]*\\)(?:[^\\/:*?"<>|\s]+\\)*[^\\/:*?"<>|\s]*"#,
+    )
+    .expect("file path regex")
+});
+static REGISTRY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)(?:\b(?:HKEY_LOCAL_MACHINE|HKEY_CURRENT_USER|HKEY_CLASSES_ROOT|HKEY_USERS|HKEY_CURRENT_CONFIG|HKLM|HKCU|HKCR|HKU)|\\REGISTRY\\(?:MACHINE|USER))\\[^\s"'<>]+"#,
+    )
+    .expect("registry regex")
+});
+static PIPE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)(?:\\\\[.?]\\pipe\\|\\Device\\NamedPipe\\)[^\s"'<>\\]+"#).expect("pipe regex")
+});
+static MUTEX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:Global|Local|Session\\\d+)\\[A-Za-z0-9_.{}\-]{4,}").expect("mutex regex")
+});
+
+/// One raw occurrence before deduplication.
+pub(crate) struct RawIoc {
+    kind: IocKind,
+    value: String,
+    address: u64,
+    encoding: ExecArtifactEncoding,
+}
+
+/// The indicators in a scanned string.
+pub(crate) fn visit(s: &ScannedString<'_>) -> Option<Vec<RawIoc>> {
+    let found = extract_from_text(s.text);
+    (!found.is_empty()).then(|| {
+        found
+            .into_iter()
+            .map(|(kind, offset, value)| RawIoc {
+                kind,
+                value,
+                address: s.address + char_offset_to_bytes(offset, s.encoding),
+                encoding: s.encoding,
+            })
+            .collect()
+    })
+}
+
+/// Deduplicate the scan's indicators, locate them and apply the hit budget.
+pub(crate) fn finish(
+    raw: ScanOutput<Vec<RawIoc>>,
+    budget: &ScanBudget,
+    locator: &Locator,
+) -> ScanOutput<Indicator> {
+    let mut out = ScanOutput {
+        hits: dedup_iocs(raw.hits.into_iter().flatten(), locator),
+        coverage: raw.coverage,
+    };
+    out.apply_hit_budget(budget);
+    out
+}
+
+fn char_offset_to_bytes(offset: usize, encoding: ExecArtifactEncoding) -> u64 {
+    match encoding {
+        ExecArtifactEncoding::Ascii => offset as u64,
+        ExecArtifactEncoding::Utf16Le => offset as u64 * 2,
+    }
+}
+
+/// Return `(kind, char offset, value)` for every indicator found in `text`.
+pub(crate) fn extract_from_text(text: &str) -> Vec<(IocKind, usize, String)> {
+    let mut out = Vec::new();
+    // Cheap pre-filter: every pattern needs at least one of these.
+    if !text
+        .bytes()
+        .any(|b| matches!(b, b'.' | b':' | b'\\' | b'@'))
+    {
+        return out;
+    }
+
+    for m in URL_RE.find_iter(text) {
+        let url = m.as_str().trim_end_matches(['.', ',', ';', ')', ']']);
+        out.push((IocKind::Url, m.start(), url.to_string()));
+    }
+    for m in EMAIL_RE.find_iter(text) {
+        out.push((IocKind::Email, m.start(), m.as_str().to_string()));
+    }
+    for caps in DOMAIN_RE.captures_iter(text) {
+        let m = caps.get(0).expect("whole match");
+        let tld = caps.get(1).map(|t| t.as_str().to_ascii_lowercase());
+        let Some(tld) = tld.filter(|t| DOMAIN_TLDS.contains(&t.as_str())) else {
+            continue;
+        };
+        if EXTENSION_TLDS.contains(&tld.as_str())
+            && m.as_str().matches('.').count() < 2
+            && !text[..m.start()].ends_with("://")
+        {
+            continue;
+        }
+        // Skip the host part of an email address; the email itself is the indicator.
+        if m.start() > 0 && text.as_bytes()[m.start() - 1] == b'@' {
+            continue;
+        }
+        out.push((IocKind::Domain, m.start(), m.as_str().to_ascii_lowercase()));
+    }
+    for m in IPV4_RE.find_iter(text) {
+        if is_interesting_ipv4(m.as_str()) && !is_dotted_version(text, m.start(), m.end()) {
+            out.push((IocKind::Ipv4, m.start(), m.as_str().to_string()));
+        }
+    }
+    if text.contains(':') {
+        for m in IPV6_RE.find_iter(text) {
+            if is_interesting_ipv6(m.as_str()) {
+                out.push((IocKind::Ipv6, m.start(), m.as_str().to_ascii_lowercase()));
+            }
+        }
+    }
+    if text.contains('\\') {
+        for m in FILE_PATH_RE.find_iter(text) {
+            let path = m.as_str().trim_end_matches(['.', ',', ';']);
+            if path.len() > 3 {
+                out.push((IocKind::FilePath, m.start(), path.to_string()));
+            }
+        }
+        for m in REGISTRY_RE.find_iter(text) {
+            out.push((IocKind::RegistryKey, m.start(), m.as_str().to_string()));
+        }
+        for m in PIPE_RE.find_iter(text) {
+            out.push((IocKind::NamedPipe, m.start(), m.as_str().to_string()));
+        }
+        for m in MUTEX_RE.find_iter(text) {
+            // `...\AppData\Local\Temp` is a path component, not an object namespace.
+            if m.start() > 0 && text.as_bytes()[m.start() - 1] == b'\\' {
+                continue;
+            }
+            out.push((IocKind::Mutex, m.start(), m.as_str().to_string()));
+        }
+    }
+    out
+}
+
+fn is_interesting_ipv4(s: &str) -> bool {
+    let octets: Vec<u8> = s.split('.').filter_map(|o| o.parse().ok()).collect();
+    if octets.len() != 4 {
+        return false;
+    }
+    // 0.x.x.x and broadcast/netmask-looking values are noise.
+    octets[0] != 0 && octets[0] != 255
+}
+
+/// `1.2.3.4` inside `1.2.3.4.5` or `v1.2.3.4` is a version string, not an address.
+fn is_dotted_version(text: &str, start: usize, end: usize) -> bool {
+    let bytes = text.as_bytes();
+    let before = start.checked_sub(1).map(|i| bytes[i]);
+    let after = bytes.get(end).copied();
+    matches!(before, Some(b'.' | b'v' | b'V'))
+        || (after == Some(b'.') && bytes.get(end + 1).is_some_and(u8::is_ascii_digit))
+}
+
+fn is_interesting_ipv6(s: &str) -> bool {
+    // Require some hex groups around the colons; bare "::" / "a::b" in C++ symbols is noise.
+    if s.len() < 6 || s.matches(':').count() < 2 {
+        return false;
+    }
+    let Ok(addr) = s.parse::<Ipv6Addr>() else {
+        return false;
+    };
+    !addr.is_unspecified() && !addr.is_loopback()
+}
+
+fn dedup_iocs(raw: impl Iterator<Item = RawIoc>, locator: &Locator) -> Vec<Indicator> {
+    let mut index: HashMap<(IocKind, String), usize> = HashMap::new();
+    let mut out: Vec<Indicator> = Vec::new();
+    for r in raw {
+        let key = (r.kind, r.value.to_ascii_lowercase());
+        if let Some(&i) = index.get(&key) {
+            out[i].count += 1;
+            continue;
+        }
+        index.insert(key, out.len());
+        out.push(Indicator {
+            kind: r.kind,
+            container: locator.describe(r.address),
+            value: r.value,
+            count: 1,
+            address: r.address,
+            encoding: r.encoding,
+        });
+    }
+    out.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(b.count.cmp(&a.count))
+            .then(a.address.cmp(&b.address))
+    });
+    out
+}
+
+/// Resolves an address to the module, thread stack or allocation that contains it.
+pub(crate) struct Locator {
+    modules: Vec<(u64, u64, String)>,
+    stacks: Vec<(u64, u64, u32)>,
+    /// `(base, end, allocation_base, type)` from `MemoryInfoListStream`, sorted by base.
+    regions: Vec<(u64, u64, u64, String)>,
+    /// `(base, end)` of captured memory ranges, sorted by base; used when memory info is absent.
+    captured: Vec<(u64, u64)>,
+}
+
+impl Locator {
+    pub(crate) fn new<'a, T>(
+        dump: &minidump::Minidump<'a, T>,
+        modules: &[ModuleInfo],
+        threads: &[ThreadInfo],
+    ) -> Self
+    where
+        T: Deref<Target = [u8]> + 'a,
+    {
+        let mut regions: Vec<(u64, u64, u64, String)> = dump
+            .get_stream::<minidump::MinidumpMemoryInfoList>()
+            .map(|mi| {
+                mi.iter()
+                    .map(|r| {
+                        (
+                            r.raw.base_address,
+                            r.raw.base_address.saturating_add(r.raw.region_size),
+                            r.raw.allocation_base,
+                            memory_type_label(r.ty),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        regions.sort_by_key(|r| r.0);
+
+        let mut captured: Vec<(u64, u64)> =
+            if let Ok(mem64) = dump.get_stream::<minidump::MinidumpMemory64List>() {
+                mem64
+                    .iter()
+                    .map(|r| (r.base_address, r.base_address.saturating_add(r.size)))
+                    .collect()
+            } else if let Ok(mem) = dump.get_stream::<minidump::MinidumpMemoryList>() {
+                mem.iter()
+                    .map(|r| (r.base_address, r.base_address.saturating_add(r.size)))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+        captured.sort_by_key(|r| r.0);
+
+        Self {
+            modules: modules
+                .iter()
+                .map(|m| {
+                    (
+                        m.base,
+                        m.base.saturating_add(m.size),
+                        short_module_name(&m.name),
+                    )
+                })
+                .collect(),
+            stacks: threads
+                .iter()
+                .map(|t| {
+                    (
+                        t.stack_start,
+                        t.stack_start.saturating_add(t.stack_size),
+                        t.thread_id,
+                    )
+                })
+                .collect(),
+            regions,
+            captured,
+        }
+    }
+
+    pub(crate) fn describe(&self, addr: u64) -> Option<String> {
+        if let Some((_, _, name)) = self
+            .modules
+            .iter()
+            .find(|(s, e, _)| addr >= *s && addr < *e)
+        {
+            return Some(name.clone());
+        }
+        if let Some((_, _, tid)) = self.stacks.iter().find(|(s, e, _)| addr >= *s && addr < *e) {
+            return Some(format!("stack tid=0x{tid:X}"));
+        }
+        if let Some(idx) = self.regions.partition_point(|r| r.0 <= addr).checked_sub(1) {
+            let (_, end, alloc_base, ty) = &self.regions[idx];
+            if addr < *end {
+                return Some(format!("{ty} alloc 0x{alloc_base:X}"));
+            }
+        }
+        let idx = self
+            .captured
+            .partition_point(|r| r.0 <= addr)
+            .checked_sub(1)?;
+        let (base, end) = self.captured[idx];
+        (addr < end).then(|| format!("region 0x{base:X}"))
+    }
+}
+
+fn memory_type_label(ty: minidump::format::MemoryType) -> String {
+    use minidump::format::MemoryType as MT;
+    if ty.contains(MT::MEM_IMAGE) {
+        "image".into()
+    } else if ty.contains(MT::MEM_MAPPED) {
+        "mapped".into()
+    } else if ty.contains(MT::MEM_PRIVATE) {
+        "private".into()
+    } else {
+        "unknown".into()
+    }
+}
+
+fn short_module_name(path: &str) -> String {
+    path.rsplit(['\\', '/']).next().unwrap_or(path).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn domains(text: &str) -> Vec<String> {
+        extract_from_text(text)
+            .into_iter()
+            .filter(|(kind, _, _)| *kind == IocKind::Domain)
+            .map(|(_, _, value)| value)
+            .collect()
+    }
+
+    #[test]
+    fn file_names_under_extension_tlds_are_not_domains() {
+        assert!(domains("COMMAND.COM /c dir").is_empty());
+        assert!(domains("C:\\Windows\\System32\\command.com").is_empty());
+        assert!(domains("perl build.pl --release").is_empty());
+        assert!(domains("src/main.cc:42").is_empty());
+        assert!(domains("kernel32.dll app.config").is_empty());
+    }
+
+    #[test]
+    fn domains_need_url_context_or_a_subdomain_under_extension_tlds() {
+        assert_eq!(domains("beacon to https://evil.com/x"), ["evil.com"]);
+        assert_eq!(domains("resolve www.Example.com now"), ["www.example.com"]);
+        assert_eq!(domains("c2=evil.ru"), ["evil.ru"]);
+        // The host of an email address is part of the email indicator.
+        assert!(domains("ops@example.com").is_empty());
+    }
+
+    #[test]
+    fn file_paths_keep_extension_tld_names() {
+        let found = extract_from_text("C:\\Windows\\command.com");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, IocKind::FilePath);
+        assert_eq!(found[0].2, "C:\\Windows\\command.com");
+    }
+}
@@ -0,0 +1,238 @@
+use std::ops::Deref;
+
+use minidump::format::MINIDUMP_STREAM_TYPE;
+
+use crate::model::{AuxvEntry, LinuxDso, LinuxDsoDebug, LinuxMapping, LinuxProcessStreams};
+
+/// `link_map` entries beyond this are ignored (a corrupt count should not allocate gigabytes).
+const MAX_DSOS: usize = 4096;
+
+/// Parse the `/proc`-derived streams. Returns `None` when the dump has none of them.
+pub(crate) fn extract_linux_streams<'a, T>(
+    dump: &minidump::Minidump<'a, T>,
+    file: &[u8],
+) -> Option<LinuxProcessStreams>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let mut out = LinuxProcessStreams::default();
+    let mut any = false;
+
+    if let Some(text) = raw_text(dump, MINIDUMP_STREAM_TYPE::LinuxCpuInfo) {
+        any = true;
+        let fields: Vec<(String, String)> = key_values(&text, ':').collect();
+        out.cpu_count = Some(fields.iter().filter(|(k, _)| k == "processor").count());
+        out.cpu_model = ["model name", "Hardware", "Processor", "cpu model"]
+            .iter()
+            .find_map(|name| fields.iter().find(|(k, _)| k == name))
+            .map(|(_, v)| v.clone());
+    }
+    if let Some(text) = raw_text(dump, MINIDUMP_STREAM_TYPE::LinuxProcStatus) {
+        any = true;
+        out.proc_status = key_values(&text, ':').collect();
+    }
+    if let Some(text) = raw_text(dump, MINIDUMP_STREAM_TYPE::LinuxMaps) {
+        any = true;
+        out.maps = text.lines().filter_map(parse_maps_line).collect();
+    }
+    let is_64 = is_64_bit(dump);
+    if let Some(raw) = raw_stream(dump, MINIDUMP_STREAM_TYPE::LinuxAuxv) {
+        any = true;
+        out.auxv = parse_auxv(raw, is_64);
+    }
+    if let Some(raw) = raw_stream(dump, MINIDUMP_STREAM_TYPE::LinuxDsoDebug) {
+        any = true;
+        out.dso_debug = parse_dso_debug(raw, file, is_64);
+    }
+    let has_process_streams = raw_stream(dump, MINIDUMP_STREAM_TYPE::LinuxCmdLine).is_some()
+        || raw_stream(dump, MINIDUMP_STREAM_TYPE::LinuxEnviron).is_some();
+
+    (any || has_process_streams).then_some(out)
+}
+
+/// `LinuxCmdLine`: NUL-separated argv, joined with spaces.
+pub(crate) fn command_line<'a, T>(dump: &minidump::Minidump<'a, T>) -> Option<String>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let raw = raw_stream(dump, MINIDUMP_STREAM_TYPE::LinuxCmdLine)?;
+    let args: Vec<String> = raw
+        .split(|&b| b == 0)
+        .filter(|a| !a.is_empty())
+        .map(|a| String::from_utf8_lossy(a).into_owned())
+        .collect();
+    (!args.is_empty()).then(|| args.join(" "))
+}
+
+/// `LinuxEnviron`: NUL-separated `NAME=value` entries.
+pub(crate) fn environment<'a, T>(dump: &minidump::Minidump<'a, T>) -> Vec<(String, String)>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    let Some(raw) = raw_stream(dump, MINIDUMP_STREAM_TYPE::LinuxEnviron) else {
+        return Vec::new();
+    };
+    raw.split(|&b| b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (k, v) = entry.split_once('=')?;
+            Some((k.to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+fn raw_stream<'a, T>(
+    dump: &'a minidump::Minidump<'a, T>,
+    ty: MINIDUMP_STREAM_TYPE,
+) -> Option<&'a [u8]>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    dump.get_raw_stream(ty as u32)
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
+fn raw_text<'a, T>(dump: &'a minidump::Minidump<'a, T>, ty: MINIDUMP_STREAM_TYPE) -> Option<String>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    raw_stream(dump, ty).map(|b| String::from_utf8_lossy(b).into_owned())
+}
+
+fn key_values(text: &str, sep: char) -> impl Iterator<Item = (String, String)> + '_ {
+    text.lines().filter_map(move |line| {
+        let (k, v) = line.split_once(sep)?;
+        Some((k.trim().to_string(), v.trim().to_string()))
+    })
+}
+
+/// `start-end perms offset dev inode [path]`
+fn parse_maps_line(line: &str) -> Option<LinuxMapping> {
+    let mut fields = line.splitn(6, ' ');
+    let (start, end) = fields.next()?.split_once('-')?;
+    let perms = fields.next()?.to_string();
+    let offset = u64::from_str_radix(fields.next()?, 16).ok()?;
+    let _dev = fields.next()?;
+    let inode = fields.next()?.parse().ok()?;
+    let path = fields.next().unwrap_or("").trim().to_string();
+    Some(LinuxMapping {
+        start: u64::from_str_radix(start, 16).ok()?,
+        end: u64::from_str_radix(end, 16).ok()?,
+        perms,
+        offset,
+        inode,
+        path,
+    })
+}
+
+fn is_64_bit<'a, T>(dump: &minidump::Minidump<'a, T>) -> bool
+where
+    T: Deref<Target = [u8]> + 'a,
+{
+    use minidump::system_info::Cpu;
+    match dump.get_stream::<minidump::MinidumpSystemInfo>() {
+        Ok(sys) => !matches!(sys.cpu, Cpu::X86 | Cpu::Arm | Cpu::Ppc | Cpu::Mips),
+        Err(_) => true,
+    }
+}
+
+fn parse_auxv(raw: &[u8], is_64: bool) -> Vec<AuxvEntry> {
+    let word = if is_64 { 8 } else { 4 };
+    raw.chunks_exact(word * 2)
+        .map(|pair| (read_word(pair, 0, is_64), read_word(pair, word, is_64)))
+        .take_while(|&(key, _)| key != 0) // AT_NULL
+        .map(|(key, value)| AuxvEntry {
+            key,
+            name: auxv_name(key).map(str::to_string),
+            value,
+        })
+        .collect()
+}
+
+fn auxv_name(key: u64) -> Option<&'static str> {
+    Some(match key {
+        3 => "AT_PHDR",
+        4 => "AT_PHENT",
+        5 => "AT_PHNUM",
+        6 => "AT_PAGESZ",
+        7 => "AT_BASE",
+        8 => "AT_FLAGS",
+        9 => "AT_ENTRY",
+        11 => "AT_UID",
+        12 => "AT_EUID",
+        13 => "AT_GID",
+        14 => "AT_EGID",
+        15 => "AT_PLATFORM",
+        16 => "AT_HWCAP",
+        17 => "AT_CLKTCK",
+        23 => "AT_SECURE",
+        24 => "AT_BASE_PLATFORM",
+        25 => "AT_RANDOM",
+        26 => "AT_HWCAP2",
+        31 => "AT_EXECFN",
+        32 => "AT_SYSINFO",
+        33 => "AT_SYSINFO_EHDR",
+        51 => "AT_MINSIGSTKSZ",
+        _ => return None,
+    })
+}
+
+/// `DSO_DEBUG_32/64` followed (at `map`) by `LINK_MAP_32/64` entries whose names are
+/// `MINIDUMP_STRING`s elsewhere in the file. The structs are packed.
+fn parse_dso_debug(raw: &[u8], file: &[u8], is_64: bool) -> Option<LinuxDsoDebug> {
+    let word = if is_64 { 8 } else { 4 };
+    if raw.len() < 12 + 3 * word {
+        return None;
+    }
+    let version = read_u32(raw, 0)?;
+    let map_rva = read_u32(raw, 4)? as usize;
+    let count = (read_u32(raw, 8)? as usize).min(MAX_DSOS);
+    let mut debug = LinuxDsoDebug {
+        version,
+        brk: read_word(raw, 12, is_64),
+        ldbase: read_word(raw, 12 + word, is_64),
+        dynamic: read_word(raw, 12 + 2 * word, is_64),
+        dsos: Vec::new(),
+    };
+
+    let entry_size = 2 * word + 4;
+    for i in 0..count {
+        let Some(entry) = file.get(map_rva + i * entry_size..map_rva + (i + 1) * entry_size) else {
+            break;
+        };
+        let name_rva = read_u32(entry, word)? as usize;
+        debug.dsos.push(LinuxDso {
+            addr: read_word(entry, 0, is_64),
+            ld: read_word(entry, word + 4, is_64),
+            name: read_minidump_string(file, name_rva).unwrap_or_default(),
+        });
+    }
+    Some(debug)
+}
+
+/// `MINIDUMP_STRING`: u32 byte length, then UTF-16LE.
+fn read_minidump_string(file: &[u8], rva: usize) -> Option<String> {
+    let len = read_u32(file, rva)? as usize;
+    let bytes = file.get(rva + 4..rva.checked_add(4 + len)?)?;
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+fn read_u32(buf: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(buf.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_word(buf: &[u8], at: usize, is_64: bool) -> u64 {
+    if is_64 {
+        buf.get(at..at + 8)
+            .and_then(|b| b.try_into().ok())
+            .map(u64::from_le_bytes)
+            .unwrap_or(0)
+    } else {
+        read_u32(buf, at).unwrap_or(0) as u64
+    }
+}
@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+use std::ops::Deref;
+
+use rayon::prelude::*;
+
+use crate::model::{ExecArtifactEncoding, ScanCoverage};
+
+const SCAN_BUDGET_ENV_VAR: &str = "LOG_ATLAS_SCAN_BUDGET_MB";
+const MAX_HITS_ENV_VAR: &str = "LOG_ATLAS_SCAN_MAX_HITS";
+
+/// Size of one unit of parallel work.
+const CHUNK_BYTES: usize = 4 * 1024 * 1024;
+/// Extra bytes read on each side of a chunk so strings straddling chunk or region
+/// boundaries are seen whole by exactly one chunk. Must cover the longest UTF-16LE string
+/// any caller asks for (`2 * max_len`).
+const OVERLAP_BYTES: usize = 32 * 1024;
+
+/// How much of the dump a scan may look at.
+///
+/// Defaults to unlimited; `LOG_ATLAS_SCAN_BUDGET_MB` / `LOG_ATLAS_SCAN_MAX_HITS` can cap it
+/// when a quick, partial look at a very large dump is preferred.
+#[derive(Clone, Debug, Default)]
+pub struct ScanBudget {
+    pub max_bytes: Option<u64>,
+    pub max_hits: Option<usize>,
+}
+
+impl ScanBudget {
+    pub fn from_env() -> Self {
+        let max_bytes = std::env::var(SCAN_BUDGET_ENV_VAR)
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(|mb| mb.saturating_mul(1024 * 1024));
+        let max_hits = std::env::var(MAX_HITS_ENV_VAR)
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok());
+        Self {
+            max_bytes,
+            max_hits,
+        }
+    }
+}
+
+/// Length bounds (in characters) for strings handed to a scan visitor.
+#[derive(Clone, Copy, Debug)]
+pub struct StringLimits {
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+impl StringLimits {
+    /// Bounds admitting every string either admits, for visitors sharing one scan.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            min_len: self.min_len.min(other.min_len),
+            max_len: self.max_len.max(other.max_len),
+        }
+    }
+
+    pub fn contains(&self, len: usize) -> bool {
+        (self.min_len..=self.max_len).contains(&len)
+    }
+}
+
+/// A printable string recovered from dump memory.
+pub struct ScannedString<'s> {
+    pub text: &'s str,
+    /// Virtual address of the first byte of the string.
+    pub address: u64,
+    pub encoding: ExecArtifactEncoding,
+}
+
+pub struct ScanOutput<R> {
+    pub hits: Vec<R>,
+    pub coverage: ScanCoverage,
+}
+
+/// A captured memory range, borrowed from the dump.
+#[derive(Clone, Copy)]
+struct Segment<'d> {
+    base: u64,
+    bytes: &'d [u8],
+}
+
+/// A unit of parallel work: `[start, end)` inside a run of address-contiguous segments.
+struct Chunk {
+    run: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Walk every captured memory region in parallel and hand each printable ASCII / UTF-16LE
+/// string to `visit`. Hits are returned sorted by address; the hit budget is left to the
+/// caller (see [`ScanOutput::apply_hit_budget`]).
+///
+/// Regions that are adjacent in the address space are stitched together, so a string that
+/// starts at the end of one region and continues into the next is reported once, whole.
+pub fn scan_strings<'a, T, R, F>(
+    dump: &minidump::Minidump<'a, T>,
+    budget: &ScanBudget,
+    limits: StringLimits,
+    visit: F,
+) -> ScanOutput<R>
+where
+    T: Deref<Target = [u8]> + 'a,
+    R: Send,
+    F: Fn(&ScannedString<'_>) -> Option<R> + Sync,
+{
+    let mut segments = collect_segments(dump);
+    segments.sort_by_key(|s| s.base);
+
+    let mut coverage = ScanCoverage {
+        regions_total: segments.len(),
+        bytes_total: segments.iter().map(|s| s.bytes.len() as u64).sum(),
+        budget_bytes: budget.max_bytes,
+        ..Default::default()
+    };
+
+    // Apply the byte budget in address order, trimming the last region that fits partially.
+    if let Some(max) = budget.max_bytes {
+        let mut remaining = max;
+        segments.retain_mut(|s| {
+            if remaining == 0 {
+                return false;
+            }
+            let take = (s.bytes.len() as u64).min(remaining) as usize;
+            s.bytes = &s.bytes[..take];
+            remaining -= take as u64;
+            true
+        });
+    }
+    coverage.regions_scanned = segments.len();
+    coverage.bytes_scanned = segments.iter().map(|s| s.bytes.len() as u64).sum();
+    coverage.bytes_skipped = coverage.bytes_total - coverage.bytes_scanned;
+
+    let runs = group_runs(&segments);
+    let chunks: Vec<Chunk> = runs
+        .iter()
+        .enumerate()
+        .flat_map(|(run_idx, run)| {
+            let len = run_len(run);
+            (0..len).step_by(CHUNK_BYTES).map(move |start| Chunk {
+                run: run_idx,
+                start,
+                end: (start + CHUNK_BYTES).min(len),
+            })
+        })
+        .collect();
+
+    let mut hits: Vec<(u64, R)> = chunks
+        .par_iter()
+        .flat_map_iter(|chunk| scan_chunk(&runs[chunk.run], chunk, limits, &visit))
+        .collect();
+    hits.sort_by_key(|(addr, _)| *addr);
+
+    ScanOutput {
+        hits: hits.into_iter().map(|(_, r)| r).collect(),
+        coverage,
+    }
+}
+
+/// Hand every printable ASCII / UTF-16LE string of `bytes` to `visit`, in offset order per
+/// encoding; `address` is `base` plus the offset into `bytes`. For data that is not captured
+/// memory, such as the rest of a dump file.
+pub fn for_each_string(
+    bytes: &[u8],
+    base: u64,
+    limits: StringLimits,
+    mut visit: impl FnMut(&ScannedString<'_>),
+) {
+    for_each_ascii(bytes, limits, |offset, _, text| {
+        visit(&ScannedString {
+            text,
+            address: base + offset as u64,
+            encoding: ExecArtifactEncoding::Ascii,
+        })
+    });
+    for_each_utf16le(bytes, limits, |offset, _, text| {
+        visit(&ScannedString {
+            text,
+            address: base + offset as u64,
+            encoding: ExecArtifactEncoding::Utf16Le,
+        })
+    });
+}
+
+impl<R> ScanOutput<R> {
+    /// Apply the hit budget. Callers dedup first so the cap counts distinct findings.
+    pub fn apply_hit_budget(&mut self, budget: &ScanBudget) {
+        if let Some(max) = budget.max_hits
+            && self.hits.len() > max
+        {
+            self.coverage.hits_truncated += self.hits.len() - max;
+            self.hits.truncate(max);
+        }
+    }
+}
+
+fn collect_segments<'d, T>(dump: &'d minidump::Minidump<'d, T>) -> Vec<Segment<'d>>
+where
+    T: Deref<Target = [u8]> + 'd,
+{
+    // Prefer Memory64 when present (full dumps), otherwise MemoryList.
+    if let Ok(mem64) = dump.get_stream::<minidump::MinidumpMemory64List>() {
+        mem64
+            .iter()
+            .map(|r| Segment {
+                base: r.base_address,
+                bytes: r.bytes,
+            })
+            .collect()
+    } else if let Ok(mem) = dump.get_stream::<minidump::MinidumpMemoryList>() {
+        mem.iter()
+            .map(|r| Segment {
+                base: r.base_address,
+                bytes: r.bytes,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+fn group_runs<'d>(segments: &[Segment<'d>]) -> Vec<Vec<Segment<'d>>> {
+    let mut runs: Vec<Vec<Segment<'d>>> = Vec::new();
+    for seg in segments.iter().filter(|s| !s.bytes.is_empty()) {
+        if let Some(run) = runs.last_mut()
+            && let Some(last) = run.last()
+            && last.base.saturating_add(last.bytes.len() as u64) == seg.base
+        {
+            run.push(*seg);
+            continue;
+        }
+        runs.push(vec![*seg]);
+    }
+    runs
+}
+
+fn run_len(run: &[Segment<'_>]) -> usize {
+    run.iter().map(|s| s.bytes.len()).sum()
+}
+
+/// Materialize `[start, end)` of a run; borrowed when it falls inside one segment.
+fn run_slice<'d>(run: &[Segment<'d>], start: usize, end: usize) -> Cow<'d, [u8]> {
+    let mut offset = 0usize;
+    for seg in run {
+        let seg_end = offset + seg.bytes.len();
+        if start >= offset && end <= seg_end {
+            return Cow::Borrowed(&seg.bytes[start - offset..end - offset]);
+        }
+        offset = seg_end;
+    }
+
+    let mut buf = Vec::with_capacity(end - start);
+    let mut offset = 0usize;
+    for seg in run {
+        let seg_end = offset + seg.bytes.len();
+        let lo = start.max(offset);
+        let hi = end.min(seg_end);
+        if lo < hi {
+            buf.extend_from_slice(&seg.bytes[lo - offset..hi - offset]);
+        }
+        offset = seg_end;
+    }
+    Cow::Owned(buf)
+}
+
+fn scan_chunk<R, F>(
+    run: &[Segment<'_>],
+    chunk: &Chunk,
+    limits: StringLimits,
+    visit: &F,
+) -> Vec<(u64, R)>
+where
+    F: Fn(&ScannedString<'_>) -> Option<R>,
+{
+    let len = run_len(run);
+    let win_start = chunk.start.saturating_sub(OVERLAP_BYTES);
+    let win_end = (chunk.end + OVERLAP_BYTES).min(len);
+    let window = run_slice(run, win_start, win_end);
+    let base = run[0].base + win_start as u64;
+    let open_left = win_start > 0;
+    let open_right = win_end < len;
+
+    let mut out = Vec::new();
+    let mut emit = |offset: usize, at_window_end: bool, text: &str, encoding| {
+        let abs = win_start + offset;
+        // Only the chunk that owns the first byte reports a string; strings cut off by the
+        // window edges are longer than the overlap and therefore above any sane `max_len`.
+        if abs < chunk.start || abs >= chunk.end {
+            return;
+        }
+        if (open_left && offset == 0) || (open_right && at_window_end) {
+            return;
+        }
+        let s = ScannedString {
+            text,
+            address: base + offset as u64,
+            encoding,
+        };
+        if let Some(r) = visit(&s) {
+            out.push((s.address, r));
+        }
+    };
+
+    for_each_ascii(&window, limits, |o, e, t| {
+        emit(o, e, t, ExecArtifactEncoding::Ascii)
+    });
+    for_each_utf16le(&window, limits, |o, e, t| {
+        emit(o, e, t, ExecArtifactEncoding::Utf16Le)
+    });
+    out
+}
+
+/// Calls `f(offset, reaches_end_of_buffer, text)` for every string within `limits`.
+fn for_each_ascii(bytes: &[u8], limits: StringLimits, mut f: impl FnMut(usize, bool, &str)) {
+    let mut i = 0usize;
+    while i < bytes.len() {
+        if !is_ascii_printable(bytes[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_ascii_printable(bytes[i]) {
+            i += 1;
+        }
+        let s = &bytes[start..i];
+        if s.len() >= limits.min_len
+            && s.len() <= limits.max_len
+            && let Ok(text) = std::str::from_utf8(s)
+        {
+            f(start, i == bytes.len(), text);
+        }
+    }
+}
+
+fn for_each_utf16le(bytes: &[u8], limits: StringLimits, mut f: impl FnMut(usize, bool, &str)) {
+    // Look for (printable,0) (printable,0) ... sequences.
+    let mut chars: Vec<u8> = Vec::new();
+    let mut i = 0usize;
+    while i + 1 < bytes.len() {
+        if !(bytes[i + 1] == 0 && is_ascii_printable(bytes[i])) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let mut j = i;
+        chars.clear();
+        while j + 1 < bytes.len() && bytes[j + 1] == 0 && is_ascii_printable(bytes[j]) {
+            chars.push(bytes[j]);
+            j += 2;
+        }
+
+        if chars.len() >= limits.min_len
+            && chars.len() <= limits.max_len
+            && let Ok(text) = std::str::from_utf8(&chars)
+        {
+            f(start, j + 1 >= bytes.len(), text);
+        }
+
+        i = j.max(i + 2);
+    }
+}
+
+fn is_ascii_printable(b: u8) -> bool {
+    matches!(b, b'\t' | b' '..=b'~')
+}
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::ops::Deref;
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
@@ -8,7 +9,10 @@ use crate::model::{StackFrameInfo, StackwalkReport, ThreadStackTrace};
 
 const SYMBOL_PATH_ENV_VARS: [&str; 2] = ["LOG_ATLAS_SYMBOL_PATH", "MINIDUMP_SYMBOL_PATH"];
 
-pub fn extract_stackwalk(dump: &minidump::Minidump<Vec<u8>>) -> Result<StackwalkReport> {
+pub fn extract_stackwalk<'a, T>(dump: &minidump::Minidump<'a, T>) -> Result<StackwalkReport>
+where
+    T: Deref<Target = [u8]> + 'a,
+{
     let symbol_paths = discover_symbol_paths();
     let supplier = breakpad_symbols::SimpleSymbolSupplier::new(symbol_paths.clone());
     let symbolizer = breakpad_symbols::Symbolizer::new(supplier);
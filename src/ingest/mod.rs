@@ -1,4 +1,113 @@
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+pub mod compressed;
+mod decode;
+pub mod elf_core;
+mod elf_core_minidump;
+pub mod evtx;
+pub mod journal;
+pub mod jsonl;
+mod linux_crash;
 pub mod minidump;
+mod minidump_crashpad;
 mod minidump_exec;
 mod minidump_injection;
+pub(crate) mod minidump_ioc;
+mod minidump_linux;
+pub(crate) mod minidump_scan;
+pub mod minidump_secrets;
 mod minidump_stackwalk;
+pub mod syslog;
+pub mod sysmon;
+pub mod wer;
+
+const MAX_TITLE_CHARS: usize = 160;
+
+/// Input formats `detect_format` can tell apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    Minidump,
+    ElfCore,
+    Jsonl,
+    Evtx,
+    Syslog,
+    Journal,
+    /// Windows Error Reporting folder or zip: `Report.wer` plus the dump it describes.
+    WerArchive,
+}
+
+/// Pick the ingester for `path` from its leading bytes, falling back to the extension.
+/// Folders are WER report folders.
+pub fn detect_format(path: &Path) -> Result<InputFormat> {
+    if path.is_dir() {
+        if wer::is_wer_input(path, &[]) {
+            return Ok(InputFormat::WerArchive);
+        }
+        anyhow::bail!("{} is a folder without a Report.wer", path.display());
+    }
+    let mut head = Vec::with_capacity(4096);
+    std::fs::File::open(path)
+        .with_context(|| format!("open {}", path.display()))?
+        .take(4096)
+        .read_to_end(&mut head)
+        .with_context(|| format!("read {}", path.display()))?;
+
+    if head.starts_with(b"MDMP") {
+        return Ok(InputFormat::Minidump);
+    }
+    if elf_core::is_elf_core(&head) {
+        return Ok(InputFormat::ElfCore);
+    }
+    if wer::is_wer_input(path, &head) {
+        return Ok(InputFormat::WerArchive);
+    }
+    if head.starts_with(b"ElfFile\0") {
+        return Ok(InputFormat::Evtx);
+    }
+    let text = String::from_utf8_lossy(&head);
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') {
+        return Ok(if text.contains("\"__REALTIME_TIMESTAMP\"") {
+            InputFormat::Journal
+        } else {
+            InputFormat::Jsonl
+        });
+    }
+    if trimmed.starts_with("__CURSOR=") || text.contains("\n__REALTIME_TIMESTAMP=") {
+        return Ok(InputFormat::Journal);
+    }
+    if trimmed
+        .lines()
+        .next()
+        .is_some_and(syslog::looks_like_syslog)
+    {
+        return Ok(InputFormat::Syslog);
+    }
+
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    Ok(match ext.as_str() {
+        "jsonl" | "ndjson" => InputFormat::Jsonl,
+        "evtx" => InputFormat::Evtx,
+        "log" | "syslog" => InputFormat::Syslog,
+        "journal" => InputFormat::Journal,
+        "core" => InputFormat::ElfCore,
+        _ => InputFormat::Minidump,
+    })
+}
+
+/// First line of a log message, shortened for the timeline.
+pub(crate) fn message_title(message: &str) -> String {
+    let first = message.lines().next().unwrap_or_default();
+    if first.chars().count() > MAX_TITLE_CHARS {
+        let cut: String = first.chars().take(MAX_TITLE_CHARS).collect();
+        format!("{cut}...")
+    } else {
+        first.to_string()
+    }
+}
@@ -0,0 +1,487 @@
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+use crate::model::{AttrValue, Event, EventId, EventStore, Severity};
+
+const SOURCE_NAME: &str = "ingest::syslog";
+
+/// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD]... MSG`
+static RFC5424_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^<(?P<pri>\d{1,3})>1 (?P<ts>\S+) (?P<host>\S+) (?P<app>\S+) (?P<pid>\S+) (?P<msgid>\S+) ?(?P<rest>.*)$")
+        .expect("rfc5424 regex")
+});
+
+/// `[<PRI>]Mmm dd hh:mm:ss HOST TAG[PID]: MSG`, also with an RFC 3339 timestamp as written by
+/// rsyslog's high-precision template.
+static RFC3164_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:<(?P<pri>\d{1,3})>)?(?:(?P<bsd>[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2}(?:\.\d+)?)|(?P<iso>\d{4}-\d{2}-\d{2}T\S+)) (?P<host>\S+) (?P<rest>.*)$",
+    )
+    .expect("rfc3164 regex")
+});
+
+/// `TAG[PID]: MSG` / `TAG: MSG`.
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<app>[^\s\[:]+)(?:\[(?P<pid>[^\]]*)\])?: ?(?P<msg>.*)$").expect("tag regex")
+});
+
+const FACILITIES: &[&str] = &[
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+pub struct IngestedSyslog {
+    pub events: EventStore,
+    pub lines: u64,
+    pub unparsed: u64,
+}
+
+/// Parse an RFC 3164 / RFC 5424 syslog text file. RFC 3164 timestamps have no year or zone:
+/// they are read as UTC in the year that puts them at or before the file's modification time.
+pub fn ingest(path: &Path) -> Result<IngestedSyslog> {
+    let file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let meta = file.metadata().ok();
+    let file_size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+    let reference = meta
+        .and_then(|m| m.modified().ok())
+        .map(OffsetDateTime::from)
+        .unwrap_or_else(OffsetDateTime::now_utc);
+    ingest_reader(BufReader::new(file), path, file_size, reference)
+}
+
+pub fn ingest_reader(
+    mut reader: impl BufRead,
+    path: &Path,
+    file_size: u64,
+    reference: OffsetDateTime,
+) -> Result<IngestedSyslog> {
+    let mut records: Vec<Event> = Vec::new();
+    let mut lines = 0u64;
+    let mut unparsed = 0u64;
+    let mut line: Vec<u8> = Vec::new();
+
+    loop {
+        line.clear();
+        let n = reader
+            .read_until(b'\n', &mut line)
+            .with_context(|| format!("read {} line {}", path.display(), lines + 1))?;
+        if n == 0 {
+            break;
+        }
+        lines += 1;
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\r', '\n']);
+        if text.trim().is_empty() {
+            continue;
+        }
+        match parse_line(text, reference) {
+            Some(ev) => records.push(ev),
+            None => {
+                unparsed += 1;
+                records.push(Event {
+                    id: EventId(0),
+                    t_ms: 0,
+                    timestamp: None,
+                    severity: Severity::Info,
+                    title: super::message_title(text),
+                    details: text.to_string(),
+                    source: SOURCE_NAME.into(),
+                    attributes: Vec::new(),
+                    links: Vec::new(),
+                });
+            }
+        }
+    }
+
+    super::linux_crash::extract_crashes(&mut records);
+    let start = crate::model::derive_t_ms(&mut records);
+    let crashes = records
+        .iter()
+        .filter(|e| e.attributes.iter().any(|(k, _)| k == "crash.kind"))
+        .count();
+
+    let mut events = EventStore::default();
+    events.push(Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp: start,
+        severity: if unparsed > 0 {
+            Severity::Warning
+        } else {
+            Severity::Info
+        },
+        title: "Syslog loaded".into(),
+        details: format!(
+            "Path: {}\nSize: {} bytes\nLines: {}\nEvents: {}\nUnparsed lines: {}\nCrash events: {}",
+            path.display(),
+            file_size,
+            lines,
+            records.len(),
+            unparsed,
+            crashes
+        ),
+        source: SOURCE_NAME.into(),
+        attributes: Vec::new(),
+        links: Vec::new(),
+    });
+    for ev in records {
+        events.push(ev);
+    }
+
+    Ok(IngestedSyslog {
+        events,
+        lines,
+        unparsed,
+    })
+}
+
+/// Whether `line` looks like one of the syslog formats we parse (used for format sniffing).
+pub fn looks_like_syslog(line: &str) -> bool {
+    RFC5424_RE.is_match(line) || RFC3164_RE.is_match(line)
+}
+
+fn parse_line(line: &str, reference: OffsetDateTime) -> Option<Event> {
+    let mut attributes = Vec::new();
+    let (pri, timestamp, host, app, pid, message) = if let Some(c) = RFC5424_RE.captures(line) {
+        let nil = |s: &str| (s != "-").then(|| s.to_string());
+        let (structured, message) = parse_structured_data(&c["rest"]);
+        if let Some(msgid) = nil(&c["msgid"]) {
+            attributes.push(("msgid".to_string(), msgid));
+        }
+        attributes.extend(structured);
+        (
+            c["pri"].parse::<u16>().ok(),
+            OffsetDateTime::parse(&c["ts"], &Rfc3339).ok(),
+            nil(&c["host"]),
+            nil(&c["app"]),
+            nil(&c["pid"]),
+            message.trim_start_matches('\u{feff}').to_string(),
+        )
+    } else if let Some(c) = RFC3164_RE.captures(line) {
+        let timestamp = match (c.name("bsd"), c.name("iso")) {
+            (Some(bsd), _) => parse_bsd_timestamp(bsd.as_str(), reference),
+            (_, Some(iso)) => OffsetDateTime::parse(iso.as_str(), &Rfc3339).ok(),
+            _ => None,
+        };
+        let rest = &c["rest"];
+        let (app, pid, message) = match TAG_RE.captures(rest) {
+            Some(t) => (
+                Some(t["app"].to_string()),
+                t.name("pid").map(|p| p.as_str().to_string()),
+                t["msg"].to_string(),
+            ),
+            None => (None, None, rest.to_string()),
+        };
+        (
+            c.name("pri").and_then(|p| p.as_str().parse::<u16>().ok()),
+            timestamp,
+            Some(c["host"].to_string()),
+            app,
+            pid,
+            message,
+        )
+    } else {
+        return None;
+    };
+
+    let mut head: Vec<(String, AttrValue)> = Vec::new();
+    if let Some(host) = &host {
+        head.push(("host".to_string(), host.clone().into()));
+    }
+    if let Some(app) = &app {
+        head.push(("app".to_string(), app.clone().into()));
+    }
+    if let Some(pid) = pid {
+        let value = pid
+            .parse::<u64>()
+            .map_or_else(|_| pid.into(), AttrValue::UInt);
+        head.push(("pid".to_string(), value));
+    }
+    let severity = match pri {
+        Some(pri) => {
+            let facility = (pri >> 3) as usize;
+            head.push((
+                "facility".to_string(),
+                FACILITIES
+                    .get(facility)
+                    .map(|f| f.to_string())
+                    .unwrap_or_else(|| facility.to_string())
+                    .into(),
+            ));
+            head.push(("priority".to_string(), u64::from(pri & 7).into()));
+            priority_severity((pri & 7) as u8)
+        }
+        None => Severity::Info,
+    };
+    head.extend(attributes.into_iter().map(|(k, v)| (k, v.into())));
+
+    Some(Event {
+        id: EventId(0),
+        t_ms: 0,
+        timestamp,
+        severity,
+        title: super::message_title(&message),
+        details: message,
+        source: app.unwrap_or_else(|| SOURCE_NAME.into()),
+        attributes: head,
+        links: Vec::new(),
+    })
+}
+
+/// Syslog priority (0 emerg .. 7 debug) to `Severity`.
+pub fn priority_severity(priority: u8) -> Severity {
+    match priority {
+        0..=3 => Severity::High,
+        4 => Severity::Warning,
+        _ => Severity::Info,
+    }
+}
+
+/// `Mmm dd hh:mm:ss[.frac]`, placed in the latest year that is not after `reference`
+/// (allowing a day of clock skew).
+fn parse_bsd_timestamp(s: &str, reference: OffsetDateTime) -> Option<OffsetDateTime> {
+    let mut parts = s.split_whitespace();
+    let month = match parts.next()? {
+        "Jan" => Month::January,
+        "Feb" => Month::February,
+        "Mar" => Month::March,
+        "Apr" => Month::April,
+        "May" => Month::May,
+        "Jun" => Month::June,
+        "Jul" => Month::July,
+        "Aug" => Month::August,
+        "Sep" => Month::September,
+        "Oct" => Month::October,
+        "Nov" => Month::November,
+        "Dec" => Month::December,
+        _ => return None,
+    };
+    let day: u8 = parts.next()?.parse().ok()?;
+    let clock = parts.next()?;
+    let (hms, frac) = clock.split_once('.').unwrap_or((clock, ""));
+    let mut hms = hms.split(':').map(|p| p.parse::<u8>().ok());
+    let (h, m, sec) = (hms.next()??, hms.next()??, hms.next()??);
+    let nanos = if frac.is_empty() {
+        0
+    } else {
+        let digits: String = frac.chars().chain(std::iter::repeat('0')).take(9).collect();
+        digits.parse().ok()?
+    };
+    let time = Time::from_hms_nano(h, m, sec, nanos).ok()?;
+
+    let at = |year: i32| {
+        Date::from_calendar_date(year, month, day)
+            .ok()
+            .map(|d| PrimitiveDateTime::new(d, time).assume_utc())
+    };
+    let year = reference.year();
+    match at(year) {
+        Some(ts) if ts <= reference + time::Duration::days(1) => Some(ts),
+        _ => at(year - 1),
+    }
+}
+
+/// Split RFC 5424 `STRUCTURED-DATA MSG` into `sd.<id>.<param>` attributes and the message.
+fn parse_structured_data(rest: &str) -> (Vec<(String, String)>, &str) {
+    let mut out = Vec::new();
+    if let Some(msg) = rest.strip_prefix('-') {
+        return (out, msg.strip_prefix(' ').unwrap_or(msg));
+    }
+    let bytes = rest.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() && bytes[i] == b'[' {
+        let Some(id_end) = rest[i + 1..].find([' ', ']']).map(|p| p + i + 1) else {
+            break;
+        };
+        let id = &rest[i + 1..id_end];
+        i = id_end;
+        loop {
+            while i < bytes.len() && bytes[i] == b' ' {
+                i += 1;
+            }
+            if i >= bytes.len() {
+                return (out, "");
+            }
+            if bytes[i] == b']' {
+                i += 1;
+                break;
+            }
+            let Some(eq) = rest[i..].find('=').map(|p| p + i) else {
+                return (out, &rest[i..]);
+            };
+            let name = &rest[i..eq];
+            i = eq + 1;
+            if bytes.get(i) != Some(&b'"') {
+                return (out, &rest[i..]);
+            }
+            i += 1;
+            let mut value = String::new();
+            while i < bytes.len() && bytes[i] != b'"' {
+                if bytes[i] == b'\\' && matches!(bytes.get(i + 1), Some(b'"' | b'\\' | b']')) {
+                    i += 1;
+                }
+                let ch = rest[i..].chars().next().unwrap_or_default();
+                value.push(ch);
+                i += ch.len_utf8();
+            }
+            i += 1;
+            out.push((format!("sd.{id}.{name}"), value));
+        }
+    }
+    let msg = rest.get(i..).unwrap_or("");
+    (out, msg.strip_prefix(' ').unwrap_or(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn ts(s: &str) -> OffsetDateTime {
+        OffsetDateTime::parse(s, &Rfc3339).unwrap()
+    }
+
+    fn attr<'a>(ev: &'a Event, key: &str) -> Option<&'a AttrValue> {
+        ev.attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn bsd_timestamps_roll_back_over_the_new_year() {
+        let reference = ts("2024-01-02T08:00:00Z");
+        assert_eq!(
+            parse_bsd_timestamp("Dec 31 23:59:59", reference),
+            Some(ts("2023-12-31T23:59:59Z"))
+        );
+        assert_eq!(
+            parse_bsd_timestamp("Jan  2 07:59:59.25", reference),
+            Some(ts("2024-01-02T07:59:59.25Z"))
+        );
+        // A day of clock skew past the reference stays in the reference year.
+        assert_eq!(
+            parse_bsd_timestamp("Jan  3 07:00:00", reference),
+            Some(ts("2024-01-03T07:00:00Z"))
+        );
+        assert_eq!(
+            parse_bsd_timestamp("Jan  4 00:00:00", reference),
+            Some(ts("2023-01-04T00:00:00Z"))
+        );
+        // Feb 29 only exists in the previous year here.
+        assert_eq!(
+            parse_bsd_timestamp("Feb 29 12:00:00", ts("2025-03-01T00:00:00Z")),
+            Some(ts("2024-02-29T12:00:00Z"))
+        );
+        assert_eq!(parse_bsd_timestamp("Foo  1 00:00:00", reference), None);
+        assert_eq!(parse_bsd_timestamp("Jan  1 25:00:00", reference), None);
+    }
+
+    #[test]
+    fn rfc3164_lines_map_tag_pid_and_priority() {
+        let ev = parse_line(
+            "<11>Mar  5 10:00:00 web01 sshd[4242]: error: kex failed",
+            ts("2024-06-01T00:00:00Z"),
+        )
+        .unwrap();
+        assert_eq!(ev.timestamp, Some(ts("2024-03-05T10:00:00Z")));
+        assert_eq!(ev.source, "sshd");
+        assert_eq!(ev.details, "error: kex failed");
+        assert_eq!(ev.severity, Severity::High);
+        assert_eq!(attr(&ev, "host"), Some(&AttrValue::from("web01")));
+        assert_eq!(attr(&ev, "pid"), Some(&AttrValue::UInt(4242)));
+        assert_eq!(attr(&ev, "facility"), Some(&AttrValue::from("user")));
+        assert_eq!(attr(&ev, "priority"), Some(&AttrValue::UInt(3)));
+    }
+
+    #[test]
+    fn rfc5424_structured_data_becomes_attributes() {
+        let ev = parse_line(
+            concat!(
+                r#"<164>1 2024-03-05T10:00:00.5+01:00 host app 77 ID47 "#,
+                r#"[exampleSDID@32473 iut="3" eventSource="App\"lic\]ation"][meta seq="1"] "#,
+                "\u{feff}BOM message"
+            ),
+            ts("2024-06-01T00:00:00Z"),
+        )
+        .unwrap();
+        assert_eq!(ev.timestamp, Some(ts("2024-03-05T09:00:00.5Z")));
+        assert_eq!(ev.severity, Severity::Warning);
+        assert_eq!(ev.details, "BOM message");
+        assert_eq!(attr(&ev, "facility"), Some(&AttrValue::from("local4")));
+        assert_eq!(attr(&ev, "msgid"), Some(&AttrValue::from("ID47")));
+        assert_eq!(
+            attr(&ev, "sd.exampleSDID@32473.iut"),
+            Some(&AttrValue::from("3"))
+        );
+        assert_eq!(
+            attr(&ev, "sd.exampleSDID@32473.eventSource"),
+            Some(&AttrValue::from("App\"lic]ation"))
+        );
+        assert_eq!(attr(&ev, "sd.meta.seq"), Some(&AttrValue::from("1")));
+    }
+
+    #[test]
+    fn rfc5424_nil_values_are_left_out() {
+        let ev = parse_line("<14>1 - - - - - - just text", ts("2024-06-01T00:00:00Z")).unwrap();
+        assert_eq!(ev.timestamp, None);
+        assert_eq!(ev.source, SOURCE_NAME);
+        assert_eq!(ev.details, "just text");
+        for key in ["host", "app", "pid", "msgid"] {
+            assert!(attr(&ev, key).is_none(), "{key}");
+        }
+        assert_eq!(
+            parse_structured_data("[id a=\"1\"]"),
+            (vec![("sd.id.a".into(), "1".into())], "")
+        );
+    }
+
+    #[test]
+    fn unparsed_lines_are_kept_and_counted() {
+        let input = "Jan  1 00:00:00 host kernel: boot\nnot a syslog line\n\n";
+        let ingested = ingest_reader(
+            Cursor::new(input),
+            Path::new("messages"),
+            input.len() as u64,
+            ts("2024-06-01T00:00:00Z"),
+        )
+        .unwrap();
+        assert_eq!(ingested.lines, 3);
+        assert_eq!(ingested.unparsed, 1);
+        let events: Vec<&Event> = ingested.events.iter().collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].title, "Syslog loaded");
+        assert_eq!(events[0].severity, Severity::Warning);
+        assert!(
+            events
+                .iter()
+                .any(|e| e.details == "not a syslog line" && e.source == SOURCE_NAME)
+        );
+    }
+}
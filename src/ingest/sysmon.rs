@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use crate::model::{
+    Event, EventStore, ProcessNode, ProcessTree, Severity, SysmonProcessRef, SysmonRecord,
+};
+
+/// `System/Provider/@Name` of Sysmon records.
+pub const PROVIDER: &str = "Microsoft-Windows-Sysmon";
+
+/// Normalize one Sysmon record's `EventData` into a typed record. Returns `None` for event IDs
+/// we do not model.
+pub fn parse_fields<V: std::fmt::Display>(
+    event_id: u32,
+    fields: &[(String, V)],
+) -> Option<SysmonRecord> {
+    // Sysmon is inconsistent about `Guid` vs `GUID` (ID 10 uses the latter).
+    let get = |name: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.to_string().trim().to_string())
+            .filter(|v| v != "-")
+            .unwrap_or_default()
+    };
+    let process = |prefix: &str| SysmonProcessRef {
+        guid: get(&format!("{prefix}ProcessGuid")),
+        pid: get(&format!("{prefix}ProcessId")).parse().ok(),
+        image: get(&format!("{prefix}Image")),
+    };
+
+    let record = match event_id {
+        1 => SysmonRecord::ProcessCreate {
+            process: process(""),
+            command_line: get("CommandLine"),
+            parent: process("Parent"),
+            parent_command_line: get("ParentCommandLine"),
+            user: get("User"),
+            hashes: parse_hashes(&get("Hashes")),
+        },
+        3 => SysmonRecord::NetworkConnect {
+            process: process(""),
+            protocol: get("Protocol"),
+            source: endpoint(&get("SourceIp"), &get("SourcePort")),
+            destination: endpoint(&get("DestinationIp"), &get("DestinationPort")),
+            destination_hostname: get("DestinationHostname"),
+        },
+        7 => SysmonRecord::ImageLoad {
+            process: process(""),
+            image_loaded: get("ImageLoaded"),
+            signed: get("Signed"),
+            hashes: parse_hashes(&get("Hashes")),
+        },
+        8 => SysmonRecord::CreateRemoteThread {
+            source: process("Source"),
+            target: process("Target"),
+            start_address: get("StartAddress"),
+            start_module: get("StartModule"),
+        },
+        10 => SysmonRecord::ProcessAccess {
+            source: process("Source"),
+            target: process("Target"),
+            granted_access: get("GrantedAccess"),
+        },
+        11 => SysmonRecord::FileCreate {
+            process: process(""),
+            target_filename: get("TargetFilename"),
+        },
+        _ => return None,
+    };
+    Some(record)
+}
+
+/// Typed record for a timeline event, if it came from Sysmon (EVTX or an exported log with
+/// the same attribute names).
+pub fn record_from_event(ev: &Event) -> Option<SysmonRecord> {
+    if ev.attr("provider")?.as_str()? != PROVIDER {
+        return None;
+    }
+    let event_id = ev.attr("event_id")?.as_u64()?.try_into().ok()?;
+    parse_fields(event_id, &ev.attributes)
+}
+
+/// Severity floor for Sysmon events that are interesting on their own.
+pub fn record_severity(record: &SysmonRecord) -> Option<Severity> {
+    match record {
+        SysmonRecord::CreateRemoteThread { .. } => Some(Severity::Warning),
+        _ => None,
+    }
+}
+
+/// Rebuild the parent/child process tree from the Sysmon events in `events`. Returns `None`
+/// when there are none.
+pub fn build_tree(events: &EventStore) -> Option<ProcessTree> {
+    let mut builder = TreeBuilder::default();
+    for ev in events.iter() {
+        if let Some(record) = record_from_event(ev) {
+            builder.add(record, ev.timestamp.map(|t| t.unix_timestamp()));
+        }
+    }
+    if builder.tree.records.is_empty() {
+        return None;
+    }
+    Some(builder.finish())
+}
+
+#[derive(Default)]
+struct TreeBuilder {
+    tree: ProcessTree,
+    by_key: HashMap<String, usize>,
+}
+
+impl TreeBuilder {
+    fn add(&mut self, record: SysmonRecord, time_unix: Option<i64>) {
+        let record_idx = self.tree.records.len();
+        match &record {
+            SysmonRecord::ProcessCreate {
+                process,
+                command_line,
+                parent,
+                parent_command_line,
+                user,
+                hashes,
+            } => {
+                if let Some(child) = self.node(process) {
+                    let node = &mut self.tree.nodes[child];
+                    node.observed = true;
+                    node.command_line = command_line.clone();
+                    node.user = user.clone();
+                    node.hashes = hashes.clone();
+                    node.start_unix = time_unix;
+
+                    // A GUID is created once; keep the first parent if a record repeats.
+                    // Records that would make a process its own ancestor are not linked, so
+                    // every node stays reachable from a root.
+                    if self.tree.nodes[child].parent.is_none()
+                        && let Some(parent_idx) = self.node(parent)
+                        && !self.tree.ancestry(parent_idx).contains(&child)
+                    {
+                        let p = &mut self.tree.nodes[parent_idx];
+                        if p.command_line.is_empty() {
+                            p.command_line = parent_command_line.clone();
+                        }
+                        if !p.children.contains(&child) {
+                            p.children.push(child);
+                        }
+                        self.tree.nodes[child].parent = Some(parent_idx);
+                    }
+                }
+            }
+            SysmonRecord::CreateRemoteThread { source, target, .. }
+            | SysmonRecord::ProcessAccess { source, target, .. } => {
+                // Cross-process activity shows up on both ends.
+                for p in [source, target] {
+                    if let Some(idx) = self.node(p) {
+                        self.tree.nodes[idx].activity.push(record_idx);
+                    }
+                }
+            }
+            other => {
+                if let Some(idx) = self.node(other.process()) {
+                    self.tree.nodes[idx].activity.push(record_idx);
+                }
+            }
+        }
+        self.tree.records.push(record);
+    }
+
+    /// Node for a process reference, created (as not yet observed) on first sight.
+    fn node(&mut self, p: &SysmonProcessRef) -> Option<usize> {
+        let key = if !p.guid.is_empty() {
+            p.guid.to_ascii_lowercase()
+        } else {
+            format!("pid:{}", p.pid?)
+        };
+        if let Some(&idx) = self.by_key.get(&key) {
+            let node = &mut self.tree.nodes[idx];
+            if node.image.is_empty() {
+                node.image = p.image.clone();
+            }
+            return Some(idx);
+        }
+        let idx = self.tree.nodes.len();
+        self.tree.nodes.push(ProcessNode {
+            guid: p.guid.clone(),
+            pid: p.pid,
+            image: p.image.clone(),
+            command_line: String::new(),
+            user: String::new(),
+            hashes: Vec::new(),
+            start_unix: None,
+            observed: false,
+            parent: None,
+            children: Vec::new(),
+            activity: Vec::new(),
+        });
+        self.by_key.insert(key, idx);
+        Some(idx)
+    }
+
+    fn finish(mut self) -> ProcessTree {
+        let starts: Vec<i64> = self
+            .tree
+            .nodes
+            .iter()
+            .map(|n| n.start_unix.unwrap_or(i64::MIN))
+            .collect();
+        for node in &mut self.tree.nodes {
+            node.children.sort_by_key(|&c| starts[c]);
+        }
+        let mut roots: Vec<usize> = (0..self.tree.nodes.len())
+            .filter(|&i| self.tree.nodes[i].parent.is_none())
+            .collect();
+        roots.sort_by_key(|&r| starts[r]);
+        self.tree.roots = roots;
+        self.tree
+    }
+}
+
+/// `SHA256=AB..,MD5=CD..` -> `[("SHA256", "AB.."), ("MD5", "CD..")]`.
+fn parse_hashes(s: &str) -> Vec<(String, String)> {
+    s.split(',')
+        .filter_map(|part| {
+            let (alg, value) = part.split_once('=')?;
+            Some((alg.trim().to_string(), value.trim().to_string()))
+        })
+        .filter(|(_, v)| !v.is_empty())
+        .collect()
+}
+
+fn endpoint(ip: &str, port: &str) -> String {
+    match (ip.is_empty(), port.is_empty()) {
+        (true, _) => String::new(),
+        (false, true) => ip.to_string(),
+        // IPv6 literals need brackets to stay unambiguous with a port.
+        (false, false) if ip.contains(':') => format!("[{ip}]:{port}"),
+        (false, false) => format!("{ip}:{port}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(guid: &str, pid: u32, parent_guid: &str, parent_pid: u32) -> SysmonRecord {
+        let fields = [
+            ("ProcessGuid", guid.to_string()),
+            ("ProcessId", pid.to_string()),
+            ("Image", format!("C:\\{guid}.exe")),
+            ("CommandLine", format!("{guid}.exe")),
+            ("ParentProcessGuid", parent_guid.to_string()),
+            ("ParentProcessId", parent_pid.to_string()),
+            ("ParentImage", format!("C:\\{parent_guid}.exe")),
+        ]
+        .map(|(k, v)| (k.to_string(), v));
+        parse_fields(1, &fields).unwrap()
+    }
+
+    fn build(records: Vec<(SysmonRecord, i64)>) -> ProcessTree {
+        let mut builder = TreeBuilder::default();
+        for (record, start) in records {
+            builder.add(record, Some(start));
+        }
+        builder.finish()
+    }
+
+    fn by_guid(tree: &ProcessTree, guid: &str) -> usize {
+        tree.nodes.iter().position(|n| n.guid == guid).unwrap()
+    }
+
+    #[test]
+    fn reused_pids_stay_separate_processes() {
+        let tree = build(vec![
+            (create("{A}", 100, "{P}", 4), 10),
+            (create("{C1}", 200, "{A}", 100), 20),
+            (create("{B}", 100, "{P}", 4), 50),
+            (create("{C2}", 300, "{B}", 100), 60),
+        ]);
+        let (a, b) = (by_guid(&tree, "{A}"), by_guid(&tree, "{B}"));
+        assert_ne!(a, b);
+        assert_eq!(tree.nodes[by_guid(&tree, "{C1}")].parent, Some(a));
+        assert_eq!(tree.nodes[by_guid(&tree, "{C2}")].parent, Some(b));
+
+        // The latest process started by the given time, allowing two seconds of skew.
+        assert_eq!(tree.find_pid(100, Some(30)), Some(a));
+        assert_eq!(tree.find_pid(100, Some(49)), Some(b));
+        assert_eq!(tree.find_pid(100, Some(5)), Some(b));
+        assert_eq!(tree.find_pid(100, None), Some(b));
+        assert_eq!(tree.find_pid(999, None), None);
+    }
+
+    #[test]
+    fn repeated_create_keeps_the_first_parent() {
+        let tree = build(vec![
+            (create("{A}", 100, "{P}", 4), 10),
+            (create("{A}", 100, "{Q}", 8), 11),
+        ]);
+        let a = by_guid(&tree, "{A}");
+        assert_eq!(tree.nodes[a].parent, Some(by_guid(&tree, "{P}")));
+        assert!(tree.nodes.iter().all(|n| n.guid != "{Q}"));
+    }
+
+    #[test]
+    fn parent_cycles_are_not_linked() {
+        let tree = build(vec![
+            (create("{A}", 100, "{B}", 200), 10),
+            (create("{B}", 200, "{C}", 300), 20),
+            (create("{C}", 300, "{A}", 100), 30),
+            (create("{D}", 400, "{D}", 400), 40),
+        ]);
+        let (a, b, c, d) = (
+            by_guid(&tree, "{A}"),
+            by_guid(&tree, "{B}"),
+            by_guid(&tree, "{C}"),
+            by_guid(&tree, "{D}"),
+        );
+        assert_eq!(tree.nodes[a].parent, Some(b));
+        assert_eq!(tree.nodes[b].parent, Some(c));
+        assert_eq!(tree.nodes[c].parent, None);
+        assert_eq!(tree.nodes[d].parent, None);
+        assert!(tree.nodes[d].children.is_empty());
+        assert_eq!(tree.roots, vec![c, d]);
+        assert_eq!(tree.ancestry(a), vec![a, b, c]);
+        assert_eq!(tree.descendants(c), vec![b, a]);
+    }
+}
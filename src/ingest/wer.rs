@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::ingest::minidump::IngestedMinidump;
+use crate::model::{AttrValue, Event, EventId, EventStore, MinidumpSummary, Severity, WerReport};
+
+const REPORT_FILE: &str = "Report.wer";
+/// Dump extensions in order of preference: heap dumps carry the most memory.
+const DUMP_EXTENSIONS: [&str; 3] = ["hdmp", "mdmp", "dmp"];
+
+/// Whether `path` looks like a WER report: a folder holding `Report.wer`, a zip archive, or
+/// the `Report.wer` file itself.
+pub fn is_wer_input(path: &Path, head: &[u8]) -> bool {
+    if path.is_dir() {
+        return path.join(REPORT_FILE).is_file();
+    }
+    head.starts_with(b"PK\x03\x04")
+        || path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case(REPORT_FILE))
+}
+
+/// Ingest a WER report folder or zip archive as one case: `Report.wer` is attached to the
+/// report of the dump stored next to it, and its bucketing parameters are checked against the
+/// dump's own crash signature.
+pub fn ingest(path: &Path) -> Result<IngestedMinidump> {
+    let archive = if path.is_dir() {
+        Archive::read_dir(path)?
+    } else if path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case(REPORT_FILE))
+    {
+        Archive::read_dir(path.parent().unwrap_or(Path::new(".")))?
+    } else {
+        Archive::read_zip(path)?
+    };
+
+    let report_bytes = archive
+        .report
+        .as_ref()
+        .with_context(|| format!("no {REPORT_FILE} in {}", path.display()))?;
+    let mut wer = parse_report_wer(report_bytes);
+    wer.files = archive.files.clone();
+
+    let mut ingested = match &archive.dump {
+        Some(DumpSource::File(file)) => crate::ingest::minidump::ingest(file)?,
+        Some(DumpSource::Bytes(name, bytes)) => {
+            let dump = minidump::Minidump::read(&bytes[..])
+                .with_context(|| format!("parse minidump {name}"))?;
+            let shown = PathBuf::from(format!("{}!{name}", path.display()));
+            crate::ingest::minidump::ingest_dump(&dump, bytes, &shown)?
+        }
+        None => IngestedMinidump {
+            summary: MinidumpSummary::default(),
+            report: Default::default(),
+            events: EventStore::default(),
+        },
+    };
+    ingested.report.wer = Some(wer);
+
+    let report = &ingested.report;
+    let wer = report.wer.as_ref().expect("WER report attached above");
+    let mut events: Vec<Event> = ingested.events.iter().cloned().collect();
+    // Right after "Minidump summary" when a dump was loaded.
+    let at = events.len().min(2);
+    let t_ms = events.get(at.saturating_sub(1)).map_or(0, |e| e.t_ms);
+    events.insert(at, wer_event(wer, path, archive.dump.as_ref(), t_ms));
+    if let Some(det) = report.wer_mismatch() {
+        let t_ms = events.iter().map(|e| e.t_ms).max().unwrap_or(0) + 5;
+        let mut attributes = vec![
+            ("detection_id".into(), det.id().into()),
+            ("rule".into(), det.rule.into()),
+        ];
+        if !det.techniques.is_empty() {
+            attributes.push(("technique".into(), det.techniques.into()));
+        }
+        events.push(Event {
+            id: EventId(0),
+            t_ms,
+            severity: det.severity,
+            title: format!("Detection: {}", det.title),
+            details: det.details,
+            source: "detector::basic".into(),
+            timestamp: None,
+            attributes,
+            links: det.links,
+        });
+    }
+    // Ids are reassigned in order so the inserted event cannot collide with a later one.
+    for e in &mut events {
+        e.id = EventId(0);
+    }
+    ingested.events = EventStore::from_events(events);
+    Ok(ingested)
+}
+
+enum DumpSource {
+    File(PathBuf),
+    /// Entry name and contents of a dump inside a zip archive.
+    Bytes(String, Vec<u8>),
+}
+
+struct Archive {
+    report: Option<Vec<u8>>,
+    dump: Option<DumpSource>,
+    files: Vec<String>,
+}
+
+impl Archive {
+    fn read_dir(dir: &Path) -> Result<Self> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .with_context(|| format!("read {}", dir.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_file())
+            .collect();
+        files.sort();
+        let report = files
+            .iter()
+            .find(|p| file_name(p).eq_ignore_ascii_case(REPORT_FILE))
+            .map(|p| std::fs::read(p).with_context(|| format!("read {}", p.display())))
+            .transpose()?;
+        let dump = pick_dump(files.iter().map(|p| file_name(p)))
+            .and_then(|name| files.iter().find(|p| file_name(p) == name))
+            .map(|p| DumpSource::File(p.clone()));
+        Ok(Self {
+            report,
+            dump,
+            files: files.iter().map(|p| file_name(p).to_string()).collect(),
+        })
+    }
+
+    fn read_zip(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+        let mut zip = zip::ZipArchive::new(file).context("read zip archive")?;
+        let names: Vec<String> = zip
+            .file_names()
+            .filter(|n| !n.ends_with('/'))
+            .map(str::to_string)
+            .collect();
+
+        let mut read = |name: &str| -> Result<Vec<u8>> {
+            let mut entry = zip
+                .by_name(name)
+                .with_context(|| format!("open {name} in archive"))?;
+            let mut buf = Vec::with_capacity(entry.size() as usize);
+            entry
+                .read_to_end(&mut buf)
+                .with_context(|| format!("extract {name}"))?;
+            Ok(buf)
+        };
+        let report = names
+            .iter()
+            .find(|n| file_name(Path::new(n)).eq_ignore_ascii_case(REPORT_FILE))
+            .map(|n| read(n))
+            .transpose()?;
+        let dump = match pick_dump(names.iter().map(String::as_str)) {
+            Some(name) => Some(DumpSource::Bytes(name.to_string(), read(name)?)),
+            None => None,
+        };
+        Ok(Self {
+            report,
+            dump,
+            files: names,
+        })
+    }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+fn pick_dump<'a>(names: impl Iterator<Item = &'a str> + Clone) -> Option<&'a str> {
+    DUMP_EXTENSIONS.iter().find_map(|ext| {
+        names.clone().find(|n| {
+            Path::new(n)
+                .extension()
+                .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(ext))
+        })
+    })
+}
+
+/// Parse `Report.wer`: `key=value` lines, usually UTF-16LE with a BOM.
+pub fn parse_report_wer(bytes: &[u8]) -> WerReport {
+    let text = if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes)).into_owned()
+    };
+
+    let mut wer = WerReport::default();
+    let mut sig: BTreeMap<usize, (String, String)> = BTreeMap::new();
+    let mut dynamic: BTreeMap<usize, (String, String)> = BTreeMap::new();
+    let mut modules: BTreeMap<usize, String> = BTreeMap::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if let Some((table, index, member)) = indexed_key(key) {
+            let slot = |map: &mut BTreeMap<usize, (String, String)>| {
+                let entry = map.entry(index).or_default();
+                match member {
+                    "Name" => entry.0 = value.to_string(),
+                    "Value" => entry.1 = value.to_string(),
+                    _ => {}
+                }
+            };
+            match table {
+                "Sig" => slot(&mut sig),
+                "DynamicSig" => slot(&mut dynamic),
+                "LoadedModule" => {
+                    modules.insert(index, value.to_string());
+                }
+                _ => {}
+            }
+        }
+        wer.fields.push((key.to_string(), value.to_string()));
+    }
+    wer.signature = sig.into_values().collect();
+    wer.dynamic_signature = dynamic.into_values().collect();
+    wer.loaded_modules = modules.into_values().collect();
+
+    let field = |k: &str| wer.field(k).filter(|v| !v.is_empty()).map(str::to_string);
+    let event_type = field("EventType");
+    let app_name = field("AppName").or_else(|| field("FriendlyEventName"));
+    let app_path = field("AppPath");
+    let report_id = field("ReportIdentifier");
+    let event_time_unix = field("EventTime")
+        .and_then(|t| t.parse().ok())
+        .and_then(crate::util::time::filetime_to_unix_seconds);
+    let bucket = field("Response.BucketId")
+        .or_else(|| field("Response.LegacyBucketId"))
+        .or_else(|| field("FaultBucket"));
+    let bucket_table = field("Response.BucketTable");
+    let os_version = wer
+        .dynamic_signature
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case("OS Version"))
+        .map(|(_, v)| v.clone());
+
+    wer.event_type = event_type;
+    wer.app_name = app_name;
+    wer.app_path = app_path;
+    wer.report_id = report_id;
+    wer.event_time_unix = event_time_unix;
+    wer.bucket = bucket;
+    wer.bucket_table = bucket_table;
+    wer.os_version = os_version;
+    wer
+}
+
+/// `Sig[3].Value` -> `("Sig", 3, "Value")`; `LoadedModule[0]` -> `("LoadedModule", 0, "")`.
+fn indexed_key(key: &str) -> Option<(&str, usize, &str)> {
+    let (table, rest) = key.split_once('[')?;
+    let (index, member) = rest.split_once(']')?;
+    Some((
+        table,
+        index.parse().ok()?,
+        member.strip_prefix('.').unwrap_or(member),
+    ))
+}
+
+fn wer_event(wer: &WerReport, path: &Path, dump: Option<&DumpSource>, t_ms: u64) -> Event {
+    let opt = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".into());
+    let mut details = format!(
+        "Path: {}\nEvent type: {}\nApplication: {}\nApplication path: {}\nOS version: {}\nBucket: {}\nReport id: {}\nDump: {}",
+        path.display(),
+        opt(&wer.event_type),
+        opt(&wer.app_name),
+        opt(&wer.app_path),
+        opt(&wer.os_version),
+        opt(&wer.bucket),
+        opt(&wer.report_id),
+        match dump {
+            Some(DumpSource::File(p)) => p.display().to_string(),
+            Some(DumpSource::Bytes(name, _)) => name.clone(),
+            None => "(none in report)".into(),
+        }
+    );
+    if !wer.signature.is_empty() {
+        details.push_str("\n\nSignature:");
+        for (name, value) in &wer.signature {
+            details.push_str(&format!("\n  {name}: {value}"));
+        }
+    }
+    if !wer.files.is_empty() {
+        details.push_str(&format!("\n\nFiles: {}", wer.files.join(", ")));
+    }
+
+    let mut attributes: Vec<(String, AttrValue)> = [
+        ("EventType", &wer.event_type),
+        ("AppName", &wer.app_name),
+        ("AppPath", &wer.app_path),
+        ("Bucket", &wer.bucket),
+        ("OS Version", &wer.os_version),
+    ]
+    .into_iter()
+    .filter_map(|(k, v)| v.clone().map(|v| (k.to_string(), v.into())))
+    .collect();
+    attributes.extend(
+        wer.signature
+            .iter()
+            .map(|(k, v)| (k.clone(), v.as_str().into())),
+    );
+
+    Event {
+        id: EventId(0),
+        t_ms,
+        severity: Severity::Info,
+        title: format!(
+            "WER report: {} ({})",
+            wer.app_name.as_deref().unwrap_or("unknown application"),
+            wer.event_type.as_deref().unwrap_or("unknown event")
+        ),
+        details,
+        source: "ingest::wer".into(),
+        timestamp: wer
+            .event_time_unix
+            .and_then(|t| time::OffsetDateTime::from_unix_timestamp(t as i64).ok()),
+        attributes,
+        links: Vec::new(),
+    }
+}
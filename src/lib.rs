@@ -1,6 +1,14 @@
 pub mod app;
+pub mod baseline;
+pub mod case;
+pub mod cli;
+pub mod correlate;
+pub mod diff;
+pub mod export;
 pub mod ingest;
 pub mod model;
+pub mod query;
+pub mod triage;
 pub mod ui;
 pub mod util;
 
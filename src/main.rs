@@ -1,3 +1,24 @@
 fn main() -> eframe::Result<()> {
-    log_atlas::run()
+    let args = match log_atlas::cli::Args::parse(std::env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("log-atlas: {e}\n\n{}", log_atlas::cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", log_atlas::cli::USAGE);
+        return Ok(());
+    }
+    if args.headless() {
+        let mut out = std::io::stdout().lock();
+        match log_atlas::cli::run_headless(&args, &mut out) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                eprintln!("log-atlas: {e:#}");
+                std::process::exit(1);
+            }
+        }
+    }
+    log_atlas::run(args.inputs)
 }
@@ -0,0 +1,191 @@
+//! MITRE ATT&CK techniques and tactics that detections map to. Only the techniques the
+//! built-in rules use are listed; sub-techniques not listed take their parent's tactics.
+
+use serde::Serialize;
+
+/// Tactics in kill-chain order, which is also the order the Detections tab groups them in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tactic {
+    Execution,
+    Persistence,
+    PrivilegeEscalation,
+    DefenseEvasion,
+    CommandAndControl,
+}
+
+impl Tactic {
+    pub fn id(self) -> &'static str {
+        match self {
+            Tactic::Execution => "TA0002",
+            Tactic::Persistence => "TA0003",
+            Tactic::PrivilegeEscalation => "TA0004",
+            Tactic::DefenseEvasion => "TA0005",
+            Tactic::CommandAndControl => "TA0011",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Tactic::Execution => "Execution",
+            Tactic::Persistence => "Persistence",
+            Tactic::PrivilegeEscalation => "Privilege Escalation",
+            Tactic::DefenseEvasion => "Defense Evasion",
+            Tactic::CommandAndControl => "Command and Control",
+        }
+    }
+}
+
+pub struct Technique {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// As ATT&CK lists them; the first is the one detections are grouped under.
+    pub tactics: &'static [Tactic],
+}
+
+use Tactic::*;
+
+const TECHNIQUES: &[Technique] = &[
+    Technique {
+        id: "T1027",
+        name: "Obfuscated Files or Information",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1047",
+        name: "Windows Management Instrumentation",
+        tactics: &[Execution],
+    },
+    Technique {
+        id: "T1053.005",
+        name: "Scheduled Task",
+        tactics: &[Execution, Persistence, PrivilegeEscalation],
+    },
+    Technique {
+        id: "T1055",
+        name: "Process Injection",
+        tactics: &[DefenseEvasion, PrivilegeEscalation],
+    },
+    Technique {
+        id: "T1055.008",
+        name: "Ptrace System Calls",
+        tactics: &[DefenseEvasion, PrivilegeEscalation],
+    },
+    Technique {
+        id: "T1059",
+        name: "Command and Scripting Interpreter",
+        tactics: &[Execution],
+    },
+    Technique {
+        id: "T1059.001",
+        name: "PowerShell",
+        tactics: &[Execution],
+    },
+    Technique {
+        id: "T1059.003",
+        name: "Windows Command Shell",
+        tactics: &[Execution],
+    },
+    Technique {
+        id: "T1059.004",
+        name: "Unix Shell",
+        tactics: &[Execution],
+    },
+    Technique {
+        id: "T1059.006",
+        name: "Python",
+        tactics: &[Execution],
+    },
+    Technique {
+        id: "T1105",
+        name: "Ingress Tool Transfer",
+        tactics: &[CommandAndControl],
+    },
+    Technique {
+        id: "T1127.001",
+        name: "MSBuild",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1140",
+        name: "Deobfuscate/Decode Files or Information",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1197",
+        name: "BITS Jobs",
+        tactics: &[DefenseEvasion, Persistence],
+    },
+    Technique {
+        id: "T1218",
+        name: "System Binary Proxy Execution",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1218.004",
+        name: "InstallUtil",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1218.005",
+        name: "Mshta",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1218.010",
+        name: "Regsvr32",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1218.011",
+        name: "Rundll32",
+        tactics: &[DefenseEvasion],
+    },
+    Technique {
+        id: "T1574",
+        name: "Hijack Execution Flow",
+        tactics: &[Persistence, PrivilegeEscalation, DefenseEvasion],
+    },
+    Technique {
+        id: "T1574.006",
+        name: "Dynamic Linker Hijacking",
+        tactics: &[Persistence, PrivilegeEscalation, DefenseEvasion],
+    },
+    Technique {
+        id: "T1620",
+        name: "Reflective Code Loading",
+        tactics: &[DefenseEvasion],
+    },
+];
+
+/// The technique with `id`, else its parent technique for an unlisted sub-technique.
+pub fn technique(id: &str) -> Option<&'static Technique> {
+    let find = |id: &str| TECHNIQUES.iter().find(|t| t.id == id);
+    find(id).or_else(|| find(id.split_once('.')?.0))
+}
+
+/// A technique id with its name and tactics, as exports write it.
+#[derive(Clone, Debug, Serialize)]
+pub struct AttackMapping {
+    pub technique: String,
+    pub name: &'static str,
+    /// Tactic ids (`TA0005`), the grouping one first.
+    pub tactics: Vec<&'static str>,
+}
+
+impl AttackMapping {
+    pub fn of(techniques: &[String]) -> Vec<Self> {
+        techniques
+            .iter()
+            .map(|id| {
+                let t = technique(id);
+                AttackMapping {
+                    technique: id.clone(),
+                    name: t.map_or("", |t| t.name),
+                    tactics: t
+                        .map_or_else(Vec::new, |t| t.tactics.iter().map(|a| a.id()).collect()),
+                }
+            })
+            .collect()
+    }
+}
@@ -1,7 +1,9 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct EventId(pub u64);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Severity {
     Info,
     Warning,
@@ -18,13 +20,203 @@ impl Severity {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Event {
     pub id: EventId,
-    /// Milliseconds from capture start (placeholder until we parse real timestamps).
+    /// Milliseconds from capture start; derived from `timestamp` when the source has one.
     pub t_ms: u64,
+    /// Wall-clock time of the event, when the source records one.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub timestamp: Option<time::OffsetDateTime>,
     pub severity: Severity,
     pub title: String,
     pub details: String,
     pub source: String,
+    /// Structured fields, in source order. Ingesters that build `details` themselves render it
+    /// from these (`render_attributes`).
+    pub attributes: Vec<(String, AttrValue)>,
+    /// Report entities this event is about; the timeline jumps to the first one on click.
+    pub links: Vec<EntityRef>,
+}
+
+impl Event {
+    pub fn attr(&self, key: &str) -> Option<&AttrValue> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+}
+
+/// Typed value of an event attribute.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AttrValue {
+    Str(String),
+    Int(i64),
+    UInt(u64),
+    /// Address, code or flags: shown in hex.
+    Addr(u64),
+    Time(#[serde(with = "time::serde::rfc3339")] time::OffsetDateTime),
+    List(Vec<AttrValue>),
+}
+
+impl AttrValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttrValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Numeric value, also parsed from decimal or `0x` hex strings.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            AttrValue::UInt(v) | AttrValue::Addr(v) => Some(*v),
+            AttrValue::Int(v) => u64::try_from(*v).ok(),
+            AttrValue::Str(s) => {
+                let s = s.trim();
+                match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                    Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                    None => s.parse().ok(),
+                }
+            }
+            AttrValue::Time(_) | AttrValue::List(_) => None,
+        }
+    }
+
+    /// Short type name, for the attribute grid.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AttrValue::Str(_) => "str",
+            AttrValue::Int(_) | AttrValue::UInt(_) => "int",
+            AttrValue::Addr(_) => "addr",
+            AttrValue::Time(_) => "time",
+            AttrValue::List(_) => "list",
+        }
+    }
+}
+
+impl std::fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttrValue::Str(s) => f.write_str(s),
+            AttrValue::Int(v) => write!(f, "{v}"),
+            AttrValue::UInt(v) => write!(f, "{v}"),
+            AttrValue::Addr(v) => write!(f, "0x{v:X}"),
+            AttrValue::Time(t) => f.write_str(&crate::util::time::format_timestamp(*t)),
+            AttrValue::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(v: String) -> Self {
+        AttrValue::Str(v)
+    }
+}
+
+impl From<&str> for AttrValue {
+    fn from(v: &str) -> Self {
+        AttrValue::Str(v.to_string())
+    }
+}
+
+impl From<u64> for AttrValue {
+    fn from(v: u64) -> Self {
+        AttrValue::UInt(v)
+    }
+}
+
+impl From<u32> for AttrValue {
+    fn from(v: u32) -> Self {
+        AttrValue::UInt(v.into())
+    }
+}
+
+impl From<usize> for AttrValue {
+    fn from(v: usize) -> Self {
+        AttrValue::UInt(v as u64)
+    }
+}
+
+impl From<i64> for AttrValue {
+    fn from(v: i64) -> Self {
+        AttrValue::Int(v)
+    }
+}
+
+impl From<time::OffsetDateTime> for AttrValue {
+    fn from(v: time::OffsetDateTime) -> Self {
+        AttrValue::Time(v)
+    }
+}
+
+impl From<Vec<String>> for AttrValue {
+    fn from(v: Vec<String>) -> Self {
+        AttrValue::List(v.into_iter().map(AttrValue::Str).collect())
+    }
+}
+
+/// `key: value` lines for `details`; lists get one indented line per item.
+pub fn render_attributes(attributes: &[(String, AttrValue)]) -> String {
+    let mut lines = Vec::with_capacity(attributes.len());
+    for (key, value) in attributes {
+        match value {
+            AttrValue::List(items) => {
+                lines.push(format!("{key}:"));
+                lines.extend(items.iter().map(|item| format!("  {item}")));
+            }
+            _ => lines.push(format!("{key}: {value}")),
+        }
+    }
+    lines.join("\n")
+}
+
+/// A report entity an event points at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntityRef {
+    /// Index into `MinidumpReport::modules`.
+    Module(usize),
+    Thread(u32),
+    /// Base address of one of `MinidumpReport::injected_regions`.
+    Region(u64),
+    /// Index into `MinidumpReport::exec_artifacts`.
+    ExecArtifact(usize),
+    /// An address in the process, e.g. where an exception was raised.
+    Address(u64),
+}
+
+impl EntityRef {
+    pub fn label(self) -> String {
+        match self {
+            EntityRef::Module(idx) => format!("module #{idx}"),
+            EntityRef::Thread(tid) => format!("thread 0x{tid:X}"),
+            EntityRef::Region(base) => format!("region 0x{base:016X}"),
+            EntityRef::ExecArtifact(idx) => format!("exec artifact #{idx}"),
+            EntityRef::Address(addr) => format!("address 0x{addr:016X}"),
+        }
+    }
+}
+
+/// Set `t_ms` from wall-clock timestamps, relative to the earliest one. Events without a
+/// timestamp inherit the offset of the event before them, so source order is preserved.
+/// Returns the earliest timestamp.
+pub fn derive_t_ms(events: &mut [Event]) -> Option<time::OffsetDateTime> {
+    let start = events.iter().filter_map(|e| e.timestamp).min();
+    let mut last_t_ms = 0u64;
+    for ev in events.iter_mut() {
+        if let (Some(ts), Some(start)) = (ev.timestamp, start) {
+            last_t_ms = (ts - start).whole_milliseconds().max(0) as u64;
+        }
+        ev.t_ms = last_t_ms;
+    }
+    start
 }
@@ -1,6 +1,9 @@
-use crate::model::Severity;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Default)]
+use crate::model::attack::{self, Tactic};
+use crate::model::{CrashSignature, EntityRef, Severity, WerReport};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct MinidumpReport {
     pub os: Option<String>,
     pub cpu: Option<String>,
@@ -8,6 +11,16 @@ pub struct MinidumpReport {
     pub process: Option<ProcessInfo>,
     /// Best-effort execution artifacts (heuristics over dump memory).
     pub exec_artifacts: Vec<ProcessExecArtifact>,
+    /// How much dump memory the string scan covered.
+    pub string_scan: Option<ScanCoverage>,
+    /// Deduplicated indicators of compromise recovered from dump memory.
+    pub iocs: Vec<Indicator>,
+    /// How much dump memory the IOC scan covered.
+    pub ioc_scan: Option<ScanCoverage>,
+    /// Likely credentials / secrets found in dump memory (values masked).
+    pub sensitive: Vec<SensitiveFinding>,
+    /// How much dump memory the secret scan covered.
+    pub sensitive_scan: Option<ScanCoverage>,
     /// Best-effort detection of executable memory regions that look like injection/shellcode.
     ///
     /// Requires `MemoryInfoListStream` to be present in the dump.
@@ -22,9 +35,79 @@ pub struct MinidumpReport {
     pub stackwalk: Option<StackwalkReport>,
     /// Non-fatal stackwalk failure reason.
     pub stackwalk_error: Option<String>,
+    /// `/proc`-derived streams written by Breakpad / Crashpad on Linux and Android.
+    pub linux: Option<LinuxProcessStreams>,
+    /// `CrashpadInfo` stream: report / client ids and client annotations.
+    pub crashpad: Option<CrashpadInfo>,
+    /// Annotation rules evaluated by `detections()` (from `LOG_ATLAS_ANNOTATION_RULES`).
+    pub annotation_rules: Vec<AnnotationRule>,
+    /// Windows Error Reporting `Report.wer` shipped alongside the dump.
+    pub wer: Option<WerReport>,
 }
 
 impl MinidumpReport {
+    /// Whether the dump came from Linux / Android (exception codes are then signal numbers).
+    pub fn is_linux(&self) -> bool {
+        self.linux.is_some()
+            || self
+                .os
+                .as_deref()
+                .is_some_and(|os| os == "Linux" || os == "Android")
+    }
+
+    /// WER-style signature of this crash: main image, fault module, exception code and offset.
+    pub fn crash_signature(&self) -> Option<CrashSignature> {
+        let exc = self.exception.as_ref()?;
+        let app = self.modules.first();
+        let fault = self.fault_module_index().map(|idx| &self.modules[idx]);
+        let basename = |name: &str| name.rsplit(['\\', '/']).next().unwrap_or(name).to_string();
+        Some(CrashSignature {
+            app_name: app.map(|m| basename(&m.name)),
+            app_version: app.and_then(|m| m.file_version.clone()),
+            app_timestamp: app.map(|m| m.time_date_stamp).filter(|t| *t != 0),
+            fault_module: fault.map(|m| basename(&m.name)),
+            fault_module_version: fault.and_then(|m| m.file_version.clone()),
+            fault_module_timestamp: fault.map(|m| m.time_date_stamp).filter(|t| *t != 0),
+            exception_code: Some(exc.code),
+            exception_offset: Some(fault.map_or(exc.address, |m| exc.address - m.base)),
+        })
+    }
+
+    /// Index of the module containing the exception address.
+    pub fn fault_module_index(&self) -> Option<usize> {
+        let addr = self.exception.as_ref()?.address;
+        self.modules
+            .iter()
+            .position(|m| addr >= m.base && addr - m.base < m.size)
+    }
+
+    /// `module+0xOFFSET` for an address inside a module, else the absolute address.
+    pub fn location(&self, addr: u64) -> String {
+        match self
+            .modules
+            .iter()
+            .find(|m| addr >= m.base && addr - m.base < m.size)
+        {
+            Some(m) => {
+                let name = m.name.rsplit(['\\', '/']).next().unwrap_or(&m.name);
+                format!("{name}+0x{:X}", addr - m.base)
+            }
+            None => format!("0x{addr:016X}"),
+        }
+    }
+
+    /// Crashing thread, exception address and fault module, for detections about the
+    /// exception.
+    fn exception_links(&self) -> Vec<EntityRef> {
+        let mut links = Vec::new();
+        if let Some(exc) = &self.exception {
+            links.push(EntityRef::Thread(exc.thread_id));
+            links.push(EntityRef::Address(exc.address));
+        }
+        links.extend(self.fault_module_index().map(EntityRef::Module));
+        links
+    }
+
     pub fn last_thread_create_time_unix(&self) -> Option<u64> {
         self.threads.iter().filter_map(|t| t.create_time_unix).max()
     }
@@ -32,41 +115,161 @@ impl MinidumpReport {
     pub fn detections(&self) -> Vec<Detection> {
         let mut out = Vec::new();
 
-        if let Some(exc) = &self.exception {
-            if exc.code == 0xC000_0005 {
-                out.push(Detection {
-                    severity: Severity::High,
-                    title: "Access violation".into(),
-                    details: format!(
-                        "Exception code 0xC0000005 at address 0x{:016X} (thread_id={}).",
-                        exc.address, exc.thread_id
-                    ),
-                });
-            }
+        if let Some(exc) = &self.exception
+            && exc.code == 0xC000_0005
+        {
+            out.push(Detection {
+                rule: "exception.access_violation".into(),
+                key: self.location(exc.address),
+                severity: Severity::High,
+                title: "Access violation".into(),
+                details: format!(
+                    "Exception code 0xC0000005 at address 0x{:016X} (thread_id={}).",
+                    exc.address, exc.thread_id
+                ),
+                techniques: Vec::new(),
+                confidence: 100,
+                links: self.exception_links(),
+            });
         }
 
-        for m in &self.modules {
+        if let Some(exc) = &self.exception
+            && self.is_linux()
+            && let Some((signal, severity)) = linux_signal(exc.code)
+        {
+            out.push(Detection {
+                rule: "exception.fatal_signal".into(),
+                key: format!("{} {}", exc.code, self.location(exc.address)),
+                severity,
+                title: format!("Fatal signal {signal}"),
+                details: format!(
+                    "Signal {} ({signal}) at address 0x{:016X} (thread_id={}).",
+                    exc.code, exc.address, exc.thread_id
+                ),
+                techniques: Vec::new(),
+                confidence: 100,
+                links: self.exception_links(),
+            });
+        }
+
+        for (idx, m) in self.modules.iter().enumerate() {
             let name_lc = m.name.to_ascii_lowercase();
             if name_lc.contains("\\appdata\\local\\temp\\")
                 || name_lc.contains("/tmp/")
+                || name_lc.contains("/var/tmp/")
+                || name_lc.contains("/dev/shm/")
                 || name_lc.contains("\\temp\\")
             {
                 out.push(Detection {
+                    rule: "module.temp_path".into(),
+                    key: m.name.clone(),
                     severity: Severity::Warning,
                     title: "Module loaded from temp path".into(),
                     details: format!("Module: {}", m.name),
+                    techniques: vec!["T1574".into()],
+                    confidence: 40,
+                    links: vec![EntityRef::Module(idx)],
+                });
+            }
+            if name_lc.ends_with(" (deleted)") || name_lc.contains("/memfd:") {
+                out.push(Detection {
+                    rule: "module.fileless".into(),
+                    key: m.name.clone(),
+                    severity: Severity::High,
+                    title: "Module mapped from deleted or memory-only file".into(),
+                    details: format!(
+                        "Module: {}\nThe backing file no longer exists on disk (deleted after load, or a memfd), a common fileless-execution pattern.",
+                        m.name
+                    ),
+                    techniques: vec!["T1620".into()],
+                    confidence: 80,
+                    links: vec![EntityRef::Module(idx)],
+                });
+            }
+        }
+
+        if let Some(p) = &self.process {
+            for (name, value) in &p.environment {
+                if name == "LD_PRELOAD" || name == "LD_AUDIT" {
+                    out.push(Detection {
+                        rule: "process.dynamic_linker_hijack".into(),
+                        key: name.clone(),
+                        severity: Severity::Warning,
+                        title: format!("Library injection via {name}"),
+                        details: format!(
+                            "{name}={value}\nThe dynamic linker loads these libraries into the process before its own dependencies."
+                        ),
+                        techniques: vec!["T1574.006".into()],
+                        confidence: 70,
+                        links: preloaded_modules(&self.modules, value),
+                    });
+                }
+            }
+        }
+        if let Some(tracer) = self
+            .linux
+            .as_ref()
+            .and_then(|l| l.status_field("TracerPid"))
+            .filter(|t| *t != "0")
+        {
+            out.push(Detection {
+                rule: "process.traced".into(),
+                key: String::new(),
+                severity: Severity::Warning,
+                title: "Process was being traced".into(),
+                details: format!(
+                    "TracerPid={tracer}: another process was attached with ptrace (a debugger, or code injection)."
+                ),
+                techniques: vec!["T1055.008".into()],
+                confidence: 50,
+                links: Vec::new(),
+            });
+        }
+
+        if let Some(crashpad) = &self.crashpad {
+            for rule in &self.annotation_rules {
+                let matched: Vec<&Annotation> = crashpad
+                    .annotations
+                    .iter()
+                    .filter(|a| rule.matches(a))
+                    .collect();
+                if matched.is_empty() {
+                    continue;
+                }
+                out.push(Detection {
+                    rule: "annotation.rule".into(),
+                    key: rule.to_string(),
+                    severity: rule.severity,
+                    title: format!("Annotation rule matched: {rule}"),
+                    details: matched
+                        .iter()
+                        .map(|a| format!("{}={} ({})", a.key, a.value, a.scope()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    techniques: Vec::new(),
+                    confidence: 100,
+                    links: Vec::new(),
                 });
             }
         }
 
-        for a in &self.exec_artifacts {
-            if let Some(det) = detect_exec_artifact(a) {
-                out.push(det);
+        out.extend(self.wer_mismatch());
+
+        for (idx, a) in self.exec_artifacts.iter().enumerate() {
+            // Re-run the rules on decoded payloads: the interesting part of an encoded
+            // command only becomes visible after decoding.
+            for candidate in std::iter::once(a).chain(a.descendants()) {
+                if let Some(mut det) = detect_exec_artifact(a, candidate) {
+                    det.links.push(EntityRef::ExecArtifact(idx));
+                    out.push(det);
+                }
             }
         }
 
         for r in &self.injected_regions {
             out.push(Detection {
+                rule: "memory.injected_region".into(),
+                key: format!("0x{:X}", r.base),
                 severity: r.risk,
                 title: "Suspicious executable memory allocation".into(),
                 details: format!(
@@ -86,12 +289,48 @@ impl MinidumpReport {
                             .join("\n")
                     }
                 ),
+                techniques: vec!["T1055".into()],
+                confidence: match r.risk {
+                    Severity::High => 75,
+                    Severity::Warning => 50,
+                    Severity::Info => 25,
+                },
+                links: vec![EntityRef::Region(r.base)],
             });
         }
 
         out
     }
 
+    /// Compare `Report.wer`'s bucketing parameters with the signature computed from the dump.
+    pub fn wer_mismatch(&self) -> Option<Detection> {
+        let mismatches = self.wer.as_ref()?.compare(&self.crash_signature()?);
+        if mismatches.is_empty() {
+            return None;
+        }
+        Some(Detection {
+            rule: "wer.signature_mismatch".into(),
+            key: String::new(),
+            severity: Severity::Warning,
+            title: "WER signature mismatch".into(),
+            details: format!(
+                "{}\n\nReport.wer does not describe the crash recorded in the dump: the report and dump may not belong together, or WER bucketed a different fault.",
+                mismatches
+                    .iter()
+                    .map(|m| format!("{}: WER={} dump={}", m.parameter, m.wer, m.dump))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            techniques: Vec::new(),
+            confidence: 80,
+            links: self
+                .fault_module_index()
+                .map(EntityRef::Module)
+                .into_iter()
+                .collect(),
+        })
+    }
+
     pub fn exception_stack(&self) -> Option<&ThreadStackTrace> {
         let tid = self.exception.as_ref()?.thread_id;
         self.stackwalk
@@ -110,9 +349,12 @@ impl MinidumpReport {
     }
 }
 
-fn detect_exec_artifact(a: &ProcessExecArtifact) -> Option<Detection> {
+/// `root` is the artifact recovered from memory, `a` is either `root` or one of its decoded
+/// payloads; decoded payloads inherit the root's LOLBin context.
+fn detect_exec_artifact(root: &ProcessExecArtifact, a: &ProcessExecArtifact) -> Option<Detection> {
     let cl = a.command_line.to_ascii_lowercase();
-    let img = a.image.to_ascii_lowercase();
+    let img = root.image.to_ascii_lowercase();
+    let root_cl = root.command_line.to_ascii_lowercase();
 
     let is_lolbin = [
         "powershell",
@@ -129,28 +371,81 @@ fn detect_exec_artifact(a: &ProcessExecArtifact) -> Option<Detection> {
         "bitsadmin",
         "msbuild",
         "installutil",
+        "/bin/sh",
+        "/bin/bash",
+        "bash -c",
+        "sh -c",
+        "curl ",
+        "wget ",
+        "ncat",
+        "nc -e",
+        "socat",
+        "python -c",
+        "python3 -c",
+        "perl -e",
+        "busybox",
     ]
     .iter()
-    .any(|k| img.contains(k) || cl.contains(k));
+    .any(|k| img.contains(k) || root_cl.contains(k) || cl.contains(k));
 
     if !is_lolbin {
         return None;
     }
 
     let mut reasons: Vec<&str> = Vec::new();
-    if cl.contains(" -enc") || cl.contains(" -encodedcommand") {
+    if cl.contains(" -enc")
+        || cl.contains(" -encodedcommand")
+        || cl.contains("base64 -d")
+        || cl.contains("base64 --decode")
+    {
         reasons.push("encoded command");
     }
+    if cl.contains("| sh") || cl.contains("|sh") || cl.contains("| bash") || cl.contains("|bash") {
+        reasons.push("download and execute");
+    }
+    if cl.contains("/dev/tcp/") || cl.contains("/dev/udp/") {
+        reasons.push("reverse shell pattern");
+    }
     if cl.contains("frombase64string") || cl.contains("iex") || cl.contains("invoke-expression") {
         reasons.push("in-memory execution pattern");
     }
-    if cl.contains("\\appdata\\local\\temp\\") || cl.contains("\\temp\\") || cl.contains("/tmp/") {
+    if cl.contains("\\appdata\\local\\temp\\")
+        || cl.contains("\\temp\\")
+        || cl.contains("/tmp/")
+        || cl.contains("/dev/shm/")
+    {
         reasons.push("temp path");
     }
     if cl.contains("http://") || cl.contains("https://") {
         reasons.push("network indicator");
     }
 
+    let mut techniques = exec_techniques(&format!("{img} {cl}"), &reasons);
+    if let Some(step) = a.decoded_via {
+        techniques.insert(0, "T1140".to_string());
+        // A decoded payload is only worth its own detection if the rules fire on its content.
+        if reasons.is_empty() {
+            return None;
+        }
+        return Some(Detection {
+            rule: "exec.decoded_payload".into(),
+            key: format!("{}\0{}", root.command_line, a.command_line),
+            severity: Severity::High,
+            title: "Suspicious decoded payload".into(),
+            details: format!(
+                "Reasons: {}\nDecoded via: {}\nImage: {}\nOriginal command line: {}\nDecoded: {}",
+                reasons.join(", "),
+                step.label(),
+                root.image,
+                root.command_line,
+                a.command_line
+            ),
+            techniques,
+            confidence: (60 + 10 * reasons.len()).min(95) as u8,
+            links: Vec::new(),
+        });
+    }
+
     let severity = if reasons.is_empty() {
         Severity::Warning
     } else {
@@ -158,6 +453,8 @@ fn detect_exec_artifact(a: &ProcessExecArtifact) -> Option<Detection> {
     };
 
     Some(Detection {
+        rule: "exec.suspicious_artifact".into(),
+        key: format!("{}\0{}", a.image, a.command_line),
         severity,
         title: "Suspicious execution artifact".into(),
         details: if reasons.is_empty() {
@@ -170,12 +467,96 @@ fn detect_exec_artifact(a: &ProcessExecArtifact) -> Option<Detection> {
                 a.command_line
             )
         },
+        techniques,
+        confidence: if reasons.is_empty() {
+            30
+        } else {
+            (50 + 15 * reasons.len()).min(95) as u8
+        },
+        links: Vec::new(),
+    })
+}
+
+/// ATT&CK techniques of an execution artifact (`text` is its lowercase image and command
+/// line): the interpreter or proxy binary it runs, then what its reasons show.
+fn exec_techniques(text: &str, reasons: &[&str]) -> Vec<String> {
+    const BINARIES: &[(&[&str], &str)] = &[
+        (&["powershell", "pwsh"], "T1059.001"),
+        (&["cmd.exe"], "T1059.003"),
+        (&["wscript", "cscript", "perl -e"], "T1059"),
+        (
+            &["/bin/sh", "/bin/bash", "bash -c", "sh -c", "busybox"],
+            "T1059.004",
+        ),
+        (&["python -c", "python3 -c"], "T1059.006"),
+        (&["rundll32"], "T1218.011"),
+        (&["regsvr32"], "T1218.010"),
+        (&["mshta"], "T1218.005"),
+        (&["installutil"], "T1218.004"),
+        (&["msbuild"], "T1127.001"),
+        (&["certutil"], "T1140"),
+        (&["bitsadmin"], "T1197"),
+        (&["schtasks"], "T1053.005"),
+        (&["wmic"], "T1047"),
+        (&["curl ", "wget "], "T1105"),
+    ];
+    let mut out: Vec<String> = Vec::new();
+    let mut add = |id: &str| {
+        if !out.iter().any(|t| t == id) {
+            out.push(id.to_string());
+        }
+    };
+    for (needles, id) in BINARIES {
+        if needles.iter().any(|n| text.contains(n)) {
+            add(id);
+        }
+    }
+    for reason in reasons {
+        match *reason {
+            "encoded command" => add("T1027"),
+            "download and execute" | "network indicator" => add("T1105"),
+            "reverse shell pattern" => add("T1059.004"),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Modules named in an `LD_PRELOAD` / `LD_AUDIT` value (paths separated by `:` or spaces).
+fn preloaded_modules(modules: &[ModuleInfo], value: &str) -> Vec<EntityRef> {
+    value
+        .split([':', ' '])
+        .filter(|p| !p.is_empty())
+        .filter_map(|p| modules.iter().position(|m| m.name == p))
+        .map(EntityRef::Module)
+        .collect()
+}
+
+/// Name and severity of Linux fatal signals, as Breakpad records them in the exception code.
+fn linux_signal(code: u32) -> Option<(&'static str, Severity)> {
+    Some(match code {
+        4 => ("SIGILL", Severity::High),
+        5 => ("SIGTRAP", Severity::Warning),
+        6 => ("SIGABRT", Severity::Warning),
+        7 => ("SIGBUS", Severity::High),
+        8 => ("SIGFPE", Severity::Warning),
+        11 => ("SIGSEGV", Severity::High),
+        31 => ("SIGSYS", Severity::Warning),
+        _ => return None,
     })
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProcessInfo {
     pub pid: Option<u32>,
+    /// Parent PID (`PPid` in `/proc/self/status`).
+    pub ppid: Option<u32>,
+    /// Real UID (`Uid` in `/proc/self/status`).
+    pub uid: Option<u32>,
+    /// Command line (`LinuxCmdLine`, arguments joined with spaces).
+    pub command_line: Option<String>,
+    /// Environment variables (`LinuxEnviron`).
+    pub environment: Vec<(String, String)>,
     /// Unix epoch seconds (time_t in the stream), if present.
     pub create_time_unix: Option<u64>,
     pub integrity_level: Option<u32>,
@@ -185,13 +566,23 @@ pub struct ProcessInfo {
     pub main_image_version: Option<String>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ExecArtifactEncoding {
     Ascii,
     Utf16Le,
 }
 
-#[derive(Clone, Debug)]
+impl ExecArtifactEncoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            ExecArtifactEncoding::Ascii => "ascii",
+            ExecArtifactEncoding::Utf16Le => "utf16le",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProcessExecArtifact {
     /// Extracted executable/script "image" (best-effort).
     pub image: String,
@@ -201,9 +592,228 @@ pub struct ProcessExecArtifact {
     pub encoding: ExecArtifactEncoding,
     /// Virtual memory address of the string if known (base+offset).
     pub address: Option<u64>,
+    /// How this artifact was decoded from its parent; `None` for strings recovered from memory.
+    pub decoded_via: Option<DecodeStep>,
+    /// Payloads decoded from this artifact's command line (encoded commands, base64, ...).
+    pub children: Vec<ProcessExecArtifact>,
+}
+
+impl ProcessExecArtifact {
+    /// Depth-first walk over decoded children (not including `self`).
+    pub fn descendants(&self) -> Vec<&ProcessExecArtifact> {
+        let mut out = Vec::new();
+        for c in &self.children {
+            out.push(c);
+            out.extend(c.descendants());
+        }
+        out
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecodeStep {
+    /// PowerShell `-EncodedCommand`: base64 of UTF-16LE script text.
+    EncodedCommand,
+    Base64,
+    /// Base64 followed by `IO.Compression.GZipStream`.
+    Gzip,
+    /// Base64 followed by `IO.Compression.DeflateStream`.
+    Deflate,
+    /// `[char]72+[char]101...` / `[char[]](72,101,...)` obfuscation.
+    CharCodes,
+}
+
+impl DecodeStep {
+    pub fn label(self) -> &'static str {
+        match self {
+            DecodeStep::EncodedCommand => "encoded-command",
+            DecodeStep::Base64 => "base64",
+            DecodeStep::Gzip => "base64+gzip",
+            DecodeStep::Deflate => "base64+deflate",
+            DecodeStep::CharCodes => "char-codes",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IocKind {
+    Url,
+    Domain,
+    Ipv4,
+    Ipv6,
+    Email,
+    FilePath,
+    RegistryKey,
+    NamedPipe,
+    Mutex,
+}
+
+impl IocKind {
+    pub const ALL: [IocKind; 9] = [
+        IocKind::Url,
+        IocKind::Domain,
+        IocKind::Ipv4,
+        IocKind::Ipv6,
+        IocKind::Email,
+        IocKind::FilePath,
+        IocKind::RegistryKey,
+        IocKind::NamedPipe,
+        IocKind::Mutex,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IocKind::Url => "url",
+            IocKind::Domain => "domain",
+            IocKind::Ipv4 => "ipv4",
+            IocKind::Ipv6 => "ipv6",
+            IocKind::Email => "email",
+            IocKind::FilePath => "file_path",
+            IocKind::RegistryKey => "registry_key",
+            IocKind::NamedPipe => "named_pipe",
+            IocKind::Mutex => "mutex",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Indicator {
+    pub kind: IocKind,
+    pub value: String,
+    /// Occurrences in dump memory (after deduplication on kind + case-folded value).
+    pub count: usize,
+    /// Virtual address of the first occurrence.
+    pub address: u64,
+    /// Encoding of the first occurrence.
+    pub encoding: ExecArtifactEncoding,
+    /// Module or allocation containing the first occurrence, if it could be resolved.
+    pub container: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretKind {
+    Jwt,
+    BearerToken,
+    AwsKey,
+    GcpKey,
+    AzureKey,
+    PrivateKey,
+    Password,
+    NtlmHash,
 }
 
-#[derive(Clone, Debug)]
+impl SecretKind {
+    pub const ALL: [SecretKind; 8] = [
+        SecretKind::Jwt,
+        SecretKind::BearerToken,
+        SecretKind::AwsKey,
+        SecretKind::GcpKey,
+        SecretKind::AzureKey,
+        SecretKind::PrivateKey,
+        SecretKind::Password,
+        SecretKind::NtlmHash,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SecretKind::Jwt => "jwt",
+            SecretKind::BearerToken => "bearer_token",
+            SecretKind::AwsKey => "aws_key",
+            SecretKind::GcpKey => "gcp_key",
+            SecretKind::AzureKey => "azure_key",
+            SecretKind::PrivateKey => "private_key",
+            SecretKind::Password => "password",
+            SecretKind::NtlmHash => "ntlm_hash",
+        }
+    }
+}
+
+/// A byte range of process memory, by virtual address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemorySpan {
+    pub address: u64,
+    pub len: u64,
+}
+
+/// A likely secret found in dump memory. The secret itself is never stored; only a masked
+/// preview and the memory spans it occupies (for redaction).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SensitiveFinding {
+    pub kind: SecretKind,
+    /// Masked rendering safe to display or share, e.g. `AKIA************MPLE`.
+    pub preview: String,
+    /// Encoding of the first occurrence.
+    pub encoding: ExecArtifactEncoding,
+    /// Module or allocation containing the first occurrence, if it could be resolved.
+    pub container: Option<String>,
+    /// Every occurrence of this value, sorted by address.
+    pub spans: Vec<MemorySpan>,
+}
+
+impl SensitiveFinding {
+    pub fn count(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn address(&self) -> u64 {
+        self.spans.first().map_or(0, |s| s.address)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScanCoverage {
+    /// Captured memory regions in the dump.
+    pub regions_total: usize,
+    /// Regions (fully or partially) scanned within the budget.
+    pub regions_scanned: usize,
+    pub bytes_total: u64,
+    pub bytes_scanned: u64,
+    /// Bytes left unscanned because the byte budget ran out.
+    pub bytes_skipped: u64,
+    /// Hits dropped because the hit budget ran out.
+    pub hits_truncated: usize,
+    /// Byte budget in effect, if any.
+    pub budget_bytes: Option<u64>,
+}
+
+impl ScanCoverage {
+    pub fn is_complete(&self) -> bool {
+        self.bytes_skipped == 0 && self.hits_truncated == 0
+    }
+
+    pub fn percent_scanned(&self) -> f64 {
+        if self.bytes_total == 0 {
+            return 100.0;
+        }
+        self.bytes_scanned as f64 * 100.0 / self.bytes_total as f64
+    }
+
+    pub fn pretty(&self) -> String {
+        let mut out = format!(
+            "scanned {} of {} bytes ({:.1}%) in {}/{} regions",
+            self.bytes_scanned,
+            self.bytes_total,
+            self.percent_scanned(),
+            self.regions_scanned,
+            self.regions_total
+        );
+        if self.bytes_skipped > 0 {
+            out.push_str(&format!(
+                "; skipped {} bytes (budget {} bytes)",
+                self.bytes_skipped,
+                self.budget_bytes.unwrap_or(0)
+            ));
+        }
+        if self.hits_truncated > 0 {
+            out.push_str(&format!("; truncated {} hits", self.hits_truncated));
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InjectedRegion {
     /// Allocation base (useful "base address" to pivot on).
     pub base: u64,
@@ -221,7 +831,225 @@ pub struct InjectedRegion {
     pub risk: Severity,
 }
 
-#[derive(Clone, Debug)]
+/// Streams Breakpad / Crashpad copy from `/proc` on Linux and Android. `LinuxCmdLine` and
+/// `LinuxEnviron` go to `ProcessInfo`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LinuxProcessStreams {
+    /// `model name` (x86) or `Hardware` / `Processor` (ARM) from `LinuxCpuInfo`.
+    pub cpu_model: Option<String>,
+    /// Number of `processor` entries in `LinuxCpuInfo`.
+    pub cpu_count: Option<usize>,
+    /// `LinuxProcStatus` (`/proc/self/status`) as `(field, value)` pairs.
+    pub proc_status: Vec<(String, String)>,
+    /// `LinuxMaps` (`/proc/self/maps`).
+    pub maps: Vec<LinuxMapping>,
+    /// `LinuxAuxv` (`/proc/self/auxv`).
+    pub auxv: Vec<AuxvEntry>,
+    /// `LinuxDsoDebug` (the dynamic linker's `r_debug` / `link_map` list).
+    pub dso_debug: Option<LinuxDsoDebug>,
+}
+
+impl LinuxProcessStreams {
+    pub fn status_field(&self, name: &str) -> Option<&str> {
+        self.proc_status
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// One `/proc/self/maps` line.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinuxMapping {
+    pub start: u64,
+    /// Exclusive end address.
+    pub end: u64,
+    /// `rwxp` / `r-xs` ...
+    pub perms: String,
+    pub offset: u64,
+    pub inode: u64,
+    /// Backing file, `[heap]` / `[stack]` / `[vdso]` style pseudo-paths, or empty for
+    /// anonymous mappings.
+    pub path: String,
+}
+
+impl LinuxMapping {
+    pub fn is_executable(&self) -> bool {
+        self.perms.as_bytes().get(2) == Some(&b'x')
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.perms.as_bytes().get(1) == Some(&b'w')
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Kernel-provided mappings (`[vdso]`, `[vsyscall]`, `[vvar]`) that are executable by
+    /// design.
+    pub fn is_kernel_provided(&self) -> bool {
+        matches!(self.path.as_str(), "[vdso]" | "[vsyscall]" | "[vvar]")
+    }
+
+    /// Backed by a memfd or a file deleted after mapping.
+    pub fn is_fileless(&self) -> bool {
+        self.path.starts_with("/memfd:") || self.path.ends_with(" (deleted)")
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuxvEntry {
+    pub key: u64,
+    /// `AT_*` name when known.
+    pub name: Option<String>,
+    pub value: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinuxDsoDebug {
+    pub version: u32,
+    pub brk: u64,
+    pub ldbase: u64,
+    pub dynamic: u64,
+    pub dsos: Vec<LinuxDso>,
+}
+
+/// One `link_map` entry: a shared object as the dynamic linker knows it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinuxDso {
+    /// Load bias (`l_addr`).
+    pub addr: u64,
+    /// Address of the object's `.dynamic` section (`l_ld`).
+    pub ld: u64,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CrashpadInfo {
+    /// Empty when the writer left the id zeroed.
+    pub report_id: String,
+    pub client_id: String,
+    pub annotations: Vec<Annotation>,
+}
+
+impl CrashpadInfo {
+    /// Value of the first annotation named `key` (process-level annotations come first).
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.annotations
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.value.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Annotation {
+    pub key: String,
+    pub value: String,
+    /// Module the annotation is attached to; `None` for process-level simple annotations.
+    pub module: Option<String>,
+    pub kind: AnnotationKind,
+}
+
+impl Annotation {
+    pub fn scope(&self) -> &str {
+        self.module
+            .as_deref()
+            .map_or("process", |m| m.rsplit(['/', '\\']).next().unwrap_or(m))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnnotationKind {
+    /// `simple_annotations` string pair.
+    Simple,
+    /// Module `list_annotations` entry; the key is its index.
+    List,
+    /// Module annotation object (`crashpad::Annotation`).
+    Object,
+}
+
+impl AnnotationKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AnnotationKind::Simple => "simple",
+            AnnotationKind::List => "list",
+            AnnotationKind::Object => "object",
+        }
+    }
+}
+
+/// A user rule over annotations: `[high:|warning:|info:]key`, `key=value`, `key!=value` or
+/// `key~substring`. Keys and values compare case-insensitively.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnotationRule {
+    pub key: String,
+    pub op: AnnotationOp,
+    pub severity: Severity,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnnotationOp {
+    Present,
+    Equals(String),
+    NotEquals(String),
+    Contains(String),
+}
+
+impl AnnotationRule {
+    /// Parse one rule; `None` for blank or malformed input. Severity defaults to `Warning`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (severity, rule) = match text.split_once(':') {
+            Some((sev, rest)) if sev.eq_ignore_ascii_case("high") => (Severity::High, rest),
+            Some((sev, rest)) if sev.eq_ignore_ascii_case("warning") => (Severity::Warning, rest),
+            Some((sev, rest)) if sev.eq_ignore_ascii_case("info") => (Severity::Info, rest),
+            _ => (Severity::Warning, text),
+        };
+        let (key, op) = if let Some((k, v)) = rule.split_once("!=") {
+            (k, AnnotationOp::NotEquals(v.trim().to_string()))
+        } else if let Some((k, v)) = rule.split_once('=') {
+            (k, AnnotationOp::Equals(v.trim().to_string()))
+        } else if let Some((k, v)) = rule.split_once('~') {
+            (k, AnnotationOp::Contains(v.trim().to_ascii_lowercase()))
+        } else {
+            (rule, AnnotationOp::Present)
+        };
+        let key = key.trim();
+        (!key.is_empty()).then(|| Self {
+            key: key.to_string(),
+            op,
+            severity,
+        })
+    }
+
+    /// `NotEquals` only matches annotations that are present with another value.
+    pub fn matches(&self, a: &Annotation) -> bool {
+        if !a.key.eq_ignore_ascii_case(&self.key) {
+            return false;
+        }
+        match &self.op {
+            AnnotationOp::Present => true,
+            AnnotationOp::Equals(v) => a.value.eq_ignore_ascii_case(v),
+            AnnotationOp::NotEquals(v) => !a.value.eq_ignore_ascii_case(v),
+            AnnotationOp::Contains(v) => a.value.to_ascii_lowercase().contains(v),
+        }
+    }
+}
+
+impl std::fmt::Display for AnnotationRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.op {
+            AnnotationOp::Present => write!(f, "{}", self.key),
+            AnnotationOp::Equals(v) => write!(f, "{}={v}", self.key),
+            AnnotationOp::NotEquals(v) => write!(f, "{}!={v}", self.key),
+            AnnotationOp::Contains(v) => write!(f, "{}~{v}", self.key),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ModuleInfo {
     pub name: String,
     pub base: u64,
@@ -229,9 +1057,13 @@ pub struct ModuleInfo {
     pub checksum: u32,
     pub time_date_stamp: u32,
     pub file_version: Option<String>,
+    /// Breakpad debug identifier (PDB GUID and age, or derived from the ELF build id): the
+    /// build the dump recorded, since dumps do not hold module file contents to hash.
+    #[serde(default)]
+    pub debug_id: Option<String>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ThreadInfo {
     pub thread_id: u32,
     pub name: Option<String>,
@@ -249,7 +1081,7 @@ pub struct ThreadInfo {
     pub stack_size: u64,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExceptionInfo {
     pub thread_id: u32,
     pub code: u32,
@@ -258,7 +1090,7 @@ pub struct ExceptionInfo {
     pub number_parameters: u32,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct StackwalkReport {
     pub requesting_thread_id: Option<u32>,
     /// Search paths used for Breakpad .sym lookup.
@@ -275,7 +1107,7 @@ impl StackwalkReport {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ThreadStackTrace {
     pub thread_id: u32,
     pub thread_name: Option<String>,
@@ -284,7 +1116,7 @@ pub struct ThreadStackTrace {
     pub frames: Vec<StackFrameInfo>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StackFrameInfo {
     pub index: usize,
     pub instruction: u64,
@@ -298,9 +1130,174 @@ pub struct StackFrameInfo {
     pub trust: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Detection {
+    /// Id of the rule that fired (`exec.suspicious_artifact`): the same for every finding of
+    /// the rule, unlike `id()`.
+    #[serde(default)]
+    pub rule: String,
+    /// What the finding is about within its rule, from evidence that does not change between
+    /// analyses of the same dump (module name, region base, artifact text); empty for rules
+    /// that fire at most once per dump.
+    #[serde(default)]
+    pub key: String,
     pub severity: Severity,
     pub title: String,
     pub details: String,
+    /// MITRE ATT&CK technique ids (`T1055`, `T1059.001`); empty for crash findings.
+    #[serde(default)]
+    pub techniques: Vec<String>,
+    /// How sure the rule is of the finding, 0-100.
+    #[serde(default)]
+    pub confidence: u8,
+    /// Evidence: the report entities and addresses the detection is about.
+    pub links: Vec<EntityRef>,
+}
+
+impl Detection {
+    /// Stable id: the same finding in the same (or a re-ingested) dump gets the same id, so
+    /// triage state survives re-analysis, a different scan budget and rewording. Hex of the
+    /// first 8 bytes of SHA-256 over the rule and key (title and details for a detection
+    /// without a rule).
+    pub fn id(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let (a, b) = if self.rule.is_empty() {
+            (&self.title, &self.details)
+        } else {
+            (&self.rule, &self.key)
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(a.as_bytes());
+        hasher.update([0]);
+        hasher.update(b.as_bytes());
+        hasher.finalize()[..8]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    /// Tactic the detection is grouped under: the first tactic of its first technique.
+    pub fn tactic(&self) -> Option<Tactic> {
+        let id = self.techniques.first()?;
+        attack::technique(id)?.tactics.first().copied()
+    }
+
+    /// `links` resolved against the report they point into.
+    pub fn evidence(&self, report: &MinidumpReport) -> Vec<Evidence> {
+        self.links
+            .iter()
+            .map(|link| match *link {
+                EntityRef::Module(index) => {
+                    let m = report.modules.get(index);
+                    Evidence::Module {
+                        index,
+                        name: m.map(|m| m.name.clone()).unwrap_or_default(),
+                        version: m.and_then(|m| m.file_version.clone()),
+                    }
+                }
+                EntityRef::Thread(thread_id) => Evidence::Thread {
+                    thread_id,
+                    start: report
+                        .threads
+                        .iter()
+                        .find(|t| t.thread_id == thread_id)
+                        .and_then(|t| t.start_address)
+                        .map(|a| report.location(a)),
+                },
+                EntityRef::Region(base) => Evidence::Region {
+                    base,
+                    size: report
+                        .injected_regions
+                        .iter()
+                        .find(|r| r.base == base)
+                        .map(|r| r.size),
+                },
+                EntityRef::Address(address) => Evidence::Address {
+                    address,
+                    location: report.location(address),
+                },
+                EntityRef::ExecArtifact(index) => {
+                    let a = report.exec_artifacts.get(index);
+                    Evidence::ExecArtifact {
+                        index,
+                        image: a.map(|a| a.image.clone()).unwrap_or_default(),
+                        command_line: a.map(|a| a.command_line.clone()).unwrap_or_default(),
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// A `Detection::links` entry with what it points at, for exports.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Evidence {
+    Module {
+        index: usize,
+        name: String,
+        version: Option<String>,
+    },
+    Thread {
+        thread_id: u32,
+        /// Start address as `module+0xOFFSET`.
+        start: Option<String>,
+    },
+    Region {
+        base: u64,
+        size: Option<u64>,
+    },
+    Address {
+        address: u64,
+        /// `module+0xOFFSET`, or the address again outside every module.
+        location: String,
+    },
+    ExecArtifact {
+        index: usize,
+        image: String,
+        command_line: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region_detection(base: u64, details: &str) -> Detection {
+        Detection {
+            rule: "memory.injected_region".into(),
+            key: format!("0x{base:X}"),
+            severity: Severity::High,
+            title: "Suspicious executable memory allocation".into(),
+            details: details.into(),
+            techniques: vec!["T1055".into()],
+            confidence: 75,
+            links: vec![EntityRef::Region(base)],
+        }
+    }
+
+    #[test]
+    fn detection_ids_ignore_wording_and_counts() {
+        let a = region_detection(0x1000, "size=0x1000\nReasons:\n- private RWX");
+        let mut b = region_detection(0x1000, "size=0x2000\nReasons:\n- private RWX\n- no image");
+        b.title = "Executable private memory".into();
+        b.severity = Severity::Warning;
+        assert_eq!(a.id(), b.id());
+        assert_ne!(a.id(), region_detection(0x2000, &a.details).id());
+
+        let mut other_rule = a.clone();
+        other_rule.rule = "module.temp_path".into();
+        assert_ne!(a.id(), other_rule.id());
+    }
+
+    #[test]
+    fn detections_without_a_rule_keep_title_and_details_ids() {
+        let mut legacy = region_detection(0x1000, "size=0x1000");
+        legacy.rule.clear();
+        legacy.key.clear();
+        let mut reworded = legacy.clone();
+        reworded.details = "size=0x2000".into();
+        assert_eq!(legacy.id().len(), 16);
+        assert_ne!(legacy.id(), reworded.id());
+    }
 }
@@ -1,4 +1,8 @@
-#[derive(Clone, Debug, Default)]
+use serde::{Deserialize, Serialize};
+
+use crate::model::{AttrValue, render_attributes};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct MinidumpSummary {
     pub file_size: Option<u64>,
     pub time_date_stamp: Option<u32>,
@@ -10,38 +14,41 @@ pub struct MinidumpSummary {
 }
 
 impl MinidumpSummary {
-    pub fn pretty(&self) -> String {
-        let mut lines = Vec::new();
-
+    /// Summary fields as typed event attributes.
+    pub fn attributes(&self) -> Vec<(String, AttrValue)> {
+        let mut attrs: Vec<(String, AttrValue)> = Vec::new();
         if let Some(sz) = self.file_size {
-            lines.push(format!("File size: {sz} bytes"));
+            attrs.push(("file_size".into(), sz.into()));
         }
         if let Some(ts) = self.time_date_stamp {
-            lines.push(format!("TimeDateStamp (unix): {ts}"));
-            if let Some(utc) = crate::util::time::unix_seconds_to_utc_string(ts as u64) {
-                lines.push(format!("TimeDateStamp (utc): {utc}"));
-            }
+            let value = time::OffsetDateTime::from_unix_timestamp(ts.into())
+                .map_or(AttrValue::UInt(ts.into()), AttrValue::Time);
+            attrs.push(("time_date_stamp".into(), value));
         }
         if let Some(os) = &self.os {
-            lines.push(format!("OS: {os}"));
+            attrs.push(("os".into(), os.as_str().into()));
         }
         if let Some(cpu) = &self.cpu {
-            lines.push(format!("CPU: {cpu}"));
+            attrs.push(("cpu".into(), cpu.as_str().into()));
         }
         if let Some(n) = self.thread_count {
-            lines.push(format!("Threads: {n}"));
+            attrs.push(("threads".into(), n.into()));
         }
         if let Some(n) = self.module_count {
-            lines.push(format!("Modules: {n}"));
+            attrs.push(("modules".into(), n.into()));
         }
         if let Some(e) = &self.exception {
-            lines.push(format!("Exception: {e}"));
+            attrs.push(("exception".into(), e.as_str().into()));
         }
+        attrs
+    }
 
-        if lines.is_empty() {
+    pub fn pretty(&self) -> String {
+        let attrs = self.attributes();
+        if attrs.is_empty() {
             "<no summary>".into()
         } else {
-            lines.join("\n")
+            render_attributes(&attrs)
         }
     }
 }
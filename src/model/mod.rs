@@ -1,13 +1,20 @@
+pub mod attack;
 mod event;
 mod minidump_report;
 mod minidump_summary;
+mod process_tree;
 mod store;
+mod wer;
 
-pub use event::{Event, EventId, Severity};
+pub use event::{AttrValue, EntityRef, Event, EventId, Severity, derive_t_ms, render_attributes};
 pub use minidump_report::{
-    Detection, ExceptionInfo, ExecArtifactEncoding, InjectedRegion, MinidumpReport, ModuleInfo,
-    ProcessExecArtifact, ProcessInfo, StackFrameInfo, StackwalkReport, ThreadInfo,
-    ThreadStackTrace,
+    Annotation, AnnotationKind, AnnotationOp, AnnotationRule, AuxvEntry, CrashpadInfo, DecodeStep,
+    Detection, Evidence, ExceptionInfo, ExecArtifactEncoding, Indicator, InjectedRegion, IocKind,
+    LinuxDso, LinuxDsoDebug, LinuxMapping, LinuxProcessStreams, MemorySpan, MinidumpReport,
+    ModuleInfo, ProcessExecArtifact, ProcessInfo, ScanCoverage, SecretKind, SensitiveFinding,
+    StackFrameInfo, StackwalkReport, ThreadInfo, ThreadStackTrace,
 };
 pub use minidump_summary::MinidumpSummary;
-pub use store::EventStore;
+pub use process_tree::{ProcessNode, ProcessTree, SysmonProcessRef, SysmonRecord};
+pub use store::{EventStore, next_generation};
+pub use wer::{CrashSignature, WerMismatch, WerReport};
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+/// A Sysmon event normalized into a typed record.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SysmonRecord {
+    /// Event ID 1.
+    ProcessCreate {
+        process: SysmonProcessRef,
+        command_line: String,
+        parent: SysmonProcessRef,
+        parent_command_line: String,
+        user: String,
+        hashes: Vec<(String, String)>,
+    },
+    /// Event ID 3.
+    NetworkConnect {
+        process: SysmonProcessRef,
+        protocol: String,
+        source: String,
+        destination: String,
+        destination_hostname: String,
+    },
+    /// Event ID 7.
+    ImageLoad {
+        process: SysmonProcessRef,
+        image_loaded: String,
+        signed: String,
+        hashes: Vec<(String, String)>,
+    },
+    /// Event ID 8.
+    CreateRemoteThread {
+        source: SysmonProcessRef,
+        target: SysmonProcessRef,
+        start_address: String,
+        start_module: String,
+    },
+    /// Event ID 10.
+    ProcessAccess {
+        source: SysmonProcessRef,
+        target: SysmonProcessRef,
+        granted_access: String,
+    },
+    /// Event ID 11.
+    FileCreate {
+        process: SysmonProcessRef,
+        target_filename: String,
+    },
+}
+
+/// Identity of a process as Sysmon reports it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SysmonProcessRef {
+    pub guid: String,
+    pub pid: Option<u32>,
+    pub image: String,
+}
+
+impl SysmonRecord {
+    pub fn event_id(&self) -> u32 {
+        match self {
+            SysmonRecord::ProcessCreate { .. } => 1,
+            SysmonRecord::NetworkConnect { .. } => 3,
+            SysmonRecord::ImageLoad { .. } => 7,
+            SysmonRecord::CreateRemoteThread { .. } => 8,
+            SysmonRecord::ProcessAccess { .. } => 10,
+            SysmonRecord::FileCreate { .. } => 11,
+        }
+    }
+
+    /// The process the record is attributed to (the actor, for cross-process records).
+    pub fn process(&self) -> &SysmonProcessRef {
+        match self {
+            SysmonRecord::ProcessCreate { process, .. }
+            | SysmonRecord::NetworkConnect { process, .. }
+            | SysmonRecord::ImageLoad { process, .. }
+            | SysmonRecord::FileCreate { process, .. } => process,
+            SysmonRecord::CreateRemoteThread { source, .. }
+            | SysmonRecord::ProcessAccess { source, .. } => source,
+        }
+    }
+
+    /// One-line description for the timeline.
+    pub fn title(&self) -> String {
+        match self {
+            SysmonRecord::ProcessCreate { process, .. } => {
+                format!("Process create: {}", short_image(&process.image))
+            }
+            SysmonRecord::NetworkConnect {
+                process,
+                destination,
+                destination_hostname,
+                ..
+            } => {
+                let dest = if destination_hostname.is_empty() {
+                    destination.clone()
+                } else {
+                    format!("{destination_hostname} ({destination})")
+                };
+                format!("Network connect: {} -> {dest}", short_image(&process.image))
+            }
+            SysmonRecord::ImageLoad {
+                process,
+                image_loaded,
+                ..
+            } => format!(
+                "Image load: {} in {}",
+                short_image(image_loaded),
+                short_image(&process.image)
+            ),
+            SysmonRecord::CreateRemoteThread { source, target, .. } => format!(
+                "CreateRemoteThread: {} -> {}",
+                short_image(&source.image),
+                short_image(&target.image)
+            ),
+            SysmonRecord::ProcessAccess {
+                source,
+                target,
+                granted_access,
+            } => format!(
+                "Process access: {} -> {} ({granted_access})",
+                short_image(&source.image),
+                short_image(&target.image)
+            ),
+            SysmonRecord::FileCreate {
+                process,
+                target_filename,
+            } => format!(
+                "File create: {} by {}",
+                target_filename,
+                short_image(&process.image)
+            ),
+        }
+    }
+}
+
+/// Parent/child process tree rebuilt from Sysmon process-create events.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProcessTree {
+    pub nodes: Vec<ProcessNode>,
+    /// Indices of nodes without a known parent.
+    pub roots: Vec<usize>,
+    pub records: Vec<SysmonRecord>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProcessNode {
+    pub guid: String,
+    pub pid: Option<u32>,
+    pub image: String,
+    pub command_line: String,
+    pub user: String,
+    pub hashes: Vec<(String, String)>,
+    /// Unix epoch seconds of the process-create event, if it was observed.
+    pub start_unix: Option<i64>,
+    /// `false` for parents only known from a child's `Parent*` fields.
+    pub observed: bool,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// Indices into `ProcessTree::records` of non-create events attributed to this process.
+    pub activity: Vec<usize>,
+}
+
+impl ProcessTree {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Best node for a PID: PIDs are reused, so prefer the latest process started at or
+    /// before `started_unix` (the crashing process's create time), else the latest one.
+    pub fn find_pid(&self, pid: u32, started_unix: Option<u64>) -> Option<usize> {
+        let candidates = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.pid == Some(pid));
+        let by_start = |(_, n): &(usize, &ProcessNode)| n.start_unix.unwrap_or(i64::MIN);
+        if let Some(created) = started_unix {
+            // Sysmon's UtcTime and the dump's create time can differ by a second or two.
+            let limit = created as i64 + 2;
+            if let Some((idx, _)) = candidates
+                .clone()
+                .filter(|(_, n)| n.start_unix.is_none_or(|s| s <= limit))
+                .max_by_key(by_start)
+            {
+                return Some(idx);
+            }
+        }
+        candidates.max_by_key(by_start).map(|(idx, _)| idx)
+    }
+
+    /// `idx` and its ancestors, root last.
+    pub fn ancestry(&self, idx: usize) -> Vec<usize> {
+        let mut out = vec![idx];
+        let mut cur = idx;
+        while let Some(p) = self.nodes[cur].parent {
+            if out.contains(&p) {
+                break;
+            }
+            out.push(p);
+            cur = p;
+        }
+        out
+    }
+
+    /// All descendants of `idx` in depth-first order.
+    pub fn descendants(&self, idx: usize) -> Vec<usize> {
+        let mut out = Vec::new();
+        let mut stack: Vec<usize> = self.nodes[idx].children.iter().rev().copied().collect();
+        while let Some(n) = stack.pop() {
+            if out.contains(&n) {
+                continue;
+            }
+            out.push(n);
+            stack.extend(self.nodes[n].children.iter().rev());
+        }
+        out
+    }
+}
+
+fn short_image(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
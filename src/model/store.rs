@@ -1,9 +1,56 @@
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use crate::model::{Event, EventId, Severity};
 
+/// Source of `EventStore::generation`: unique across stores, so a cache keyed on it cannot
+/// mistake a freshly loaded store for the one it was built from.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// A generation no store or merged timeline has had yet.
+pub fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Append-only event list with the indexes the UI and queries need at log scale. Events are
+/// addressed by their position in insertion (source) order; every index holds positions.
 #[derive(Default)]
 pub struct EventStore {
     events: Vec<Event>,
     next_id: u64,
+    by_id: HashMap<EventId, usize>,
+    /// Time-ordered indexes; empty after an out-of-order push until the next query sorts them.
+    time_index: OnceCell<TimeIndex>,
+    by_source: BTreeMap<String, Vec<usize>>,
+    /// Lowercased title, details, source and attribute keys/values, NUL-separated: the text
+    /// bare query words search.
+    search_text: Vec<Box<str>>,
+    generation: u64,
+}
+
+#[derive(Default)]
+struct TimeIndex {
+    /// Ordered by `t_ms`, ties in insertion order.
+    by_time: Vec<usize>,
+    /// One list per `Severity`, ordered like `by_time`.
+    by_severity: [Vec<usize>; 3],
+}
+
+impl TimeIndex {
+    /// One stable sort of every position, instead of an insert per event.
+    fn build(events: &[Event]) -> Self {
+        let mut by_time: Vec<usize> = (0..events.len()).collect();
+        by_time.sort_by_key(|&p| events[p].t_ms);
+        let mut by_severity: [Vec<usize>; 3] = Default::default();
+        for &p in &by_time {
+            by_severity[events[p].severity as usize].push(p);
+        }
+        Self {
+            by_time,
+            by_severity,
+        }
+    }
 }
 
 impl EventStore {
@@ -11,30 +58,117 @@ impl EventStore {
         self.events.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events in insertion order.
     pub fn iter(&self) -> impl Iterator<Item = &Event> {
         self.events.iter()
     }
 
+    /// Events in insertion order, as a slice.
+    pub fn as_slice(&self) -> &[Event] {
+        &self.events
+    }
+
     pub fn get(&self, id: EventId) -> Option<&Event> {
-        self.events.iter().find(|e| e.id == id)
+        self.position(id).map(|pos| &self.events[pos])
+    }
+
+    pub fn position(&self, id: EventId) -> Option<usize> {
+        self.by_id.get(&id).copied()
+    }
+
+    /// Event at insertion position `pos` (as found in the indexes).
+    pub fn at(&self, pos: usize) -> &Event {
+        &self.events[pos]
     }
 
     pub fn first_id(&self) -> Option<EventId> {
         self.events.first().map(|e| e.id)
     }
 
+    /// Positions ordered by `t_ms`.
+    pub fn by_time(&self) -> &[usize] {
+        &self.time_index().by_time
+    }
+
+    /// Positions of `severity` events ordered by `t_ms`.
+    pub fn by_severity(&self, severity: Severity) -> &[usize] {
+        &self.time_index().by_severity[severity as usize]
+    }
+
+    fn time_index(&self) -> &TimeIndex {
+        self.time_index
+            .get_or_init(|| TimeIndex::build(&self.events))
+    }
+
+    /// Distinct sources with their event positions (insertion order).
+    pub fn by_source(&self) -> impl Iterator<Item = (&str, &[usize])> {
+        self.by_source
+            .iter()
+            .map(|(s, positions)| (s.as_str(), positions.as_slice()))
+    }
+
+    /// The `t_ms` range `[from, to)` of `positions` (a time-ordered index).
+    pub fn time_range<'a>(&self, positions: &'a [usize], from: u64, to: u64) -> &'a [usize] {
+        let lo = positions.partition_point(|&p| self.events[p].t_ms < from);
+        let hi = positions.partition_point(|&p| self.events[p].t_ms < to);
+        &positions[lo..hi.max(lo)]
+    }
+
+    pub fn max_t_ms(&self) -> u64 {
+        self.by_time().last().map_or(0, |&p| self.events[p].t_ms)
+    }
+
+    /// Lowercased searchable text of the event at `pos`.
+    pub fn search_text(&self, pos: usize) -> &str {
+        &self.search_text[pos]
+    }
+
+    /// Changes whenever an event is added; caches of filtered rows compare it.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     pub fn push(&mut self, mut event: Event) -> EventId {
         if event.id.0 == 0 {
             event.id = EventId(self.next_id.max(1));
         }
         self.next_id = self.next_id.max(event.id.0 + 1);
         let id = event.id;
+        let pos = self.events.len();
+
+        // Lookups by a duplicated id keep finding the first event, as the linear scan did.
+        self.by_id.entry(id).or_insert(pos);
+        // Events mostly arrive in time order and are appended; an older one leaves the indexes
+        // to be sorted again when next queried.
+        if let Some(index) = self.time_index.get_mut() {
+            let latest = index.by_time.last().map(|&p| self.events[p].t_ms);
+            if latest.is_none_or(|t| t <= event.t_ms) {
+                index.by_time.push(pos);
+                index.by_severity[event.severity as usize].push(pos);
+            } else {
+                self.time_index = OnceCell::new();
+            }
+        }
+        self.by_source
+            .entry(event.source.clone())
+            .or_default()
+            .push(pos);
+        self.search_text.push(search_text(&event).into_boxed_str());
+        self.generation = next_generation();
+
         self.events.push(event);
         id
     }
 
+    /// Store of `events`; the time indexes are sorted once, on first use.
     pub fn from_events(events: Vec<Event>) -> Self {
         let mut s = Self::default();
+        s.events.reserve(events.len());
+        s.search_text.reserve(events.len());
         for ev in events {
             s.push(ev);
         }
@@ -52,6 +186,9 @@ impl EventStore {
             details: "UI scaffold is running. Minidump parsing will plug into this event stream."
                 .into(),
             source: "runtime".into(),
+            timestamp: None,
+            attributes: Vec::new(),
+            links: Vec::new(),
         });
 
         s.push(Event {
@@ -62,6 +199,9 @@ impl EventStore {
             details: "Placeholder example event. Later: correlate minidump modules, signatures, and known-bad hashes."
                 .into(),
             source: "detector::modules".into(),
+            timestamp: None,
+            attributes: Vec::new(),
+            links: Vec::new(),
         });
 
         s.push(Event {
@@ -73,8 +213,19 @@ impl EventStore {
                 "Placeholder example event. Later: stack + memory heuristics to flag exploitation patterns."
                     .into(),
             source: "detector::heuristics".into(),
+            timestamp: None,
+            attributes: Vec::new(),
+            links: Vec::new(),
         });
 
         s
     }
 }
+
+fn search_text(ev: &Event) -> String {
+    let mut text = format!("{}\0{}\0{}", ev.title, ev.details, ev.source);
+    for (k, v) in &ev.attributes {
+        text.push_str(&format!("\0{k}\0{v}"));
+    }
+    text.to_lowercase()
+}
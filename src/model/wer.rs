@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+/// A parsed Windows Error Reporting `Report.wer` file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WerReport {
+    /// `APPCRASH`, `BEX64`, `APPHANG`, ...
+    pub event_type: Option<String>,
+    /// Friendly application name (`AppName`).
+    pub app_name: Option<String>,
+    pub app_path: Option<String>,
+    pub report_id: Option<String>,
+    /// `EventTime` (FILETIME) as unix seconds.
+    pub event_time_unix: Option<u64>,
+    /// `OS Version` dynamic signature parameter.
+    pub os_version: Option<String>,
+    /// `Response.BucketId` (or the legacy bucket when that is all there is).
+    pub bucket: Option<String>,
+    pub bucket_table: Option<String>,
+    /// `Sig[n]` name/value pairs, in index order.
+    pub signature: Vec<(String, String)>,
+    /// `DynamicSig[n]` name/value pairs.
+    pub dynamic_signature: Vec<(String, String)>,
+    pub loaded_modules: Vec<String>,
+    /// Every `key=value` line, in file order.
+    pub fields: Vec<(String, String)>,
+    /// Files found next to `Report.wer` (archive or folder), for the case summary.
+    pub files: Vec<String>,
+}
+
+/// The WER-style crash signature computed from a dump, for comparison with `Report.wer`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CrashSignature {
+    pub app_name: Option<String>,
+    pub app_version: Option<String>,
+    pub app_timestamp: Option<u32>,
+    /// Module containing the exception address; `None` when it lies outside every module.
+    pub fault_module: Option<String>,
+    pub fault_module_version: Option<String>,
+    pub fault_module_timestamp: Option<u32>,
+    pub exception_code: Option<u32>,
+    /// Exception address relative to the fault module base (absolute when there is none).
+    pub exception_offset: Option<u64>,
+}
+
+impl CrashSignature {
+    /// Known parameters, named as WER names its `Sig[n]` entries.
+    pub fn parameters(&self) -> Vec<(&'static str, String)> {
+        let mut out = Vec::new();
+        let mut push = |name, value: Option<String>| {
+            if let Some(v) = value {
+                out.push((name, v));
+            }
+        };
+        push("Application Name", self.app_name.clone());
+        push("Application Version", self.app_version.clone());
+        push(
+            "Application Timestamp",
+            self.app_timestamp.map(|t| format!("{t:08x}")),
+        );
+        push(
+            "Fault Module Name",
+            Some(
+                self.fault_module
+                    .clone()
+                    .unwrap_or_else(|| "unknown".into()),
+            ),
+        );
+        push("Fault Module Version", self.fault_module_version.clone());
+        push(
+            "Fault Module Timestamp",
+            self.fault_module_timestamp.map(|t| format!("{t:08x}")),
+        );
+        push(
+            "Exception Code",
+            self.exception_code.map(|c| format!("{c:08x}")),
+        );
+        push(
+            "Exception Offset",
+            self.exception_offset.map(|o| format!("{o:016x}")),
+        );
+        out
+    }
+}
+
+/// A signature parameter on which WER and the loaded dump disagree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WerMismatch {
+    pub parameter: String,
+    pub wer: String,
+    pub dump: String,
+}
+
+impl WerReport {
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn signature_value(&self, name: &str) -> Option<&str> {
+        self.signature
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Compare the `Sig[n]` parameters WER bucketed on with the signature computed from the
+    /// dump. Parameters only one side knows are skipped. Readable (non-hash) buckets must
+    /// mention the dump's fault module.
+    pub fn compare(&self, ours: &CrashSignature) -> Vec<WerMismatch> {
+        let mut out = Vec::new();
+        for (name, dump) in ours.parameters() {
+            let Some(wer) = self.signature_value(name) else {
+                continue;
+            };
+            let same = match name {
+                "Application Timestamp"
+                | "Fault Module Timestamp"
+                | "Exception Code"
+                | "Exception Offset" => {
+                    parse_hex(wer).is_some() && parse_hex(wer) == parse_hex(&dump)
+                }
+                "Application Name" | "Fault Module Name" => {
+                    base_name(wer).eq_ignore_ascii_case(base_name(&dump))
+                }
+                _ => wer.trim().eq_ignore_ascii_case(dump.trim()),
+            };
+            if !same {
+                out.push(WerMismatch {
+                    parameter: name.to_string(),
+                    wer: wer.to_string(),
+                    dump,
+                });
+            }
+        }
+
+        if let (Some(bucket), Some(module)) = (&self.bucket, &ours.fault_module)
+            && is_readable_bucket(bucket)
+        {
+            let stem = base_name(module)
+                .rsplit_once('.')
+                .map_or(base_name(module), |(stem, _)| stem)
+                .to_ascii_lowercase();
+            if !bucket.to_ascii_lowercase().contains(&stem) {
+                out.push(WerMismatch {
+                    parameter: "Bucket".into(),
+                    wer: bucket.clone(),
+                    dump: format!("fault module {module}"),
+                });
+            }
+        }
+        out
+    }
+}
+
+/// Hashed buckets (numbers, hex, GUIDs) say nothing about the crash location.
+fn is_readable_bucket(bucket: &str) -> bool {
+    !bucket
+        .chars()
+        .all(|c| c.is_ascii_hexdigit() || c == '-' || c == '{' || c == '}')
+}
+
+fn base_name(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path).trim()
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u64::from_str_radix(s, 16).ok()
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+const HISTORY_ENV_VAR: &str = "LOG_ATLAS_QUERY_HISTORY";
+const MAX_ENTRIES: usize = 100;
+
+/// Saved queries, most recent first. Each entry belongs to a scope (the filter box it was
+/// saved from: `timeline`, `modules`, ...). Stored one `scope<TAB>query` per line in
+/// `LOG_ATLAS_QUERY_HISTORY`, else `log-atlas/queries.txt` in the user's config directory.
+#[derive(Debug, Default)]
+pub struct QueryHistory {
+    pub entries: Vec<(String, String)>,
+    path: Option<PathBuf>,
+}
+
+impl QueryHistory {
+    /// Load the history file; a missing or unreadable file gives an empty history.
+    pub fn load() -> Self {
+        let path = crate::util::config_file(HISTORY_ENV_VAR, "queries.txt");
+        let entries = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|text| {
+                text.lines()
+                    .filter_map(|l| l.split_once('\t'))
+                    .filter(|(_, q)| !q.trim().is_empty())
+                    .map(|(s, q)| (s.to_string(), q.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    /// Saved queries of `scope`, most recent first.
+    pub fn for_scope<'a>(&'a self, scope: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(s, _)| s == scope)
+            .map(|(_, q)| q.as_str())
+    }
+
+    /// Save `query` under `scope`, moving an identical entry to the front.
+    pub fn save(&mut self, scope: &str, query: &str) -> Result<()> {
+        let query = query.trim().replace(['\n', '\r', '\t'], " ");
+        if query.is_empty() {
+            return Ok(());
+        }
+        self.entries.retain(|(s, q)| !(s == scope && *q == query));
+        self.entries.insert(0, (scope.to_string(), query));
+        self.entries.truncate(MAX_ENTRIES);
+        self.write()
+    }
+
+    pub fn remove(&mut self, scope: &str, query: &str) -> Result<()> {
+        self.entries.retain(|(s, q)| !(s == scope && q == query));
+        self.write()
+    }
+
+    fn write(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let text: String = self
+            .entries
+            .iter()
+            .map(|(s, q)| format!("{s}\t{q}\n"))
+            .collect();
+        std::fs::write(path, text).with_context(|| format!("write {}", path.display()))
+    }
+}
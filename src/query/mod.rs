@@ -0,0 +1,451 @@
+//! A small query language shared by the timeline, the report tabs and the CLI.
+//!
+//! `severity>=warn AND source:detector::* AND module~"temp"`, `addr in 0x7ff0..0x7fff`,
+//! `NOT (pid=4 OR image:*svchost*)`. Bare words search every text field, like the old
+//! substring filters. See `docs/DATA_FORMATS.md` ("Queries") for the full syntax.
+
+mod history;
+mod parse;
+mod records;
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Range;
+
+use regex::Regex;
+use time::OffsetDateTime;
+
+use crate::model::{AttrValue, Severity};
+
+pub use history::QueryHistory;
+pub use records::{
+    AnnotationRecord, ArtifactRecord, DetectionRecord, EventRecord, IndicatorRecord, LinkRecord,
+    ModuleRecord, ProcessRecord, RegionRecord, StackRecord, ThreadRecord,
+};
+
+/// Something a query can be evaluated against: an event or a row of a report table.
+pub trait Record {
+    /// Value of `name` (lowercase), if the record has that field. Multi-valued fields are
+    /// `AttrValue::List`; a comparison matches when any element does.
+    fn field(&self, name: &str) -> Option<AttrValue>;
+
+    /// Call `visit` with each text searched by bare words until it returns `true`.
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool;
+
+    /// Every text `any_text` visits, already lowercased and joined, when the record has one
+    /// prebuilt. Bare words then search it without lowercasing per query.
+    fn lowercase_text(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// A parsed query. The empty query matches everything.
+#[derive(Clone, Debug)]
+pub struct Query {
+    expr: Option<Expr>,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Clone, Debug)]
+enum Term {
+    /// Bare word: case-insensitive substring of any text field. Stored lowercase.
+    Text(String),
+    Compare {
+        field: String,
+        op: Op,
+        value: Box<Literal>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    /// `:` case-insensitive match with `*` / `?` wildcards.
+    Glob,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// `~` case-insensitive regex search.
+    Regex,
+    NotRegex,
+    /// `in low..high`, inclusive.
+    In,
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Glob => ":",
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Regex => "~",
+            Op::NotRegex => "!~",
+            Op::In => "in",
+        }
+    }
+}
+
+/// A comparison value with every interpretation the operators may need.
+#[derive(Clone, Debug)]
+struct Literal {
+    text: String,
+    lower: String,
+    number: Option<i128>,
+    severity: Option<Severity>,
+    time: Option<OffsetDateTime>,
+    regex: Option<Regex>,
+    range: Option<(i128, i128)>,
+}
+
+/// A syntax error with the byte range of the offending text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl QueryError {
+    fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// 1-based character column of the error in `query`.
+    pub fn column(&self, query: &str) -> usize {
+        query
+            .get(..self.span.start)
+            .map_or(self.span.start, |s| s.chars().count())
+            + 1
+    }
+
+    /// `query` with a caret line under the error, for terminals.
+    pub fn annotate(&self, query: &str) -> String {
+        let col = self.column(query);
+        let width = query
+            .get(self.span.clone())
+            .map_or(1, |s| s.chars().count().max(1));
+        format!(
+            "{query}\n{}{} {}",
+            " ".repeat(col - 1),
+            "^".repeat(width),
+            self.message
+        )
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at offset {})", self.message, self.span.start)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        Ok(Self {
+            expr: parse::Parser::new(text).parse()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expr.is_none()
+    }
+
+    pub fn matches(&self, record: &dyn Record) -> bool {
+        self.expr.as_ref().is_none_or(|e| eval(e, record))
+    }
+}
+
+fn eval(expr: &Expr, record: &dyn Record) -> bool {
+    match expr {
+        Expr::And(a, b) => eval(a, record) && eval(b, record),
+        Expr::Or(a, b) => eval(a, record) || eval(b, record),
+        Expr::Not(e) => !eval(e, record),
+        Expr::Term(Term::Text(needle)) => match record.lowercase_text() {
+            Some(text) => text.contains(needle.as_str()),
+            None => record.any_text(&mut |s| contains_ignore_case(s, needle)),
+        },
+        Expr::Term(Term::Compare { field, op, value }) => {
+            // A field the record does not have never matches, whatever the operator. Negated
+            // operators on lists mean "no element matches".
+            let Some(v) = record.field(field) else {
+                return false;
+            };
+            match op {
+                Op::Ne => !any_value(&v, Op::Eq, value),
+                Op::NotRegex => !any_value(&v, Op::Regex, value),
+                op => any_value(&v, *op, value),
+            }
+        }
+    }
+}
+
+fn any_value(v: &AttrValue, op: Op, lit: &Literal) -> bool {
+    match v {
+        AttrValue::List(items) => items.iter().any(|i| any_value(i, op, lit)),
+        v => compare(v, op, lit),
+    }
+}
+
+fn compare(v: &AttrValue, op: Op, lit: &Literal) -> bool {
+    match op {
+        Op::Regex => lit
+            .regex
+            .as_ref()
+            .is_some_and(|re| re.is_match(&v.to_string())),
+        Op::In => lit
+            .range
+            .zip(number(v))
+            .is_some_and(|((lo, hi), n)| lo <= n && n <= hi),
+        Op::Glob | Op::Eq => match typed_cmp(v, lit) {
+            Some(o) => o == Ordering::Equal,
+            None if op == Op::Glob => glob_match(&lit.lower, &v.to_string().to_lowercase()),
+            None => v.to_string().to_lowercase() == lit.lower,
+        },
+        Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+            let o =
+                typed_cmp(v, lit).unwrap_or_else(|| v.to_string().to_lowercase().cmp(&lit.lower));
+            match op {
+                Op::Lt => o == Ordering::Less,
+                Op::Le => o != Ordering::Greater,
+                Op::Gt => o == Ordering::Greater,
+                _ => o != Ordering::Less,
+            }
+        }
+        Op::Ne => !compare(v, Op::Eq, lit),
+        Op::NotRegex => !compare(v, Op::Regex, lit),
+    }
+}
+
+/// Severity, numeric or time comparison when both sides have that interpretation.
+fn typed_cmp(v: &AttrValue, lit: &Literal) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (v.as_str().and_then(parse::parse_severity), lit.severity) {
+        return Some(a.cmp(&b));
+    }
+    if let (Some(a), Some(b)) = (number(v), lit.number) {
+        return Some(a.cmp(&b));
+    }
+    if let (AttrValue::Time(a), Some(b)) = (v, lit.time) {
+        return Some(a.cmp(&b));
+    }
+    None
+}
+
+fn number(v: &AttrValue) -> Option<i128> {
+    match v {
+        AttrValue::Int(i) => Some((*i).into()),
+        AttrValue::UInt(u) | AttrValue::Addr(u) => Some((*u).into()),
+        AttrValue::Str(s) => parse::parse_number(s),
+        AttrValue::Time(_) | AttrValue::List(_) => None,
+    }
+}
+
+fn contains_ignore_case(haystack: &str, needle_lower: &str) -> bool {
+    needle_lower.is_empty() || haystack.to_lowercase().contains(needle_lower)
+}
+
+/// `*` matches any run of characters, `?` exactly one. Both inputs are lowercase.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ti = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// The text of a filter box and its parsed query, re-parsed only when the text changes.
+#[derive(Default)]
+pub struct QueryFilter {
+    pub text: String,
+    compiled: Option<(String, Result<Query, QueryError>)>,
+}
+
+impl QueryFilter {
+    /// Parse `text` if it changed since the last call.
+    pub fn compile(&mut self) {
+        if self.compiled.as_ref().is_none_or(|(t, _)| *t != self.text) {
+            self.compiled = Some((self.text.clone(), Query::parse(&self.text)));
+        }
+    }
+
+    /// Last compiled query; `None` while the text has a syntax error or was not compiled.
+    pub fn query(&self) -> Option<&Query> {
+        self.compiled.as_ref()?.1.as_ref().ok()
+    }
+
+    pub fn error(&self) -> Option<&QueryError> {
+        self.compiled.as_ref()?.1.as_ref().err()
+    }
+
+    /// Whether `record` passes the filter. A query with a syntax error filters nothing.
+    pub fn matches(&self, record: &dyn Record) -> bool {
+        self.query().is_none_or(|q| q.matches(record))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.query().is_some_and(|q| !q.is_empty())
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A record with named fields; bare words search their text.
+    struct Row(Vec<(&'static str, AttrValue)>);
+
+    impl Record for Row {
+        fn field(&self, name: &str) -> Option<AttrValue> {
+            self.0
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| v.clone())
+        }
+
+        fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+            self.0.iter().any(|(_, v)| visit(&v.to_string()))
+        }
+    }
+
+    fn matches(query: &str, row: &Row) -> bool {
+        Query::parse(query).expect("query parses").matches(row)
+    }
+
+    fn text(words: &'static str) -> Row {
+        Row(vec![("message", AttrValue::Str(words.into()))])
+    }
+
+    fn tags() -> Row {
+        Row(vec![(
+            "tags",
+            AttrValue::List(vec![
+                AttrValue::Str("alpha".into()),
+                AttrValue::Str("beta".into()),
+            ]),
+        )])
+    }
+
+    #[test]
+    fn precedence_and_parentheses() {
+        // `a OR (b AND c)`.
+        assert!(matches("a OR b AND c", &text("a")));
+        assert!(!matches("a OR b AND c", &text("b")));
+        assert!(matches("a OR b AND c", &text("b c")));
+        // `(a OR b) AND c`.
+        assert!(!matches("(a OR b) AND c", &text("a")));
+        assert!(matches("(a OR b) c", &text("b c")));
+        assert!(matches("NOT (x OR (y AND NOT z))", &text("y z")));
+        assert!(!matches("NOT (x OR (y AND NOT z))", &text("y")));
+    }
+
+    #[test]
+    fn ranges_are_inclusive() {
+        let row = Row(vec![
+            ("addr", AttrValue::Addr(0x7ff5)),
+            ("pid", AttrValue::Str("42".into())),
+        ]);
+        assert!(matches("addr in 0x7ff0..0x7fff", &row));
+        assert!(matches("addr in 0x7ff5..0x7ff5", &row));
+        assert!(!matches("addr in 0x7ff6..0x7fff", &row));
+        assert!(matches("pid in 40..42", &row));
+        assert!(!matches("missing in 0..100", &row));
+    }
+
+    #[test]
+    fn negated_operators_on_lists() {
+        let row = tags();
+        assert!(matches("tags=beta", &row));
+        // `!=` / `!~` hold when no element matches.
+        assert!(!matches("tags!=alpha", &row));
+        assert!(matches("tags!=gamma", &row));
+        assert!(!matches("tags!~^b", &row));
+        assert!(matches("tags!~^g", &row));
+        assert!(matches("tags:ALP*", &row));
+        // A missing field matches neither an operator nor its negation.
+        assert!(!matches("other!=alpha", &row));
+        assert!(!matches("other!~x", &row));
+    }
+
+    #[test]
+    fn glob_corner_cases() {
+        for (pattern, text, expected) in [
+            ("", "", true),
+            ("", "a", false),
+            ("*", "", true),
+            ("**", "abc", true),
+            ("?", "", false),
+            ("?", "é", true),
+            ("a*", "a", true),
+            ("*a", "ba", true),
+            ("*ab", "aab", true),
+            ("a*b", "abc", false),
+            ("a*b*c", "axxbyyc", true),
+            ("a*b*c", "axxbyy", false),
+            ("?*?", "ab", true),
+            ("?*?", "a", false),
+            ("*x*", "yyy", false),
+            ("a?c", "abbc", false),
+            ("*.dll", "c:\\windows\\kernel32.dll", true),
+        ] {
+            assert_eq!(
+                glob_match(pattern, text),
+                expected,
+                "glob_match({pattern:?}, {text:?})"
+            );
+        }
+    }
+
+    #[test]
+    fn error_column_counts_characters() {
+        let query = "é ü (a";
+        let err = Query::parse(query).unwrap_err();
+        assert_eq!(err.span, 6..7);
+        assert_eq!(err.column(query), 5);
+        assert_eq!(err.annotate(query), "é ü (a\n    ^ unclosed `(`");
+
+        let query = "名前~日(";
+        let err = Query::parse(query).unwrap_err();
+        assert_eq!(err.column(query), 4);
+        let annotated = err.annotate(query);
+        assert!(annotated.starts_with("名前~日(\n   ^^ invalid regex"));
+        assert!(annotated.ends_with("unclosed group"));
+        // A span that does not fall on a character boundary falls back to the byte offset.
+        let err = QueryError::new("x", 1..2);
+        assert_eq!(err.column("é"), 2);
+    }
+}
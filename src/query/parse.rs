@@ -0,0 +1,434 @@
+use regex::Regex;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, OffsetDateTime, PrimitiveDateTime};
+
+use super::{Expr, Literal, Op, QueryError, Term};
+use crate::model::Severity;
+
+/// Recursive-descent parser over the query text; positions are byte offsets into it.
+pub(super) struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+/// Characters that end a field name and start an operator.
+const OP_CHARS: &[char] = &[':', '=', '!', '<', '>', '~'];
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    /// `or EOF`; `None` for an empty (match-all) query.
+    pub(super) fn parse(mut self) -> Result<Option<Expr>, QueryError> {
+        self.skip_ws();
+        if self.at_end() {
+            return Ok(None);
+        }
+        let expr = self.or()?;
+        self.skip_ws();
+        if !self.at_end() {
+            return Err(self.error_here("unexpected `)`"));
+        }
+        Ok(Some(expr))
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.and()?;
+        while self.keyword("or") {
+            let rhs = self.and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    /// Terms next to each other are AND-ed; the `AND` keyword is optional.
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.unary()?;
+        loop {
+            self.skip_ws();
+            if self.at_end() || self.peek() == Some(')') || self.peek_keyword("or") {
+                return Ok(lhs);
+            }
+            self.keyword("and");
+            let rhs = self.unary()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        self.skip_ws();
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if let Some('-' | '!') = self.peek()
+            && self
+                .src
+                .get(self.pos + 1..)
+                .and_then(|r| r.chars().next())
+                .is_some_and(|c| !c.is_whitespace() && c != ')')
+        {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, QueryError> {
+        self.skip_ws();
+        match self.peek() {
+            None => Err(self.error_here("expected a term")),
+            Some(')') => Err(self.error_here("unexpected `)`")),
+            Some('(') => {
+                let open = self.pos;
+                self.pos += 1;
+                self.skip_ws();
+                if self.peek() == Some(')') {
+                    return Err(self.error_here("empty parentheses"));
+                }
+                let inner = self.or()?;
+                self.skip_ws();
+                if self.peek() != Some(')') {
+                    return Err(QueryError::new("unclosed `(`", open..open + 1));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Some('"') => {
+                let (text, _) = self.quoted()?;
+                Ok(Expr::Term(Term::Text(text.to_lowercase())))
+            }
+            Some(_) => self.term(),
+        }
+    }
+
+    /// A bare word, or `field <op> value`.
+    fn term(&mut self) -> Result<Expr, QueryError> {
+        let word = self
+            .take_while(|c| !c.is_whitespace() && c != '(' && c != ')' && !OP_CHARS.contains(&c));
+        if word.is_empty() {
+            return Err(self.error_here("expected a field name before the operator"));
+        }
+        let after_word = self.pos;
+        self.skip_ws();
+        let Some(op) = self.operator() else {
+            self.pos = after_word;
+            if !self.at_end() && OP_CHARS.contains(&self.peek().unwrap_or(' ')) {
+                return Err(self.error_here("unknown operator"));
+            }
+            return Ok(Expr::Term(Term::Text(word.to_lowercase())));
+        };
+        let op_span = after_word..self.pos;
+        self.skip_ws();
+        let value_start = self.pos;
+        let (raw, quoted) = match self.peek() {
+            Some('"') => self.quoted()?,
+            _ => {
+                let raw = self.take_while(|c| !c.is_whitespace() && c != ')');
+                (raw.to_string(), false)
+            }
+        };
+        let value_span = value_start..self.pos;
+        if raw.is_empty() && !quoted {
+            return Err(QueryError::new(
+                format!("expected a value after `{}`", op.symbol()),
+                op_span,
+            ));
+        }
+        let value = Box::new(literal(raw, op, value_span)?);
+        Ok(Expr::Term(Term::Compare {
+            field: word.to_lowercase(),
+            op,
+            value,
+        }))
+    }
+
+    fn operator(&mut self) -> Option<Op> {
+        let rest = &self.src[self.pos..];
+        for (sym, op) in [
+            ("!=", Op::Ne),
+            ("!~", Op::NotRegex),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("==", Op::Eq),
+            ("=", Op::Eq),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+            ("~", Op::Regex),
+            (":", Op::Glob),
+        ] {
+            if rest.starts_with(sym) {
+                self.pos += sym.len();
+                return Some(op);
+            }
+        }
+        // `in` is only an operator when a range follows, so `error in logs` stays three words.
+        if self.peek_keyword("in") {
+            let next = rest[2..].trim_start();
+            let next = &next[..next
+                .find(|c: char| c.is_whitespace() || c == ')')
+                .unwrap_or(next.len())];
+            if next.contains("..") {
+                self.pos += 2;
+                return Some(Op::In);
+            }
+        }
+        None
+    }
+
+    /// `"..."` with `\"` and `\\` escapes.
+    fn quoted(&mut self) -> Result<(String, bool), QueryError> {
+        let open = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        let mut chars = self.src[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok((out, true));
+                }
+                '\\' => match chars.next() {
+                    Some((_, e @ ('"' | '\\'))) => out.push(e),
+                    Some((_, e)) => {
+                        out.push('\\');
+                        out.push(e);
+                    }
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        Err(QueryError::new("unterminated string", open..self.src.len()))
+    }
+
+    /// Consume `kw` (case-insensitive) when it stands alone as a word.
+    fn keyword(&mut self, kw: &str) -> bool {
+        self.skip_ws();
+        if self.peek_keyword(kw) {
+            self.pos += kw.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek_keyword(&self, kw: &str) -> bool {
+        let rest = &self.src[self.pos..];
+        rest.get(..kw.len())
+            .is_some_and(|w| w.eq_ignore_ascii_case(kw))
+            && rest[kw.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace() || c == '(')
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let rest = &self.src[self.pos..];
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn skip_ws(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.src.len()
+    }
+
+    fn error_here(&self, message: &str) -> QueryError {
+        let end = self.peek().map_or(self.pos, |c| self.pos + c.len_utf8());
+        QueryError::new(message, self.pos..end)
+    }
+}
+
+/// Pre-compute every interpretation of a comparison value the operator can use.
+fn literal(text: String, op: Op, span: std::ops::Range<usize>) -> Result<Literal, QueryError> {
+    let mut lit = Literal {
+        number: parse_number(&text),
+        severity: parse_severity(&text),
+        time: parse_time(&text),
+        lower: text.to_lowercase(),
+        regex: None,
+        range: None,
+        text,
+    };
+    match op {
+        Op::Regex | Op::NotRegex => {
+            let re = Regex::new(&format!("(?i){}", lit.text)).map_err(|e| {
+                QueryError::new(format!("invalid regex: {}", regex_message(&e)), span)
+            })?;
+            lit.regex = Some(re);
+        }
+        Op::In => {
+            let Some((lo, hi)) = lit.text.split_once("..") else {
+                return Err(QueryError::new("expected a range `low..high`", span));
+            };
+            let (Some(lo), Some(hi)) = (parse_number(lo), parse_number(hi)) else {
+                return Err(QueryError::new(
+                    "range bounds must be numbers (decimal or 0x hex)",
+                    span,
+                ));
+            };
+            if lo > hi {
+                return Err(QueryError::new("range is empty (low > high)", span));
+            }
+            lit.range = Some((lo, hi));
+        }
+        _ => {}
+    }
+    Ok(lit)
+}
+
+fn regex_message(e: &regex::Error) -> String {
+    match e {
+        regex::Error::Syntax(s) => s.lines().last().unwrap_or(s).trim().to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Decimal (optionally negative) or `0x` hex.
+pub(super) fn parse_number(s: &str) -> Option<i128> {
+    let s = s.trim();
+    let (neg, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            digits.parse().ok()?
+        }
+        None => return None,
+    };
+    Some(if neg { -value } else { value })
+}
+
+pub(super) fn parse_severity(s: &str) -> Option<Severity> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "info" => Some(Severity::Info),
+        "warn" | "warning" => Some(Severity::Warning),
+        "high" => Some(Severity::High),
+        _ => None,
+    }
+}
+
+/// RFC 3339, `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SS` (UTC).
+fn parse_time(s: &str) -> Option<OffsetDateTime> {
+    if let Ok(t) = OffsetDateTime::parse(s, &Rfc3339) {
+        return Some(t);
+    }
+    let date_fmt = time::format_description::parse("[year]-[month]-[day]").ok()?;
+    if let Ok(d) = Date::parse(s, &date_fmt) {
+        return Some(d.midnight().assume_utc());
+    }
+    let dt_fmt =
+        time::format_description::parse("[year]-[month]-[day]T[hour]:[minute]:[second]").ok()?;
+    PrimitiveDateTime::parse(s, &dt_fmt)
+        .ok()
+        .map(PrimitiveDateTime::assume_utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parse tree with explicit grouping, e.g. `(a OR (b AND c))`.
+    fn shape(query: &str) -> String {
+        fn render(e: &Expr) -> String {
+            match e {
+                Expr::And(a, b) => format!("({} AND {})", render(a), render(b)),
+                Expr::Or(a, b) => format!("({} OR {})", render(a), render(b)),
+                Expr::Not(e) => format!("NOT {}", render(e)),
+                Expr::Term(Term::Text(t)) => t.clone(),
+                Expr::Term(Term::Compare { field, op, value }) => {
+                    format!("{field}{}{}", op.symbol(), value.text)
+                }
+            }
+        }
+        let expr = Parser::new(query).parse().expect("query parses");
+        expr.as_ref().map_or_else(String::new, render)
+    }
+
+    fn error(query: &str) -> QueryError {
+        Parser::new(query).parse().expect_err("query is rejected")
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(shape("a OR b AND c"), "(a OR (b AND c))");
+        assert_eq!(shape("a b or c"), "((a AND b) OR c)");
+        assert_eq!(shape("a OR b OR c"), "((a OR b) OR c)");
+        assert_eq!(shape("NOT a AND b"), "(NOT a AND b)");
+        assert_eq!(shape("-a !b"), "(NOT a AND NOT b)");
+        assert_eq!(shape("  "), "");
+    }
+
+    #[test]
+    fn nested_parentheses() {
+        assert_eq!(shape("(a OR b) AND c"), "((a OR b) AND c)");
+        assert_eq!(
+            shape("((a OR b) c) OR NOT (d AND (e OR f))"),
+            "(((a OR b) AND c) OR NOT (d AND (e OR f)))"
+        );
+        assert_eq!(shape("(((a)))"), "a");
+        assert_eq!(
+            shape("not(pid=4 or image:*svchost*)"),
+            "NOT (pid=4 OR image:*svchost*)"
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        let Some(Expr::Term(Term::Compare { field, op, value })) =
+            Parser::new("addr in 0x7ff0..0x7fff").parse().unwrap()
+        else {
+            panic!("expected a comparison");
+        };
+        assert_eq!((field.as_str(), op), ("addr", Op::In));
+        assert_eq!(value.range, Some((0x7ff0, 0x7fff)));
+        assert_eq!(shape("pid in -5..5"), "pidin-5..5");
+        // Without a range `in` is a word.
+        assert_eq!(shape("error in logs"), "((error AND in) AND logs)");
+
+        assert_eq!(error("x in 5..1").message, "range is empty (low > high)");
+        assert_eq!(
+            error("x in a..b").message,
+            "range bounds must be numbers (decimal or 0x hex)"
+        );
+        assert_eq!(error("x in 1..2..3").span, 5..12);
+    }
+
+    #[test]
+    fn error_spans() {
+        assert_eq!(error("(a").message, "unclosed `(`");
+        assert_eq!(error("(a").span, 0..1);
+        assert_eq!(error("a )").span, 2..3);
+        assert_eq!(error("()").message, "empty parentheses");
+        assert_eq!(error("a AND").message, "expected a term");
+        assert_eq!(error("a AND").span, 5..5);
+        assert_eq!(error("\"abc").span, 0..4);
+        assert_eq!(error("pid=").message, "expected a value after `=`");
+        assert_eq!(error("pid=").span, 3..4);
+        assert_eq!(
+            error("=4").message,
+            "expected a field name before the operator"
+        );
+        assert!(error("module~(").message.starts_with("invalid regex"));
+        assert_eq!(error("module~(").span, 7..8);
+
+        // Spans are byte offsets, past multi-byte characters.
+        assert_eq!(error("é ü (a").span, 6..7);
+        assert_eq!(error("名前=").span, 6..7);
+        assert_eq!(error("x~日(").span, 2..6);
+    }
+}
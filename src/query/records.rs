@@ -0,0 +1,437 @@
+//! `Record` views over events and report rows. Field names are what the query language
+//! exposes; they are listed in `docs/DATA_FORMATS.md`.
+
+use super::Record;
+use crate::baseline::ModuleBaseline;
+use crate::correlate::Link;
+use crate::model::{
+    Annotation, AttrValue, Detection, EntityRef, Event, EventId, Indicator, InjectedRegion,
+    MinidumpReport, ModuleInfo, ProcessExecArtifact, ProcessNode, ThreadInfo, ThreadStackTrace,
+};
+use crate::triage::TriageStatus;
+
+fn any_of(texts: &[&str], visit: &mut dyn FnMut(&str) -> bool) -> bool {
+    texts.iter().any(|t| visit(t))
+}
+
+fn time(unix: i64) -> Option<AttrValue> {
+    time::OffsetDateTime::from_unix_timestamp(unix)
+        .ok()
+        .map(AttrValue::Time)
+}
+
+fn list(items: impl IntoIterator<Item = AttrValue>) -> AttrValue {
+    AttrValue::List(items.into_iter().collect())
+}
+
+/// A timeline event. With the report loaded, `module`, `thread`, `region` and `artifact`
+/// resolve the event's links.
+pub struct EventRecord<'a> {
+    pub event: &'a Event,
+    /// `id` and `t` fields: the merged timeline's, else the event's own.
+    pub id: EventId,
+    pub t_ms: u64,
+    pub report: Option<&'a MinidumpReport>,
+    /// The store's prebuilt search text for the event, if it came from one.
+    pub search_text: Option<&'a str>,
+}
+
+impl<'a> EventRecord<'a> {
+    pub fn new(event: &'a Event, report: Option<&'a MinidumpReport>) -> Self {
+        Self {
+            event,
+            id: event.id,
+            t_ms: event.t_ms,
+            report,
+            search_text: None,
+        }
+    }
+}
+
+/// `module` / `thread` / `region` / `artifact` / `address` values of the entities in `links`.
+fn linked(links: &[EntityRef], report: Option<&MinidumpReport>, name: &str) -> Option<AttrValue> {
+    let values: Vec<AttrValue> = links
+        .iter()
+        .filter_map(|link| match (name, *link) {
+            ("module", EntityRef::Module(idx)) => {
+                report?.modules.get(idx).map(|m| m.name.as_str().into())
+            }
+            ("thread", EntityRef::Thread(tid)) => Some(tid.into()),
+            ("region", EntityRef::Region(base)) => Some(AttrValue::Addr(base)),
+            ("address", EntityRef::Address(addr)) => Some(AttrValue::Addr(addr)),
+            ("artifact", EntityRef::ExecArtifact(idx)) => report?
+                .exec_artifacts
+                .get(idx)
+                .map(|a| a.image.as_str().into()),
+            _ => None,
+        })
+        .collect();
+    (!values.is_empty()).then(|| list(values))
+}
+
+impl Record for EventRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let ev = self.event;
+        match name {
+            "severity" => return Some(ev.severity.label().into()),
+            "title" => return Some(ev.title.as_str().into()),
+            "details" => return Some(ev.details.as_str().into()),
+            "source" => return Some(ev.source.as_str().into()),
+            "t" | "t_ms" => return Some(self.t_ms.into()),
+            "id" => return Some(self.id.0.into()),
+            "time" | "timestamp" => return ev.timestamp.map(AttrValue::Time),
+            _ => {}
+        }
+        if let Some((_, v)) = ev
+            .attributes
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        {
+            return Some(v.clone());
+        }
+        if let Some(v) = linked(&ev.links, self.report, name) {
+            return Some(v);
+        }
+        // `addr` covers every address-typed attribute and linked region; other names fall back
+        // to the last segment of dotted keys (`pid` finds `crash.pid`).
+        let mut values: Vec<AttrValue> = ev
+            .attributes
+            .iter()
+            .filter(|(k, v)| {
+                if name == "addr" {
+                    matches!(v, AttrValue::Addr(_))
+                } else {
+                    k.rsplit('.')
+                        .next()
+                        .is_some_and(|s| s.eq_ignore_ascii_case(name))
+                }
+            })
+            .map(|(_, v)| v.clone())
+            .collect();
+        if name == "addr" {
+            values.extend(ev.links.iter().filter_map(|l| match l {
+                EntityRef::Region(base) | EntityRef::Address(base) => Some(AttrValue::Addr(*base)),
+                _ => None,
+            }));
+        }
+        (!values.is_empty()).then(|| list(values))
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let ev = self.event;
+        any_of(&[&ev.title, &ev.details, &ev.source], visit)
+            || ev
+                .attributes
+                .iter()
+                .any(|(k, v)| visit(k) || visit(&v.to_string()))
+    }
+
+    fn lowercase_text(&self) -> Option<&str> {
+        self.search_text
+    }
+}
+
+pub struct ModuleRecord<'a> {
+    pub index: usize,
+    pub module: &'a ModuleInfo,
+    /// Whether the module is in the baseline of the dump's product version; `None` when no
+    /// baseline applies.
+    pub baseline: Option<ModuleBaseline>,
+}
+
+impl Record for ModuleRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let m = self.module;
+        Some(match name {
+            "index" => self.index.into(),
+            "name" | "module" => m.name.as_str().into(),
+            "base" | "addr" => AttrValue::Addr(m.base),
+            "end" => AttrValue::Addr(m.base.saturating_add(m.size)),
+            "size" => m.size.into(),
+            "checksum" => AttrValue::Addr(m.checksum.into()),
+            "timestamp" => AttrValue::Addr(m.time_date_stamp.into()),
+            "version" => m.file_version.as_deref()?.into(),
+            "debug_id" => m.debug_id.as_deref()?.into(),
+            "baseline" => self.baseline?.label().into(),
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let m = self.module;
+        any_of(
+            &[
+                &m.name,
+                &format!("{:016X}", m.base),
+                m.file_version.as_deref().unwrap_or_default(),
+            ],
+            visit,
+        )
+    }
+}
+
+pub struct ThreadRecord<'a>(pub &'a ThreadInfo);
+
+impl Record for ThreadRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let t = self.0;
+        Some(match name {
+            "tid" | "thread" | "thread_id" => t.thread_id.into(),
+            "name" => t.name.as_deref()?.into(),
+            "created" | "time" => time(t.create_time_unix? as i64)?,
+            "start" | "addr" => AttrValue::Addr(t.start_address?),
+            "teb" => AttrValue::Addr(t.teb),
+            "stack_start" => AttrValue::Addr(t.stack_start),
+            "stack_size" => t.stack_size.into(),
+            "priority" => t.priority.into(),
+            "priority_class" => t.priority_class.into(),
+            "suspend_count" => t.suspend_count.into(),
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let t = self.0;
+        any_of(
+            &[
+                &t.thread_id.to_string(),
+                &format!("0x{:X}", t.thread_id),
+                t.name.as_deref().unwrap_or_default(),
+            ],
+            visit,
+        )
+    }
+}
+
+pub struct StackRecord<'a>(pub &'a ThreadStackTrace);
+
+impl Record for StackRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let t = self.0;
+        let frames = || t.frames.iter();
+        Some(match name {
+            "tid" | "thread" | "thread_id" => t.thread_id.into(),
+            "name" => t.thread_name.as_deref()?.into(),
+            "status" => t.status.as_str().into(),
+            "frames" => t.frames.len().into(),
+            "requesting" => t.is_requesting_thread.to_string().into(),
+            "module" => list(frames().filter_map(|f| f.module.as_deref().map(Into::into))),
+            "function" => list(frames().filter_map(|f| f.function.as_deref().map(Into::into))),
+            "addr" | "instruction" => list(frames().map(|f| AttrValue::Addr(f.instruction))),
+            "source_file" => {
+                list(frames().filter_map(|f| f.source_file.as_deref().map(Into::into)))
+            }
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let t = self.0;
+        let top = t.frames.first();
+        any_of(
+            &[
+                &format!("0x{:X}", t.thread_id),
+                t.thread_name.as_deref().unwrap_or_default(),
+                top.and_then(|f| f.module.as_deref()).unwrap_or_default(),
+                top.and_then(|f| f.function.as_deref()).unwrap_or_default(),
+            ],
+            visit,
+        )
+    }
+}
+
+pub struct RegionRecord<'a>(pub &'a InjectedRegion);
+
+impl Record for RegionRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let r = self.0;
+        Some(match name {
+            "base" | "addr" => AttrValue::Addr(r.base),
+            "end" => AttrValue::Addr(r.base.saturating_add(r.size)),
+            "size" => r.size.into(),
+            "protection" | "protect" => r.protection.as_str().into(),
+            "type" => r.ty.as_str().into(),
+            "state" => r.state.as_str().into(),
+            "reason" | "reasons" => r.reasons.clone().into(),
+            "risk" | "severity" => r.risk.label().into(),
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let r = self.0;
+        any_of(&[&r.protection, &r.ty, &r.state], visit)
+            || visit(&format!("{:016X}", r.base))
+            || r.reasons.iter().any(|s| visit(s))
+    }
+}
+
+pub struct ArtifactRecord<'a> {
+    pub index: usize,
+    pub artifact: &'a ProcessExecArtifact,
+}
+
+impl Record for ArtifactRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let a = self.artifact;
+        Some(match name {
+            "index" => self.index.into(),
+            "image" => a.image.as_str().into(),
+            "command_line" | "cmd" => a.command_line.as_str().into(),
+            "encoding" => a.encoding.label().into(),
+            "addr" | "address" => AttrValue::Addr(a.address?),
+            "decoded" => list(
+                a.descendants()
+                    .into_iter()
+                    .map(|c| c.command_line.as_str().into()),
+            ),
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let a = self.artifact;
+        any_of(&[&a.image, &a.command_line], visit)
+    }
+}
+
+pub struct IndicatorRecord<'a>(pub &'a Indicator);
+
+impl Record for IndicatorRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let i = self.0;
+        Some(match name {
+            "kind" => i.kind.label().into(),
+            "value" => i.value.as_str().into(),
+            "count" => i.count.into(),
+            "addr" | "address" => AttrValue::Addr(i.address),
+            "encoding" => i.encoding.label().into(),
+            "container" | "module" => i.container.as_deref()?.into(),
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let i = self.0;
+        any_of(
+            &[&i.value, i.container.as_deref().unwrap_or_default()],
+            visit,
+        )
+    }
+}
+
+pub struct AnnotationRecord<'a>(pub &'a Annotation);
+
+impl Record for AnnotationRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let a = self.0;
+        Some(match name {
+            "key" => a.key.as_str().into(),
+            "value" => a.value.as_str().into(),
+            "scope" => a.scope().into(),
+            "module" => a.module.as_deref()?.into(),
+            "kind" => a.kind.label().into(),
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let a = self.0;
+        any_of(&[&a.key, &a.value, a.scope()], visit)
+    }
+}
+
+pub struct ProcessRecord<'a>(pub &'a ProcessNode);
+
+impl Record for ProcessRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let n = self.0;
+        Some(match name {
+            "pid" => n.pid?.into(),
+            "image" => n.image.as_str().into(),
+            "command_line" | "cmd" => n.command_line.as_str().into(),
+            "user" => n.user.as_str().into(),
+            "guid" => n.guid.as_str().into(),
+            "start" | "time" => time(n.start_unix?)?,
+            "observed" => n.observed.to_string().into(),
+            "children" => n.children.len().into(),
+            "hash" | "hashes" => list(n.hashes.iter().map(|(_, h)| h.as_str().into())),
+            _ => return None,
+        })
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let n = self.0;
+        any_of(&[&n.image, &n.command_line], visit) || n.pid.is_some_and(|p| visit(&p.to_string()))
+    }
+}
+
+/// A detection, with the same link fields as `EventRecord`, plus its id, triage `status`
+/// (`new`, `confirmed`, `false_positive`, `ignored`) and `tags`.
+pub struct DetectionRecord<'a> {
+    pub detection: &'a Detection,
+    pub report: &'a MinidumpReport,
+    pub id: &'a str,
+    pub status: TriageStatus,
+    pub tags: &'a [String],
+}
+
+impl Record for DetectionRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        let d = self.detection;
+        match name {
+            "severity" => Some(d.severity.label().into()),
+            "title" => Some(d.title.as_str().into()),
+            "details" => Some(d.details.as_str().into()),
+            "id" => Some(self.id.into()),
+            "status" => Some(self.status.name().into()),
+            "tag" | "tags" => Some(list(self.tags.iter().map(|t| t.as_str().into()))),
+            "rule" => Some(d.rule.as_str().into()),
+            "technique" | "techniques" => {
+                Some(list(d.techniques.iter().map(|t| t.as_str().into())))
+            }
+            "tactic" => d.tactic().map(|t| t.label().into()),
+            "confidence" => Some(u32::from(d.confidence).into()),
+            "addr" => {
+                let addrs: Vec<AttrValue> = d
+                    .links
+                    .iter()
+                    .filter_map(|l| match l {
+                        EntityRef::Region(a) | EntityRef::Address(a) => Some(AttrValue::Addr(*a)),
+                        _ => None,
+                    })
+                    .collect();
+                (!addrs.is_empty()).then(|| list(addrs))
+            }
+            name => linked(&d.links, Some(self.report), name),
+        }
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        let d = self.detection;
+        any_of(&[&d.title, &d.details], visit) || self.tags.iter().any(|t| visit(t))
+    }
+}
+
+/// An event linked to an incident: the event's fields plus `incident`, `reason` (`pid`,
+/// `thread`, `image`, `module`, `time`) and `delta_ms` (event time minus capture time).
+pub struct LinkRecord<'a> {
+    pub incident: usize,
+    pub link: &'a Link,
+    pub event: EventRecord<'a>,
+}
+
+impl Record for LinkRecord<'_> {
+    fn field(&self, name: &str) -> Option<AttrValue> {
+        match name {
+            "incident" => Some(self.incident.into()),
+            "reason" => Some(list(self.link.reasons.iter().map(|r| r.kind().into()))),
+            "delta_ms" | "delta" => self.link.delta_ms.map(AttrValue::Int),
+            _ => self.event.field(name),
+        }
+    }
+
+    fn any_text(&self, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        self.link.reasons.iter().any(|r| visit(&r.to_string())) || self.event.any_text(visit)
+    }
+}
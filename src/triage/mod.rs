@@ -0,0 +1,247 @@
+//! Triage: the analyst's status and tags on detections and events, and the allowlist of
+//! suppressions that carries "this is known good" from one dump to the next.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::app::SourceId;
+use crate::model::attack::AttackMapping;
+use crate::model::{Detection, EventId, Evidence, next_generation};
+use crate::query::{Query, Record};
+
+const ALLOWLIST_ENV_VAR: &str = "LOG_ATLAS_ALLOWLIST";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageStatus {
+    #[default]
+    New,
+    Confirmed,
+    FalsePositive,
+    Ignored,
+}
+
+impl TriageStatus {
+    pub const ALL: [TriageStatus; 4] = [
+        TriageStatus::New,
+        TriageStatus::Confirmed,
+        TriageStatus::FalsePositive,
+        TriageStatus::Ignored,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TriageStatus::New => "New",
+            TriageStatus::Confirmed => "Confirmed",
+            TriageStatus::FalsePositive => "False positive",
+            TriageStatus::Ignored => "Ignored",
+        }
+    }
+
+    /// Name in queries, exports and the allowlist file.
+    pub fn name(self) -> &'static str {
+        match self {
+            TriageStatus::New => "new",
+            TriageStatus::Confirmed => "confirmed",
+            TriageStatus::FalsePositive => "false_positive",
+            TriageStatus::Ignored => "ignored",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    /// False positive or ignored: hidden from the Detections tab unless asked for.
+    pub fn is_dismissed(self) -> bool {
+        matches!(self, TriageStatus::FalsePositive | TriageStatus::Ignored)
+    }
+}
+
+/// What triage state and notes are attached to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Subject {
+    /// A merged-timeline event.
+    Event(EventId),
+    /// A detection of a source's dump, by `Detection::id`. Its timeline event (the one with
+    /// a `detection_id` attribute) shares this state.
+    Detection(SourceId, String),
+}
+
+/// Status and tags set by the analyst.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Triage {
+    pub status: TriageStatus,
+    pub tags: Vec<String>,
+}
+
+impl Triage {
+    pub fn is_empty(&self) -> bool {
+        *self == Triage::default()
+    }
+
+    /// Add each comma-separated tag of `text` that is not there yet.
+    pub fn add_tags(&mut self, text: &str) {
+        for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            if !self.tags.iter().any(|t| t == tag) {
+                self.tags.push(tag.to_string());
+            }
+        }
+    }
+}
+
+/// A detection with its id and effective triage: the analyst's status when set, else the
+/// status of the first allowlist entry it matches, else `Ignored` when a suppressing baseline
+/// covers it, else `New`.
+#[derive(Clone, Debug, Serialize)]
+pub struct TriagedDetection {
+    /// File name of the source the detection was computed for.
+    pub source: String,
+    pub id: String,
+    #[serde(flatten)]
+    pub detection: Detection,
+    /// What each of `detection.links` points at in the report.
+    pub evidence: Vec<Evidence>,
+    /// Name and tactics of each of `detection.techniques`.
+    pub attack: Vec<AttackMapping>,
+    pub status: TriageStatus,
+    pub tags: Vec<String>,
+    /// Text of the notes on the detection.
+    pub notes: Vec<String>,
+    /// Reason of the allowlist entry that set `status`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowlisted: Option<String>,
+    /// Index of that entry in `Allowlist::entries`.
+    #[serde(skip)]
+    pub allow_entry: Option<usize>,
+    /// Product baseline (`app.exe 1.2.3.4`) that covers every module and artifact of the
+    /// detection; it downgraded its severity or suppressed it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
+}
+
+/// A suppression: detections matching `query` get `status` unless the analyst set one.
+#[derive(Clone, Debug)]
+pub struct AllowEntry {
+    pub status: TriageStatus,
+    pub query: String,
+    pub reason: String,
+    /// `None` when `query` does not parse (the entry is kept but never matches).
+    compiled: Option<Query>,
+}
+
+impl AllowEntry {
+    pub fn is_valid(&self) -> bool {
+        self.compiled.is_some()
+    }
+}
+
+/// Suppressions reused across dumps and sessions. Stored one `status<TAB>query<TAB>reason` per
+/// line in `LOG_ATLAS_ALLOWLIST`, else `log-atlas/allowlist.txt` in the user's config
+/// directory.
+#[derive(Debug, Default)]
+pub struct Allowlist {
+    pub entries: Vec<AllowEntry>,
+    path: Option<PathBuf>,
+    /// Changes with the entries, like `EventStore::generation`.
+    generation: u64,
+}
+
+impl Allowlist {
+    /// Load the allowlist file; a missing or unreadable file gives an empty allowlist.
+    pub fn load() -> Self {
+        let path = crate::util::config_file(ALLOWLIST_ENV_VAR, "allowlist.txt");
+        let entries = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|text| text.lines().filter_map(parse_line).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path,
+            generation: next_generation(),
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Append an entry; `query` must parse and `status` must not be `New`.
+    pub fn add(&mut self, status: TriageStatus, query: &str, reason: &str) -> Result<()> {
+        if status == TriageStatus::New {
+            bail!("an allowlist entry needs a status other than new");
+        }
+        let query = clean(query);
+        if query.is_empty() {
+            bail!("an allowlist entry needs a query");
+        }
+        let compiled = Query::parse(&query).map_err(|e| {
+            anyhow!(
+                "invalid query at column {}: {}",
+                e.column(&query),
+                e.message
+            )
+        })?;
+        self.entries.push(AllowEntry {
+            status,
+            query,
+            reason: clean(reason),
+            compiled: Some(compiled),
+        });
+        self.generation = next_generation();
+        self.write()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<()> {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+            self.generation = next_generation();
+        }
+        self.write()
+    }
+
+    /// Index of the first entry `record` matches.
+    pub fn matching(&self, record: &dyn Record) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.compiled.as_ref().is_some_and(|q| q.matches(record)))
+    }
+
+    fn write(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let text: String = self
+            .entries
+            .iter()
+            .map(|e| format!("{}\t{}\t{}\n", e.status.name(), e.query, e.reason))
+            .collect();
+        std::fs::write(path, text).with_context(|| format!("write {}", path.display()))
+    }
+}
+
+fn parse_line(line: &str) -> Option<AllowEntry> {
+    let mut fields = line.splitn(3, '\t');
+    let status = TriageStatus::parse(fields.next()?.trim())?;
+    let query = fields.next()?.trim().to_string();
+    if query.is_empty() {
+        return None;
+    }
+    Some(AllowEntry {
+        status,
+        compiled: Query::parse(&query).ok(),
+        query,
+        reason: fields.next().unwrap_or_default().trim().to_string(),
+    })
+}
+
+/// One line of the allowlist file per field.
+fn clean(text: &str) -> String {
+    text.trim().replace(['\n', '\r', '\t'], " ")
+}
@@ -1,5 +1,15 @@
-use crate::app::DetailsTab;
-use crate::app::LogAtlasApp;
+use std::collections::BTreeMap;
+
+use crate::app::{DetailsTab, UiState};
+use crate::app::{LogAtlasApp, TimelineEvent};
+use crate::baseline::{BaselineAction, Baselines, ModuleBaseline};
+use crate::model::attack::Tactic;
+use crate::model::{Detection, EntityRef, MinidumpReport, Severity};
+use crate::query::{
+    AnnotationRecord, ArtifactRecord, DetectionRecord, EventRecord, IndicatorRecord, LinkRecord,
+    ModuleRecord, ProcessRecord, RegionRecord, StackRecord, ThreadRecord,
+};
+use crate::triage::{Allowlist, Subject, TriageStatus, TriagedDetection};
 use eframe::egui;
 
 pub fn details_panel(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
@@ -21,33 +31,71 @@ pub fn details_panel(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
         DetailsTab::Stacks => stacks(ui, app),
         DetailsTab::Exception => exception(ui, app),
         DetailsTab::Detections => detections(ui, app),
+        DetailsTab::Incidents => incidents(ui, app),
+        DetailsTab::Notes => notes(ui, app),
+        DetailsTab::Iocs => iocs(ui, app),
+        DetailsTab::Sensitive => sensitive(ui, app),
+        DetailsTab::ProcessTree => process_tree(ui, app),
+        DetailsTab::Diff => super::diff::dump_diff(ui, app),
     }
 }
 
 fn header(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    if let Some(path) = &app.dump_path {
+    // Report tabs show one dump; with several loaded, pick which (selecting an event of a
+    // dump picks it too).
+    let dumps: Vec<_> = app
+        .session
+        .sources
+        .iter()
+        .filter(|s| s.dump.is_some())
+        .map(|s| (s.id, s.name(), s.color, s.path.display().to_string()))
+        .collect();
+    if let Some(shown) = app.session.dump_source().map(|s| s.id) {
+        let mut focus = None;
         ui.horizontal(|ui| {
-            ui.label(egui::RichText::new("Minidump").strong());
-            let display = path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| path.display().to_string());
-            ui.monospace(display)
-                .on_hover_text(path.display().to_string());
+            ui.label(egui::RichText::new("Dump").strong());
+            let (_, name, color, path) = dumps
+                .iter()
+                .find(|(id, ..)| *id == shown)
+                .expect("shown dump is a source");
+            if dumps.len() == 1 {
+                ui.label(egui::RichText::new(name).monospace().color(*color))
+                    .on_hover_text(path);
+                return;
+            }
+            egui::ComboBox::from_id_source("inspector_dump")
+                .selected_text(egui::RichText::new(name).monospace().color(*color))
+                .show_ui(ui, |ui| {
+                    for (id, name, color, path) in &dumps {
+                        if ui
+                            .selectable_label(
+                                *id == shown,
+                                egui::RichText::new(name).monospace().color(*color),
+                            )
+                            .on_hover_text(path)
+                            .clicked()
+                        {
+                            focus = Some(*id);
+                        }
+                    }
+                });
         });
+        if let Some(id) = focus {
+            app.focus_source(id);
+        }
     }
 
     if let Some(id) = app.selected
-        && let Some(ev) = app.events.get(id)
+        && let Some(ev) = app.session.timeline().get(id)
     {
         ui.horizontal(|ui| {
             ui.label(
-                egui::RichText::new(ev.severity.label())
-                    .color(crate::ui::severity_color(ev.severity))
+                egui::RichText::new(ev.event.severity.label())
+                    .color(crate::ui::severity_color(ev.event.severity))
                     .strong(),
             );
             ui.monospace(format!("+{}ms", ev.t_ms));
-            ui.label(&ev.title);
+            ui.label(&ev.event.title);
         });
     }
 }
@@ -56,7 +104,7 @@ fn tab_bar(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
     ui.horizontal_wrapped(|ui| {
         ui.selectable_value(&mut app.ui.details_tab, DetailsTab::Event, "Event");
 
-        let enabled = app.dump_report.is_some();
+        let enabled = app.session.dump_report().is_some();
         ui.add_enabled_ui(enabled, |ui| {
             ui.selectable_value(&mut app.ui.details_tab, DetailsTab::Overview, "Overview");
             ui.selectable_value(&mut app.ui.details_tab, DetailsTab::Processes, "Processes");
@@ -70,7 +118,38 @@ fn tab_bar(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                 DetailsTab::Detections,
                 "Detections",
             );
+            ui.selectable_value(&mut app.ui.details_tab, DetailsTab::Iocs, "IOCs");
+            ui.selectable_value(
+                &mut app.ui.details_tab,
+                DetailsTab::Sensitive,
+                "Sensitive Data",
+            );
+        });
+        ui.add_enabled_ui(!app.session.incidents.is_empty(), |ui| {
+            ui.selectable_value(&mut app.ui.details_tab, DetailsTab::Incidents, "Incidents");
+        });
+        let dumps = app
+            .session
+            .sources
+            .iter()
+            .filter(|s| s.dump.is_some())
+            .count();
+        ui.add_enabled_ui(dumps >= 2, |ui| {
+            ui.selectable_value(&mut app.ui.details_tab, DetailsTab::Diff, "Diff");
+        });
+        ui.add_enabled_ui(app.session.process_tree().is_some(), |ui| {
+            ui.selectable_value(
+                &mut app.ui.details_tab,
+                DetailsTab::ProcessTree,
+                "Process Tree",
+            );
         });
+        let notes = if app.session.notes().is_empty() {
+            "Notes".to_string()
+        } else {
+            format!("Notes ({})", app.session.notes().len())
+        };
+        ui.selectable_value(&mut app.ui.details_tab, DetailsTab::Notes, notes);
     });
 }
 
@@ -83,13 +162,19 @@ fn event_details(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
         return;
     };
 
-    let Some(ev) = app.events.get(id) else {
+    let Some(TimelineEvent {
+        t_ms, event: ev, ..
+    }) = app.session.timeline().get(id)
+    else {
         ui.label("Selected event not found.");
         return;
     };
 
     ui.horizontal(|ui| {
-        ui.monospace(format!("+{}ms", ev.t_ms));
+        ui.monospace(format!("+{t_ms}ms"));
+        if let Some(ts) = ev.timestamp {
+            ui.monospace(crate::util::time::format_timestamp(ts));
+        }
         ui.add_space(8.0);
         ui.label(
             egui::RichText::new(ev.severity.label())
@@ -104,22 +189,200 @@ fn event_details(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
     ui.label(egui::RichText::new("Source").strong());
     ui.monospace(&ev.source);
 
+    // A detection event shares the detection's triage and notes.
+    let subject = app.session.subject_of(id);
+    let (status, allowlisted) = app.effective_triage(&subject);
+    ui.add_space(10.0);
+    ui.label(egui::RichText::new("Triage").strong());
+    let tags = app.session.triage_of(&subject).map_or(&[][..], |t| &t.tags);
+    let edit = triage_controls(ui, &subject, status, tags, allowlisted.as_deref());
+
     ui.add_space(10.0);
     ui.label(egui::RichText::new("Details").strong());
+    let mut jump = None;
+    let mut remove_note = None;
+    let mut add_note = false;
     egui::ScrollArea::vertical()
         .id_source("event_details_scroll")
         .auto_shrink([false, false])
         .show(ui, |ui| {
             ui.add(egui::Label::new(&ev.details).wrap(true));
+
+            if !ev.attributes.is_empty() {
+                ui.add_space(10.0);
+                ui.label(egui::RichText::new("Attributes").strong());
+                egui::Grid::new("event_attributes_grid")
+                    .striped(true)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        for (key, value) in &ev.attributes {
+                            ui.monospace(key);
+                            ui.add(egui::Label::new(value.to_string()).wrap(true));
+                            ui.weak(value.kind());
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            if !ev.links.is_empty() && app.session.dump_report().is_some() {
+                ui.add_space(10.0);
+                ui.label(egui::RichText::new("Links").strong());
+                ui.horizontal_wrapped(|ui| {
+                    for link in &ev.links {
+                        if ui.button(link.label()).clicked() {
+                            jump = Some(*link);
+                        }
+                    }
+                });
+            }
+
+            ui.add_space(10.0);
+            ui.label(egui::RichText::new("Notes").strong());
+            let notes = app
+                .session
+                .notes()
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| n.about.as_ref() == Some(&subject));
+            remove_note = note_list(ui, notes, |_, _| {});
+            add_note = note_editor(ui, &mut app.ui.note_draft, "Add note");
+        });
+
+    if let Some(edit) = edit {
+        edit.apply(app, subject.clone());
+    }
+    if let Some(target) = jump {
+        app.jump_to(target);
+    }
+    if let Some(index) = remove_note {
+        app.session.remove_note(index);
+    }
+    if add_note {
+        let text = std::mem::take(&mut app.ui.note_draft);
+        app.session.add_note(crate::case::Note::new(
+            Some(subject),
+            text.trim().to_string(),
+        ));
+    }
+}
+
+/// Every note of the session: on the case, and on events (click to select the event).
+fn notes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
+    ui.label(egui::RichText::new("Case Notes").strong());
+    ui.label(
+        "Notes are saved with the case (File -> Save case). Event and detection notes are added in the Event and Detections tabs.",
+    );
+    ui.add_space(6.0);
+    let add = note_editor(ui, &mut app.ui.note_draft, "Add case note");
+    ui.add_space(6.0);
+
+    let session = &app.session;
+    let mut select = None;
+    let mut remove = None;
+    egui::ScrollArea::vertical()
+        .id_source("notes_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if session.notes().is_empty() {
+                ui.label("No notes yet.");
+            }
+            remove = note_list(ui, session.notes().iter().enumerate(), |ui, note| {
+                let id = match &note.about {
+                    None => {
+                        ui.weak("case");
+                        return;
+                    }
+                    Some(Subject::Event(id)) => Some(*id),
+                    Some(Subject::Detection(source, detection)) => {
+                        session.detection_event(*source, detection)
+                    }
+                };
+                let Some((id, ev)) = id.and_then(|id| Some((id, session.event(id)?))) else {
+                    ui.weak("event not found");
+                    return;
+                };
+                let source = session.source_of(id);
+                if let Some(source) = source {
+                    ui.label(
+                        egui::RichText::new(source.name())
+                            .monospace()
+                            .color(source.color),
+                    );
+                }
+                if ui
+                    .add_enabled(
+                        source.is_some_and(|s| s.visible),
+                        egui::SelectableLabel::new(app.selected == Some(id), &ev.title),
+                    )
+                    .clicked()
+                {
+                    select = Some(id);
+                }
+            });
+        });
+
+    if let Some(id) = select {
+        app.select(id);
+    }
+    if let Some(index) = remove {
+        app.session.remove_note(index);
+    }
+    if add {
+        let text = std::mem::take(&mut app.ui.note_draft);
+        app.session
+            .add_note(crate::case::Note::new(None, text.trim().to_string()));
+    }
+}
+
+/// Notes with author and time; `about` draws what a note is attached to. Returns the index
+/// of a note to delete.
+fn note_list<'a>(
+    ui: &mut egui::Ui,
+    notes: impl Iterator<Item = (usize, &'a crate::case::Note)>,
+    mut about: impl FnMut(&mut egui::Ui, &crate::case::Note),
+) -> Option<usize> {
+    let mut remove = None;
+    for (index, note) in notes {
+        ui.horizontal_wrapped(|ui| {
+            if ui.small_button("x").on_hover_text("Delete note").clicked() {
+                remove = Some(index);
+            }
+            ui.label(egui::RichText::new(&note.author).strong());
+            ui.weak(crate::util::time::format_timestamp(note.created));
+            about(ui, note);
         });
+        ui.add(egui::Label::new(&note.text).wrap(true));
+        ui.add_space(4.0);
+    }
+    remove
+}
+
+/// Text box for a new note; true when `button` was clicked with some text.
+fn note_editor(ui: &mut egui::Ui, draft: &mut String, button: &str) -> bool {
+    ui.add(
+        egui::TextEdit::multiline(draft)
+            .desired_rows(2)
+            .desired_width(f32::INFINITY)
+            .hint_text("Write a note"),
+    );
+    ui.add_enabled(!draft.trim().is_empty(), egui::Button::new(button))
+        .clicked()
+}
+
+/// After `LogAtlasApp::jump_to`, bring the selected row into view once.
+fn scroll_if_jumped(pending: &mut bool, selected: bool, response: &egui::Response) {
+    if selected && *pending {
+        response.scroll_to_me(Some(egui::Align::Center));
+        *pending = false;
+    }
 }
 
 fn overview(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(sum) = &app.dump_summary else {
+    let Some(sum) = app.session.dump_summary() else {
         ui.label("Load a minidump to see an overview.");
         return;
     };
-    let Some(report) = &app.dump_report else {
+    let Some(report) = app.session.dump_report() else {
         ui.label("Load a minidump to see an overview.");
         return;
     };
@@ -135,6 +398,11 @@ fn overview(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
         }
     }
 
+    if let Some(wer) = &report.wer {
+        ui.add_space(12.0);
+        wer_report(ui, wer, report);
+    }
+
     ui.add_space(12.0);
     ui.label(egui::RichText::new("System").strong());
     if let Some(os) = &report.os {
@@ -143,6 +411,19 @@ fn overview(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
     if let Some(cpu) = &report.cpu {
         ui.monospace(format!("cpu={cpu}"));
     }
+    if let Some(linux) = &report.linux {
+        if let Some(model) = &linux.cpu_model {
+            ui.monospace(format!("cpu_model={model}"));
+        }
+        if let Some(n) = linux.cpu_count {
+            ui.monospace(format!("cpu_count={n}"));
+        }
+    }
+
+    if let Some(crashpad) = &report.crashpad {
+        ui.add_space(12.0);
+        crashpad_annotations(ui, crashpad, &mut app.ui);
+    }
 
     ui.add_space(12.0);
     ui.label(egui::RichText::new("Process").strong());
@@ -156,6 +437,12 @@ fn overview(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
         if let Some(pid) = p.pid {
             ui.monospace(format!("pid={pid}"));
         }
+        if let Some(ppid) = p.ppid {
+            ui.monospace(format!("ppid={ppid}"));
+        }
+        if let Some(uid) = p.uid {
+            ui.monospace(format!("uid={uid}"));
+        }
         if let Some(t) = p.create_time_unix {
             ui.monospace(format!("create_time_unix={t}"));
             if let Some(utc) = crate::util::time::unix_seconds_to_utc_string(t) {
@@ -185,6 +472,9 @@ fn overview(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
     if let Some(n) = report.memory_region_64_count {
         ui.monospace(format!("memory64_regions={n}"));
     }
+    if let Some(linux) = &report.linux {
+        ui.monospace(format!("linux_maps={}", linux.maps.len()));
+    }
 
     ui.add_space(12.0);
     ui.label(egui::RichText::new("Thread Timing").strong());
@@ -226,6 +516,9 @@ fn overview(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
             report.exec_artifacts.len()
         ));
     }
+    if let Some(cov) = &report.string_scan {
+        scan_coverage_label(ui, cov);
+    }
 
     ui.add_space(12.0);
     ui.label(egui::RichText::new("Exception").strong());
@@ -242,8 +535,131 @@ fn overview(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
     }
 }
 
+/// `Report.wer` fields, and its signature parameters next to the ones computed from the dump.
+fn wer_report(
+    ui: &mut egui::Ui,
+    wer: &crate::model::WerReport,
+    report: &crate::model::MinidumpReport,
+) {
+    ui.label(egui::RichText::new("Windows Error Reporting").strong());
+    for (key, value) in [
+        ("event_type", &wer.event_type),
+        ("app_name", &wer.app_name),
+        ("app_path", &wer.app_path),
+        ("report_id", &wer.report_id),
+        ("os_version", &wer.os_version),
+        ("bucket", &wer.bucket),
+        ("bucket_table", &wer.bucket_table),
+    ] {
+        if let Some(v) = value {
+            ui.monospace(format!("{key}={v}"));
+        }
+    }
+    if let Some(utc) = wer
+        .event_time_unix
+        .and_then(crate::util::time::unix_seconds_to_utc_string)
+    {
+        ui.monospace(format!("event_time_utc={utc}"));
+    }
+
+    let ours = report.crash_signature();
+    let mismatched: Vec<String> = ours
+        .as_ref()
+        .map(|s| wer.compare(s).into_iter().map(|m| m.parameter).collect())
+        .unwrap_or_default();
+    if mismatched.iter().any(|p| p == "Bucket") {
+        ui.colored_label(
+            crate::ui::severity_color(crate::model::Severity::Warning),
+            "Bucket does not name the fault module found in the dump.",
+        );
+    }
+    if wer.signature.is_empty() {
+        return;
+    }
+    egui::Grid::new("wer_signature_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("parameter");
+            ui.strong("WER");
+            ui.strong("dump");
+            ui.end_row();
+            for (name, value) in &wer.signature {
+                let dump = ours.as_ref().and_then(|s| {
+                    s.parameters()
+                        .into_iter()
+                        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                        .map(|(_, v)| v)
+                });
+                ui.monospace(name);
+                ui.monospace(value);
+                if mismatched.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+                    ui.colored_label(
+                        crate::ui::severity_color(crate::model::Severity::Warning),
+                        egui::RichText::new(dump.unwrap_or_default()).monospace(),
+                    );
+                } else {
+                    ui.monospace(dump.as_deref().unwrap_or("-"));
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Key/value table of the `CrashpadInfo` annotations, filtered by `annotation_filter`.
+fn crashpad_annotations(
+    ui: &mut egui::Ui,
+    crashpad: &crate::model::CrashpadInfo,
+    state: &mut UiState,
+) {
+    ui.label(egui::RichText::new("Crashpad Annotations").strong());
+    if !crashpad.report_id.is_empty() {
+        ui.monospace(format!("report_id={}", crashpad.report_id));
+    }
+    if !crashpad.client_id.is_empty() {
+        ui.monospace(format!("client_id={}", crashpad.client_id));
+    }
+    if crashpad.annotations.is_empty() {
+        ui.label("CrashpadInfo stream present, no annotations.");
+        return;
+    }
+    crate::ui::query_box(
+        ui,
+        "annotations",
+        &mut state.annotation_filter,
+        &mut state.query_history,
+        &mut state.last_error,
+    );
+    egui::ScrollArea::vertical()
+        .id_source("crashpad_annotations_scroll")
+        .max_height(220.0)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            egui::Grid::new("crashpad_annotations_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("key");
+                    ui.strong("value");
+                    ui.strong("scope");
+                    ui.strong("kind");
+                    ui.end_row();
+                    for a in &crashpad.annotations {
+                        if !state.annotation_filter.matches(&AnnotationRecord(a)) {
+                            continue;
+                        }
+                        ui.monospace(&a.key);
+                        ui.add(
+                            egui::Label::new(egui::RichText::new(&a.value).monospace()).wrap(true),
+                        );
+                        ui.monospace(a.scope());
+                        ui.monospace(a.kind.label());
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
 fn processes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(report) = &app.dump_report else {
+    let Some(report) = app.session.dump_report() else {
         ui.label("Load a minidump to inspect process details.");
         return;
     };
@@ -260,6 +676,12 @@ fn processes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
             if let Some(pid) = p.pid {
                 ui.monospace(format!("pid={pid}"));
             }
+            if let Some(ppid) = p.ppid {
+                ui.monospace(format!("ppid={ppid}"));
+            }
+            if let Some(uid) = p.uid {
+                ui.monospace(format!("uid={uid}"));
+            }
             if let Some(t) = p.create_time_unix {
                 ui.monospace(format!("create_time_unix={t}"));
                 if let Some(utc) = crate::util::time::unix_seconds_to_utc_string(t) {
@@ -275,34 +697,72 @@ fn processes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
             if let Some(pp) = p.protected_process {
                 ui.monospace(format!("protected_process={pp}"));
             }
+            if let Some(cl) = &p.command_line {
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new("Command line (LinuxCmdLine)").strong());
+                ui.add(
+                    egui::Label::new(
+                        egui::RichText::new(crate::ingest::minidump_secrets::redact_secrets(cl))
+                            .monospace(),
+                    )
+                    .wrap(true),
+                );
+            }
+            if !p.environment.is_empty() {
+                ui.add_space(6.0);
+                egui::CollapsingHeader::new(format!(
+                    "Environment (LinuxEnviron, {} variables)",
+                    p.environment.len()
+                ))
+                .id_source("process_environment")
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("process_environment_scroll")
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            for (k, v) in &p.environment {
+                                ui.monospace(crate::ingest::minidump_secrets::redact_secrets(
+                                    &format!("{k}={v}"),
+                                ));
+                            }
+                        });
+                });
+            }
         } else {
             ui.label("No MiscInfo stream (process fields unavailable).");
         }
 
+        if let Some(linux) = &report.linux {
+            ui.add_space(12.0);
+            linux_process(ui, linux);
+        }
+
         ui.add_space(12.0);
         ui.label(egui::RichText::new("Recovered Exec Artifacts").strong());
         ui.label("Best-effort scan of dump memory for command-line like strings (not guaranteed).");
+        if let Some(cov) = &report.string_scan {
+            scan_coverage_label(ui, cov);
+        }
 
         ui.add_space(6.0);
-        ui.horizontal(|ui| {
-            ui.label("Filter:");
-            ui.text_edit_singleline(&mut app.ui.process_filter);
-            if ui.button("Clear##process_filter").clicked() {
-                app.ui.process_filter.clear();
-            }
-        });
-
-        let filter = app.ui.process_filter.trim().to_ascii_lowercase();
+        crate::ui::query_box(
+            ui,
+            "artifacts",
+            &mut app.ui.process_filter,
+            &mut app.ui.query_history,
+            &mut app.ui.last_error,
+        );
 
         egui::ScrollArea::vertical()
             .id_source("exec_artifacts_scroll")
             .auto_shrink([false, false])
             .show(ui, |ui| {
                 for (idx, a) in report.exec_artifacts.iter().enumerate() {
-                    if !filter.is_empty()
-                        && !a.image.to_ascii_lowercase().contains(&filter)
-                        && !a.command_line.to_ascii_lowercase().contains(&filter)
-                    {
+                    let record = ArtifactRecord {
+                        index: idx,
+                        artifact: a,
+                    };
+                    if !app.ui.process_filter.matches(&record) {
                         continue;
                     }
 
@@ -310,9 +770,11 @@ fn processes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                         let selected = app.ui.selected_exec_artifact == Some(idx);
 
                         ui.horizontal(|ui| {
-                            if ui.selectable_label(selected, format!("#{idx}")).clicked() {
+                            let response = ui.selectable_label(selected, format!("#{idx}"));
+                            if response.clicked() {
                                 app.ui.selected_exec_artifact = Some(idx);
                             }
+                            scroll_if_jumped(&mut app.ui.scroll_to_selected, selected, &response);
                             ui.monospace(match a.encoding {
                                 crate::model::ExecArtifactEncoding::Ascii => "ascii",
                                 crate::model::ExecArtifactEncoding::Utf16Le => "utf16le",
@@ -324,6 +786,19 @@ fn processes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                         });
                         ui.add_space(2.0);
                         ui.add(egui::Label::new(&a.command_line).wrap(true));
+                        for child in a.descendants() {
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(format!(
+                                        "decoded ({}): {}",
+                                        child.decoded_via.map_or("-", |s| s.label()),
+                                        child.command_line
+                                    ))
+                                    .color(DECODED_TEXT_COLOR),
+                                )
+                                .wrap(true),
+                            );
+                        }
 
                         ui.add_space(6.0);
                         ui.separator();
@@ -332,17 +807,37 @@ fn processes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                 }
             });
 
-        if let Some(idx) = app.ui.selected_exec_artifact {
-            if let Some(a) = report.exec_artifacts.get(idx) {
-                ui.add_space(10.0);
-                ui.separator();
-                ui.add_space(10.0);
+        if let Some(idx) = app.ui.selected_exec_artifact
+            && let Some(a) = report.exec_artifacts.get(idx)
+        {
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
                 ui.label(egui::RichText::new("Selected Artifact").strong());
-                ui.monospace(format!("image={}", a.image));
-                if let Some(addr) = a.address {
-                    ui.monospace(format!("address=0x{addr:016X}"));
+                let known = app
+                    .baselines
+                    .for_report(report)
+                    .is_some_and(|b| b.command_line(&a.command_line));
+                if known {
+                    ui.weak("in baseline");
+                } else if ui
+                    .button("Mark known good")
+                    .on_hover_text(
+                        "Add this command line to the baseline of the dump's product version",
+                    )
+                    .clicked()
+                    && let Err(e) = app.baselines.add_command_line(report, &a.command_line)
+                {
+                    app.ui.last_error = Some(e.to_string());
                 }
-                ui.add_space(6.0);
+            });
+            ui.monospace(format!("image={}", a.image));
+            if let Some(addr) = a.address {
+                ui.monospace(format!("address=0x{addr:016X}"));
+            }
+            ui.add_space(6.0);
+            if a.children.is_empty() {
                 egui::ScrollArea::vertical()
                     .id_source("selected_artifact_scroll")
                     .max_height(140.0)
@@ -350,13 +845,117 @@ fn processes(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                     .show(ui, |ui| {
                         ui.add(egui::Label::new(&a.command_line).wrap(true));
                     });
+            } else {
+                ui.columns(2, |cols| {
+                    cols[0].label(egui::RichText::new("Command line").strong());
+                    egui::ScrollArea::vertical()
+                        .id_source("selected_artifact_scroll")
+                        .max_height(240.0)
+                        .auto_shrink([false, false])
+                        .show(&mut cols[0], |ui| {
+                            ui.add(egui::Label::new(&a.command_line).wrap(true));
+                        });
+
+                    cols[1].label(egui::RichText::new("Decoded").strong());
+                    egui::ScrollArea::vertical()
+                        .id_source("selected_artifact_decoded_scroll")
+                        .max_height(240.0)
+                        .auto_shrink([false, false])
+                        .show(&mut cols[1], |ui| {
+                            for child in a.descendants() {
+                                ui.monospace(format!(
+                                    "[{}]",
+                                    child.decoded_via.map_or("-", |s| s.label())
+                                ));
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(&child.command_line)
+                                            .monospace()
+                                            .color(DECODED_TEXT_COLOR),
+                                    )
+                                    .wrap(true),
+                                );
+                                ui.add_space(6.0);
+                            }
+                        });
+                });
             }
         }
     });
 }
 
+fn linux_process(ui: &mut egui::Ui, linux: &crate::model::LinuxProcessStreams) {
+    ui.label(egui::RichText::new("Linux").strong());
+    for field in [
+        "State",
+        "TracerPid",
+        "Threads",
+        "VmRSS",
+        "Seccomp",
+        "CapEff",
+    ] {
+        if let Some(v) = linux.status_field(field) {
+            ui.monospace(format!("{}={v}", field.to_ascii_lowercase()));
+        }
+    }
+    if !linux.proc_status.is_empty() {
+        egui::CollapsingHeader::new(format!(
+            "/proc/self/status ({} fields)",
+            linux.proc_status.len()
+        ))
+        .id_source("linux_proc_status")
+        .show(ui, |ui| {
+            for (k, v) in &linux.proc_status {
+                ui.monospace(format!("{k}: {v}"));
+            }
+        });
+    }
+    if !linux.auxv.is_empty() {
+        egui::CollapsingHeader::new(format!("Auxiliary vector ({})", linux.auxv.len()))
+            .id_source("linux_auxv")
+            .show(ui, |ui| {
+                for a in &linux.auxv {
+                    match &a.name {
+                        Some(name) => ui.monospace(format!("{name}=0x{:X}", a.value)),
+                        None => ui.monospace(format!("AT_{}=0x{:X}", a.key, a.value)),
+                    };
+                }
+            });
+    }
+    if let Some(dso) = &linux.dso_debug {
+        egui::CollapsingHeader::new(format!("DSO debug ({} link_map entries)", dso.dsos.len()))
+            .id_source("linux_dso_debug")
+            .show(ui, |ui| {
+                ui.monospace(format!(
+                    "version={} ldbase=0x{:X} dynamic=0x{:X} brk=0x{:X}",
+                    dso.version, dso.ldbase, dso.dynamic, dso.brk
+                ));
+                for d in &dso.dsos {
+                    ui.monospace(format!(
+                        "0x{:016X} {}",
+                        d.addr,
+                        if d.name.is_empty() { "(main)" } else { &d.name }
+                    ));
+                }
+            });
+    }
+}
+
+const DECODED_TEXT_COLOR: egui::Color32 = egui::Color32::from_rgb(150, 200, 255);
+
+fn scan_coverage_label(ui: &mut egui::Ui, cov: &crate::model::ScanCoverage) {
+    if cov.is_complete() {
+        ui.monospace(format!("coverage: {}", cov.pretty()));
+    } else {
+        ui.colored_label(
+            crate::ui::severity_color(crate::model::Severity::Warning),
+            format!("coverage (partial): {}", cov.pretty()),
+        );
+    }
+}
+
 fn memory(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(report) = &app.dump_report else {
+    let Some(report) = app.session.dump_report() else {
         ui.label("Load a minidump to inspect memory.");
         return;
     };
@@ -378,28 +977,53 @@ fn memory(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
         } else {
             ui.monospace("memory_info_regions=-");
         }
+        if let Some(linux) = &report.linux {
+            ui.monospace(format!("linux_maps={}", linux.maps.len()));
+            if !linux.maps.is_empty() {
+                ui.add_space(6.0);
+                linux_maps(ui, &linux.maps);
+            }
+        }
 
         ui.add_space(12.0);
         ui.label(egui::RichText::new("Injected / Exec Memory").strong());
-        ui.label("Best-effort: committed private executable allocations not backed by modules.");
+        if report.is_linux() {
+            ui.label("Best-effort: executable anonymous, heap/stack or memfd/deleted mappings not backed by modules.");
+        } else {
+            ui.label("Best-effort: committed private executable allocations not backed by modules.");
+        }
 
         if report.injected_regions.is_empty() {
-            ui.label("None detected (or MemoryInfoList stream missing).");
+            ui.label("None detected (or MemoryInfoList / LinuxMaps stream missing).");
             return;
         }
 
+        ui.add_space(6.0);
+        crate::ui::query_box(
+            ui,
+            "regions",
+            &mut app.ui.region_filter,
+            &mut app.ui.query_history,
+            &mut app.ui.last_error,
+        );
+
         egui::ScrollArea::vertical()
             .id_source("memory_injected_regions_scroll")
             .auto_shrink([false, false])
             .show(ui, |ui| {
                 for (idx, r) in report.injected_regions.iter().enumerate() {
+                    if !app.ui.region_filter.matches(&RegionRecord(r)) {
+                        continue;
+                    }
                     ui.push_id(idx, |ui| {
                         let selected = app.ui.selected_injected_region == Some(idx);
 
                         ui.horizontal(|ui| {
-                            if ui.selectable_label(selected, format!("#{idx}")).clicked() {
+                            let response = ui.selectable_label(selected, format!("#{idx}"));
+                            if response.clicked() {
                                 app.ui.selected_injected_region = Some(idx);
                             }
+                            scroll_if_jumped(&mut app.ui.scroll_to_selected, selected, &response);
 
                             ui.label(
                                 egui::RichText::new(r.risk.label())
@@ -425,43 +1049,92 @@ fn memory(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                 }
             });
 
-        if let Some(idx) = app.ui.selected_injected_region {
-            if let Some(r) = report.injected_regions.get(idx) {
-                ui.add_space(8.0);
-                ui.separator();
-                ui.add_space(8.0);
-                ui.label(egui::RichText::new("Selected Allocation").strong());
-                ui.monospace(format!("base=0x{:016X} size=0x{:X}", r.base, r.size));
-                ui.monospace(format!("protection={}", r.protection));
-                ui.monospace(format!("type={}", r.ty));
-                ui.monospace(format!("state={}", r.state));
-                if !r.reasons.is_empty() {
-                    ui.add_space(6.0);
-                    for reason in &r.reasons {
-                        ui.add(egui::Label::new(format!("- {reason}")).wrap(true));
-                    }
+        if let Some(idx) = app.ui.selected_injected_region
+            && let Some(r) = report.injected_regions.get(idx)
+        {
+            ui.add_space(8.0);
+            ui.separator();
+            ui.add_space(8.0);
+            ui.label(egui::RichText::new("Selected Allocation").strong());
+            ui.monospace(format!("base=0x{:016X} size=0x{:X}", r.base, r.size));
+            ui.monospace(format!("protection={}", r.protection));
+            ui.monospace(format!("type={}", r.ty));
+            ui.monospace(format!("state={}", r.state));
+            if !r.reasons.is_empty() {
+                ui.add_space(6.0);
+                for reason in &r.reasons {
+                    ui.add(egui::Label::new(format!("- {reason}")).wrap(true));
                 }
             }
         }
     });
 }
 
+/// `/proc/self/maps` table; executable mappings without a backing file are highlighted.
+fn linux_maps(ui: &mut egui::Ui, maps: &[crate::model::LinuxMapping]) {
+    egui::CollapsingHeader::new(format!("Linux maps ({})", maps.len()))
+        .id_source("memory_linux_maps")
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_source("memory_linux_maps_scroll")
+                .max_height(260.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    egui::Grid::new("memory_linux_maps_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("start");
+                            ui.strong("end");
+                            ui.strong("perms");
+                            ui.strong("offset");
+                            ui.strong("path");
+                            ui.end_row();
+                            for m in maps {
+                                let suspicious = m.is_executable()
+                                    && !m.is_kernel_provided()
+                                    && (m.is_anonymous() || m.is_fileless());
+                                let text = |s: String| {
+                                    let t = egui::RichText::new(s).monospace();
+                                    if suspicious {
+                                        t.color(crate::ui::severity_color(
+                                            crate::model::Severity::Warning,
+                                        ))
+                                    } else {
+                                        t
+                                    }
+                                };
+                                ui.label(text(format!("0x{:016X}", m.start)));
+                                ui.label(text(format!("0x{:016X}", m.end)));
+                                ui.label(text(m.perms.clone()));
+                                ui.label(text(format!("0x{:X}", m.offset)));
+                                ui.label(text(if m.path.is_empty() {
+                                    "(anonymous)".into()
+                                } else {
+                                    m.path.clone()
+                                }));
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+}
+
 fn modules(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(report) = &app.dump_report else {
+    let Some(report) = app.session.dump_report() else {
         ui.label("Load a minidump to browse modules.");
         return;
     };
 
-    ui.horizontal(|ui| {
-        ui.label("Filter:");
-        ui.text_edit_singleline(&mut app.ui.module_filter);
-        if ui.button("Clear##module_filter").clicked() {
-            app.ui.module_filter.clear();
-        }
-    });
+    crate::ui::query_box(
+        ui,
+        "modules",
+        &mut app.ui.module_filter,
+        &mut app.ui.query_history,
+        &mut app.ui.last_error,
+    );
+    let edit = module_baseline(ui, &app.baselines, &mut app.ui.unknown_modules_only, report);
     ui.add_space(6.0);
-
-    let filter = app.ui.module_filter.trim().to_ascii_lowercase();
+    let baseline = app.baselines.for_report(report);
 
     egui::ScrollArea::vertical()
         .id_source("modules_scroll")
@@ -476,66 +1149,180 @@ fn modules(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                     ui.label(egui::RichText::new("Size").strong());
                     ui.label(egui::RichText::new("TimeDateStamp").strong());
                     ui.label(egui::RichText::new("Version").strong());
+                    ui.label(egui::RichText::new("Baseline").strong());
                     ui.label(egui::RichText::new("Name").strong());
                     ui.end_row();
 
                     for (idx, m) in report.modules.iter().enumerate() {
-                        if !filter.is_empty()
-                            && !m.name.to_ascii_lowercase().contains(&filter)
-                            && !format!("{:016X}", m.base)
-                                .to_ascii_lowercase()
-                                .contains(&filter)
+                        let record = ModuleRecord {
+                            index: idx,
+                            module: m,
+                            baseline: baseline.as_ref().map(|b| b.module(m)),
+                        };
+                        if !app.ui.module_filter.matches(&record)
+                            || (app.ui.unknown_modules_only
+                                && record.baseline != Some(ModuleBaseline::Unknown))
                         {
                             continue;
                         }
 
                         let selected = app.ui.selected_module == Some(idx);
-                        if ui.selectable_label(selected, idx.to_string()).clicked() {
+                        let response = ui.selectable_label(selected, idx.to_string());
+                        if response.clicked() {
                             app.ui.selected_module = Some(idx);
                         }
+                        scroll_if_jumped(&mut app.ui.scroll_to_selected, selected, &response);
                         ui.monospace(format!("0x{:016X}", m.base));
                         ui.monospace(format!("0x{:X}", m.size));
                         ui.monospace(format!("0x{:08X}", m.time_date_stamp));
                         ui.monospace(m.file_version.as_deref().unwrap_or("-"));
+                        match record.baseline {
+                            Some(ModuleBaseline::Unknown) => ui.label(
+                                egui::RichText::new("unknown")
+                                    .color(crate::ui::severity_color(Severity::Warning)),
+                            ),
+                            Some(known) => ui.weak(known.label()),
+                            None => ui.weak("-"),
+                        };
                         ui.label(&m.name);
                         ui.end_row();
                     }
                 });
         });
+    if let Some(edit) = edit {
+        let res = match edit {
+            BaselineEdit::Record => app.baselines.record(report).map(|_| ()),
+            BaselineEdit::Action(action) => app.baselines.set_action(report, action),
+            BaselineEdit::AddCommandLine(pattern) => {
+                app.baselines.add_command_line(report, &pattern)
+            }
+            BaselineEdit::RemoveCommandLine(pattern) => {
+                app.baselines.remove_command_line(report, &pattern)
+            }
+        };
+        if let Err(e) = res {
+            app.ui.last_error = Some(e.to_string());
+        }
+    }
 
-    if let Some(idx) = app.ui.selected_module {
-        if let Some(m) = report.modules.get(idx) {
-            ui.add_space(10.0);
-            ui.separator();
-            ui.add_space(10.0);
-            ui.label(egui::RichText::new("Selected Module").strong());
-            ui.monospace(&m.name);
-            ui.monospace(format!("base=0x{:016X} size=0x{:X}", m.base, m.size));
-            ui.monospace(format!(
-                "checksum=0x{:08X} timestamp=0x{:08X}",
-                m.checksum, m.time_date_stamp
-            ));
+    if let Some(idx) = app.ui.selected_module
+        && let Some(m) = report.modules.get(idx)
+    {
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+        ui.label(egui::RichText::new("Selected Module").strong());
+        ui.monospace(&m.name);
+        ui.monospace(format!("base=0x{:016X} size=0x{:X}", m.base, m.size));
+        ui.monospace(format!(
+            "checksum=0x{:08X} timestamp=0x{:08X}",
+            m.checksum, m.time_date_stamp
+        ));
+        if let Some(id) = &m.debug_id {
+            ui.monospace(format!("debug_id={id}"));
         }
     }
 }
 
+/// A change to the baseline made in the Modules tab.
+enum BaselineEdit {
+    Record,
+    Action(BaselineAction),
+    AddCommandLine(String),
+    RemoveCommandLine(String),
+}
+
+/// Baseline summary of the dump's product version: modules not in it, what happens to
+/// detections it covers, its known-good command lines, and recording this dump into it.
+fn module_baseline(
+    ui: &mut egui::Ui,
+    baselines: &Baselines,
+    unknown_only: &mut bool,
+    report: &MinidumpReport,
+) -> Option<BaselineEdit> {
+    let (product, version) = crate::baseline::product_of(report)?;
+    let label = crate::baseline::label(product, version);
+    let applied = baselines.for_report(report);
+    let mut edit = None;
+    ui.horizontal_wrapped(|ui| {
+        match &applied {
+            Some(b) => {
+                let unknown = b.unknown_modules(report).len();
+                ui.label(format!(
+                    "Baseline {label}: {unknown} of {} modules not in it",
+                    report.modules.len()
+                ));
+                ui.checkbox(unknown_only, "Only unknown");
+                let action = b.action();
+                egui::ComboBox::from_id_source("baseline_action")
+                    .selected_text(action.label())
+                    .show_ui(ui, |ui| {
+                        for a in BaselineAction::ALL {
+                            if ui.selectable_label(a == action, a.label()).clicked() {
+                                edit = Some(BaselineEdit::Action(a));
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "What happens to detections about baseline modules and command lines only",
+                    );
+            }
+            None => {
+                ui.label(format!("No baseline for {label}"));
+            }
+        }
+        if ui
+            .button("Record modules")
+            .on_hover_text(format!(
+                "Add this dump's modules to the known-good baseline of {label} (LOG_ATLAS_BASELINE)"
+            ))
+            .clicked()
+        {
+            edit = Some(BaselineEdit::Record);
+        }
+    });
+    let Some(applied) = applied else {
+        return edit;
+    };
+    let patterns: Vec<&str> = applied.command_lines().collect();
+    egui::CollapsingHeader::new(format!("Known-good command lines ({})", patterns.len()))
+        .id_source("baseline_command_lines")
+        .show(ui, |ui| {
+            for pattern in patterns {
+                ui.horizontal(|ui| {
+                    if ui.small_button("x").on_hover_text("Remove").clicked() {
+                        edit = Some(BaselineEdit::RemoveCommandLine(pattern.to_string()));
+                    }
+                    ui.monospace(pattern);
+                });
+            }
+            if let Some(text) = inline_entry(
+                ui,
+                egui::Id::new("baseline_command_line"),
+                "*\\updater.exe --install *",
+            ) {
+                edit = Some(BaselineEdit::AddCommandLine(text));
+            }
+        });
+    edit
+}
+
 fn threads(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(report) = &app.dump_report else {
+    let Some(report) = app.session.dump_report() else {
         ui.label("Load a minidump to browse threads.");
         return;
     };
 
-    ui.horizontal(|ui| {
-        ui.label("Filter:");
-        ui.text_edit_singleline(&mut app.ui.thread_filter);
-        if ui.button("Clear##thread_filter").clicked() {
-            app.ui.thread_filter.clear();
-        }
-    });
+    crate::ui::query_box(
+        ui,
+        "threads",
+        &mut app.ui.thread_filter,
+        &mut app.ui.query_history,
+        &mut app.ui.last_error,
+    );
     ui.add_space(6.0);
 
-    let filter = app.ui.thread_filter.trim().to_ascii_lowercase();
-
     egui::ScrollArea::vertical()
         .id_source("threads_scroll")
         .auto_shrink([false, false])
@@ -554,22 +1341,17 @@ fn threads(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
 
                     for t in &report.threads {
                         let name = t.name.as_deref().unwrap_or("-");
-                        if !filter.is_empty()
-                            && !format!("{}", t.thread_id)
-                                .to_ascii_lowercase()
-                                .contains(&filter)
-                            && !name.to_ascii_lowercase().contains(&filter)
-                        {
+                        if !app.ui.thread_filter.matches(&ThreadRecord(t)) {
                             continue;
                         }
 
                         let selected = app.ui.selected_thread == Some(t.thread_id);
-                        if ui
-                            .selectable_label(selected, format!("0x{:X}", t.thread_id))
-                            .clicked()
-                        {
+                        let response =
+                            ui.selectable_label(selected, format!("0x{:X}", t.thread_id));
+                        if response.clicked() {
                             app.ui.selected_thread = Some(t.thread_id);
                         }
+                        scroll_if_jumped(&mut app.ui.scroll_to_selected, selected, &response);
                         ui.label(name);
                         ui.monospace(
                             t.create_time_unix
@@ -584,42 +1366,42 @@ fn threads(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                 });
         });
 
-    if let Some(tid) = app.ui.selected_thread {
-        if let Some(t) = report.threads.iter().find(|t| t.thread_id == tid) {
-            ui.add_space(10.0);
-            ui.separator();
-            ui.add_space(10.0);
-            ui.label(egui::RichText::new("Selected Thread").strong());
-            ui.monospace(format!("thread_id=0x{tid:X}"));
-            if let Some(name) = &t.name {
-                ui.monospace(format!("name={name}"));
-            }
-            if let Some(ft) = t.create_time_filetime {
-                ui.monospace(format!("create_time_filetime={ft}"));
-            }
-            if let Some(unix) = t.create_time_unix {
-                ui.monospace(format!("create_time_unix={unix}"));
-                if let Some(utc) = crate::util::time::unix_seconds_to_utc_string(unix) {
-                    ui.monospace(format!("create_time_utc={utc}"));
-                }
-            }
-            if let Some(start) = t.start_address {
-                ui.monospace(format!("start_address=0x{start:016X}"));
+    if let Some(tid) = app.ui.selected_thread
+        && let Some(t) = report.threads.iter().find(|t| t.thread_id == tid)
+    {
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+        ui.label(egui::RichText::new("Selected Thread").strong());
+        ui.monospace(format!("thread_id=0x{tid:X}"));
+        if let Some(name) = &t.name {
+            ui.monospace(format!("name={name}"));
+        }
+        if let Some(ft) = t.create_time_filetime {
+            ui.monospace(format!("create_time_filetime={ft}"));
+        }
+        if let Some(unix) = t.create_time_unix {
+            ui.monospace(format!("create_time_unix={unix}"));
+            if let Some(utc) = crate::util::time::unix_seconds_to_utc_string(unix) {
+                ui.monospace(format!("create_time_utc={utc}"));
             }
-            ui.monospace(format!(
-                "suspend_count={} priority_class=0x{:X} priority=0x{:X}",
-                t.suspend_count, t.priority_class, t.priority
-            ));
-            ui.monospace(format!(
-                "teb=0x{:016X} stack_start=0x{:016X} stack_size=0x{:X}",
-                t.teb, t.stack_start, t.stack_size
-            ));
         }
+        if let Some(start) = t.start_address {
+            ui.monospace(format!("start_address=0x{start:016X}"));
+        }
+        ui.monospace(format!(
+            "suspend_count={} priority_class=0x{:X} priority=0x{:X}",
+            t.suspend_count, t.priority_class, t.priority
+        ));
+        ui.monospace(format!(
+            "teb=0x{:016X} stack_start=0x{:016X} stack_size=0x{:X}",
+            t.teb, t.stack_start, t.stack_size
+        ));
     }
 }
 
 fn stacks(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(report) = &app.dump_report else {
+    let Some(report) = app.session.dump_report() else {
         ui.label("Load a minidump to inspect call stacks.");
         return;
     };
@@ -667,16 +1449,14 @@ fn stacks(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
     });
 
     ui.add_space(8.0);
-    ui.horizontal(|ui| {
-        ui.label("Filter:");
-        ui.text_edit_singleline(&mut app.ui.stack_filter);
-        if ui.button("Clear").clicked() {
-            app.ui.stack_filter.clear();
-        }
-    });
+    crate::ui::query_box(
+        ui,
+        "stacks",
+        &mut app.ui.stack_filter,
+        &mut app.ui.query_history,
+        &mut app.ui.last_error,
+    );
     ui.add_space(6.0);
-
-    let filter = app.ui.stack_filter.trim().to_ascii_lowercase();
     ui.columns(2, |cols| {
         cols[0].label(egui::RichText::new("Threads").strong());
         cols[0].add_space(4.0);
@@ -692,11 +1472,7 @@ fn stacks(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
                         .unwrap_or_else(|| "-".into());
                     let name = t.thread_name.as_deref().unwrap_or("-");
                     let tid_s = format!("0x{:X}", t.thread_id);
-                    if !filter.is_empty()
-                        && !tid_s.to_ascii_lowercase().contains(&filter)
-                        && !name.to_ascii_lowercase().contains(&filter)
-                        && !top.to_ascii_lowercase().contains(&filter)
-                    {
+                    if !app.ui.stack_filter.matches(&StackRecord(t)) {
                         continue;
                     }
 
@@ -849,7 +1625,7 @@ fn stack_status_color(status: &str) -> egui::Color32 {
 }
 
 fn exception(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(report) = &app.dump_report else {
+    let Some(report) = app.session.dump_report() else {
         ui.label("Load a minidump to inspect the exception stream.");
         return;
     };
@@ -911,34 +1687,945 @@ fn exception(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
 }
 
 fn detections(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    let Some(report) = &app.dump_report else {
+    let Some(source) = app.session.dump_source() else {
         ui.label("Load a minidump to see detections.");
         return;
     };
-
-    let dets = report.detections();
+    let Some(report) = source.dump_report() else {
+        return;
+    };
+    let source_id = source.id;
+    let dets = app.detections(source);
     if dets.is_empty() {
         ui.label("No detections fired (basic rules).");
-        return;
+    } else {
+        crate::ui::query_box(
+            ui,
+            "detections",
+            &mut app.ui.detection_filter,
+            &mut app.ui.query_history,
+            &mut app.ui.last_error,
+        );
     }
+    let visible: Vec<&TriagedDetection> = dets
+        .iter()
+        .filter(|d| match app.ui.detection_status_filter {
+            Some(status) => d.status == status,
+            None => app.ui.show_dismissed || !d.status.is_dismissed(),
+        })
+        .filter(|d| {
+            app.ui.detection_filter.matches(&DetectionRecord {
+                detection: &d.detection,
+                report,
+                id: &d.id,
+                status: d.status,
+                tags: &d.tags,
+            })
+        })
+        .collect();
+
+    if !dets.is_empty() {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("detection_status_filter")
+                .selected_text(
+                    app.ui
+                        .detection_status_filter
+                        .map_or("Any status", TriageStatus::label),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut app.ui.detection_status_filter, None, "Any status");
+                    for status in TriageStatus::ALL {
+                        ui.selectable_value(
+                            &mut app.ui.detection_status_filter,
+                            Some(status),
+                            status.label(),
+                        );
+                    }
+                });
+            ui.add_enabled(
+                app.ui.detection_status_filter.is_none(),
+                egui::Checkbox::new(&mut app.ui.show_dismissed, "Show dismissed"),
+            )
+            .on_hover_text("False positives and ignored detections");
+            ui.label(format!("showing {} of {}", visible.len(), dets.len()));
+            if ui.button("Export JSON Lines...").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("JSON Lines", &["jsonl", "ndjson"])
+                    .set_file_name("detections.jsonl")
+                    .save_file()
+            {
+                let rows: Vec<TriagedDetection> = visible.iter().map(|d| (*d).clone()).collect();
+                let res = crate::export::detections_to_json(&rows)
+                    .and_then(|s| std::fs::write(&path, s).map_err(Into::into));
+                if let Err(e) = res {
+                    app.ui.last_error = Some(format!("export {}: {e}", path.display()));
+                }
+            }
+        });
+    }
+    ui.add_space(6.0);
+    let remove_entry = allowlist(ui, &mut app.allowlist, &mut app.ui, &dets);
+    ui.add_space(6.0);
 
+    let mut edits = Vec::new();
+    let mut notes = Vec::new();
+    let mut remove_note = None;
+    let mut select = None;
+    let mut prefill = None;
     egui::ScrollArea::vertical()
         .id_source("detections_scroll")
         .auto_shrink([false, false])
         .show(ui, |ui| {
-            for det in dets {
-                ui.horizontal(|ui| {
+            // Grouped by ATT&CK tactic in kill-chain order; crash findings without a
+            // technique last.
+            let mut groups: BTreeMap<(bool, Option<Tactic>), Vec<&TriagedDetection>> =
+                BTreeMap::new();
+            for det in visible {
+                let tactic = det.detection.tactic();
+                groups
+                    .entry((tactic.is_none(), tactic))
+                    .or_default()
+                    .push(det);
+            }
+            for ((_, tactic), dets) in groups {
+                let title = match tactic {
+                    Some(t) => format!("{} ({}): {}", t.label(), t.id(), dets.len()),
+                    None => format!("No ATT&CK technique: {}", dets.len()),
+                };
+                egui::CollapsingHeader::new(egui::RichText::new(title).strong())
+                    .id_source(("detections_tactic", tactic))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for det in dets {
+                            let subject = Subject::Detection(source_id, det.id.clone());
+                            let dismissed = det.status.is_dismissed();
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(det.detection.severity.label())
+                                        .color(crate::ui::severity_color(det.detection.severity))
+                                        .strong(),
+                                );
+                                let title = egui::RichText::new(&det.detection.title).strong();
+                                ui.label(if dismissed { title.weak() } else { title });
+                                ui.weak(&det.id);
+                                if let Some(label) = &det.baseline {
+                                    ui.weak(format!("baseline {label}")).on_hover_text(
+                            "Every module and command line it names is in this baseline",
+                        );
+                                }
+                            });
+                            ui.horizontal_wrapped(|ui| {
+                                ui.monospace(&det.detection.rule);
+                                for t in &det.attack {
+                                    ui.label(
+                                        egui::RichText::new(&t.technique).monospace().strong(),
+                                    )
+                                    .on_hover_text(format!(
+                                        "{} ({})",
+                                        t.name,
+                                        t.tactics.join(", ")
+                                    ));
+                                }
+                                ui.weak(format!("confidence {}%", det.detection.confidence));
+                            });
+                            ui.horizontal_wrapped(|ui| {
+                                if let Some(edit) = triage_controls(
+                                    ui,
+                                    &subject,
+                                    det.status,
+                                    &det.tags,
+                                    det.allowlisted.as_deref(),
+                                ) {
+                                    edits.push((subject.clone(), edit));
+                                }
+                                let event = app.session.detection_event(source_id, &det.id);
+                                if ui
+                                    .add_enabled(event.is_some(), egui::Button::new("Event"))
+                                    .on_hover_text("Select the detection's timeline event")
+                                    .clicked()
+                                {
+                                    select = event;
+                                }
+                                if ui
+                                    .button("Allowlist...")
+                                    .on_hover_text(
+                                        "Start an allowlist entry matching this detection",
+                                    )
+                                    .clicked()
+                                {
+                                    prefill = Some(allowlist_query(&det.detection, report));
+                                }
+                            });
+                            ui.add(egui::Label::new(&det.detection.details).wrap(true));
+                            let on_det = app
+                                .session
+                                .notes()
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, n)| n.about.as_ref() == Some(&subject));
+                            if let Some(index) = note_list(ui, on_det, |_, _| {}) {
+                                remove_note = Some(index);
+                            }
+                            if let Some(text) =
+                                inline_entry(ui, egui::Id::new(("note", &subject)), "add note")
+                            {
+                                notes.push((subject, text));
+                            }
+                            ui.add_space(8.0);
+                            ui.separator();
+                            ui.add_space(8.0);
+                        }
+                    });
+            }
+        });
+
+    for (subject, edit) in edits {
+        edit.apply(app, subject);
+    }
+    for (subject, text) in notes {
+        app.session.add_note(crate::case::Note::new(
+            Some(subject),
+            text.trim().to_string(),
+        ));
+    }
+    if let Some(index) = remove_note {
+        app.session.remove_note(index);
+    }
+    if let Some(id) = select {
+        app.select(id);
+    }
+    if let Some(query) = prefill {
+        app.ui.allowlist_draft.query = query;
+        app.ui.reveal_allowlist = true;
+    }
+    if let Some(index) = remove_entry
+        && let Err(e) = app.allowlist.remove(index)
+    {
+        app.ui.last_error = Some(e.to_string());
+    }
+}
+
+/// The allowlist: its entries (with how many of `dets` each suppresses) and the editor for a
+/// new one. Returns the index of an entry to delete.
+fn allowlist(
+    ui: &mut egui::Ui,
+    list: &mut Allowlist,
+    state: &mut UiState,
+    dets: &[TriagedDetection],
+) -> Option<usize> {
+    let mut remove = None;
+    let open = std::mem::take(&mut state.reveal_allowlist).then_some(true);
+    egui::CollapsingHeader::new(format!("Allowlist ({})", list.entries.len()))
+        .id_source("detections_allowlist")
+        .open(open)
+        .show(ui, |ui| {
+            ui.label(
+                "Detections matching an entry get its status unless you set one. Entries apply to every dump and are saved in LOG_ATLAS_ALLOWLIST (default: log-atlas/allowlist.txt in the config directory).",
+            );
+            egui::Grid::new("allowlist_grid")
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    for (index, entry) in list.entries.iter().enumerate() {
+                        if ui.small_button("x").on_hover_text("Delete entry").clicked() {
+                            remove = Some(index);
+                        }
+                        ui.label(entry.status.label());
+                        let query = egui::RichText::new(&entry.query).monospace();
+                        if entry.is_valid() {
+                            ui.label(query);
+                        } else {
+                            ui.label(query.color(ui.visuals().error_fg_color))
+                                .on_hover_text("Invalid query: never matches");
+                        }
+                        ui.label(&entry.reason);
+                        let hits = dets
+                            .iter()
+                            .filter(|d| d.allow_entry == Some(index))
+                            .count();
+                        ui.weak(format!("{hits} here"));
+                        ui.end_row();
+                    }
+                });
+            ui.add_space(4.0);
+            let draft = &mut state.allowlist_draft;
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("allowlist_status")
+                    .selected_text(draft.status.label())
+                    .show_ui(ui, |ui| {
+                        for status in TriageStatus::ALL.into_iter().skip(1) {
+                            ui.selectable_value(&mut draft.status, status, status.label());
+                        }
+                    });
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.query)
+                        .code_editor()
+                        .desired_width(260.0)
+                        .hint_text("title=\"Module loaded from temp path\" AND module:*updater*"),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.reason)
+                        .desired_width(160.0)
+                        .hint_text("reason"),
+                );
+                if ui
+                    .add_enabled(!draft.query.trim().is_empty(), egui::Button::new("Add"))
+                    .clicked()
+                {
+                    match list.add(draft.status, &draft.query, &draft.reason) {
+                        Ok(()) => *draft = Default::default(),
+                        Err(e) => state.last_error = Some(e.to_string()),
+                    }
+                }
+            });
+        });
+    remove
+}
+
+/// Query for an allowlist entry matching `det`: its title, plus the module or artifact image
+/// it is about.
+fn allowlist_query(det: &Detection, report: &MinidumpReport) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut query = format!("title={}", quote(&det.title));
+    let about = match det.links.first() {
+        Some(EntityRef::Module(i)) => report.modules.get(*i).map(|m| ("module", &m.name)),
+        Some(EntityRef::ExecArtifact(i)) => report
+            .exec_artifacts
+            .get(*i)
+            .map(|a| ("artifact", &a.image)),
+        _ => None,
+    };
+    if let Some((field, value)) = about {
+        query.push_str(&format!(" AND {field}={}", quote(value)));
+    }
+    query
+}
+
+/// A change made with `triage_controls`.
+enum TriageEdit {
+    Status(TriageStatus),
+    AddTags(String),
+    RemoveTag(usize),
+}
+
+impl TriageEdit {
+    fn apply(self, app: &mut LogAtlasApp, subject: Subject) {
+        app.session.update_triage(subject, |t| match self {
+            TriageEdit::Status(status) => t.status = status,
+            TriageEdit::AddTags(text) => t.add_tags(&text),
+            TriageEdit::RemoveTag(index) => {
+                if index < t.tags.len() {
+                    t.tags.remove(index);
+                }
+            }
+        });
+    }
+}
+
+/// Status box and tags of `subject`; `status` is its effective status and `allowlisted` the
+/// reason of the allowlist entry that set it.
+fn triage_controls(
+    ui: &mut egui::Ui,
+    subject: &Subject,
+    status: TriageStatus,
+    tags: &[String],
+    allowlisted: Option<&str>,
+) -> Option<TriageEdit> {
+    let mut edit = None;
+    ui.horizontal_wrapped(|ui| {
+        egui::ComboBox::from_id_source(("triage_status", subject))
+            .selected_text(status.label())
+            .show_ui(ui, |ui| {
+                for s in TriageStatus::ALL {
+                    if ui.selectable_label(s == status, s.label()).clicked() {
+                        edit = Some(TriageEdit::Status(s));
+                    }
+                }
+            });
+        if let Some(reason) = allowlisted {
+            ui.weak("allowlisted").on_hover_text(reason);
+        }
+        for (index, tag) in tags.iter().enumerate() {
+            if ui
+                .small_button(format!("#{tag}"))
+                .on_hover_text("Remove tag")
+                .clicked()
+            {
+                edit = Some(TriageEdit::RemoveTag(index));
+            }
+        }
+        if let Some(text) = inline_entry(ui, egui::Id::new(("tags", subject)), "add tags") {
+            edit = Some(TriageEdit::AddTags(text));
+        }
+    });
+    edit
+}
+
+/// One-line text box whose text lives in egui memory under `id`; returns the text when Enter
+/// is pressed on it.
+fn inline_entry(ui: &mut egui::Ui, id: egui::Id, hint: &str) -> Option<String> {
+    let mut text: String = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .id(id.with("edit"))
+            .desired_width(120.0)
+            .hint_text(hint),
+    );
+    if response.lost_focus()
+        && ui.input(|i| i.key_pressed(egui::Key::Enter))
+        && !text.trim().is_empty()
+    {
+        ui.data_mut(|d| d.remove::<String>(id));
+        return Some(text);
+    }
+    ui.data_mut(|d| d.insert_temp(id, text));
+    None
+}
+
+fn incidents(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
+    let session = &app.session;
+    if session.incidents.is_empty() {
+        ui.label("Load a dump and logs of the same machine to correlate them.");
+        return;
+    }
+
+    ui.label(egui::RichText::new("Incidents").strong());
+    ui.label(format!(
+        "Log events linked to the dumps by PID, thread id, image or module name, or logged as a warning or error within {}s of the dump capture (LOG_ATLAS_CORRELATION_WINDOW_SECS).",
+        crate::correlate::window_secs()
+    ));
+    crate::ui::query_box(
+        ui,
+        "incidents",
+        &mut app.ui.incident_filter,
+        &mut app.ui.query_history,
+        &mut app.ui.last_error,
+    );
+    ui.add_space(6.0);
+
+    let mut select = None;
+    egui::ScrollArea::vertical()
+        .id_source("incidents_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for (index, incident) in session.incidents.iter().enumerate() {
+                ui.horizontal_wrapped(|ui| {
                     ui.label(
-                        egui::RichText::new(det.severity.label())
-                            .color(crate::ui::severity_color(det.severity))
-                            .strong(),
+                        egui::RichText::new(format!("#{index} {}", incident.title())).strong(),
                     );
-                    ui.label(egui::RichText::new(det.title).strong());
+                    if let Some(capture) = incident.capture {
+                        ui.monospace(crate::util::time::format_timestamp(capture));
+                    }
+                    for source in session.incident_sources(incident) {
+                        ui.label(
+                            egui::RichText::new(source.name())
+                                .monospace()
+                                .color(source.color),
+                        );
+                    }
+                    ui.weak(format!("{} linked events", incident.links.len()));
                 });
-                ui.add(egui::Label::new(det.details).wrap(true));
+                egui::Grid::new(("incident_links", index))
+                    .striped(true)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        for link in &incident.links {
+                            let Some(ev) = session.event(link.event) else {
+                                continue;
+                            };
+                            let record = LinkRecord {
+                                incident: index,
+                                link,
+                                event: EventRecord {
+                                    id: link.event,
+                                    ..EventRecord::new(ev, None)
+                                },
+                            };
+                            if !app.ui.incident_filter.matches(&record) {
+                                continue;
+                            }
+                            let source = session.source_of(link.event);
+                            ui.monospace(
+                                link.delta_ms
+                                    .map_or_else(|| "-".into(), crate::correlate::format_delta),
+                            );
+                            if let Some(source) = source {
+                                ui.label(
+                                    egui::RichText::new(source.name())
+                                        .monospace()
+                                        .color(source.color),
+                                );
+                            }
+                            ui.label(
+                                egui::RichText::new(ev.severity.label())
+                                    .color(crate::ui::severity_color(ev.severity)),
+                            );
+                            // Events of hidden sources are not on the timeline to select.
+                            let visible = source.is_some_and(|s| s.visible);
+                            if ui
+                                .add_enabled(visible, egui::SelectableLabel::new(false, &ev.title))
+                                .on_hover_text(&ev.details)
+                                .clicked()
+                            {
+                                select = Some(link.event);
+                            }
+                            ui.label(
+                                link.reasons
+                                    .iter()
+                                    .map(ToString::to_string)
+                                    .collect::<Vec<_>>()
+                                    .join("; "),
+                            );
+                            ui.end_row();
+                        }
+                    });
                 ui.add_space(8.0);
                 ui.separator();
                 ui.add_space(8.0);
             }
         });
+
+    if let Some(id) = select {
+        app.select(id);
+    }
+}
+
+fn iocs(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
+    let Some(report) = app.session.dump_report() else {
+        ui.label("Load a minidump to see indicators.");
+        return;
+    };
+
+    ui.label(egui::RichText::new("Indicators of Compromise").strong());
+    ui.label("URLs, domains, IPs, emails, paths, registry keys, pipes and mutex names found in dump memory (ASCII + UTF-16LE).");
+    if let Some(cov) = &report.ioc_scan {
+        scan_coverage_label(ui, cov);
+    }
+
+    ui.add_space(6.0);
+    ui.horizontal_wrapped(|ui| {
+        for kind in crate::model::IocKind::ALL {
+            let n = report.iocs.iter().filter(|i| i.kind == kind).count();
+            if n > 0 {
+                ui.monospace(format!("{}={n}", kind.label()));
+            }
+        }
+    });
+
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        ui.label("Kind:");
+        egui::ComboBox::from_id_source("ioc_kind_filter")
+            .selected_text(app.ui.ioc_kind_filter.map_or("all", |k| k.label()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut app.ui.ioc_kind_filter, None, "all");
+                for kind in crate::model::IocKind::ALL {
+                    ui.selectable_value(&mut app.ui.ioc_kind_filter, Some(kind), kind.label());
+                }
+            });
+    });
+    crate::ui::query_box(
+        ui,
+        "iocs",
+        &mut app.ui.ioc_filter,
+        &mut app.ui.query_history,
+        &mut app.ui.last_error,
+    );
+    let visible: Vec<(usize, &crate::model::Indicator)> = report
+        .iocs
+        .iter()
+        .enumerate()
+        .filter(|(_, i)| app.ui.ioc_kind_filter.is_none_or(|k| k == i.kind))
+        .filter(|(_, i)| app.ui.ioc_filter.matches(&IndicatorRecord(i)))
+        .collect();
+
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "showing {} of {}",
+            visible.len(),
+            report.iocs.len()
+        ));
+        let rows: Vec<crate::model::Indicator> =
+            visible.iter().map(|(_, i)| (*i).clone()).collect();
+        if ui.button("Export CSV...").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("CSV", &["csv"])
+                .set_file_name("iocs.csv")
+                .save_file()
+            && let Err(e) = std::fs::write(&path, crate::export::iocs_to_csv(&rows))
+        {
+            app.ui.last_error = Some(format!("export {}: {e}", path.display()));
+        }
+        if ui.button("Export JSON Lines...").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("JSON Lines", &["jsonl", "ndjson"])
+                .set_file_name("iocs.jsonl")
+                .save_file()
+        {
+            let res = crate::export::iocs_to_json(&rows)
+                .and_then(|s| std::fs::write(&path, s).map_err(Into::into));
+            if let Err(e) = res {
+                app.ui.last_error = Some(format!("export {}: {e}", path.display()));
+            }
+        }
+    });
+    ui.add_space(6.0);
+
+    egui::ScrollArea::vertical()
+        .id_source("iocs_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("iocs_grid")
+                .striped(true)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Kind").strong());
+                    ui.label(egui::RichText::new("Count").strong());
+                    ui.label(egui::RichText::new("Address").strong());
+                    ui.label(egui::RichText::new("Container").strong());
+                    ui.label(egui::RichText::new("Value").strong());
+                    ui.end_row();
+
+                    for (idx, ioc) in &visible {
+                        let selected = app.ui.selected_ioc == Some(*idx);
+                        if ui.selectable_label(selected, ioc.kind.label()).clicked() {
+                            app.ui.selected_ioc = Some(*idx);
+                        }
+                        ui.monospace(ioc.count.to_string());
+                        ui.monospace(format!("0x{:016X}", ioc.address));
+                        ui.label(ioc.container.as_deref().unwrap_or("-"));
+                        ui.label(&ioc.value);
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn sensitive(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
+    let Some(report) = app.session.dump_report() else {
+        ui.label("Load a minidump to see sensitive data findings.");
+        return;
+    };
+
+    ui.label(egui::RichText::new("Sensitive Data").strong());
+    ui.label("Likely credentials in dump memory: JWT/bearer tokens, cloud keys, private keys, passwords and NTLM hashes. Values are masked; review before sharing the dump.");
+    if let Some(cov) = &report.sensitive_scan {
+        scan_coverage_label(ui, cov);
+    }
+
+    ui.add_space(6.0);
+    if report.sensitive.is_empty() {
+        ui.label("(no likely secrets found)");
+        return;
+    }
+    ui.horizontal_wrapped(|ui| {
+        for kind in crate::model::SecretKind::ALL {
+            let n = report.sensitive.iter().filter(|f| f.kind == kind).count();
+            if n > 0 {
+                ui.monospace(format!("{}={n}", kind.label()));
+            }
+        }
+    });
+
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        ui.label("Kind:");
+        egui::ComboBox::from_id_source("sensitive_kind_filter")
+            .selected_text(app.ui.sensitive_kind_filter.map_or("all", |k| k.label()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut app.ui.sensitive_kind_filter, None, "all");
+                for kind in crate::model::SecretKind::ALL {
+                    ui.selectable_value(
+                        &mut app.ui.sensitive_kind_filter,
+                        Some(kind),
+                        kind.label(),
+                    );
+                }
+            });
+        if ui.button("Export JSON Lines...").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("JSON Lines", &["jsonl", "ndjson"])
+                .set_file_name("sensitive.jsonl")
+                .save_file()
+        {
+            let res = crate::export::sensitive_to_json(&report.sensitive)
+                .and_then(|s| std::fs::write(&path, s).map_err(Into::into));
+            if let Err(e) = res {
+                app.ui.last_error = Some(format!("export {}: {e}", path.display()));
+            }
+        }
+    });
+    ui.add_space(6.0);
+
+    egui::ScrollArea::vertical()
+        .id_source("sensitive_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("sensitive_grid")
+                .striped(true)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new("Kind").strong());
+                    ui.label(egui::RichText::new("Count").strong());
+                    ui.label(egui::RichText::new("Address").strong());
+                    ui.label(egui::RichText::new("Container").strong());
+                    ui.label(egui::RichText::new("Preview").strong());
+                    ui.end_row();
+
+                    for f in report
+                        .sensitive
+                        .iter()
+                        .filter(|f| app.ui.sensitive_kind_filter.is_none_or(|k| k == f.kind))
+                    {
+                        ui.label(f.kind.label());
+                        ui.monospace(f.count().to_string());
+                        ui.monospace(format!("0x{:016X}", f.address()));
+                        ui.label(f.container.as_deref().unwrap_or("-"));
+                        ui.monospace(&f.preview);
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn process_tree(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
+    let dump_node = app.dump_process_node();
+    let dump_pid = app
+        .session
+        .dump_report()
+        .and_then(|r| r.process.as_ref())
+        .and_then(|p| p.pid);
+    let Some(tree) = app.session.process_tree() else {
+        ui.label("Load an event log with Sysmon events to see the process tree.");
+        return;
+    };
+
+    ui.label(egui::RichText::new("Process Tree").strong());
+    ui.label("Parent/child processes rebuilt from Sysmon events (process create, network, image load, remote thread, process access, file create).");
+    ui.monospace(format!(
+        "processes={} roots={} sysmon_records={}",
+        tree.nodes.len(),
+        tree.roots.len(),
+        tree.records.len()
+    ));
+
+    ui.add_space(6.0);
+    match (dump_pid, dump_node) {
+        (Some(pid), Some(idx)) => {
+            let chain: Vec<String> = tree
+                .ancestry(idx)
+                .into_iter()
+                .rev()
+                .map(|i| process_label(&tree.nodes[i]))
+                .collect();
+            ui.horizontal_wrapped(|ui| {
+                ui.label(
+                    egui::RichText::new(format!("Minidump process {pid}:"))
+                        .color(crate::ui::severity_color(crate::model::Severity::High)),
+                );
+                ui.monospace(chain.join(" -> "));
+            });
+            ui.label(format!("spawned {} processes", tree.descendants(idx).len()));
+            if ui.button("Select minidump process").clicked() {
+                app.ui.selected_tree_node = Some(idx);
+                app.ui.process_tree_filter.clear();
+            }
+        }
+        (Some(pid), None) => {
+            ui.label(format!(
+                "Minidump process {pid} does not appear in the Sysmon events."
+            ));
+        }
+        (None, _) => {
+            ui.label("Load a minidump to locate its process in the tree.");
+        }
+    }
+
+    ui.add_space(6.0);
+    crate::ui::query_box(
+        ui,
+        "processes",
+        &mut app.ui.process_tree_filter,
+        &mut app.ui.query_history,
+        &mut app.ui.last_error,
+    );
+    ui.horizontal(|ui| {
+        if ui.button("Export JSON...").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("JSON", &["json"])
+                .set_file_name("process_tree.json")
+                .save_file()
+        {
+            let res = crate::export::process_tree_to_json(tree, dump_node)
+                .and_then(|s| std::fs::write(&path, s).map_err(Into::into));
+            if let Err(e) = res {
+                app.ui.last_error = Some(format!("export {}: {e}", path.display()));
+            }
+        }
+    });
+    ui.add_space(6.0);
+
+    let expand: Vec<usize> = dump_node
+        .into_iter()
+        .chain(app.ui.selected_tree_node)
+        .flat_map(|i| tree.ancestry(i))
+        .collect();
+    let mut clicked = None;
+    egui::ScrollArea::vertical()
+        .id_source("process_tree_scroll")
+        .max_height(ui.available_height() * 0.6)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if !app.ui.process_tree_filter.is_active() {
+                let view = TreeView {
+                    tree,
+                    expand: &expand,
+                    dump_node,
+                    selected: app.ui.selected_tree_node,
+                };
+                let mut visited = vec![false; tree.nodes.len()];
+                for &root in &tree.roots {
+                    process_tree_node(ui, &view, root, &mut visited, &mut clicked);
+                }
+            } else {
+                for (idx, node) in tree.nodes.iter().enumerate() {
+                    if app.ui.process_tree_filter.matches(&ProcessRecord(node))
+                        && ui
+                            .selectable_label(
+                                app.ui.selected_tree_node == Some(idx),
+                                process_label(node),
+                            )
+                            .clicked()
+                    {
+                        clicked = Some(idx);
+                    }
+                }
+            }
+        });
+    if clicked.is_some() {
+        app.ui.selected_tree_node = clicked;
+    }
+
+    let Some(node) = app.ui.selected_tree_node.and_then(|i| tree.nodes.get(i)) else {
+        return;
+    };
+    ui.separator();
+    egui::ScrollArea::vertical()
+        .id_source("process_tree_node_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("process_tree_node_grid")
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    let row = |ui: &mut egui::Ui, k: &str, v: &str| {
+                        ui.label(egui::RichText::new(k).strong());
+                        ui.add(egui::Label::new(if v.is_empty() { "-" } else { v }).wrap(true));
+                        ui.end_row();
+                    };
+                    row(ui, "Image", &node.image);
+                    row(
+                        ui,
+                        "PID",
+                        &node.pid.map(|p| p.to_string()).unwrap_or_default(),
+                    );
+                    row(ui, "GUID", &node.guid);
+                    row(ui, "Command line", &node.command_line);
+                    row(ui, "User", &node.user);
+                    row(
+                        ui,
+                        "Started",
+                        &node
+                            .start_unix
+                            .and_then(|s| time::OffsetDateTime::from_unix_timestamp(s).ok())
+                            .map(crate::util::time::format_timestamp)
+                            .unwrap_or_default(),
+                    );
+                    for (alg, hash) in &node.hashes {
+                        row(ui, alg, hash);
+                    }
+                    if !node.observed {
+                        row(
+                            ui,
+                            "Note",
+                            "Only seen as a parent or peer; its own process-create event is not in the log.",
+                        );
+                    }
+                });
+            if !node.activity.is_empty() {
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new(format!("Activity ({})", node.activity.len())).strong());
+                for &a in node.activity.iter().take(500) {
+                    ui.monospace(tree.records[a].title());
+                }
+            }
+        });
+}
+
+struct TreeView<'a> {
+    tree: &'a crate::model::ProcessTree,
+    /// Nodes whose children start expanded.
+    expand: &'a [usize],
+    dump_node: Option<usize>,
+    selected: Option<usize>,
+}
+
+fn process_tree_node(
+    ui: &mut egui::Ui,
+    view: &TreeView,
+    idx: usize,
+    visited: &mut [bool],
+    clicked: &mut Option<usize>,
+) {
+    // Guards against parent cycles from malformed logs.
+    if std::mem::replace(&mut visited[idx], true) {
+        return;
+    }
+    let tree = view.tree;
+    let node = &tree.nodes[idx];
+    let mut text = egui::RichText::new(process_label(node)).monospace();
+    if view.dump_node == Some(idx) {
+        text = text
+            .color(crate::ui::severity_color(crate::model::Severity::High))
+            .strong();
+    } else if !node.observed {
+        text = text.weak();
+    }
+
+    if node.children.is_empty() {
+        if ui
+            .selectable_label(view.selected == Some(idx), text)
+            .clicked()
+        {
+            *clicked = Some(idx);
+        }
+        return;
+    }
+    let header = egui::CollapsingHeader::new(text)
+        .id_source(("process_tree_node", idx))
+        .default_open(view.expand.contains(&idx))
+        .show(ui, |ui| {
+            for &child in &node.children {
+                process_tree_node(ui, view, child, visited, clicked);
+            }
+        });
+    if header.header_response.clicked() {
+        *clicked = Some(idx);
+    }
+}
+
+fn process_label(node: &crate::model::ProcessNode) -> String {
+    let name = node
+        .image
+        .rsplit(['\\', '/'])
+        .next()
+        .filter(|n| !n.is_empty())
+        .unwrap_or("?");
+    let pid = node.pid.map_or("?".into(), |p| p.to_string());
+    if node.command_line.is_empty() {
+        format!("{name} ({pid})")
+    } else {
+        let cmd: String = node.command_line.chars().take(120).collect();
+        format!("{name} ({pid})  {cmd}")
+    }
 }
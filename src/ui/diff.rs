@@ -0,0 +1,271 @@
+use eframe::egui;
+
+use crate::app::{LogAtlasApp, SourceId};
+use crate::diff::{Changes, ReportDiff};
+use crate::model::Severity;
+
+const ADDED: egui::Color32 = egui::Color32::from_rgb(110, 200, 110);
+
+/// Diff tab: the shown dump against another loaded dump, before on the left, after on the
+/// right.
+pub(super) fn dump_diff(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
+    let Some(after) = app.session.dump_source() else {
+        ui.label("Load two minidumps to compare them.");
+        return;
+    };
+    let others: Vec<(SourceId, String)> = app
+        .session
+        .sources
+        .iter()
+        .filter(|s| s.dump.is_some() && s.id != after.id)
+        .map(|s| (s.id, s.name()))
+        .collect();
+    let Some(first) = others.first() else {
+        ui.label("Load a second minidump to compare with this one.");
+        return;
+    };
+    let before_id = app
+        .ui
+        .diff_before
+        .filter(|id| others.iter().any(|(o, _)| o == id))
+        .unwrap_or(first.0);
+    let before = app
+        .session
+        .source(before_id)
+        .expect("listed dump is a source");
+    let (Some(before_report), Some(after_report)) = (before.dump_report(), after.dump_report())
+    else {
+        return;
+    };
+    let diff = crate::diff::diff(
+        (&before.name(), before_report),
+        (&after.name(), after_report),
+    );
+
+    let mut pick = None;
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("Compare").strong());
+        egui::ComboBox::from_id_source("diff_before")
+            .selected_text(egui::RichText::new(&diff.before).monospace())
+            .show_ui(ui, |ui| {
+                for (id, name) in &others {
+                    if ui
+                        .selectable_label(*id == before_id, egui::RichText::new(name).monospace())
+                        .clicked()
+                    {
+                        pick = Some(*id);
+                    }
+                }
+            });
+        ui.label("with");
+        ui.label(egui::RichText::new(&diff.after).monospace().strong())
+            .on_hover_text("The dump shown in the Inspector; pick another in the Dump box");
+        if ui.button("Export JSON...").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("JSON", &["json"])
+                .set_file_name("diff.json")
+                .save_file()
+        {
+            let res = crate::export::diff_to_json(&diff)
+                .and_then(|s| std::fs::write(&path, s).map_err(Into::into));
+            if let Err(e) = res {
+                app.ui.last_error = Some(format!("export {}: {e}", path.display()));
+            }
+        }
+    });
+    if let Some(id) = pick {
+        app.ui.diff_before = Some(id);
+    }
+    ui.add_space(6.0);
+    if diff.is_empty() {
+        ui.label("No differences.");
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .id_source("diff_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| sections(ui, &diff));
+}
+
+fn sections(ui: &mut egui::Ui, diff: &ReportDiff) {
+    section(ui, "Crash signature", diff.signature.len(), "", |ui| {
+        egui::Grid::new("diff_signature_grid")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("Parameter").strong());
+                ui.label(egui::RichText::new(&diff.before).strong());
+                ui.label(egui::RichText::new(&diff.after).strong());
+                ui.end_row();
+                for c in &diff.signature {
+                    ui.label(&c.parameter);
+                    ui.monospace(c.before.as_deref().unwrap_or("-"));
+                    ui.monospace(c.after.as_deref().unwrap_or("-"));
+                    ui.end_row();
+                }
+            });
+    });
+
+    let m = &diff.modules;
+    section(
+        ui,
+        "Modules",
+        m.added.len() + m.removed.len() + m.changed.len(),
+        &counts(m.added.len(), m.removed.len(), m.changed.len()),
+        |ui| {
+            side_by_side(ui, "diff_modules_grid", diff, |ui| {
+                let module = |m: &crate::model::ModuleInfo| {
+                    format!("{}  {}", m.name, m.file_version.as_deref().unwrap_or("-"))
+                };
+                for c in &m.changed {
+                    let build = |m: &crate::model::ModuleInfo| {
+                        format!(
+                            "{}  ts=0x{:08X}  size=0x{:X}",
+                            module(m),
+                            m.time_date_stamp,
+                            m.size
+                        )
+                    };
+                    row(
+                        ui,
+                        Some(build(&c.before)),
+                        Some(build(&c.after)),
+                        &format!("changed: {}", c.fields.join(", ")),
+                    );
+                }
+                changes(ui, &m.removed, &m.added, module);
+            });
+        },
+    );
+
+    let t = &diff.threads;
+    changes_section(ui, "Threads", "diff_threads_grid", diff, t, |t| {
+        format!("tid={} start={}", t.thread_id, t.start)
+    });
+    changes_section(
+        ui,
+        "Injected regions",
+        "diff_regions_grid",
+        diff,
+        &diff.injected_regions,
+        |r| {
+            format!(
+                "0x{:016X} size=0x{:X} {} {}  {}",
+                r.base,
+                r.size,
+                r.protection,
+                r.risk.label(),
+                r.reasons.join("; ")
+            )
+        },
+    );
+    changes_section(
+        ui,
+        "Execution artifacts",
+        "diff_artifacts_grid",
+        diff,
+        &diff.exec_artifacts,
+        |a| match a.decoded_via {
+            Some(step) => format!("[{}] {}", step.label(), a.command_line),
+            None => a.command_line.clone(),
+        },
+    );
+    changes_section(ui, "IOCs", "diff_iocs_grid", diff, &diff.iocs, |i| {
+        format!("{}: {}", i.kind.label(), i.value)
+    });
+}
+
+/// Collapsing section, open when it has changes.
+fn section(
+    ui: &mut egui::Ui,
+    title: &str,
+    len: usize,
+    summary: &str,
+    body: impl FnOnce(&mut egui::Ui),
+) {
+    let header = if len == 0 {
+        format!("{title}: no changes")
+    } else if summary.is_empty() {
+        format!("{title} ({len})")
+    } else {
+        format!("{title} ({summary})")
+    };
+    ui.add_enabled_ui(len > 0, |ui| {
+        egui::CollapsingHeader::new(egui::RichText::new(header).strong())
+            .id_source(title)
+            .default_open(len > 0)
+            .show(ui, body);
+    });
+}
+
+fn changes_section<T>(
+    ui: &mut egui::Ui,
+    title: &str,
+    grid: &str,
+    diff: &ReportDiff,
+    c: &Changes<T>,
+    text: impl Fn(&T) -> String,
+) {
+    section(
+        ui,
+        title,
+        c.added.len() + c.removed.len(),
+        &counts(c.added.len(), c.removed.len(), 0),
+        |ui| {
+            side_by_side(ui, grid, diff, |ui| {
+                changes(ui, &c.removed, &c.added, &text)
+            })
+        },
+    );
+}
+
+fn counts(added: usize, removed: usize, changed: usize) -> String {
+    let mut parts = vec![format!("+{added}"), format!("-{removed}")];
+    if changed > 0 {
+        parts.push(format!("~{changed}"));
+    }
+    parts.join(" ")
+}
+
+/// Grid with the before dump's column on the left and the after dump's on the right.
+fn side_by_side(ui: &mut egui::Ui, id: &str, diff: &ReportDiff, body: impl FnOnce(&mut egui::Ui)) {
+    egui::Grid::new(id)
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label(egui::RichText::new(&diff.before).strong());
+            ui.label(egui::RichText::new(&diff.after).strong());
+            ui.label("");
+            ui.end_row();
+            body(ui);
+        });
+}
+
+/// One row: removed (left only), added (right only) or changed (both sides).
+fn row(ui: &mut egui::Ui, before: Option<String>, after: Option<String>, note: &str) {
+    let (before_color, after_color) = if before.is_some() && after.is_some() {
+        let changed = crate::ui::severity_color(Severity::Warning);
+        (changed, changed)
+    } else {
+        (crate::ui::severity_color(Severity::High), ADDED)
+    };
+    for (text, color) in [(before, before_color), (after, after_color)] {
+        match text {
+            Some(t) => ui.label(egui::RichText::new(t).monospace().color(color)),
+            None => ui.label(""),
+        };
+    }
+    ui.weak(note);
+    ui.end_row();
+}
+
+/// Removed items on the left, added ones on the right.
+fn changes<T>(ui: &mut egui::Ui, removed: &[T], added: &[T], text: impl Fn(&T) -> String) {
+    for item in removed {
+        row(ui, Some(format!("- {}", text(item))), None, "");
+    }
+    for item in added {
+        row(ui, None, Some(format!("+ {}", text(item))), "");
+    }
+}
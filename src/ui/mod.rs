@@ -1,9 +1,12 @@
 mod details;
+mod diff;
 mod timeline;
 mod timeline_bar;
 
 use crate::app::LogAtlasApp;
+use crate::case::InputStatus;
 use crate::model::Severity;
+use crate::query::{QueryFilter, QueryHistory};
 use eframe::egui;
 
 pub fn render_app(ctx: &egui::Context, frame: &mut eframe::Frame, app: &mut LogAtlasApp) {
@@ -54,17 +57,107 @@ fn top_bar(ctx: &egui::Context, frame: &mut eframe::Frame, app: &mut LogAtlasApp
     egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
         egui::menu::bar(ui, |ui| {
             ui.menu_button("File", |ui| {
-                if ui.button("Open minidump...").clicked() {
+                if ui.button("Open...").clicked() {
                     ui.close_menu();
                     if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("Minidump", &["dmp", "mdmp"])
+                        .add_filter(
+                            "Supported files",
+                            &[
+                                "dmp",
+                                "mdmp",
+                                "jsonl",
+                                "ndjson",
+                                "json",
+                                "evtx",
+                                "log",
+                                "syslog",
+                                "journal",
+                                "core",
+                                "hdmp",
+                                "zip",
+                                "wer",
+                                "gz",
+                                "zst",
+                                "xz",
+                                crate::case::EXTENSION,
+                            ],
+                        )
+                        .add_filter("Log Atlas case", &[crate::case::EXTENSION, "gz"])
+                        .add_filter("Minidump", &["dmp", "mdmp", "hdmp"])
+                        .add_filter("ELF core", &["core"])
+                        .add_filter("WER report", &["wer", "zip"])
+                        .add_filter("Compressed / archive", &["gz", "zst", "xz", "zip"])
+                        .add_filter("JSON Lines", &["jsonl", "ndjson"])
+                        .add_filter("Windows Event Log", &["evtx"])
+                        .add_filter("Syslog", &["log", "syslog"])
+                        .add_filter("journalctl export / json", &["journal", "json"])
+                        .add_filter("All files", &["*"])
                         .pick_file()
+                        && let Err(e) = app.open_path(path)
+                    {
+                        app.ui.last_error = Some(e.to_string());
+                    }
+                }
+                if ui.button("Open WER report folder...").clicked() {
+                    ui.close_menu();
+                    if let Some(path) = rfd::FileDialog::new().pick_folder()
+                        && let Err(e) = app.open_path(path)
                     {
-                        if let Err(e) = app.load_minidump(path) {
-                            app.ui.last_error = Some(e.to_string());
+                        app.ui.last_error = Some(e.to_string());
+                    }
+                }
+                let has_sources = !app.session.sources.is_empty();
+                if ui
+                    .add_enabled(has_sources, egui::Button::new("Save case"))
+                    .on_hover_text("Save sources, reports, selections, filters, notes and triage")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    match app.case_path.clone() {
+                        Some(path) => {
+                            if let Err(e) = app.save_case(path) {
+                                app.ui.last_error = Some(format!("save case: {e:#}"));
+                            }
                         }
+                        None => save_case_as(app),
+                    }
+                }
+                if ui
+                    .add_enabled(has_sources, egui::Button::new("Save case as..."))
+                    .clicked()
+                {
+                    ui.close_menu();
+                    save_case_as(app);
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(
+                        app.session.dump_source().is_some(),
+                        egui::Button::new("Export redacted minidump..."),
+                    )
+                    .clicked()
+                {
+                    ui.close_menu();
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Minidump", &["dmp", "mdmp"])
+                        .set_file_name("redacted.dmp")
+                        .save_file()
+                        && let Some(replace_manifest) = confirm_manifest(&path)
+                        && let Err(e) = app.export_redacted_minidump(path, replace_manifest)
+                    {
+                        app.ui.last_error = Some(format!("redacted export: {e:#}"));
                     }
                 }
+                if ui
+                    .add_enabled(
+                        !app.session.sources.is_empty(),
+                        egui::Button::new("Close all sources"),
+                    )
+                    .clicked()
+                {
+                    ui.close_menu();
+                    app.clear_session();
+                }
                 ui.separator();
                 if ui.button("Quit").clicked() {
                     let _ = frame; // keep signature stable if we later use frame APIs
@@ -89,6 +182,36 @@ fn top_bar(ctx: &egui::Context, frame: &mut eframe::Frame, app: &mut LogAtlasApp
     });
 }
 
+/// Whether to replace the manifest of a redacted export to `output` (`false` when there is
+/// none yet); `None` when the analyst declines. The save dialog only asked about the dump.
+fn confirm_manifest(output: &std::path::Path) -> Option<bool> {
+    let manifest = crate::export::manifest_path(output);
+    if !manifest.exists() {
+        return Some(false);
+    }
+    let replace = rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Warning)
+        .set_title("Replace redaction manifest?")
+        .set_description(format!(
+            "{} already exists. Replace it?",
+            manifest.display()
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+    (replace == rfd::MessageDialogResult::Yes).then_some(true)
+}
+
+fn save_case_as(app: &mut LogAtlasApp) {
+    if let Some(path) = rfd::FileDialog::new()
+        .add_filter("Log Atlas case", &[crate::case::EXTENSION])
+        .set_file_name(format!("case.{}", crate::case::EXTENSION))
+        .save_file()
+        && let Err(e) = app.save_case(path)
+    {
+        app.ui.last_error = Some(format!("save case: {e:#}"));
+    }
+}
+
 fn about_window(ctx: &egui::Context, app: &mut LogAtlasApp) {
     if !app.ui.show_about {
         return;
@@ -106,19 +229,40 @@ fn about_window(ctx: &egui::Context, app: &mut LogAtlasApp) {
 fn status_bar(ctx: &egui::Context, app: &mut LogAtlasApp) {
     egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
         ui.horizontal(|ui| {
-            ui.label(format!("Events: {}", app.events.len()));
+            ui.label(format!("Events: {}", app.session.timeline().len()));
             ui.separator();
-            if let Some(p) = &app.dump_path {
-                let name = p
-                    .file_name()
-                    .map(|s| s.to_string_lossy())
-                    .unwrap_or_else(|| p.to_string_lossy());
-                ui.label(format!("Dump: {name}"));
+            if app.session.sources.len() > 1 {
+                ui.label(format!("Sources: {}", app.session.sources.len()));
+                ui.separator();
+            }
+            if let Some(source) = app.session.dump_source() {
+                ui.label(format!("Dump: {}", source.name()));
+                ui.separator();
+            }
+            if let Some(path) = &app.case_path {
+                let name = path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |n| n.to_string_lossy().into(),
+                );
+                ui.label(format!("Case: {name}"))
+                    .on_hover_text(path.display().to_string());
+                let stale = app
+                    .session
+                    .sources
+                    .iter()
+                    .filter(|s| matches!(s.status, InputStatus::Changed | InputStatus::Missing))
+                    .count();
+                if stale > 0 {
+                    ui.colored_label(
+                        severity_color(Severity::Warning),
+                        format!("{stale} inputs changed or missing"),
+                    );
+                }
                 ui.separator();
             }
             if let Some(id) = app.selected {
-                if let Some(ev) = app.events.get(id) {
-                    ui.label(format!("Selected: {} (+{}ms)", ev.title, ev.t_ms));
+                if let Some(ev) = app.session.timeline().get(id) {
+                    ui.label(format!("Selected: {} (+{}ms)", ev.event.title, ev.t_ms));
                 } else {
                     ui.label("Selected: (missing)");
                 }
@@ -136,6 +280,69 @@ fn status_bar(ctx: &egui::Context, app: &mut LogAtlasApp) {
     });
 }
 
+/// Filter box of the timeline and the report tabs: query text, syntax error under it, and
+/// the saved queries of `scope`.
+pub fn query_box(
+    ui: &mut egui::Ui,
+    scope: &str,
+    filter: &mut QueryFilter,
+    history: &mut QueryHistory,
+    last_error: &mut Option<String>,
+) {
+    let error_color = egui::Color32::from_rgb(255, 70, 70);
+    ui.push_id(scope, |ui| {
+        filter.compile();
+        let invalid = filter.error().is_some();
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            let mut edit = egui::TextEdit::singleline(&mut filter.text)
+                .hint_text("query, e.g. severity>=warn AND source:detector::*");
+            if invalid {
+                edit = edit.text_color(error_color);
+            }
+            ui.add(edit);
+            if ui.button("Clear").clicked() {
+                filter.clear();
+            }
+            if ui
+                .add_enabled(filter.is_active(), egui::Button::new("Save"))
+                .on_hover_text("Add this query to the saved queries")
+                .clicked()
+                && let Err(e) = history.save(scope, &filter.text)
+            {
+                *last_error = Some(format!("{e:#}"));
+            }
+            ui.menu_button("Saved", |ui| {
+                let saved: Vec<String> = history.for_scope(scope).map(str::to_string).collect();
+                if saved.is_empty() {
+                    ui.label("No saved queries.");
+                }
+                for q in saved {
+                    ui.horizontal(|ui| {
+                        if ui.button("x").on_hover_text("Remove").clicked()
+                            && let Err(e) = history.remove(scope, &q)
+                        {
+                            *last_error = Some(format!("{e:#}"));
+                        }
+                        if ui.selectable_label(filter.text == q, &q).clicked() {
+                            filter.text = q.clone();
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+        });
+        // The text may have changed this frame.
+        filter.compile();
+        if let Some(err) = filter.error() {
+            ui.colored_label(
+                error_color,
+                format!("column {}: {}", err.column(&filter.text), err.message),
+            );
+        }
+    });
+}
+
 pub fn severity_color(sev: Severity) -> egui::Color32 {
     match sev {
         Severity::Info => egui::Color32::from_rgb(90, 160, 255),
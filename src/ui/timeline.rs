@@ -1,4 +1,7 @@
-use crate::app::LogAtlasApp;
+use crate::app::{FilteredRows, LogAtlasApp, TimelineEvent};
+use crate::case::InputStatus;
+use crate::model::Severity;
+use crate::triage::TriageStatus;
 use eframe::egui;
 
 pub fn timeline_panel(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
@@ -8,87 +11,190 @@ pub fn timeline_panel(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
     crate::ui::timeline_bar(ui, app);
     ui.add_space(8.0);
 
-    let mut info = 0usize;
-    let mut warning = 0usize;
-    let mut high = 0usize;
-    for ev in app.events.iter() {
-        match ev.severity {
-            crate::model::Severity::Info => info += 1,
-            crate::model::Severity::Warning => warning += 1,
-            crate::model::Severity::High => high += 1,
-        }
-    }
     ui.horizontal_wrapped(|ui| {
-        ui.label(format!("Total {}", app.events.len()));
-        ui.colored_label(
-            crate::ui::severity_color(crate::model::Severity::Info),
-            format!("Info {info}"),
-        );
-        ui.colored_label(
-            crate::ui::severity_color(crate::model::Severity::Warning),
-            format!("Warn {warning}"),
-        );
-        ui.colored_label(
-            crate::ui::severity_color(crate::model::Severity::High),
-            format!("High {high}"),
-        );
+        ui.label(format!("Total {}", app.session.timeline().len()));
+        for (severity, label) in [
+            (Severity::Info, "Info"),
+            (Severity::Warning, "Warn"),
+            (Severity::High, "High"),
+        ] {
+            ui.colored_label(
+                crate::ui::severity_color(severity),
+                format!(
+                    "{label} {}",
+                    app.session.timeline().by_severity(severity).len()
+                ),
+            );
+        }
     });
+    sources(ui, app);
     ui.add_space(6.0);
 
-    ui.horizontal(|ui| {
-        ui.label("Filter:");
-        ui.text_edit_singleline(&mut app.ui.filter);
-        if ui.button("Clear").clicked() {
-            app.ui.filter.clear();
-        }
-    });
+    crate::ui::query_box(
+        ui,
+        "timeline",
+        &mut app.ui.filter,
+        &mut app.ui.query_history,
+        &mut app.ui.last_error,
+    );
 
     ui.add_space(6.0);
 
+    let rows: &[usize] = if app.ui.filter.is_active() {
+        filtered_rows(app);
+        &app.ui.timeline_rows.rows
+    } else {
+        app.session.timeline().by_time()
+    };
+    if app.ui.filter.is_active() {
+        ui.label(format!(
+            "{} of {} events match",
+            rows.len(),
+            app.session.timeline().len()
+        ));
+    }
+
+    // Only the visible rows are laid out, so scrolling cost does not grow with the log.
+    let row_height =
+        ui.text_style_height(&egui::TextStyle::Monospace) + 2.0 * ui.spacing().button_padding.y;
+    let mut clicked = None;
     egui::ScrollArea::vertical()
         .id_source("timeline_events_scroll")
         .auto_shrink([false, false])
-        .show(ui, |ui| {
-            for ev in app.events.iter() {
-                if !passes_filter(ev, &app.ui.filter) {
-                    continue;
+        .show_rows(ui, row_height, rows.len(), |ui, visible| {
+            for &pos in &rows[visible] {
+                let TimelineEvent {
+                    id,
+                    t_ms,
+                    event: ev,
+                } = app.session.timeline().at(pos);
+                let source = app.session.source_of(id);
+                let selected = app.selected == Some(id);
+                let mut label = format!("+{t_ms:>4}ms  {:<4}  {}", ev.severity.label(), ev.title);
+                let subject = app.session.subject_of(id);
+                let triage = app.session.triage_of(&subject);
+                // The allowlist may set a detection's status without a triage entry.
+                let (status, _) = app.effective_triage(&subject);
+                if status != TriageStatus::New {
+                    label.push_str(&format!("  [{}]", status.label()));
                 }
-
-                let selected = app.selected == Some(ev.id);
-                let label = format!(
-                    "+{:>4}ms  {:<4}  {}",
-                    ev.t_ms,
-                    ev.severity.label(),
-                    ev.title
-                );
-                let label = egui::RichText::new(label)
-                    .monospace()
-                    .color(crate::ui::severity_color(ev.severity));
+                for tag in triage.iter().flat_map(|t| &t.tags) {
+                    label.push_str(&format!("  #{tag}"));
+                }
+                match app.session.notes_on(&subject).count() {
+                    0 => {}
+                    1 => label.push_str("  [1 note]"),
+                    n => label.push_str(&format!("  [{n} notes]")),
+                }
+                let color = if status.is_dismissed() {
+                    ui.visuals().weak_text_color()
+                } else {
+                    crate::ui::severity_color(ev.severity)
+                };
+                let label = egui::RichText::new(label).monospace().color(color);
 
                 let response = ui
                     .add_sized(
-                        [ui.available_width(), 0.0],
+                        [ui.available_width(), row_height],
                         egui::SelectableLabel::new(selected, label),
                     )
-                    .on_hover_text(format!("{}\nsource={}", ev.details, ev.source));
+                    .on_hover_text(match ev.timestamp {
+                        Some(ts) => format!(
+                            "{}\n{}\nsource={}",
+                            crate::util::time::format_timestamp(ts),
+                            ev.details,
+                            ev.source
+                        ),
+                        None => format!("{}\nsource={}", ev.details, ev.source),
+                    });
+                // Strip in the color of the input the event came from.
+                if let Some(source) = source {
+                    let strip = egui::Rect::from_min_size(
+                        response.rect.left_top(),
+                        egui::vec2(3.0, response.rect.height()),
+                    );
+                    ui.painter().rect_filled(strip, 0.0, source.color);
+                }
 
                 if response.clicked() {
-                    app.selected = Some(ev.id);
+                    clicked = Some((id, ev.links.first().copied()));
                 }
-
-                ui.add_space(2.0);
             }
         });
+
+    // Events about a module, thread, region or artifact open it in its report tab.
+    if let Some((id, jump)) = clicked {
+        app.select(id);
+        if let Some(target) = jump {
+            app.jump_to(target);
+        }
+    }
 }
 
-fn passes_filter(ev: &crate::model::Event, filter: &str) -> bool {
-    let f = filter.trim();
-    if f.is_empty() {
-        return true;
+/// Opened inputs with their color, event count and a visibility toggle.
+fn sources(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
+    if app.session.sources.is_empty() {
+        return;
     }
+    let mut toggled = None;
+    let stale =
+        |s: &crate::app::Source| matches!(s.status, InputStatus::Changed | InputStatus::Missing);
+    egui::CollapsingHeader::new(format!("Sources ({})", app.session.sources.len()))
+        .id_source("timeline_sources")
+        .default_open(app.session.sources.len() > 1 || app.session.sources.iter().any(stale))
+        .show(ui, |ui| {
+            for source in &app.session.sources {
+                ui.horizontal(|ui| {
+                    let mut visible = source.visible;
+                    let name = egui::RichText::new(source.name())
+                        .monospace()
+                        .color(source.color);
+                    if ui
+                        .checkbox(&mut visible, name)
+                        .on_hover_text(source.path.display().to_string())
+                        .changed()
+                    {
+                        toggled = Some((source.id, visible));
+                    }
+                    ui.weak(format!("{}, {} events", source.kind(), source.events.len()));
+                    if stale(source) {
+                        ui.colored_label(
+                            crate::ui::severity_color(Severity::Warning),
+                            source.status.label(),
+                        )
+                        .on_hover_text(
+                            "The input on disk is not the one this case was saved from; the saved report and events are shown. Open the file again to analyse the current version.",
+                        );
+                    } else if source.status == InputStatus::Checking {
+                        ui.weak(source.status.label())
+                            .on_hover_text("Hashing the input to compare it with the saved one.");
+                    }
+                });
+            }
+        });
+    if let Some((id, visible)) = toggled {
+        app.session.set_visible(id, visible);
+    }
+}
 
-    let f = f.to_ascii_lowercase();
-    ev.title.to_ascii_lowercase().contains(&f)
-        || ev.details.to_ascii_lowercase().contains(&f)
-        || ev.source.to_ascii_lowercase().contains(&f)
+/// Re-run the timeline filter over the store when the query or the events changed.
+fn filtered_rows(app: &mut LogAtlasApp) {
+    let timeline = app.session.timeline();
+    let key = (timeline.generation(), app.ui.filter.text.clone());
+    if app.ui.timeline_rows.key.as_ref() == Some(&key) {
+        return;
+    }
+    let rows = timeline
+        .by_time()
+        .iter()
+        .copied()
+        .filter(|&pos| {
+            let report = app.session.report_of(timeline.at(pos).id);
+            app.ui.filter.matches(&timeline.record(pos, report))
+        })
+        .collect();
+    app.ui.timeline_rows = FilteredRows {
+        key: Some(key),
+        rows,
+    };
 }
@@ -1,8 +1,10 @@
 use crate::app::LogAtlasApp;
+use crate::model::Severity;
 use eframe::egui;
 
 pub fn timeline_bar(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
-    if app.events.len() == 0 {
+    let timeline = app.session.timeline();
+    if timeline.is_empty() {
         return;
     }
 
@@ -22,55 +24,64 @@ pub fn timeline_bar(ui: &mut egui::Ui, app: &mut LogAtlasApp) {
         stroke,
     );
 
-    let max_t = app.events.iter().map(|e| e.t_ms).max().unwrap_or(0).max(1);
+    let max_t = timeline.max_t_ms().max(1);
+    let left = rect.left() + 6.0;
+    let width = rect.width() - 12.0;
+    let x_of = |t_ms: u64| left + width * (t_ms as f32 / max_t as f32);
 
+    // One mark per pixel column: its most severe event (the earliest on ties), found by
+    // binary search in the time-ordered severity indexes. Cost follows the bar width, not
+    // the number of events.
+    let columns = width.max(1.0) as u64;
     let mut points: Vec<(egui::Pos2, crate::model::EventId)> = Vec::new();
-    for ev in app.events.iter() {
-        let x = rect.left() + 6.0 + (rect.width() - 12.0) * (ev.t_ms as f32 / max_t as f32);
-        let p = egui::pos2(x, mid_y);
-        let r = if app.selected == Some(ev.id) {
-            5.5
-        } else {
-            4.0
-        };
-        let fill = crate::ui::severity_color(ev.severity);
-        painter.circle_filled(p, r, fill);
+    let selected = app.selected.and_then(|id| timeline.get(id));
+    for col in 0..=columns {
+        let from = (col * max_t).div_ceil(columns);
+        let to = ((col + 1) * max_t).div_ceil(columns);
+        let hit = [Severity::High, Severity::Warning, Severity::Info]
+            .into_iter()
+            .find_map(|sev| {
+                let in_column = timeline.time_range(timeline.by_severity(sev), from, to);
+                in_column.first().map(|&pos| timeline.at(pos))
+            });
+        if let Some(ev) = hit {
+            let p = egui::pos2(x_of(ev.t_ms), mid_y);
+            painter.circle_filled(p, 4.0, crate::ui::severity_color(ev.event.severity));
+            points.push((p, ev.id));
+        }
+    }
+    if let Some(ev) = selected {
+        let p = egui::pos2(x_of(ev.t_ms), mid_y);
+        painter.circle_filled(p, 5.5, crate::ui::severity_color(ev.event.severity));
         points.push((p, ev.id));
     }
 
-    if response.clicked() {
-        if let Some(pos) = response.interact_pointer_pos() {
-            if let Some((_, id)) = nearest_point(&points, pos, 10.0) {
-                app.selected = Some(*id);
-            }
-        }
+    if response.clicked()
+        && let Some(pos) = response.interact_pointer_pos()
+        && let Some((_, id)) = nearest_point(&points, pos, 10.0)
+    {
+        app.select(*id);
     }
 
-    if response.hovered() {
-        if let Some(pos) = response.hover_pos() {
-            if let Some((d, id)) = nearest_point(&points, pos, 10.0) {
-                if let Some(ev) = app.events.get(*id) {
-                    egui::show_tooltip_at_pointer(
-                        ui.ctx(),
-                        egui::Id::new("timeline_hover"),
-                        |ui| {
-                            ui.label(egui::RichText::new("Timeline").strong());
-                            ui.monospace(format!("+{}ms", ev.t_ms));
-                            ui.label(format!("{}  {}", ev.severity.label(), ev.title));
-                            let _ = d;
-                        },
-                    );
-                }
-            }
-        }
+    if response.hovered()
+        && let Some(pos) = response.hover_pos()
+        && let Some((d, id)) = nearest_point(&points, pos, 10.0)
+        && let Some(ev) = app.session.timeline().get(*id)
+    {
+        egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("timeline_hover"), |ui| {
+            ui.label(egui::RichText::new("Timeline").strong());
+            ui.monospace(format!("+{}ms", ev.t_ms));
+            ui.label(format!("{}  {}", ev.event.severity.label(), ev.event.title));
+            let _ = d;
+        });
     }
 }
 
-fn nearest_point<'a>(
-    points: &'a [(egui::Pos2, crate::model::EventId)],
+fn nearest_point(
+    points: &[(egui::Pos2, crate::model::EventId)],
     pos: egui::Pos2,
     max_dist: f32,
-) -> Option<(f32, &'a crate::model::EventId)> {
+) -> Option<(f32, &crate::model::EventId)> {
     points
         .iter()
         .map(|(p, id)| (p.distance(pos), id))
@@ -1 +1,17 @@
 pub mod time;
+
+use std::path::PathBuf;
+
+/// Path of a settings file: `env_var` when set, else `log-atlas/<file_name>` in the user's
+/// config directory (`XDG_CONFIG_HOME`, `APPDATA`, then `~/.config`).
+pub fn config_file(env_var: &str, file_name: &str) -> Option<PathBuf> {
+    if let Some(p) = std::env::var_os(env_var).filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(p));
+    }
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(config.join("log-atlas").join(file_name))
+}
@@ -22,3 +22,18 @@ pub fn filetime_to_unix_seconds(filetime: u64) -> Option<u64> {
     let secs = filetime / FILETIME_TICKS_PER_SEC;
     secs.checked_sub(WINDOWS_TO_UNIX_EPOCH_SECS)
 }
+
+/// `YYYY-MM-DD HH:MM:SS.mmm UTC`, for event timestamps.
+pub fn format_timestamp(ts: time::OffsetDateTime) -> String {
+    let dt = ts.to_offset(time::UtcOffset::UTC);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03} UTC",
+        dt.year(),
+        u8::from(dt.month()),
+        dt.day(),
+        dt.hour(),
+        dt.minute(),
+        dt.second(),
+        dt.millisecond()
+    )
+}
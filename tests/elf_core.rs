@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+
+use log_atlas::ingest::elf_core::ingest;
+
+const TID: u32 = 4242;
+const IMAGE_BASE: u64 = 0x40_0000;
+const STACK_BASE: u64 = 0x7ffd_0000;
+const RIP: u64 = IMAGE_BASE + 0x100;
+const RSP: u64 = STACK_BASE + 0x800;
+const PAGE: usize = 0x1000;
+
+const IMAGE_PATH: &str = "/usr/bin/app";
+const IMAGE_URL: &[u8] = b"https://evil.example.net/stage2.bin";
+
+const NT_PRSTATUS: u32 = 1;
+const NT_FILE: u32 = 0x4649_4c45;
+
+fn note(out: &mut Vec<u8>, name: &[u8], ty: u32, desc: &[u8]) {
+    out.extend_from_slice(&(name.len() as u32 + 1).to_le_bytes());
+    out.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+    out.extend_from_slice(&ty.to_le_bytes());
+    out.extend_from_slice(name);
+    out.push(0);
+    out.resize(out.len().next_multiple_of(4), 0);
+    out.extend_from_slice(desc);
+    out.resize(out.len().next_multiple_of(4), 0);
+}
+
+fn phdr(out: &mut Vec<u8>, p_type: u32, flags: u32, offset: u64, vaddr: u64, size: u64) {
+    out.extend_from_slice(&p_type.to_le_bytes());
+    out.extend_from_slice(&flags.to_le_bytes());
+    for v in [offset, vaddr, vaddr, size, size, 0x1000] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+}
+
+/// An x86_64 core with one thread stopped in `/usr/bin/app`: a note segment (`NT_PRSTATUS`,
+/// `NT_FILE`), the image's first page (ELF magic and a URL) and a stack page.
+fn synth_core() -> Vec<u8> {
+    let mut regs = [0u64; 27];
+    regs[16] = RIP;
+    regs[19] = RSP;
+    let mut prstatus = vec![0u8; 336];
+    prstatus[12..14].copy_from_slice(&11u16.to_le_bytes()); // cursig: SIGSEGV
+    prstatus[32..36].copy_from_slice(&TID.to_le_bytes());
+    for (i, reg) in regs.iter().enumerate() {
+        prstatus[112 + i * 8..120 + i * 8].copy_from_slice(&reg.to_le_bytes());
+    }
+    let mut nt_file = Vec::new();
+    for v in [1, PAGE as u64, IMAGE_BASE, IMAGE_BASE + PAGE as u64, 0] {
+        nt_file.extend_from_slice(&v.to_le_bytes());
+    }
+    nt_file.extend_from_slice(IMAGE_PATH.as_bytes());
+    nt_file.push(0);
+    let mut notes = Vec::new();
+    note(&mut notes, b"CORE", NT_PRSTATUS, &prstatus);
+    note(&mut notes, b"CORE", NT_FILE, &nt_file);
+
+    let mut image = vec![0u8; PAGE];
+    image[..4].copy_from_slice(b"\x7fELF");
+    image[4] = 2;
+    image[0x800..0x800 + IMAGE_URL.len()].copy_from_slice(IMAGE_URL);
+    let stack = vec![0u8; PAGE];
+
+    let notes_at = 64 + 3 * 56;
+    let mut core = vec![0u8; 64];
+    core[..4].copy_from_slice(b"\x7fELF");
+    core[4] = 2; // ELFCLASS64
+    core[5] = 1; // little-endian
+    core[6] = 1;
+    core[16..18].copy_from_slice(&4u16.to_le_bytes()); // ET_CORE
+    core[18..20].copy_from_slice(&62u16.to_le_bytes()); // EM_X86_64
+    core[32..40].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+    core[52..54].copy_from_slice(&64u16.to_le_bytes());
+    core[54..56].copy_from_slice(&56u16.to_le_bytes());
+    core[56..58].copy_from_slice(&3u16.to_le_bytes());
+    phdr(&mut core, 4, 0, notes_at, 0, notes.len() as u64);
+    phdr(&mut core, 1, 5, PAGE as u64, IMAGE_BASE, PAGE as u64);
+    phdr(&mut core, 1, 6, 2 * PAGE as u64, STACK_BASE, PAGE as u64);
+    core.extend_from_slice(&notes);
+    core.resize(PAGE, 0);
+    core.extend_from_slice(&image);
+    core.extend_from_slice(&stack);
+    core
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("log-atlas-core-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    dir.join(name)
+}
+
+fn write_core(name: &str, bytes: &[u8]) -> PathBuf {
+    let path = temp_path(name);
+    std::fs::write(&path, bytes).expect("write synthetic core");
+    path
+}
+
+#[test]
+fn synthetic_core_round_trips_through_minidump() {
+    let path = write_core("app.core", &synth_core());
+    let report = ingest(&path).expect("ingest core").report;
+
+    assert_eq!(report.threads.len(), 1);
+    let thread = &report.threads[0];
+    assert_eq!(thread.thread_id, TID);
+    // The stack starts at rsp minus the red zone and runs to the end of its segment.
+    assert_eq!(thread.stack_start, RSP - 128);
+    assert_eq!(thread.stack_size, STACK_BASE + PAGE as u64 - (RSP - 128));
+
+    let exception = report.exception.expect("exception from cursig");
+    assert_eq!(exception.thread_id, TID);
+    assert_eq!(exception.code, 11);
+
+    let stackwalk = report.stackwalk.expect("stackwalk");
+    let frame = &stackwalk.threads[0].frames[0];
+    assert_eq!(frame.instruction, RIP);
+    assert_eq!(frame.module_base, Some(IMAGE_BASE));
+
+    assert_eq!(report.modules.len(), 1);
+    assert_eq!(report.modules[0].name, IMAGE_PATH);
+    assert_eq!(report.modules[0].base, IMAGE_BASE);
+    assert_eq!(report.modules[0].size, PAGE as u64);
+
+    // The URL is only in the image's captured page, so finding it reads `Memory64List` data.
+    let url = std::str::from_utf8(IMAGE_URL).unwrap();
+    let ioc = report
+        .iocs
+        .iter()
+        .find(|i| i.value == url)
+        .expect("URL read from core memory");
+    assert_eq!(ioc.address, IMAGE_BASE + 0x800);
+}
+
+#[test]
+fn malformed_cores_are_rejected() {
+    let core = synth_core();
+    let mut cases: Vec<(&str, Vec<u8>)> = vec![
+        ("truncated-header.core", core[..40].to_vec()),
+        ("truncated-phdrs.core", core[..64 + 56 + 20].to_vec()),
+    ];
+    let mut phoff = core.clone();
+    phoff[32..40].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+    cases.push(("phoff-overflow.core", phoff));
+    let mut xnum = core.clone();
+    xnum[56..58].copy_from_slice(&0xffffu16.to_le_bytes());
+    xnum[40..48].copy_from_slice(&(u64::MAX - 20).to_le_bytes());
+    cases.push(("shoff-overflow.core", xnum));
+    let mut phentsize = core.clone();
+    phentsize[54..56].copy_from_slice(&8u16.to_le_bytes());
+    cases.push(("bad-phentsize.core", phentsize));
+
+    for (name, bytes) in cases {
+        let path = write_core(name, &bytes);
+        assert!(ingest(&path).is_err(), "{name} was accepted");
+    }
+}